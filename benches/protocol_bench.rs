@@ -0,0 +1,63 @@
+//! Benchmarks for AtlasKV protocol decoding
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use std::io::{Cursor, Read};
+
+use atlaskv::protocol::{decode_command, encode_command, read_command, Command, HEADER_SIZE};
+
+use common::count_allocations;
+
+/// How `read_command` worked before decoding from split parts: header and
+/// payload copied into one buffer, then decoded
+fn read_command_concatenated<R: Read>(reader: &mut R) -> Command {
+    let mut header = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut header).unwrap();
+    let payload_len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+
+    let mut payload = vec![0u8; payload_len];
+    reader.read_exact(&mut payload).unwrap();
+
+    let mut full_message = Vec::with_capacity(HEADER_SIZE + payload_len);
+    full_message.extend_from_slice(&header);
+    full_message.extend_from_slice(&payload);
+    decode_command(&full_message).unwrap()
+}
+
+/// Allocations made by one call of `read`
+fn allocations_per_read(frame: &[u8], read: fn(&mut Cursor<&[u8]>) -> Command) -> usize {
+    count_allocations(|| read(&mut Cursor::new(frame))).0
+}
+
+/// Reading a PUT off a stream: concatenated frame vs split header + payload
+fn read_put(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_put");
+    for value_size in [64, 4096, 64 * 1024] {
+        let frame = encode_command(&Command::Put {
+            key: b"bench_key".to_vec(),
+            value: vec![0xAB; value_size],
+        });
+
+        let before = allocations_per_read(&frame, |cursor| read_command_concatenated(cursor));
+        let after = allocations_per_read(&frame, |cursor| read_command(cursor).unwrap());
+        println!(
+            "{}-byte value: {} allocations concatenated, {} split",
+            value_size, before, after
+        );
+
+        let id = BenchmarkId::new("concatenated", value_size);
+        group.bench_with_input(id, &frame, |b, frame| {
+            b.iter(|| read_command_concatenated(&mut Cursor::new(frame.as_slice())))
+        });
+        let id = BenchmarkId::new("split", value_size);
+        group.bench_with_input(id, &frame, |b, frame| {
+            b.iter(|| read_command(&mut Cursor::new(frame.as_slice())).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, read_put);
+criterion_main!(benches);
@@ -1,15 +1,407 @@
 //! Benchmarks for AtlasKV storage operations
 
-use criterion::{criterion_group, criterion_main, Criterion};
+mod common;
 
-fn storage_benchmarks(_c: &mut Criterion) {
-    // TODO: Add benchmarks
-    // - Single key write throughput
-    // - Single key read throughput
-    // - Sequential write throughput
-    // - Random read throughput
-    // - Mixed read/write workload
+use criterion::measurement::WallTime;
+use criterion::{
+    criterion_group, criterion_main, BatchSize, BenchmarkGroup, BenchmarkId, Criterion,
+    SamplingMode, Throughput,
+};
+
+use std::fs;
+use std::time::{Duration, Instant};
+
+use atlaskv::config::{Config, WalSyncStrategy, DEFAULT_IO_BUFFER_BYTES};
+use atlaskv::memtable::MemTable;
+use atlaskv::storage::{SSTableReader, StorageManager};
+use atlaskv::Engine;
+use tempfile::TempDir;
+
+use common::count_allocations;
+
+/// Number of hot keys read over and over
+const HOT_KEYS: usize = 100;
+
+/// Value sizes the throughput benchmarks run at
+const VALUE_SIZES: [usize; 3] = [64, 1024, 64 * 1024];
+
+/// Keys the random-key throughput benchmarks pick from
+const KEY_COUNT: u64 = 1024;
+
+/// Bytes of values the throughput benchmarks write between flushes, made
+/// outside the timed section so a long run never times a flush, a huge
+/// MemTable, or a huge WAL
+const FLUSH_AT: usize = 4 * 1024 * 1024;
+
+/// WAL sync strategies the write benchmarks run under, with their ids
+const SYNC_STRATEGIES: [(&str, WalSyncStrategy); 3] = [
+    ("every_write", WalSyncStrategy::EveryWrite),
+    ("every_1000", WalSyncStrategy::EveryNEntries { count: 1000 }),
+    ("no_sync", WalSyncStrategy::NoSync),
+];
+
+/// Engine in a temp dir that only flushes when told to (see `WriteTimer`)
+fn throughput_engine(sync: WalSyncStrategy) -> (TempDir, Engine) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(sync)
+        .memtable_size_limit(usize::MAX)
+        .build();
+    let engine = Engine::open(config).unwrap();
+    (temp_dir, engine)
+}
+
+/// Key number `i` (zero-padded, so numeric order is key order)
+fn bench_key(i: u64) -> Vec<u8> {
+    format!("key_{:08}", i).into_bytes()
+}
+
+/// Deterministic xorshift64 sequence, so every run picks the same keys
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Times write operations, flushing the engine (untimed) after every
+/// `FLUSH_AT` bytes of values, across however many samples it takes
+struct WriteTimer<'a> {
+    engine: &'a Engine,
+    /// Operations per `FLUSH_AT` bytes
+    ops_per_flush: u64,
+    /// Operations since the last flush
+    pending: u64,
+}
+
+impl<'a> WriteTimer<'a> {
+    /// For operations writing at most `value_size` bytes each
+    fn new(engine: &'a Engine, value_size: usize) -> Self {
+        Self { engine, ops_per_flush: (FLUSH_AT / value_size) as u64, pending: 0 }
+    }
+
+    /// Run `op` `iters` times, returning the time spent in `op` alone
+    fn run(&mut self, iters: u64, mut op: impl FnMut()) -> Duration {
+        let mut elapsed = Duration::ZERO;
+        let mut done = 0;
+        while done < iters {
+            let batch = (self.ops_per_flush - self.pending).min(iters - done);
+            let start = Instant::now();
+            for _ in 0..batch {
+                op();
+            }
+            elapsed += start.elapsed();
+            done += batch;
+
+            self.pending += batch;
+            if self.pending == self.ops_per_flush {
+                self.engine.flush().unwrap();
+                self.pending = 0;
+            }
+        }
+        elapsed
+    }
+}
+
+/// Few, flat samples: an fsync per write makes the default hundred too slow
+fn configure_write_group(group: &mut BenchmarkGroup<'_, WallTime>) {
+    group.sample_size(10);
+    group.sampling_mode(SamplingMode::Flat);
+}
+
+/// Engine with `HOT_KEYS` keys flushed to an SSTable
+fn engine_with_hot_keys(block_cache_bytes: usize) -> (TempDir, Engine) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryNEntries { count: 1000 })
+        .block_cache_bytes(block_cache_bytes)
+        .build();
+    let engine = Engine::open(config).unwrap();
+
+    for i in 0..HOT_KEYS {
+        let key = format!("hot_key_{:04}", i);
+        engine.put(key.as_bytes(), &[0xAB; 256]).unwrap();
+    }
+    // Reads must go to the SSTable, not the MemTable
+    engine.flush().unwrap();
+
+    (temp_dir, engine)
+}
+
+/// Repeated reads of 100 hot keys, with and without the value cache
+fn hot_key_reads(c: &mut Criterion) {
+    let keys: Vec<Vec<u8>> = (0..HOT_KEYS)
+        .map(|i| format!("hot_key_{:04}", i).into_bytes())
+        .collect();
+
+    let mut group = c.benchmark_group("hot_key_reads");
+    for cache_bytes in [0, 1024 * 1024] {
+        let (_temp, engine) = engine_with_hot_keys(cache_bytes);
+
+        let id = BenchmarkId::new("block_cache_bytes", cache_bytes);
+        group.bench_with_input(id, &keys, |b, keys| {
+            b.iter(|| {
+                for key in keys {
+                    engine.get(key).unwrap();
+                }
+            })
+        });
+
+        if let Some(stats) = engine.block_cache_stats() {
+            // Only the first read of each key should reach the file
+            assert_eq!(stats.misses, HOT_KEYS as u64);
+            println!("block cache: {} hits, {} misses", stats.hits, stats.misses);
+        }
+    }
+    group.finish();
+}
+
+/// Reading a 1 MB hot value from the MemTable: copied by `get`, shared by `get_arc`
+fn hot_value_reads(c: &mut Criterion) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryNEntries { count: 1000 })
+        .build();
+    let engine = Engine::open(config).unwrap();
+    engine.put(b"hot", &vec![0xAB; 1024 * 1024]).unwrap();
+
+    let (allocations, bytes) = count_allocations(|| engine.get(b"hot").unwrap());
+    println!("get:     {} allocations, {} bytes", allocations, bytes);
+    let (allocations, bytes) = count_allocations(|| engine.get_arc(b"hot").unwrap());
+    println!("get_arc: {} allocations, {} bytes", allocations, bytes);
+
+    let mut group = c.benchmark_group("hot_value_reads");
+    group.bench_function("get", |b| b.iter(|| engine.get(b"hot").unwrap()));
+    group.bench_function("get_arc", |b| b.iter(|| engine.get_arc(b"hot").unwrap()));
+    group.finish();
+}
+
+/// Flushing a 4 MB MemTable, with the default and a 1 MB write buffer
+fn memtable_flush(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memtable_flush");
+    group.sample_size(10);
+    for buffer_bytes in [DEFAULT_IO_BUFFER_BYTES, 1024 * 1024] {
+        let id = BenchmarkId::new("write_buffer_bytes", buffer_bytes);
+        group.bench_function(id, |b| {
+            b.iter_batched(
+                || {
+                    let temp_dir = TempDir::new().unwrap();
+                    let config = Config::builder()
+                        .data_dir(temp_dir.path())
+                        .wal_sync_strategy(WalSyncStrategy::EveryNEntries { count: 1000 })
+                        .write_buffer_bytes(buffer_bytes)
+                        .build();
+                    let engine = Engine::open(config).unwrap();
+                    for i in 0..1024 {
+                        let key = format!("key_{:05}", i);
+                        engine.put(key.as_bytes(), &[0xAB; 4096]).unwrap();
+                    }
+                    (temp_dir, engine)
+                },
+                // Hand both back so Criterion drops them outside the timing
+                |(temp_dir, engine)| {
+                    engine.flush().unwrap();
+                    (temp_dir, engine)
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Opening 200 SSTables: one after another vs `StorageManager`, which
+/// loads indexes in parallel (read-only, so the manifest isn't rewritten)
+fn sstable_open(c: &mut Criterion) {
+    let temp_dir = TempDir::new().unwrap();
+    {
+        let manager = StorageManager::open(temp_dir.path()).unwrap();
+        for table in 0..200 {
+            let memtable = MemTable::new();
+            for i in 0..500 {
+                let key = format!("key_{:03}_{:05}", table, i);
+                memtable.put(key.into_bytes(), vec![0xAB; 64]);
+            }
+            manager.flush(&memtable).unwrap();
+        }
+    }
+
+    let mut group = c.benchmark_group("sstable_open");
+    group.sample_size(10);
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            for entry in fs::read_dir(temp_dir.path()).unwrap() {
+                let path = entry.unwrap().path();
+                if path.extension().is_some_and(|ext| ext == "sst") {
+                    SSTableReader::open(&path).unwrap();
+                }
+            }
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| StorageManager::open_read_only(temp_dir.path()).unwrap())
+    });
+    group.finish();
+}
+
+/// Single puts to random keys, per value size and WAL sync strategy
+fn random_writes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("random_writes");
+    configure_write_group(&mut group);
+    for (sync_name, sync) in SYNC_STRATEGIES {
+        for value_size in VALUE_SIZES {
+            let (_temp, engine) = throughput_engine(sync);
+            let value = vec![0xAB; value_size];
+            let mut keys = Xorshift(0x9E37_79B9_7F4A_7C15);
+
+            group.throughput(Throughput::Bytes(value_size as u64));
+            let mut timer = WriteTimer::new(&engine, value_size);
+            group.bench_function(BenchmarkId::new(sync_name, value_size), |b| {
+                b.iter_custom(|iters| {
+                    timer.run(iters, || {
+                        engine.put(&bench_key(keys.next() % KEY_COUNT), &value).unwrap()
+                    })
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Single puts in ascending key order, per value size and WAL sync strategy
+fn sequential_writes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sequential_writes");
+    configure_write_group(&mut group);
+    for (sync_name, sync) in SYNC_STRATEGIES {
+        for value_size in VALUE_SIZES {
+            let (_temp, engine) = throughput_engine(sync);
+            let value = vec![0xAB; value_size];
+            let mut next_key = 0;
+
+            group.throughput(Throughput::Bytes(value_size as u64));
+            let mut timer = WriteTimer::new(&engine, value_size);
+            group.bench_function(BenchmarkId::new(sync_name, value_size), |b| {
+                b.iter_custom(|iters| {
+                    timer.run(iters, || {
+                        engine.put(&bench_key(next_key), &value).unwrap();
+                        next_key += 1;
+                    })
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Single gets of random keys, per value size: hot keys sit in the
+/// MemTable, cold ones only in an SSTable (with no value cache; the OS
+/// page cache may still hold the file)
+fn random_reads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("random_reads");
+    for value_size in VALUE_SIZES {
+        for temperature in ["hot", "cold"] {
+            let (_temp, engine) = throughput_engine(WalSyncStrategy::NoSync);
+            let value = vec![0xAB; value_size];
+            for i in 0..KEY_COUNT {
+                engine.put(&bench_key(i), &value).unwrap();
+            }
+            if temperature == "cold" {
+                engine.flush().unwrap();
+            }
+            let mut keys = Xorshift(0x9E37_79B9_7F4A_7C15);
+
+            group.throughput(Throughput::Bytes(value_size as u64));
+            group.bench_function(BenchmarkId::new(temperature, value_size), |b| {
+                b.iter(|| engine.get(&bench_key(keys.next() % KEY_COUNT)).unwrap().unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Random keys, half read and half written, per value size and WAL sync
+/// strategy; every key starts out flushed, so reads hit both the MemTable
+/// and the SSTables
+fn mixed_workload(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mixed_50_50");
+    configure_write_group(&mut group);
+    for (sync_name, sync) in SYNC_STRATEGIES {
+        for value_size in VALUE_SIZES {
+            let (_temp, engine) = throughput_engine(sync);
+            let value = vec![0xAB; value_size];
+            for i in 0..KEY_COUNT {
+                engine.put(&bench_key(i), &value).unwrap();
+            }
+            engine.flush().unwrap();
+            let mut keys = Xorshift(0x9E37_79B9_7F4A_7C15);
+
+            group.throughput(Throughput::Bytes(value_size as u64));
+            let mut timer = WriteTimer::new(&engine, value_size);
+            group.bench_function(BenchmarkId::new(sync_name, value_size), |b| {
+                b.iter_custom(|iters| {
+                    timer.run(iters, || {
+                        let pick = keys.next();
+                        let key = bench_key(pick % KEY_COUNT);
+                        if pick >> 63 == 0 {
+                            engine.get(&key).unwrap();
+                        } else {
+                            engine.put(&key, &value).unwrap();
+                        }
+                    })
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Flushing a `FLUSH_AT` MemTable, per value size
+fn flush_latency(c: &mut Criterion) {
+    let mut group = c.benchmark_group("flush_latency");
+    group.sample_size(10);
+    for value_size in VALUE_SIZES {
+        group.throughput(Throughput::Bytes(FLUSH_AT as u64));
+        group.bench_function(BenchmarkId::from_parameter(value_size), |b| {
+            b.iter_batched(
+                || {
+                    let (temp_dir, engine) = throughput_engine(WalSyncStrategy::NoSync);
+                    let value = vec![0xAB; value_size];
+                    let mut i = 0;
+                    while engine.memtable_size() < FLUSH_AT {
+                        engine.put(&bench_key(i), &value).unwrap();
+                        i += 1;
+                    }
+                    (temp_dir, engine)
+                },
+                // Hand both back so Criterion drops them outside the timing
+                |(temp_dir, engine)| {
+                    engine.flush().unwrap();
+                    (temp_dir, engine)
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
 }
 
-criterion_group!(benches, storage_benchmarks);
+criterion_group!(
+    benches,
+    random_writes,
+    sequential_writes,
+    random_reads,
+    mixed_workload,
+    flush_latency,
+    hot_key_reads,
+    hot_value_reads,
+    memtable_flush,
+    sstable_open
+);
 criterion_main!(benches);
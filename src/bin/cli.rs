@@ -10,15 +10,44 @@
 //! connection abort errors (OS error 10053) on Windows due to the OS-level
 //! socket shutdown affecting all cloned handles.
 
-use std::io::{BufReader, Write};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
 use std::net::{Shutdown, TcpStream};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use atlaskv::dump::{DumpReader, DumpWriter};
 use atlaskv::protocol::{
     Command, Response, Status,
+    decode_entries, decode_get_meta_response, decode_keys, decode_version_response,
     encode_command, read_response,
 };
+use atlaskv::storage::{sync_dir, SSTableBuilder, SSTableReader};
+use atlaskv::Client;
+
+/// Entries fetched per SCANREV request while dumping
+const DUMP_PAGE_SIZE: u32 = 1000;
+
+/// Longest wait between connection attempts, however many have failed
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Standard base64 alphabet (RFC 4648)
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// How `get`, `getset`, and `getmeta` render values
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// Text, warning on stderr if the value isn't valid UTF-8
+    Utf8,
+    /// Lowercase hex
+    Hex,
+    /// Standard base64 with padding
+    Base64,
+    /// The value's bytes as-is, with no trailing newline (for piping)
+    Raw,
+}
 
 /// AtlasKV CLI
 #[derive(Parser, Debug)]
@@ -34,6 +63,26 @@ struct Args {
     #[arg(short, long, default_value = "5000")]
     timeout: u64,
 
+    /// Shared secret to authenticate with (servers started with --auth)
+    #[arg(long)]
+    auth: Option<String>,
+
+    /// Times to retry connecting before giving up (default: no retries)
+    #[arg(long, default_value = "0")]
+    retry: u32,
+
+    /// Delay before the first retry in milliseconds (doubles each retry)
+    #[arg(long, default_value = "100")]
+    retry_delay_ms: u64,
+
+    /// Print a message for each failed connection attempt
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Encoding for printed values
+    #[arg(long, value_enum, default_value = "utf8")]
+    output: OutputFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -46,6 +95,12 @@ enum Commands {
         key: String,
     },
 
+    /// Get a value and when it was last written
+    Getmeta {
+        /// The key to get
+        key: String,
+    },
+
     /// Set a key-value pair
     Set {
         /// The key to set
@@ -55,6 +110,33 @@ enum Commands {
         value: String,
     },
 
+    /// Append to the value at a key
+    Append {
+        /// The key to append to
+        key: String,
+
+        /// The data to append
+        value: String,
+    },
+
+    /// Set a key and print its previous value
+    Getset {
+        /// The key to set
+        key: String,
+
+        /// The new value
+        value: String,
+    },
+
+    /// Set a key only if it doesn't exist yet
+    Setnx {
+        /// The key to set
+        key: String,
+
+        /// The value to set
+        value: String,
+    },
+
     /// Delete a key
     Del {
         /// The key to delete
@@ -63,6 +145,75 @@ enum Commands {
 
     /// Ping the server
     Ping,
+
+    /// Check the server can still write to its storage
+    Health,
+
+    /// Show the server's version and the format versions it writes
+    VersionRemote,
+
+    /// Force the server to flush its MemTable to disk (admin)
+    Flush,
+
+    /// List per-SSTable statistics, newest first (admin)
+    Sstables,
+
+    /// Print engine statistics as JSON (admin)
+    Stats,
+
+    /// List entries from the highest key down
+    ScanRev {
+        /// Lowest key to include
+        start: String,
+
+        /// Stop before this key (default: no upper bound)
+        end: Option<String>,
+
+        /// Maximum number of entries to return
+        #[arg(short, long, default_value = "10")]
+        limit: u32,
+    },
+
+    /// List keys (without values) from the lowest key up
+    ScanKeys {
+        /// Lowest key to include
+        start: String,
+
+        /// Stop before this key (default: no upper bound)
+        end: Option<String>,
+
+        /// Maximum number of keys to return
+        #[arg(short, long, default_value = "10")]
+        limit: u32,
+    },
+
+    /// Count live keys from `start` up, without listing them
+    Count {
+        /// Lowest key to include
+        start: String,
+
+        /// Stop before this key (default: no upper bound)
+        end: Option<String>,
+    },
+
+    /// Write every live entry to a dump file (portable backup)
+    Dump {
+        /// File to write
+        file: String,
+    },
+
+    /// Load a dump file written by `dump`, overwriting matching keys
+    Restore {
+        /// File to read
+        file: String,
+    },
+
+    /// Rewrite an SSTable with a corrupt index from its data blocks (offline:
+    /// stop the server first)
+    RepairSstable {
+        /// SSTable file to repair in place
+        path: PathBuf,
+    },
 }
 
 fn main() {
@@ -73,21 +224,57 @@ fn main() {
         Commands::Get { key } => Command::Get {
             key: key.as_bytes().to_vec(),
         },
+        Commands::Getmeta { key } => Command::GetMeta {
+            key: key.as_bytes().to_vec(),
+        },
         Commands::Set { key, value } => Command::Put {
             key: key.as_bytes().to_vec(),
             value: value.as_bytes().to_vec(),
         },
+        Commands::Append { key, value } => Command::Append {
+            key: key.as_bytes().to_vec(),
+            data: value.as_bytes().to_vec(),
+        },
+        Commands::Getset { key, value } => Command::GetSet {
+            key: key.as_bytes().to_vec(),
+            value: value.as_bytes().to_vec(),
+        },
+        Commands::Setnx { key, value } => Command::SetNx {
+            key: key.as_bytes().to_vec(),
+            value: value.as_bytes().to_vec(),
+        },
         Commands::Del { key } => Command::Delete {
             key: key.as_bytes().to_vec(),
         },
         Commands::Ping => Command::Ping,
+        Commands::Health => Command::Health,
+        Commands::VersionRemote => Command::Version,
+        Commands::Flush => Command::Flush,
+        Commands::Sstables => Command::SsTables,
+        Commands::Stats => Command::StatsJson,
+        Commands::ScanRev { start, end, limit } => Command::ScanRev {
+            start: start.as_bytes().to_vec(),
+            end: end.as_deref().unwrap_or("").as_bytes().to_vec(),
+            limit: *limit,
+        },
+        Commands::ScanKeys { start, end, limit } => Command::ScanKeys {
+            start: start.as_bytes().to_vec(),
+            end: end.as_deref().unwrap_or("").as_bytes().to_vec(),
+            limit: *limit,
+        },
+        Commands::Count { start, end } => Command::Count {
+            start: start.as_bytes().to_vec(),
+            end: end.as_deref().unwrap_or("").as_bytes().to_vec(),
+        },
+        Commands::Dump { file } => return run_dump(&args, file),
+        Commands::Restore { file } => return run_restore(&args, file),
+        Commands::RepairSstable { path } => return run_repair_sstable(path),
     };
 
     // Connect to server
-    let mut stream = match TcpStream::connect_timeout(
-        &args.server.parse().expect("Invalid server address"),
-        Duration::from_millis(args.timeout),
-    ) {
+    let addr = args.server.parse().expect("Invalid server address");
+    let timeout = Duration::from_millis(args.timeout);
+    let mut stream = match with_retries(&args, || TcpStream::connect_timeout(&addr, timeout)) {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Failed to connect to {}: {}", args.server, e);
@@ -114,8 +301,37 @@ fn main() {
     // stream in a BufReader only for reading the response. This is the same
     // pattern used by Redis clients (redis-cli, mini-redis).
 
-    // Step 1: Write command bytes directly to the stream
-    let cmd_bytes = encode_command(&command);
+    // Authenticate first if asked; a rejected token ends the session
+    if let Some(token) = &args.auth {
+        let auth = Command::Auth {
+            token: token.as_bytes().to_vec(),
+        };
+        let response = round_trip(&mut stream, &auth);
+        if response.status != Status::Ok {
+            let message = response.payload.unwrap_or_default();
+            eprintln!("ERROR: {}", String::from_utf8_lossy(&message));
+            std::process::exit(1);
+        }
+    }
+
+    // Steps 1-2: Write the command, then read its response from the same stream
+    let response = round_trip(&mut stream, &command);
+
+    // Step 3: Half-close write side so the server's read loop sees EOF
+    // immediately instead of waiting for a read timeout. This is safe now
+    // because we've already received the response.
+    let _ = stream.shutdown(Shutdown::Write);
+    drop(stream);
+
+    // Handle response based on command
+    handle_response(&args.command, response, args.output);
+}
+
+/// Write one command directly to the stream and read its response
+///
+/// Exits the process on any I/O or decode error.
+fn round_trip(stream: &mut TcpStream, command: &Command) -> Response {
+    let cmd_bytes = encode_command(command);
     if let Err(e) = stream.write_all(&cmd_bytes) {
         eprintln!("Failed to send command: {}", e);
         std::process::exit(1);
@@ -125,48 +341,93 @@ fn main() {
         std::process::exit(1);
     }
 
-    // Step 2: Read response from the same stream
-    let mut reader = BufReader::new(&stream);
-    let response = match read_response(&mut reader) {
+    // Exactly one response frame is read, so nothing is lost with the BufReader
+    let mut reader = BufReader::new(&*stream);
+    match read_response(&mut reader) {
         Ok(r) => r,
         Err(e) => {
             eprintln!("Failed to read response: {}", e);
             std::process::exit(1);
         }
-    };
-
-    // Step 3: Half-close write side so the server's read loop sees EOF
-    // immediately instead of waiting for a read timeout. This is safe now
-    // because we've already received the response.
-    let _ = stream.shutdown(Shutdown::Write);
-    drop(reader);
-    drop(stream);
-
-    // Handle response based on command
-    handle_response(&args.command, response);
+    }
 }
 
-fn handle_response(cmd: &Commands, response: Response) {
+fn handle_response(cmd: &Commands, response: Response, output: OutputFormat) {
     match response.status {
         Status::Ok => {
             match cmd {
-                Commands::Get { .. } => {
+                Commands::Get { .. } | Commands::Getset { .. } => {
                     if let Some(value) = response.payload {
-                        // Try to print as UTF-8, fall back to hex
-                        match String::from_utf8(value.clone()) {
-                            Ok(s) => println!("{}", s),
-                            Err(_) => println!("{:?}", value),
-                        }
+                        print_value(&value, output);
                     } else {
                         println!("(nil)");
                     }
                 }
+                Commands::Getmeta { .. } => {
+                    let Some(payload) = response.payload else {
+                        println!("(nil)");
+                        return;
+                    };
+                    match decode_get_meta_response(&payload) {
+                        Ok((value, timestamp)) => {
+                            print_value(&value, output);
+                            println!("(written at {} ms)", timestamp);
+                        }
+                        Err(e) => eprintln!("Invalid GETMETA response: {}", e),
+                    }
+                }
                 Commands::Set { .. } => {
                     println!("OK");
                 }
+                Commands::Append { .. } | Commands::Count { .. } => {
+                    // Payload is the new length (or the count) as a big-endian u64
+                    match response.payload.as_deref().map(<[u8; 8]>::try_from) {
+                        Some(Ok(n)) => println!("(integer) {}", u64::from_be_bytes(n)),
+                        _ => println!("OK"),
+                    }
+                }
+                Commands::Setnx { .. } => {
+                    // Payload is 1 if the key was set, 0 if it already existed
+                    let written = response.payload.as_deref() == Some(&[1]);
+                    println!("(integer) {}", written as u8);
+                }
                 Commands::Del { .. } => {
+                    // Payload is 1 if the key had a live value, 0 if not
+                    let existed = response.payload.as_deref() == Some(&[1]);
+                    println!("(integer) {}", existed as u8);
+                }
+                Commands::Flush => {
                     println!("OK");
                 }
+                Commands::Sstables => {
+                    print_sstable_table(response.payload.as_deref().unwrap_or(&[]));
+                }
+                Commands::Stats | Commands::Health => {
+                    let json = response.payload.unwrap_or_default();
+                    println!("{}", String::from_utf8_lossy(&json));
+                }
+                Commands::VersionRemote => {
+                    match decode_version_response(response.payload.as_deref().unwrap_or(&[])) {
+                        Ok(info) => {
+                            println!("AtlasKV {}", info.version);
+                            println!("protocol version: {}", info.proto_version);
+                            println!("sstable format:   {}", info.sstable_format);
+                            println!("wal format:       {}", info.wal_format);
+                        }
+                        Err(e) => eprintln!("Invalid VERSION response: {}", e),
+                    }
+                }
+                Commands::ScanRev { .. } => {
+                    print_entries(response.payload.as_deref().unwrap_or(&[]));
+                }
+                Commands::ScanKeys { .. } => {
+                    print_keys(response.payload.as_deref().unwrap_or(&[]));
+                }
+                Commands::Dump { .. }
+                | Commands::Restore { .. }
+                | Commands::RepairSstable { .. } => {
+                    unreachable!("dump, restore and repair don't go through handle_response")
+                }
                 Commands::Ping => {
                     if let Some(value) = response.payload {
                         match String::from_utf8(value) {
@@ -195,3 +456,364 @@ fn handle_response(cmd: &Commands, response: Response) {
         }
     }
 }
+
+/// Call `connect` until it succeeds, retrying up to `--retry` times
+///
+/// Waits `backoff_delay` between attempts; `--verbose` reports each failure.
+fn with_retries<T, E: std::fmt::Display>(
+    args: &Args,
+    mut connect: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let base = Duration::from_millis(args.retry_delay_ms);
+    let mut attempt = 0;
+    loop {
+        match connect() {
+            Ok(conn) => return Ok(conn),
+            Err(e) if attempt < args.retry => {
+                let delay = backoff_delay(base, attempt);
+                if args.verbose {
+                    eprintln!(
+                        "Failed to connect to {}: {} (retry {}/{} in {} ms)",
+                        args.server,
+                        e,
+                        attempt + 1,
+                        args.retry,
+                        delay.as_millis()
+                    );
+                }
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Wait before retry number `attempt` (0-based): `base` doubled per
+/// earlier retry, capped at `MAX_RETRY_DELAY`
+fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+    base.saturating_mul(factor).min(MAX_RETRY_DELAY)
+}
+
+/// Open a typed client, authenticating first if `--auth` was given
+fn connect_client(args: &Args) -> Client {
+    let timeout = Duration::from_millis(args.timeout);
+    let mut client = with_retries(args, || Client::connect_timeout(args.server.as_str(), timeout))
+        .unwrap_or_else(|e| fail(&format!("Failed to connect to {}: {}", args.server, e)));
+
+    if let Some(token) = &args.auth {
+        if let Err(e) = client.auth(token.as_bytes()) {
+            fail(&format!("ERROR: {}", e));
+        }
+    }
+    client
+}
+
+/// Page through the server with SCANREV and write the entries as a dump
+fn run_dump(args: &Args, path: &str) {
+    let mut client = connect_client(args);
+
+    // Each page ends just below the lowest key of the one before
+    let mut entries = Vec::new();
+    let mut end: Option<Vec<u8>> = None;
+    loop {
+        let page = client
+            .scan_rev(b"", end.as_deref(), DUMP_PAGE_SIZE)
+            .unwrap_or_else(|e| fail(&format!("Failed to scan: {}", e)));
+        let full = page.len() == DUMP_PAGE_SIZE as usize;
+        end = page.last().map(|(key, _)| key.clone());
+        entries.extend(page);
+
+        // Nothing sorts below the empty key (and an empty end means no bound)
+        if !full || end.as_deref() == Some(b"") {
+            break;
+        }
+    }
+    entries.reverse();
+
+    let file = File::create(path)
+        .unwrap_or_else(|e| fail(&format!("Failed to create {}: {}", path, e)));
+    let result = DumpWriter::new(BufWriter::new(file)).and_then(|mut dump| {
+        for (key, value) in &entries {
+            dump.write_entry(key, value)?;
+        }
+        dump.finish()?.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        Ok(())
+    });
+    if let Err(e) = result {
+        fail(&format!("Failed to write {}: {}", path, e));
+    }
+
+    println!("Dumped {} entries to {}", entries.len(), path);
+}
+
+/// Read a whole dump (verifying it) and PUT each entry to the server
+fn run_restore(args: &Args, path: &str) {
+    let file = File::open(path)
+        .unwrap_or_else(|e| fail(&format!("Failed to open {}: {}", path, e)));
+    let entries = DumpReader::new(BufReader::new(file))
+        .and_then(|dump| dump.collect::<atlaskv::Result<Vec<_>>>())
+        .unwrap_or_else(|e| fail(&format!("Failed to read {}: {}", path, e)));
+
+    let mut client = connect_client(args);
+    for (key, value) in &entries {
+        if let Err(e) = client.put(key, value) {
+            fail(&format!("Failed to restore {}: {}", String::from_utf8_lossy(key), e));
+        }
+    }
+
+    println!("Restored {} entries from {}", entries.len(), path);
+}
+
+/// Rebuild an SSTable's index from its data blocks and write a clean copy
+/// over it
+///
+/// The copy is written beside the original, synced, then renamed over it,
+/// so a failure part way leaves the original untouched.
+fn run_repair_sstable(path: &Path) {
+    let mut reader = SSTableReader::rebuild_index(path)
+        .unwrap_or_else(|e| fail(&format!("Failed to read {}: {}", path.display(), e)));
+
+    let tmp = path.with_extension("sst.tmp");
+    let result = SSTableBuilder::new(&tmp).and_then(|mut builder| {
+        for entry in reader.iter_with_seqs()? {
+            let (key, value, meta) = entry?;
+            builder.add_entry(&key, value.as_deref(), meta)?;
+        }
+        builder.finish()
+    });
+    let sstable = result
+        .and_then(|sstable| {
+            fs::rename(&tmp, path)?;
+            sync_dir(path.parent().unwrap_or(Path::new(".")))?;
+            Ok(sstable)
+        })
+        .unwrap_or_else(|e| {
+            let _ = fs::remove_file(&tmp);
+            fail(&format!("Failed to rewrite {}: {}", path.display(), e))
+        });
+
+    println!("Recovered {} entries into {}", sstable.entry_count, path.display());
+}
+
+/// Print an error and exit
+/// Print a value to stdout in the `--output` encoding
+fn print_value(value: &[u8], output: OutputFormat) {
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = write_value(&mut stdout, value, output).and_then(|()| stdout.flush()) {
+        fail(&format!("Failed to write value: {}", e));
+    }
+}
+
+/// Write a value in the given encoding, newline-terminated unless `Raw`
+fn write_value(out: &mut impl Write, value: &[u8], output: OutputFormat) -> io::Result<()> {
+    match output {
+        OutputFormat::Utf8 => match std::str::from_utf8(value) {
+            Ok(s) => writeln!(out, "{}", s),
+            Err(_) => {
+                eprintln!("warning: value is not valid UTF-8 (try --output hex or base64)");
+                writeln!(out, "{}", String::from_utf8_lossy(value))
+            }
+        },
+        OutputFormat::Hex => writeln!(out, "{}", encode_hex(value)),
+        OutputFormat::Base64 => writeln!(out, "{}", encode_base64(value)),
+        OutputFormat::Raw => out.write_all(value),
+    }
+}
+
+/// Lowercase hex, two digits per byte
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Standard base64 (RFC 4648), padded with `=`
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+/// Print the SSTABLES payload (tab-separated lines) as an aligned table
+fn print_sstable_table(payload: &[u8]) {
+    let text = String::from_utf8_lossy(payload);
+    let rows: Vec<Vec<&str>> = text
+        .lines()
+        .map(|line| line.split('\t').collect())
+        .collect();
+
+    if rows.is_empty() {
+        println!("(no sstables)");
+        return;
+    }
+
+    let header = ["ID", "ENTRIES", "SIZE", "MIN_KEY", "MAX_KEY"];
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let print_row = |cells: &[&str]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+
+    print_row(&header);
+    for row in &rows {
+        print_row(row);
+    }
+}
+
+/// Print scan results as numbered `key => value` lines
+fn print_keys(payload: &[u8]) {
+    let keys = match decode_keys(payload) {
+        Ok(keys) => keys,
+        Err(e) => {
+            eprintln!("Failed to decode keys: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if keys.is_empty() {
+        println!("(empty)");
+        return;
+    }
+
+    for (i, key) in keys.iter().enumerate() {
+        println!("{}) {}", i + 1, String::from_utf8_lossy(key));
+    }
+}
+
+fn print_entries(payload: &[u8]) {
+    let entries = match decode_entries(payload) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Failed to decode entries: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if entries.is_empty() {
+        println!("(empty)");
+        return;
+    }
+
+    for (i, (key, value)) in entries.iter().enumerate() {
+        println!(
+            "{}) {} => {}",
+            i + 1,
+            String::from_utf8_lossy(key),
+            String::from_utf8_lossy(value)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay_doubles() {
+        let base = Duration::from_millis(100);
+        let delays: Vec<_> = (0..5).map(|attempt| backoff_delay(base, attempt)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 800, 1600].map(Duration::from_millis).to_vec()
+        );
+    }
+
+    #[test]
+    fn test_backoff_delay_capped() {
+        let base = Duration::from_millis(100);
+        assert_eq!(backoff_delay(base, 8), Duration::from_millis(25_600));
+        assert_eq!(backoff_delay(base, 9), MAX_RETRY_DELAY);
+
+        // Shifts past the width of u32 saturate rather than wrap
+        assert_eq!(backoff_delay(base, 40), MAX_RETRY_DELAY);
+        assert_eq!(backoff_delay(Duration::ZERO, 40), Duration::ZERO);
+    }
+
+    /// Render `value` with `write_value`, returning the bytes written
+    fn rendered(value: &[u8], output: OutputFormat) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_value(&mut out, value, output).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_write_value_encodings() {
+        // NUL, a non-UTF-8 byte, a newline, and a letter
+        let value = [0x00, 0xff, b'\n', b'a'];
+
+        assert_eq!(rendered(&value, OutputFormat::Hex), b"00ff0a61\n");
+        assert_eq!(rendered(&value, OutputFormat::Base64), b"AP8KYQ==\n");
+        assert_eq!(rendered(&value, OutputFormat::Raw), value);
+        assert_eq!(rendered(&value, OutputFormat::Utf8), "\0\u{fffd}\na\n".as_bytes());
+        assert_eq!(rendered(b"caf\xc3\xa9", OutputFormat::Utf8), "café\n".as_bytes());
+    }
+
+    #[test]
+    fn test_encode_base64_padding() {
+        let cases = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(encode_base64(input.as_bytes()), expected);
+        }
+    }
+
+    #[test]
+    fn test_output_flag_parses() {
+        let args = Args::parse_from(["atlaskv-cli", "--output", "base64", "get", "key"]);
+        assert_eq!(args.output, OutputFormat::Base64);
+
+        let args = Args::parse_from(["atlaskv-cli", "get", "key"]);
+        assert_eq!(args.output, OutputFormat::Utf8);
+    }
+
+    #[test]
+    fn test_retries_until_connect_succeeds() {
+        let args =
+            Args::parse_from(["atlaskv-cli", "--retry", "3", "--retry-delay-ms", "1", "ping"]);
+
+        let mut calls = 0;
+        let result = with_retries(&args, || {
+            calls += 1;
+            if calls < 3 { Err("refused") } else { Ok(calls) }
+        });
+        assert_eq!(result, Ok(3));
+
+        // Gives up after the first attempt plus `--retry` retries
+        let mut calls = 0;
+        let result: Result<(), _> = with_retries(&args, || {
+            calls += 1;
+            Err("refused")
+        });
+        assert_eq!(result, Err("refused"));
+        assert_eq!(calls, 4);
+    }
+}
@@ -26,9 +26,33 @@ struct Args {
     #[arg(short, long, default_value = "1024")]
     max_connections: usize,
 
+    /// Worker threads serving connections (default: one per CPU)
+    #[arg(short, long)]
+    workers: Option<usize>,
+
+    /// Listen backlog (pending connection queue length)
+    #[arg(long, default_value = "1024")]
+    backlog: i32,
+
+    /// Enable SO_REUSEPORT (multiple acceptors on the same port)
+    #[arg(long)]
+    reuse_port: bool,
+
     /// MemTable size limit in MB before flush
     #[arg(short = 'm', long, default_value = "64")]
     memtable_mb: usize,
+
+    /// Reject writes once SSTables + WAL + MemTable reach this many MB
+    #[arg(long)]
+    max_total_mb: Option<u64>,
+
+    /// Serve Prometheus metrics on this address (host:port), e.g. 127.0.0.1:9100
+    #[arg(long)]
+    metrics_addr: Option<String>,
+
+    /// Require clients to send AUTH with this shared secret first
+    #[arg(long)]
+    auth: Option<String>,
 }
 
 fn main() {
@@ -49,12 +73,26 @@ fn main() {
     tracing::info!("Listen address: {}", args.listen);
 
     // Build config from args
-    let config = Config::builder()
+    let mut builder = Config::builder()
         .data_dir(&args.data_dir)
         .listen_addr(&args.listen)
         .max_connections(args.max_connections)
-        .memtable_size_limit(args.memtable_mb * 1024 * 1024)
-        .build();
+        .listen_backlog(args.backlog)
+        .reuse_port(args.reuse_port)
+        .memtable_size_limit(args.memtable_mb * 1024 * 1024);
+    if let Some(workers) = args.workers {
+        builder = builder.worker_threads(workers);
+    }
+    if let Some(mb) = args.max_total_mb {
+        builder = builder.max_total_bytes(mb * 1024 * 1024);
+    }
+    if let Some(addr) = &args.metrics_addr {
+        builder = builder.metrics_addr(addr);
+    }
+    if let Some(token) = &args.auth {
+        builder = builder.auth_token(token);
+    }
+    let config = builder.build();
 
     // Open engine
     let engine = match Engine::open(config.clone()) {
@@ -0,0 +1,332 @@
+//! Client
+//!
+//! Typed, blocking client for the AtlasKV wire protocol.
+//!
+//! ## Connection Handling
+//!
+//! One TCP stream per `Client`, used with sequential write-then-read like
+//! the CLI: each call encodes its command, writes it, then reads exactly
+//! one response. The stream is never cloned into separate reader/writer
+//! handles (see the CLI module docs for why that breaks on Windows).
+//! Calls take `&mut self`, so a `Client` is not shared between threads;
+//! open one per thread instead.
+//!
+//! ## Frame CRCs
+//!
+//! `connect_with_crc` negotiates `CAP_FRAME_CRC` in a HELLO handshake, so
+//! every later frame carries a CRC32 and corruption on the wire surfaces as
+//! `AtlasError::Protocol("crc mismatch")` instead of a garbled value.
+
+use std::io::{BufReader, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::error::{AtlasError, Result};
+use crate::protocol::{
+    decode_entries, decode_get_meta_response, decode_hello_response, decode_keys,
+    decode_scan_page, decode_version_response, encode_command, encode_command_with_crc,
+    read_response, read_response_with_crc, Command, Response, ScanPage, ServerVersion, Status,
+    CAP_FRAME_CRC, PROTOCOL_VERSION,
+};
+
+/// Default connect/read/write timeout
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Entries requested per SCANCURSOR page by `scan`
+const SCAN_PAGE_LIMIT: u32 = 256;
+
+/// Blocking client holding one connection to a server
+pub struct Client {
+    /// Connected stream (written directly, read through a per-call BufReader)
+    stream: TcpStream,
+
+    /// Server address (for error messages)
+    addr: SocketAddr,
+
+    /// Frames carry a trailing CRC (negotiated by `connect_with_crc`)
+    frame_crc: bool,
+}
+
+impl Client {
+    /// Connect to a server using `DEFAULT_TIMEOUT`
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self> {
+        Self::connect_timeout(addr, DEFAULT_TIMEOUT)
+    }
+
+    /// Connect to a server with an explicit connect/read/write timeout
+    pub fn connect_timeout(addr: impl ToSocketAddrs, timeout: Duration) -> Result<Self> {
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| AtlasError::Network("No address resolved".to_string()))?;
+
+        let stream = TcpStream::connect_timeout(&addr, timeout)
+            .map_err(|e| AtlasError::Network(format!("Failed to connect to {}: {}", addr, e)))?;
+
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        // Disable Nagle's algorithm for immediate sends
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream,
+            addr,
+            frame_crc: false,
+        })
+    }
+
+    /// Connect using `DEFAULT_TIMEOUT` and enable frame CRCs
+    ///
+    /// Fails with `AtlasError::Protocol` if the server doesn't enable them.
+    pub fn connect_with_crc(addr: impl ToSocketAddrs) -> Result<Self> {
+        let mut client = Self::connect(addr)?;
+
+        let payload = client.call(&Command::Hello {
+            proto_version: PROTOCOL_VERSION,
+            features: CAP_FRAME_CRC,
+        })?;
+        let (_, capabilities) = decode_hello_response(&payload.unwrap_or_default())?;
+        if capabilities & CAP_FRAME_CRC == 0 {
+            return Err(AtlasError::Protocol(format!(
+                "Server at {} did not enable frame CRCs",
+                client.addr
+            )));
+        }
+
+        client.frame_crc = true;
+        Ok(client)
+    }
+
+    /// Authenticate with the server's shared secret
+    ///
+    /// Must be the first call on a server with `auth_token` set. On a wrong
+    /// token the server closes the connection.
+    pub fn auth(&mut self, token: &[u8]) -> Result<()> {
+        self.call(&Command::Auth {
+            token: token.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Get a value by key (`None` if absent)
+    pub fn get(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.call(&Command::Get { key: key.to_vec() })
+    }
+
+    /// Get a value and its last-write unix-millis timestamp (`None` if absent)
+    pub fn get_with_meta(&mut self, key: &[u8]) -> Result<Option<(Vec<u8>, u64)>> {
+        let payload = self.call(&Command::GetMeta { key: key.to_vec() })?;
+        payload.map(|payload| decode_get_meta_response(&payload)).transpose()
+    }
+
+    /// Put a key-value pair
+    pub fn put(&mut self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.call(&Command::Put {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Put a key-value pair only if the key has no live value
+    ///
+    /// Returns whether the value was written.
+    pub fn put_if_absent(&mut self, key: &[u8], value: &[u8]) -> Result<bool> {
+        let payload = self.call(&Command::SetNx {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        })?;
+        Ok(payload.as_deref() == Some(&[1]))
+    }
+
+    /// Delete a key
+    ///
+    /// Returns whether the key had a live value.
+    pub fn delete(&mut self, key: &[u8]) -> Result<bool> {
+        let payload = self.call(&Command::Delete { key: key.to_vec() })?;
+        Ok(payload.as_deref() == Some(&[1]))
+    }
+
+    /// Check that the server is responding
+    pub fn ping(&mut self) -> Result<()> {
+        self.call(&Command::Ping)?;
+        Ok(())
+    }
+
+    /// Check that the server can still serve writes, returning its status line
+    pub fn health(&mut self) -> Result<String> {
+        let payload = self.call(&Command::Health)?.unwrap_or_default();
+        Ok(String::from_utf8_lossy(&payload).into_owned())
+    }
+
+    /// Ask which server build this is and which format versions it writes
+    ///
+    /// Unlike `ping`, this tells a tool whether the server's SSTable, WAL
+    /// and protocol versions are ones it understands.
+    pub fn version(&mut self) -> Result<ServerVersion> {
+        let payload = self.call(&Command::Version)?.unwrap_or_default();
+        decode_version_response(&payload)
+    }
+
+    /// Scan all entries in `[start, end)` from the lowest key up
+    ///
+    /// `end = None` means no upper bound. Pages through SCANCURSOR, so a long
+    /// range is several round trips and, like `scan_page`, not a snapshot:
+    /// writes landing between pages may or may not be seen.
+    pub fn scan(&mut self, start: &[u8], end: Option<&[u8]>) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let in_range = |key: &[u8]| end.is_none_or(|end| key < end);
+        let mut entries = Vec::new();
+
+        // Step 1: SCANCURSOR resumes strictly after a key, so fetch `start` itself
+        if !start.is_empty() && in_range(start) {
+            if let Some(value) = self.get(start)? {
+                entries.push((start.to_vec(), value));
+            }
+        }
+
+        // Step 2: Page on from `start` until the range or the keys run out
+        let mut after_key = (!start.is_empty()).then(|| start.to_vec());
+        loop {
+            let page = self.scan_page(after_key.as_deref(), SCAN_PAGE_LIMIT)?;
+            let Some((last_key, _)) = page.entries.last() else {
+                return Ok(entries);
+            };
+            let next_key = last_key.clone();
+
+            entries.extend(page.entries.into_iter().take_while(|(key, _)| in_range(key)));
+            if !page.more || !in_range(&next_key) {
+                return Ok(entries);
+            }
+            after_key = Some(next_key);
+        }
+    }
+
+    /// Scan keys in `[start, end)` from the highest down, up to `limit` entries
+    ///
+    /// `end = None` means no upper bound.
+    pub fn scan_rev(
+        &mut self,
+        start: &[u8],
+        end: Option<&[u8]>,
+        limit: u32,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let payload = self.call(&Command::ScanRev {
+            start: start.to_vec(),
+            end: end.unwrap_or_default().to_vec(),
+            limit,
+        })?;
+        decode_entries(&payload.unwrap_or_default())
+    }
+
+    /// List keys in `[start, end)` from the lowest up, up to `limit` keys
+    ///
+    /// `end = None` means no upper bound. Values are never sent.
+    pub fn scan_keys(
+        &mut self,
+        start: &[u8],
+        end: Option<&[u8]>,
+        limit: u32,
+    ) -> Result<Vec<Vec<u8>>> {
+        let payload = self.call(&Command::ScanKeys {
+            start: start.to_vec(),
+            end: end.unwrap_or_default().to_vec(),
+            limit,
+        })?;
+        decode_keys(&payload.unwrap_or_default())
+    }
+
+    /// Fetch one page of an ascending scan: up to `limit` entries with keys
+    /// strictly after `after_key` (`None` = from the first key)
+    ///
+    /// `more` tells whether entries remain; pass the last key returned as
+    /// the next `after_key` to continue.
+    pub fn scan_page(&mut self, after_key: Option<&[u8]>, limit: u32) -> Result<ScanPage> {
+        let payload = self.call(&Command::ScanCursor {
+            after_key: after_key.map(<[u8]>::to_vec),
+            limit,
+        })?;
+        decode_scan_page(&payload.unwrap_or_default())
+    }
+
+    /// [`scan_page`](Self::scan_page), with the server giving up after
+    /// `deadline` of its own time
+    ///
+    /// A page cut short has `deadline_exceeded` and `more` set, and may be
+    /// short or even empty; continue from its last key (or the same
+    /// `after_key` if it's empty) as usual.
+    pub fn scan_page_with_deadline(
+        &mut self,
+        after_key: Option<&[u8]>,
+        limit: u32,
+        deadline: Duration,
+    ) -> Result<ScanPage> {
+        let payload = self.call(&Command::WithDeadline {
+            deadline_ms: u32::try_from(deadline.as_millis()).unwrap_or(u32::MAX),
+            command: Box::new(Command::ScanCursor {
+                after_key: after_key.map(<[u8]>::to_vec),
+                limit,
+            }),
+        })?;
+        decode_scan_page(&payload.unwrap_or_default())
+    }
+
+    /// Count live keys in `[start, end)` without transferring them
+    ///
+    /// `end = None` means no upper bound.
+    pub fn count(&mut self, start: &[u8], end: Option<&[u8]>) -> Result<u64> {
+        let payload = self.call(&Command::Count {
+            start: start.to_vec(),
+            end: end.unwrap_or_default().to_vec(),
+        })?;
+        let count = payload.as_deref().map(<[u8; 8]>::try_from);
+        match count {
+            Some(Ok(count)) => Ok(u64::from_be_bytes(count)),
+            _ => Err(AtlasError::Protocol("COUNT response: expected 8 bytes".to_string())),
+        }
+    }
+
+    /// Get the server address
+    pub fn server_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Send one command and read its response
+    ///
+    /// `NotFound` maps to `Ok(None)`; `Error` maps to `AtlasError::Server`.
+    fn call(&mut self, command: &Command) -> Result<Option<Vec<u8>>> {
+        // Step 1: Write command bytes directly to the stream
+        let bytes = if self.frame_crc {
+            encode_command_with_crc(command)
+        } else {
+            encode_command(command)
+        };
+        self.stream.write_all(&bytes)?;
+        self.stream.flush()?;
+
+        // Step 2: Read exactly one response from the same stream
+        let response = self.receive()?;
+
+        match response.status {
+            Status::Ok => Ok(response.payload),
+            Status::NotFound => Ok(None),
+            Status::Error => {
+                let message = response
+                    .payload
+                    .map(|p| String::from_utf8_lossy(&p).into_owned())
+                    .unwrap_or_else(|| "(unknown error)".to_string());
+                Err(AtlasError::Server(message))
+            }
+        }
+    }
+
+    /// Read one response, checking its CRC when frame CRCs are enabled
+    fn receive(&self) -> Result<Response> {
+        let mut reader = BufReader::new(&self.stream);
+        if self.frame_crc {
+            read_response_with_crc(&mut reader)
+        } else {
+            read_response(&mut reader)
+        }
+    }
+}
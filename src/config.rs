@@ -3,6 +3,20 @@
 //! Centralized configuration with sensible defaults.
 
 use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::comparator::{BytewiseComparator, Comparator};
+use crate::error::{AtlasError, Result};
+use crate::merge::MergeOperator;
+
+/// Default capacity of file read and write buffers (std's `BufWriter` default)
+pub const DEFAULT_IO_BUFFER_BYTES: usize = 8 * 1024;
+
+/// Smallest accepted `read_buffer_bytes` / `write_buffer_bytes`
+pub const MIN_IO_BUFFER_BYTES: usize = 1024;
+
+/// Largest accepted `read_buffer_bytes` / `write_buffer_bytes`
+pub const MAX_IO_BUFFER_BYTES: usize = 64 * 1024 * 1024;
 
 /// Main configuration for AtlasKV instance
 #[derive(Debug, Clone)]
@@ -17,18 +31,162 @@ pub struct Config {
     ///     └── sstables/        (SSTable files)
     pub data_dir: PathBuf,
 
+    /// Capacity of the SSTable value cache in bytes (0 = disabled)
+    pub block_cache_bytes: usize,
+
+    /// Order of keys in the MemTable, SSTables, and scans
+    ///
+    /// Must be the same every time a data directory is opened: see
+    /// `crate::comparator` for what a comparator has to guarantee.
+    pub comparator: Arc<dyn Comparator>,
+
+    /// Target size of an SSTable data block in bytes
+    ///
+    /// Lookups read one whole block, and the in-memory index holds one
+    /// entry per block: larger blocks mean a smaller index but more bytes
+    /// read per lookup.
+    pub sstable_block_size: usize,
+
+    /// Capacity of the buffer behind WAL and SSTable writes (in bytes)
+    ///
+    /// Larger buffers mean fewer write syscalls for big values and flushes.
+    /// WAL syncs still flush it, so this doesn't change durability.
+    pub write_buffer_bytes: usize,
+
+    /// Capacity of the buffer behind SSTable reads and WAL replay (in bytes)
+    pub read_buffer_bytes: usize,
+
     // -------------------------------------------------------------------------
     // WAL Configuration
     // -------------------------------------------------------------------------
     /// Sync strategy: how often to fsync WAL
     pub wal_sync_strategy: WalSyncStrategy,
 
+    /// WAL size that forces a flush even below the memtable limit (0 = no limit)
+    ///
+    /// Overwrites shrink the MemTable but not the WAL, so without this the
+    /// log (and recovery time) can grow well past `memtable_size_limit`.
+    /// Counts only what was logged since the latest checkpoint.
+    pub wal_max_bytes: u64,
+
+    /// WAL size up to which a flush logs a checkpoint instead of
+    /// truncating the WAL (0 = always truncate)
+    ///
+    /// Recovery replays only what follows the latest checkpoint, so the
+    /// flushed entries before it cost disk space and read time, not
+    /// correctness. A flush truncates once the WAL reaches this size.
+    pub wal_checkpoint_max_bytes: u64,
+
+    /// Interval at which a background thread syncs unsynced WAL entries
+    /// (milliseconds, 0 = no background sync)
+    ///
+    /// Bounds how long a write can sit unsynced under `EveryNEntries` when
+    /// writes stop short of the count.
+    pub wal_background_sync_ms: u64,
+
+    /// Size the WAL file is extended to, zero-filled, whenever it starts
+    /// empty (bytes, 0 = grow as written)
+    ///
+    /// Appends then overwrite zeros instead of growing the file, so syncs
+    /// needn't update its size. Recovery stops at the first zero header.
+    /// A log that outgrows it just grows as usual.
+    pub wal_preallocate_bytes: u64,
+
+    /// Size past which the WAL rolls over to a new segment file (bytes,
+    /// 0 = one file)
+    ///
+    /// Segments are `wal.log`, `wal.log.1`, `wal.log.2`, ...; recovery reads
+    /// them in order and a flush's truncation removes all but the first.
+    /// With `wal_preallocate_bytes` set, each segment is preallocated.
+    pub wal_segment_bytes: u64,
+
     // -------------------------------------------------------------------------
     // MemTable Configuration
     // -------------------------------------------------------------------------
     /// Max size of memtable before flush (in bytes)
     pub memtable_size_limit: usize,
 
+    /// Bytes charged per memtable entry on top of key/value lengths
+    /// (approximates BTreeMap node and Vec allocation overhead)
+    pub memtable_entry_overhead: usize,
+
+    /// Flush the MemTable and sync the WAL when the engine is dropped
+    ///
+    /// Off by default: without it a dropped engine leaves unflushed writes
+    /// in the WAL only, which is how the tests simulate a crash. Either way
+    /// nothing is lost; this only spares the next open a WAL replay.
+    pub flush_on_drop: bool,
+
+    /// Times `Engine::open` retries a failed flush of recovered WAL entries
+    /// (0 = fail on the first error)
+    ///
+    /// For disks that are briefly unavailable at startup. Attempts back off
+    /// from 100 ms, doubling each time; the last error is returned once
+    /// retries run out.
+    pub open_flush_retries: u32,
+
+    // -------------------------------------------------------------------------
+    // Merge Configuration
+    // -------------------------------------------------------------------------
+    /// Operator used to combine MERGE operands (None = merges rejected)
+    pub merge_operator: Option<Arc<dyn MergeOperator>>,
+
+    // -------------------------------------------------------------------------
+    // Limits
+    // -------------------------------------------------------------------------
+    /// Max key size in bytes (enforced by the engine and the protocol decoder)
+    pub max_key_size: usize,
+
+    /// Max value size in bytes (enforced by the engine and the protocol decoder)
+    ///
+    /// SSTables can't hold values over `storage::MAX_VALUE_SIZE` (4 GiB - 1)
+    /// whatever this is set to.
+    pub max_value_size: usize,
+
+    /// SSTable count past which each write first sleeps briefly (0 = never)
+    ///
+    /// Every SSTable is another lookup on a read miss, so this slows writers
+    /// down until `Engine::compact` brings the count back down.
+    pub sstable_stall_threshold: usize,
+
+    /// SSTable count past which writes are rejected outright (0 = never)
+    ///
+    /// Nothing compacts automatically, so once this is hit writes fail until
+    /// `Engine::compact` runs.
+    pub sstable_stop_threshold: usize,
+
+    /// Which SSTables `Engine::compact` merges
+    pub compaction_strategy: CompactionStrategy,
+
+    /// Data bytes past which a compaction rolls over to a new output
+    /// SSTable (0 = one output file)
+    ///
+    /// Files are split between keys, so each may run a block or so over,
+    /// and their key ranges never overlap.
+    pub max_sstable_bytes: u64,
+
+    /// Minimum age in milliseconds before a tombstone may be dropped (0 = no
+    /// minimum)
+    ///
+    /// Flush and compaction drop a tombstone once no older SSTable can hold
+    /// its key; with a grace period it must also have been written at least
+    /// this long ago. Tombstones of unknown age (timestamp 0) count as old.
+    pub tombstone_grace_ms: u64,
+
+    /// Store a CRC32 after every value in new SSTables (default: false)
+    ///
+    /// Reads check it, so a corrupted value fails with `AtlasError::Storage`
+    /// instead of being returned; the rest of the file stays readable.
+    /// Costs 4 bytes per value. Existing SSTables keep their format until
+    /// compaction rewrites them.
+    pub per_value_checksum: bool,
+
+    /// Cap on SSTables + WAL + MemTable in bytes (None = unlimited)
+    ///
+    /// Writes that would take the total past the cap fail with
+    /// `AtlasError::Storage("database full")`; deletes are still accepted.
+    pub max_total_bytes: Option<u64>,
+
     // -------------------------------------------------------------------------
     // Network Configuration
     // -------------------------------------------------------------------------
@@ -38,11 +196,73 @@ pub struct Config {
     /// Max concurrent client connections
     pub max_connections: usize,
 
+    /// Worker threads serving connections (None = one per CPU, must be >= 1)
+    ///
+    /// Each worker serves one connection at a time. Accepted connections
+    /// beyond the worker count wait in a queue of `max_connections` slots
+    /// until a worker frees up, so workers bound how many clients are served
+    /// at once and `max_connections` bounds how many may be waiting.
+    pub worker_threads: Option<usize>,
+
+    /// Pending-connection queue length passed to listen()
+    pub listen_backlog: i32,
+
+    /// Set SO_REUSEPORT so multiple acceptors can share the port
+    /// (ignored on platforms without support)
+    pub reuse_port: bool,
+
+    /// Set TCP_NODELAY on client connections
+    ///
+    /// On by default, for request/response latency. Turn it off for
+    /// pipelined bulk loads, where coalescing small writes into fewer
+    /// segments wins.
+    pub tcp_nodelay: bool,
+
+    /// SO_SNDBUF for client connections in bytes (0 = OS default)
+    pub tcp_send_buffer_bytes: usize,
+
+    /// SO_RCVBUF for client connections in bytes (0 = OS default)
+    pub tcp_recv_buffer_bytes: usize,
+
+    /// TCP keepalive on client connections (milliseconds, 0 = off)
+    ///
+    /// Used both as the idle time before the first probe and as the gap
+    /// between probes, so a peer that vanished without a FIN is reaped
+    /// even while the connection sits inside `idle_timeout_ms`.
+    pub keepalive_interval_ms: u64,
+
+    /// Unanswered keepalive probes before the connection drops
+    /// (0 = OS default; not settable on Windows)
+    pub keepalive_retries: u32,
+
     /// Connection read timeout (milliseconds)
+    /// Per-read deadline; on expiry the connection re-checks idle time
     pub read_timeout_ms: u64,
 
+    /// Idle connection timeout (milliseconds)
+    /// Connections with no completed command for this long are closed (0 = never)
+    pub idle_timeout_ms: u64,
+
+    /// Commands a connection may send before the server closes it (0 = unlimited)
+    ///
+    /// The last one is still answered. Bounds how long one busy client can
+    /// hold a worker thread, so others get a turn; clients reconnect to go on.
+    pub max_requests_per_connection: u64,
+
     /// Connection write timeout (milliseconds)
     pub write_timeout_ms: u64,
+
+    /// Max time shutdown waits for in-flight connections to drain (milliseconds)
+    pub shutdown_drain_ms: u64,
+
+    /// Shared secret clients must send in an AUTH command (None = no auth)
+    pub auth_token: Option<String>,
+
+    // -------------------------------------------------------------------------
+    // Metrics Configuration
+    // -------------------------------------------------------------------------
+    /// Address for the Prometheus metrics endpoint (None = disabled)
+    pub metrics_addr: Option<String>,
 }
 
 /// WAL sync strategy
@@ -53,18 +273,75 @@ pub enum WalSyncStrategy {
 
     /// fsync after N uncommitted entries (balanced durability/performance)
     EveryNEntries { count: usize },
+
+    /// Never fsync on append (fastest, least durable)
+    ///
+    /// Entries reach disk only when the buffer fills, on flush, or through
+    /// `wal_background_sync_ms`; a crash can lose everything since.
+    NoSync,
+}
+
+/// Which SSTables a compaction merges (see `storage::select_inputs`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompactionStrategy {
+    /// Every SSTable, dropping all tombstones
+    AllFiles,
+
+    /// The newest SSTables whose key ranges overlap; tombstones are kept
+    /// unless that turns out to be every SSTable
+    OverlapBased,
+
+    /// Every SSTable, but only once tombstones make up at least `min_ratio`
+    /// (0.0 to 1.0) of all entries
+    TombstoneRatio { min_ratio: f64 },
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             data_dir: PathBuf::from("./atlaskv_data"),
+            block_cache_bytes: 0,
+            comparator: Arc::new(BytewiseComparator),
+            sstable_block_size: crate::storage::DEFAULT_BLOCK_SIZE,
+            write_buffer_bytes: DEFAULT_IO_BUFFER_BYTES,
+            read_buffer_bytes: DEFAULT_IO_BUFFER_BYTES,
             wal_sync_strategy: WalSyncStrategy::EveryNEntries { count: 100 },
+            wal_max_bytes: 0,
+            wal_checkpoint_max_bytes: 0,
+            wal_background_sync_ms: 0,
+            wal_preallocate_bytes: 0,
+            wal_segment_bytes: 0,
             memtable_size_limit: 64 * 1024 * 1024, // 64 MB
+            memtable_entry_overhead: crate::memtable::DEFAULT_ENTRY_OVERHEAD,
+            flush_on_drop: false,
+            open_flush_retries: 0,
+            merge_operator: None,
+            max_key_size: 64 * 1024,          // 64 KB
+            max_value_size: 16 * 1024 * 1024, // 16 MB
+            sstable_stall_threshold: 0,
+            sstable_stop_threshold: 0,
+            compaction_strategy: CompactionStrategy::AllFiles,
+            max_sstable_bytes: 0,
+            tombstone_grace_ms: 0,
+            per_value_checksum: false,
+            max_total_bytes: None,
             listen_addr: "127.0.0.1:6379".to_string(),
             max_connections: 1024,
+            worker_threads: None,
+            listen_backlog: 1024,
+            reuse_port: false,
+            tcp_nodelay: true,
+            tcp_send_buffer_bytes: 0,
+            tcp_recv_buffer_bytes: 0,
+            keepalive_interval_ms: 0,
+            keepalive_retries: 0,
             read_timeout_ms: 30000,   // Increased to 30 seconds
+            idle_timeout_ms: 300000,  // 5 minutes
+            max_requests_per_connection: 0,
             write_timeout_ms: 30000,  // Increased to 30 seconds
+            shutdown_drain_ms: 5000,
+            auth_token: None,
+            metrics_addr: None,
         }
     }
 }
@@ -74,6 +351,23 @@ impl Config {
     pub fn builder() -> ConfigBuilder {
         ConfigBuilder::default()
     }
+
+    /// Check the I/O buffer sizes are within
+    /// `MIN_IO_BUFFER_BYTES..=MAX_IO_BUFFER_BYTES`
+    pub fn check_io_buffers(&self) -> Result<()> {
+        for (name, bytes) in [
+            ("write_buffer_bytes", self.write_buffer_bytes),
+            ("read_buffer_bytes", self.read_buffer_bytes),
+        ] {
+            if !(MIN_IO_BUFFER_BYTES..=MAX_IO_BUFFER_BYTES).contains(&bytes) {
+                return Err(AtlasError::Config(format!(
+                    "{} must be between {} and {} bytes, got {}",
+                    name, MIN_IO_BUFFER_BYTES, MAX_IO_BUFFER_BYTES, bytes
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Builder for Config
@@ -89,18 +383,157 @@ impl ConfigBuilder {
         self
     }
 
+    /// Set the SSTable value cache capacity (in bytes, 0 = disabled)
+    pub fn block_cache_bytes(mut self, bytes: usize) -> Self {
+        self.config.block_cache_bytes = bytes;
+        self
+    }
+
+    /// Set the key order (must match the one the data directory was written with)
+    pub fn comparator(mut self, comparator: impl Comparator + 'static) -> Self {
+        self.config.comparator = Arc::new(comparator);
+        self
+    }
+
+    /// Set the target SSTable data block size (in bytes)
+    pub fn sstable_block_size(mut self, bytes: usize) -> Self {
+        self.config.sstable_block_size = bytes;
+        self
+    }
+
+    /// Set the WAL and SSTable write buffer capacity (in bytes)
+    pub fn write_buffer_bytes(mut self, bytes: usize) -> Self {
+        self.config.write_buffer_bytes = bytes;
+        self
+    }
+
+    /// Set the SSTable and WAL replay read buffer capacity (in bytes)
+    pub fn read_buffer_bytes(mut self, bytes: usize) -> Self {
+        self.config.read_buffer_bytes = bytes;
+        self
+    }
+
     /// Set the WAL sync strategy
     pub fn wal_sync_strategy(mut self, strategy: WalSyncStrategy) -> Self {
         self.config.wal_sync_strategy = strategy;
         self
     }
 
+    /// Set the WAL size that forces a flush (in bytes, 0 = no limit)
+    pub fn wal_max_bytes(mut self, bytes: u64) -> Self {
+        self.config.wal_max_bytes = bytes;
+        self
+    }
+
+    /// Set the WAL size up to which flushes checkpoint instead of truncating
+    /// (in bytes, 0 = always truncate)
+    pub fn wal_checkpoint_max_bytes(mut self, bytes: u64) -> Self {
+        self.config.wal_checkpoint_max_bytes = bytes;
+        self
+    }
+
+    /// Set the background WAL sync interval (in milliseconds, 0 = off)
+    pub fn wal_background_sync_ms(mut self, ms: u64) -> Self {
+        self.config.wal_background_sync_ms = ms;
+        self
+    }
+
+    /// Set the size to preallocate the WAL file to (in bytes, 0 = off)
+    pub fn wal_preallocate_bytes(mut self, bytes: u64) -> Self {
+        self.config.wal_preallocate_bytes = bytes;
+        self
+    }
+
+    /// Set the size past which the WAL starts a new segment (in bytes, 0 = off)
+    pub fn wal_segment_bytes(mut self, bytes: u64) -> Self {
+        self.config.wal_segment_bytes = bytes;
+        self
+    }
+
     /// Set the memtable size limit (in bytes)
     pub fn memtable_size_limit(mut self, size: usize) -> Self {
         self.config.memtable_size_limit = size;
         self
     }
 
+    /// Set the per-entry memtable overhead (in bytes)
+    pub fn memtable_entry_overhead(mut self, bytes: usize) -> Self {
+        self.config.memtable_entry_overhead = bytes;
+        self
+    }
+
+    /// Flush and sync when the engine is dropped without `close`
+    pub fn flush_on_drop(mut self, enabled: bool) -> Self {
+        self.config.flush_on_drop = enabled;
+        self
+    }
+
+    /// Retry a failed recovery flush at open this many times
+    pub fn open_flush_retries(mut self, retries: u32) -> Self {
+        self.config.open_flush_retries = retries;
+        self
+    }
+
+    /// Set the merge operator used to combine MERGE operands
+    pub fn merge_operator(mut self, operator: impl MergeOperator + 'static) -> Self {
+        self.config.merge_operator = Some(Arc::new(operator));
+        self
+    }
+
+    /// Set the maximum key size (in bytes)
+    pub fn max_key_size(mut self, size: usize) -> Self {
+        self.config.max_key_size = size;
+        self
+    }
+
+    /// Set the maximum value size (in bytes)
+    pub fn max_value_size(mut self, size: usize) -> Self {
+        self.config.max_value_size = size;
+        self
+    }
+
+    /// Slow writes down once more than this many SSTables are live (0 = never)
+    pub fn sstable_stall_threshold(mut self, count: usize) -> Self {
+        self.config.sstable_stall_threshold = count;
+        self
+    }
+
+    /// Reject writes once more than this many SSTables are live (0 = never)
+    pub fn sstable_stop_threshold(mut self, count: usize) -> Self {
+        self.config.sstable_stop_threshold = count;
+        self
+    }
+
+    /// Set which SSTables a compaction merges
+    pub fn compaction_strategy(mut self, strategy: CompactionStrategy) -> Self {
+        self.config.compaction_strategy = strategy;
+        self
+    }
+
+    /// Split compaction output into SSTables of about `bytes` each (0 = don't split)
+    pub fn max_sstable_bytes(mut self, bytes: u64) -> Self {
+        self.config.max_sstable_bytes = bytes;
+        self
+    }
+
+    /// Keep tombstones at least `ms` milliseconds before dropping them (0 = no minimum)
+    pub fn tombstone_grace_ms(mut self, ms: u64) -> Self {
+        self.config.tombstone_grace_ms = ms;
+        self
+    }
+
+    /// Store a CRC32 after every value in new SSTables
+    pub fn per_value_checksum(mut self, enabled: bool) -> Self {
+        self.config.per_value_checksum = enabled;
+        self
+    }
+
+    /// Cap the database's total size (SSTables + WAL + MemTable) in bytes
+    pub fn max_total_bytes(mut self, bytes: u64) -> Self {
+        self.config.max_total_bytes = Some(bytes);
+        self
+    }
+
     /// Set the TCP listen address
     pub fn listen_addr(mut self, addr: impl Into<String>) -> Self {
         self.config.listen_addr = addr.into();
@@ -113,18 +546,96 @@ impl ConfigBuilder {
         self
     }
 
+    /// Set the number of worker threads (instead of one per CPU)
+    pub fn worker_threads(mut self, count: usize) -> Self {
+        self.config.worker_threads = Some(count);
+        self
+    }
+
+    /// Set the listen backlog (pending connection queue length)
+    pub fn listen_backlog(mut self, backlog: i32) -> Self {
+        self.config.listen_backlog = backlog;
+        self
+    }
+
+    /// Enable SO_REUSEPORT on the listener (where supported)
+    pub fn reuse_port(mut self, enabled: bool) -> Self {
+        self.config.reuse_port = enabled;
+        self
+    }
+
+    /// Enable or disable TCP_NODELAY on client connections
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.config.tcp_nodelay = enabled;
+        self
+    }
+
+    /// Set SO_SNDBUF on client connections (in bytes, 0 = OS default)
+    pub fn tcp_send_buffer_bytes(mut self, bytes: usize) -> Self {
+        self.config.tcp_send_buffer_bytes = bytes;
+        self
+    }
+
+    /// Set SO_RCVBUF on client connections (in bytes, 0 = OS default)
+    pub fn tcp_recv_buffer_bytes(mut self, bytes: usize) -> Self {
+        self.config.tcp_recv_buffer_bytes = bytes;
+        self
+    }
+
+    /// Set the TCP keepalive idle time and probe interval (in milliseconds, 0 = off)
+    pub fn keepalive_interval_ms(mut self, ms: u64) -> Self {
+        self.config.keepalive_interval_ms = ms;
+        self
+    }
+
+    /// Set the keepalive probes sent before giving up (0 = OS default)
+    pub fn keepalive_retries(mut self, retries: u32) -> Self {
+        self.config.keepalive_retries = retries;
+        self
+    }
+
     /// Set the read timeout (in milliseconds)
     pub fn read_timeout_ms(mut self, ms: u64) -> Self {
         self.config.read_timeout_ms = ms;
         self
     }
 
+    /// Set the idle connection timeout (in milliseconds, 0 = never)
+    pub fn idle_timeout_ms(mut self, ms: u64) -> Self {
+        self.config.idle_timeout_ms = ms;
+        self
+    }
+
+    /// Close connections after this many commands (0 = unlimited)
+    pub fn max_requests_per_connection(mut self, requests: u64) -> Self {
+        self.config.max_requests_per_connection = requests;
+        self
+    }
+
+    /// Set how long shutdown waits for connections to drain (in milliseconds)
+    pub fn shutdown_drain_ms(mut self, ms: u64) -> Self {
+        self.config.shutdown_drain_ms = ms;
+        self
+    }
+
     /// Set the write timeout (in milliseconds)
     pub fn write_timeout_ms(mut self, ms: u64) -> Self {
         self.config.write_timeout_ms = ms;
         self
     }
 
+    /// Require clients to authenticate with this shared secret
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.config.auth_token = Some(token.into());
+        self
+    }
+
+    /// Serve Prometheus metrics on this address (host:port)
+    pub fn metrics_addr(mut self, addr: impl Into<String>) -> Self {
+        self.config.metrics_addr = Some(addr.into());
+        self
+    }
+
     pub fn build(self) -> Config {
         self.config
     }
@@ -0,0 +1,203 @@
+//! Dump Format
+//!
+//! Portable full backups, independent of the SSTable layout. Written by
+//! `Engine::export` (or `atlaskv-cli dump`) and read back by
+//! `Engine::import` (or `atlaskv-cli restore`).
+//!
+//! ## File Format
+//! ```text
+//! [Magic: "AKVD"][Version: u32]
+//! [KeyLen: u32][ValLen: u32][Key][Value]      ... per live entry
+//! [EndMarker: u32 = 0xFFFFFFFF][Count: u64][CRC32: u32]
+//! ```
+//! All integers are little-endian. The CRC covers every byte before it.
+//! Writers emit keys in ascending order, but readers don't rely on it.
+
+use std::io::{Read, Write};
+
+use crate::error::{AtlasError, Result};
+
+/// Magic bytes at the start of every dump
+pub const MAGIC: &[u8; 4] = b"AKVD";
+
+/// Current dump format version
+pub const VERSION: u32 = 1;
+
+/// KeyLen value that ends the records and starts the trailer
+const END_MARKER: u32 = u32::MAX;
+
+/// Streams entries into a dump
+pub struct DumpWriter<W: Write> {
+    writer: W,
+    hasher: crc32fast::Hasher,
+    count: u64,
+}
+
+impl<W: Write> DumpWriter<W> {
+    /// Start a dump, writing the header
+    pub fn new(writer: W) -> Result<Self> {
+        let mut dump = Self {
+            writer,
+            hasher: crc32fast::Hasher::new(),
+            count: 0,
+        };
+        dump.write_hashed(MAGIC)?;
+        dump.write_hashed(&VERSION.to_le_bytes())?;
+        Ok(dump)
+    }
+
+    /// Append one entry
+    pub fn write_entry(&mut self, key: &[u8], value: &[u8]) -> Result<()> {
+        let key_len = u32::try_from(key.len())
+            .ok()
+            .filter(|&len| len != END_MARKER)
+            .ok_or_else(|| AtlasError::Serialization("Dump: key too large".to_string()))?;
+        let val_len = u32::try_from(value.len())
+            .map_err(|_| AtlasError::Serialization("Dump: value too large".to_string()))?;
+
+        self.write_hashed(&key_len.to_le_bytes())?;
+        self.write_hashed(&val_len.to_le_bytes())?;
+        self.write_hashed(key)?;
+        self.write_hashed(value)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Number of entries written so far
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Write the trailer and flush, returning the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        self.write_hashed(&END_MARKER.to_le_bytes())?;
+        self.write_hashed(&self.count.to_le_bytes())?;
+        let crc = self.hasher.finalize();
+        self.writer.write_all(&crc.to_le_bytes())?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_hashed(&mut self, bytes: &[u8]) -> Result<()> {
+        self.hasher.update(bytes);
+        self.writer.write_all(bytes)?;
+        Ok(())
+    }
+}
+
+/// Reads entries back from a dump, checking the trailer at the end
+///
+/// Entries are yielded as they are read, so a caller that can't undo them
+/// should collect the whole dump before acting on it: a bad count or CRC
+/// only surfaces once the trailer is reached.
+pub struct DumpReader<R: Read> {
+    reader: R,
+    hasher: crc32fast::Hasher,
+    count: u64,
+    done: bool,
+}
+
+impl<R: Read> DumpReader<R> {
+    /// Open a dump, checking its magic and version
+    pub fn new(reader: R) -> Result<Self> {
+        let mut dump = Self {
+            reader,
+            hasher: crc32fast::Hasher::new(),
+            count: 0,
+            done: false,
+        };
+
+        if dump.read_hashed(MAGIC.len())? != MAGIC {
+            return Err(corrupt("bad magic"));
+        }
+        let version = dump.read_u32()?;
+        if version != VERSION {
+            return Err(AtlasError::Serialization(format!(
+                "Dump: unsupported version {} (expected {})",
+                version, VERSION
+            )));
+        }
+        Ok(dump)
+    }
+
+    /// Read the next entry, or `None` once the trailer has been verified
+    pub fn next_entry(&mut self) -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let key_len = self.read_u32()?;
+        if key_len == END_MARKER {
+            self.read_trailer()?;
+            self.done = true;
+            return Ok(None);
+        }
+        let val_len = self.read_u32()?;
+        let key = self.read_hashed(key_len as usize)?;
+        let value = self.read_hashed(val_len as usize)?;
+        self.count += 1;
+        Ok(Some((key, value)))
+    }
+
+    /// Check the record count and CRC after the end marker
+    fn read_trailer(&mut self) -> Result<()> {
+        let count = u64::from_le_bytes(self.read_hashed(8)?.try_into().unwrap());
+        if count != self.count {
+            return Err(corrupt(&format!(
+                "trailer counts {} entries, read {}",
+                count, self.count
+            )));
+        }
+
+        let expected = self.hasher.clone().finalize();
+        let mut crc = [0u8; 4];
+        self.reader.read_exact(&mut crc).map_err(truncated)?;
+        if u32::from_le_bytes(crc) != expected {
+            return Err(corrupt("CRC mismatch"));
+        }
+        Ok(())
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_hashed(4)?.try_into().unwrap()))
+    }
+
+    /// Read exactly `len` bytes, growing the buffer only as data arrives so
+    /// a corrupt length can't force a huge allocation
+    fn read_hashed(&mut self, len: usize) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        (&mut self.reader).take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() < len {
+            return Err(corrupt("unexpected end of file"));
+        }
+        self.hasher.update(&buf);
+        Ok(buf)
+    }
+}
+
+impl<R: Read> Iterator for DumpReader<R> {
+    type Item = Result<(Vec<u8>, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_entry() {
+            Ok(entry) => entry.map(Ok),
+            Err(e) => {
+                // Don't keep reading from the middle of a bad record
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn corrupt(what: &str) -> AtlasError {
+    AtlasError::Serialization(format!("Corrupt dump: {}", what))
+}
+
+fn truncated(e: std::io::Error) -> AtlasError {
+    if e.kind() == std::io::ErrorKind::UnexpectedEof {
+        corrupt("unexpected end of file")
+    } else {
+        AtlasError::Io(e)
+    }
+}
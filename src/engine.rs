@@ -9,15 +9,60 @@
 //! - Manage crash recovery on startup
 
 use std::fs;
+use std::io::{Read, Write};
+use std::ops::Bound;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use crate::config::Config;
-use crate::error::Result;
+use crossbeam::channel::{bounded, Receiver, RecvTimeoutError, Sender, TrySendError};
+use parking_lot::{Mutex, MutexGuard};
+
+use crate::config::{Config, WalSyncStrategy};
+use crate::dump::{DumpReader, DumpWriter};
+use crate::error::{AtlasError, Result};
 use crate::memtable::{MemTable, MemTableEntry};
-use crate::protocol::Command;
-use crate::storage::StorageManager;
-use crate::wal::{Operation, WalRecovery, WalWriter};
+use crate::merge::MergeOperator;
+use crate::metrics::{self, EngineMetrics, EngineStats};
+use crate::protocol::{
+    encode_entries, encode_get_meta_response, encode_keys, encode_scan_page,
+    encode_version_response, Command, ScanPage, ServerVersion, PROTOCOL_VERSION,
+};
+use crate::storage::{
+    BlockCacheStats, MergeEntry, SSTableStats, StorageManager, SSTABLE_FORMAT_VERSION,
+};
+use crate::wal::{
+    Operation, SegmentInfo, WalEntry, WalReader, WalRecovery, WalWriter, WAL_FORMAT_VERSION,
+};
+
+/// How long a write sleeps once `sstable_stall_threshold` is passed
+const WRITE_STALL_DELAY: Duration = Duration::from_millis(1);
+
+/// Wait before the first retry of a failed recovery flush (doubles each retry)
+const OPEN_FLUSH_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Entries a WAL subscriber can fall behind by before new ones are dropped
+const WAL_SUBSCRIBER_CAPACITY: usize = 1024;
+
+/// Callback fed every logged write, in WAL order (see [`Engine::set_write_observer`])
+pub type WriteObserver = Box<dyn Fn(&Operation) + Send + Sync>;
+
+/// What a flush wrote (see [`Engine::flush_stats`])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlushStats {
+    /// Entries written to the new SSTable, tombstones included (0 if every
+    /// entry was a tombstone with nothing on disk to shadow)
+    pub entries: u64,
+    /// Size of the new SSTable file in bytes
+    pub bytes: u64,
+    /// Id of the new SSTable (0 if nothing was flushed)
+    pub sstable_id: u64,
+    /// The MemTable was empty, so no SSTable was written
+    pub was_empty: bool,
+}
 
 /// The main storage engine
 ///
@@ -45,7 +90,11 @@ pub struct Engine {
     storage_dir: PathBuf,
 
     /// Write-ahead log for durability (exclusive access needed)
-    wal: Mutex<WalWriter>,
+    /// None when opened read-only; shared with the background syncer
+    wal: Option<Arc<Mutex<WalWriter>>>,
+
+    /// Timer thread syncing the WAL (None unless `wal_background_sync_ms` is set)
+    wal_syncer: Option<WalSyncer>,
 
     /// In-memory table for recent writes (internal RwLock)
     memtable: MemTable,
@@ -55,6 +104,18 @@ pub struct Engine {
 
     /// Serializes write operations (put/delete/flush)
     write_lock: Mutex<()>,
+
+    /// Operation counters (exported via the metrics endpoint)
+    metrics: EngineMetrics,
+
+    /// Change-data-capture callback for applied writes (None = disabled)
+    observer: RwLock<Option<WriteObserver>>,
+
+    /// Channels fed every logged WAL entry (see `subscribe_wal`)
+    wal_subscribers: Mutex<Vec<Sender<WalEntry>>>,
+
+    /// Set while `apply_replicated` is inside a batch (flushes are refused)
+    replicating_batch: AtomicBool,
 }
 
 impl Engine {
@@ -63,6 +124,7 @@ impl Engine {
     // =========================================================================
     const WAL_FILENAME: &'static str = "wal.log";
     const SSTABLE_DIR: &'static str = "sstables";
+    const HEALTH_PROBE_FILENAME: &'static str = "health.probe";
 
     /// Open or create an engine with the given config
     ///
@@ -72,6 +134,9 @@ impl Engine {
     /// 3. Load existing SSTables
     /// 4. Ready to serve requests
     pub fn open(config: Config) -> Result<Self> {
+        config.check_io_buffers()?;
+        let start = Instant::now();
+
         // Step 1: Create data directory if it doesn't exist
         fs::create_dir_all(&config.data_dir)?;
 
@@ -83,68 +148,229 @@ impl Engine {
         fs::create_dir_all(&storage_dir)?;
 
         // Step 4: Open storage manager (loads existing SSTables)
-        let storage = StorageManager::open(&storage_dir)?;
+        let storage = StorageManager::open(&storage_dir)?
+            .with_block_cache(config.block_cache_bytes)
+            .with_block_size(config.sstable_block_size)
+            .with_io_buffers(config.write_buffer_bytes, config.read_buffer_bytes)
+            .with_comparator(config.comparator.clone())
+            .with_compaction_strategy(config.compaction_strategy)
+            .with_max_sstable_bytes(config.max_sstable_bytes)
+            .with_tombstone_grace_ms(config.tombstone_grace_ms)
+            .with_value_checksums(config.per_value_checksum);
+        tracing::debug!(
+            sstables = storage.sstable_count(),
+            elapsed_us = elapsed_us(start),
+            "sstables loaded"
+        );
 
         // Step 5: Create memtable
-        let memtable = MemTable::new();
+        let memtable = MemTable::with_entry_overhead(config.memtable_entry_overhead)
+            .with_comparator(config.comparator.clone());
 
         // Step 6: Recover from WAL if it exists and flush to make data durable
-        let wal = if wal_path.exists() {
-            let (entries, recovery_result) = WalRecovery::recover(&wal_path)?;
-
-            // Log recovery stats (in production, use proper logging)
-            if recovery_result.entries_recovered > 0 || recovery_result.entries_corrupted > 0 {
-                eprintln!(
-                    "[Engine] WAL recovery: {} entries recovered, {} corrupted, last_lsn={}",
-                    recovery_result.entries_recovered,
-                    recovery_result.entries_corrupted,
-                    recovery_result.last_lsn
-                );
-            }
-
-            // Replay entries to memtable
-            for entry in entries {
-                match entry.operation {
-                    Operation::Put { key, value } => {
-                        memtable.put(key, value);
-                    }
-                    Operation::Delete { key } => {
-                        memtable.delete(key);
-                    }
-                }
-            }
+        let mut last_lsn = 0;
+        if wal_path.exists() {
+            last_lsn = Self::replay_wal(&wal_path, &memtable, &config)?;
 
             // CRITICAL: Flush recovered data to SSTable immediately to make it durable
             // If we crash after this point, data is safe in SSTables
             if !memtable.is_empty() {
                 eprintln!("[Engine] Flushing {} recovered entries to SSTable", memtable.entry_count());
-                storage.flush(&memtable)?;
+                Self::resolve_merges(&memtable, &storage, config.merge_operator.as_deref())?;
+                Self::flush_recovered(&memtable, &storage, config.open_flush_retries)?;
                 memtable.clear();
             }
+        }
 
-            // Now safe to truncate WAL - recovered data is durable in SSTables
-            WalWriter::open(&wal_path, config.wal_sync_strategy)?
-        } else {
-            // No WAL to recover - start fresh
-            WalWriter::open(&wal_path, config.wal_sync_strategy)?
+        // Now safe to truncate WAL - recovered data is durable in SSTables.
+        // Sequence numbers continue past everything already written.
+        let mut wal = WalWriter::open(&wal_path, config.wal_sync_strategy)?
+            .with_buffer_size(config.write_buffer_bytes)?
+            .with_preallocation(config.wal_preallocate_bytes)?
+            .with_segment_bytes(config.wal_segment_bytes);
+        wal.reset(last_lsn.max(storage.max_seq()) + 1)?;
+        let wal = Arc::new(Mutex::new(wal));
+        let wal_syncer = match config.wal_background_sync_ms {
+            0 => None,
+            ms => Some(WalSyncer::start(Arc::clone(&wal), Duration::from_millis(ms))?),
         };
+        tracing::debug!(elapsed_us = elapsed_us(start), "engine opened");
+
+        Ok(Self {
+            config,
+            storage_dir,
+            wal: Some(wal),
+            wal_syncer,
+            memtable,
+            storage,
+            write_lock: Mutex::new(()),
+            metrics: EngineMetrics::default(),
+            observer: RwLock::new(None),
+            wal_subscribers: Mutex::new(Vec::new()),
+            replicating_batch: AtomicBool::new(false),
+        })
+    }
+
+    /// Open an existing engine for reads only
+    ///
+    /// Never mutates files: no directory creation, no WAL truncation, no
+    /// recovery flush. The WAL is replayed into the MemTable so reads see
+    /// unflushed writes. Safe to open while another handle has the data
+    /// directory open for writing.
+    ///
+    /// `put`/`delete`/`flush` (and other writes) return `AtlasError::Storage("read-only")`.
+    pub fn open_read_only(config: Config) -> Result<Self> {
+        config.check_io_buffers()?;
+
+        let storage_dir = config.data_dir.join(Self::SSTABLE_DIR);
+        let wal_path = config.data_dir.join(Self::WAL_FILENAME);
+
+        let storage = StorageManager::open_read_only(&storage_dir)?
+            .with_block_cache(config.block_cache_bytes)
+            .with_io_buffers(config.write_buffer_bytes, config.read_buffer_bytes)
+            .with_comparator(config.comparator.clone());
+
+        let memtable = MemTable::with_entry_overhead(config.memtable_entry_overhead)
+            .with_comparator(config.comparator.clone());
+        if wal_path.exists() {
+            Self::replay_wal(&wal_path, &memtable, &config)?;
+        }
 
         Ok(Self {
             config,
             storage_dir,
-            wal: Mutex::new(wal),
+            wal: None,
+            wal_syncer: None,
             memtable,
             storage,
             write_lock: Mutex::new(()),
+            metrics: EngineMetrics::default(),
+            observer: RwLock::new(None),
+            wal_subscribers: Mutex::new(Vec::new()),
+            replicating_batch: AtomicBool::new(false),
         })
     }
 
+    /// Flush the recovered MemTable, retrying up to `retries` times with
+    /// a doubling backoff
+    ///
+    /// A failed attempt publishes nothing (its SSTable id is skipped), so
+    /// retrying is safe.
+    fn flush_recovered(memtable: &MemTable, storage: &StorageManager, retries: u32) -> Result<()> {
+        let mut delay = OPEN_FLUSH_RETRY_DELAY;
+        for attempt in 1.. {
+            match storage.flush(memtable) {
+                Ok(_) => break,
+                Err(e) if attempt <= retries => {
+                    tracing::warn!(
+                        attempt,
+                        retries,
+                        delay_ms = delay.as_millis() as u64,
+                        "recovery flush failed, retrying: {}",
+                        e
+                    );
+                    thread::sleep(delay);
+                    delay *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Replay all valid WAL entries into the memtable
+    ///
+    /// Entries are tagged with their LSNs; returns the last LSN replayed.
+    fn replay_wal(wal_path: &Path, memtable: &MemTable, config: &Config) -> Result<u64> {
+        let merge_operator = config.merge_operator.as_deref();
+        let reader = WalReader::open(wal_path)?.with_buffer_size(config.read_buffer_bytes);
+        let (entries, recovery_result) = WalRecovery::recover_from(reader)?;
+
+        // Log recovery stats (in production, use proper logging)
+        if recovery_result.entries_recovered > 0 || recovery_result.entries_corrupted > 0 {
+            eprintln!(
+                "[Engine] WAL recovery: {} entries recovered, {} corrupted, last_lsn={}",
+                recovery_result.entries_recovered,
+                recovery_result.entries_corrupted,
+                recovery_result.last_lsn
+            );
+        }
+        // An unfinished batch moves the cut back without any corruption
+        if let Some(truncate_offset) = recovery_result.truncate_offset {
+            tracing::warn!(
+                truncate_offset,
+                corrupt_offset = ?recovery_result.first_corrupt_offset,
+                lsn = ?recovery_result.first_corrupt_lsn,
+                "WAL has a partial or corrupt entry or an unfinished batch; ignoring the rest"
+            );
+        }
+
+        // Replay entries to memtable
+        for entry in entries {
+            let (seq, timestamp) = (entry.lsn, entry.timestamp);
+            match entry.operation {
+                Operation::Put { key, value } => {
+                    memtable.put_at(key, value, seq, timestamp);
+                }
+                Operation::Delete { key } => {
+                    memtable.delete_at(key, seq, timestamp);
+                }
+                Operation::Merge { key, operand } => {
+                    let operator = merge_operator.ok_or_else(|| {
+                        AtlasError::Config(
+                            "WAL contains MERGE entries but no merge operator is configured"
+                                .to_string(),
+                        )
+                    })?;
+                    memtable.merge_at(key, operand, operator, seq, timestamp);
+                }
+                // Recovery consumes batch markers and checkpoints; only
+                // committed operations get here
+                Operation::BatchBegin { .. }
+                | Operation::BatchCommit
+                | Operation::Checkpoint { .. } => {}
+            }
+        }
+
+        Ok(recovery_result.last_lsn)
+    }
+
+    /// Collapse queued merge operands into plain values
+    ///
+    /// Reads each merged key's base from the SSTables and folds the operands
+    /// over it, so the flushed SSTable only holds fully-resolved values.
+    fn resolve_merges(
+        memtable: &MemTable,
+        storage: &StorageManager,
+        merge_operator: Option<&dyn MergeOperator>,
+    ) -> Result<()> {
+        for (key, entry) in memtable.iter() {
+            if let MemTableEntry::Merge(operands) = entry {
+                let operator = merge_operator.ok_or_else(|| {
+                    AtlasError::Config("No merge operator configured".to_string())
+                })?;
+                let base = storage.get(&key)?;
+                // The resolved value stands in for the last operand, so it keeps
+                // its seq and timestamp
+                let (seq, timestamp) = memtable
+                    .get_with_seq(&key)
+                    .map_or((0, 0), |found| (found.seq, found.timestamp));
+                let value = operator.merge(base.as_deref(), &operands);
+                memtable.put_at(key, value, seq, timestamp);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Open with a path (convenience method)
     ///
     /// Uses default config with the specified data directory
     pub fn open_path(path: &Path) -> Result<Self> {
-        let mut config = Config::default();
-        config.data_dir = path.to_path_buf();
+        let config = Config {
+            data_dir: path.to_path_buf(),
+            ..Config::default()
+        };
         Self::open(config)
     }
 
@@ -159,10 +385,105 @@ impl Engine {
                 Ok(None)
             }
             Command::Delete { key } => {
-                self.delete(&key)?;
-                Ok(None)
+                let existed = self.delete(&key)?;
+                Ok(Some(vec![existed as u8]))
+            }
+            Command::Append { key, data } => {
+                let new_len = self.append(&key, &data)?;
+                Ok(Some((new_len as u64).to_be_bytes().to_vec()))
+            }
+            Command::GetSet { key, value } => self.get_set(&key, &value),
+            Command::SetNx { key, value } => {
+                let written = self.put_if_absent(&key, &value)?;
+                Ok(Some(vec![written as u8]))
             }
             Command::Ping => Ok(Some(b"PONG".to_vec())),
+            Command::Health => Ok(Some(self.health()?.into_bytes())),
+            // HELLO accepts only PROTOCOL_VERSION, so every connection speaks it
+            Command::Version => Ok(Some(encode_version_response(&ServerVersion {
+                version: crate::VERSION.to_string(),
+                sstable_format: SSTABLE_FORMAT_VERSION,
+                wal_format: WAL_FORMAT_VERSION,
+                proto_version: PROTOCOL_VERSION,
+            }))),
+            Command::Flush => {
+                self.flush()?;
+                Ok(None)
+            }
+            Command::SsTables => {
+                let mut payload = Vec::new();
+                for stats in self.sstable_stats() {
+                    payload.extend_from_slice(stats.to_string().as_bytes());
+                    payload.push(b'\n');
+                }
+                Ok(Some(payload))
+            }
+            Command::StatsJson => {
+                let json = serde_json::to_vec(&self.stats())
+                    .map_err(|e| AtlasError::Serialization(e.to_string()))?;
+                Ok(Some(json))
+            }
+            Command::ScanRev { start, end, limit } => {
+                // Empty end means no upper bound
+                let end = if end.is_empty() {
+                    Bound::Unbounded
+                } else {
+                    Bound::Excluded(end.as_slice())
+                };
+                let entries = self.scan_rev(Bound::Included(&start), end, limit as usize)?;
+                Ok(Some(encode_entries(&entries)))
+            }
+            Command::ScanKeys { start, end, limit } => {
+                // Empty end means no upper bound
+                let end = if end.is_empty() {
+                    Bound::Unbounded
+                } else {
+                    Bound::Excluded(end.as_slice())
+                };
+                let keys = self.scan_keys(Bound::Included(&start), end, limit as usize)?;
+                Ok(Some(encode_keys(&keys)))
+            }
+            Command::Count { start, end } => {
+                // Empty end means no upper bound
+                let end = if end.is_empty() {
+                    Bound::Unbounded
+                } else {
+                    Bound::Excluded(end.as_slice())
+                };
+                let count = self.count_range(Bound::Included(&start), end)?;
+                Ok(Some(count.to_be_bytes().to_vec()))
+            }
+            Command::ScanCursor { after_key, limit } => {
+                let page = self.scan_after(after_key.as_deref(), limit as usize)?;
+                Ok(Some(encode_scan_page(&page)))
+            }
+            // Counted from here; only SCANCURSOR stops early. The other scans
+            // have no way to flag a partial result, so rather than run past
+            // the deadline they're refused; point commands run as usual
+            Command::WithDeadline { deadline_ms, command } => match *command {
+                Command::ScanCursor { after_key, limit } => {
+                    let deadline = Instant::now() + Duration::from_millis(deadline_ms.into());
+                    let page =
+                        self.scan_after_until(after_key.as_deref(), limit as usize, deadline)?;
+                    Ok(Some(encode_scan_page(&page)))
+                }
+                command @ (Command::ScanRev { .. }
+                | Command::ScanKeys { .. }
+                | Command::Count { .. }) => Err(AtlasError::Protocol(format!(
+                    "WITHDEADLINE can't bound {:?}; page through SCANCURSOR instead",
+                    command.command_type()
+                ))),
+                command => self.execute(command),
+            },
+            Command::Hello { .. } => Err(AtlasError::Protocol(
+                "HELLO is a connection handshake, not an engine command".to_string(),
+            )),
+            Command::GetMeta { key } => Ok(self
+                .get_with_meta(&key)?
+                .map(|(value, timestamp)| encode_get_meta_response(&value, timestamp))),
+            Command::Auth { .. } => Err(AtlasError::Protocol(
+                "AUTH is handled by the connection, not the engine".to_string(),
+            )),
         }
     }
 
@@ -171,49 +492,425 @@ impl Engine {
     /// Search order:
     /// 1. MemTable (most recent writes)
     /// 2. SSTables (newest to oldest)
+    ///
+    /// Pending merge operands in the MemTable are folded over the SSTable value.
     pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.get_arc(key)?.map(Arc::unwrap_or_clone))
+    }
+
+    /// Get a value by key without copying it out of the MemTable
+    ///
+    /// Like [`get`](Self::get), but a MemTable value comes back as a shared
+    /// `Arc` rather than a copy, which is cheaper for large, hot values.
+    /// Values read from SSTables or folded from merge operands are fresh
+    /// allocations either way.
+    pub fn get_arc(&self, key: &[u8]) -> Result<Option<Arc<Vec<u8>>>> {
+        let span = tracing::debug_span!(
+            "engine.get",
+            key_len = key.len(),
+            value_len = tracing::field::Empty,
+        );
+        let _enter = span.enter();
+        let start = Instant::now();
+
+        metrics::add(&self.metrics.gets, 1);
+
+        let result = self.get_internal_arc(key);
+        if let Ok(Some(value)) = &result {
+            span.record("value_len", value.len());
+        }
+        tracing::trace!(elapsed_us = elapsed_us(start), "get finished");
+        result
+    }
+
+    /// Check whether a key has a live value, without reading it
+    ///
+    /// Same answer as `get(key)?.is_some()`, but SSTable values (however
+    /// large) are never read, and merge operands never folded: a key with
+    /// pending operands always has a value.
+    pub fn contains(&self, key: &[u8]) -> Result<bool> {
+        metrics::add(&self.metrics.gets, 1);
+        self.contains_internal(key)
+    }
+
+    /// Internal presence check (not counted as a client lookup)
+    fn contains_internal(&self, key: &[u8]) -> Result<bool> {
+        match self.memtable.contains(key) {
+            Some(live) => Ok(live),
+            None => self.storage.contains(key),
+        }
+    }
+
+    /// Internal get implementation (not counted as a client lookup)
+    fn get_internal(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.get_internal_arc(key)?.map(Arc::unwrap_or_clone))
+    }
+
+    /// Internal get, sharing MemTable values (not counted as a client lookup)
+    fn get_internal_arc(&self, key: &[u8]) -> Result<Option<Arc<Vec<u8>>>> {
         // Step 1: Check MemTable first (most recent data)
         if let Some(entry) = self.memtable.get(key) {
             return match entry {
                 MemTableEntry::Value(value) => Ok(Some(value)),
                 MemTableEntry::Tombstone => Ok(None), // Key was deleted
+                MemTableEntry::Merge(operands) => {
+                    // Fold pending operands over the base value in SSTables
+                    let operator = self.merge_operator()?;
+                    let base = self.storage.get(key)?;
+                    Ok(Some(Arc::new(operator.merge(base.as_deref(), &operands))))
+                }
             };
         }
 
         // Step 2: Check SSTables (newest to oldest) - StorageManager internally locks
-        self.storage.get(key)
+        Ok(self.storage.get(key)?.map(Arc::new))
+    }
+
+    /// Copy a key's value into `writer`, returning its length
+    ///
+    /// For large values: one in an SSTable is copied from the file in
+    /// chunks rather than read into memory whole. MemTable values (and
+    /// keys with pending merge operands) are already in memory and are
+    /// written in one go. Returns `Ok(None)`, writing nothing, if the key
+    /// is absent or deleted.
+    pub fn get_streaming(&self, key: &[u8], mut writer: impl Write) -> Result<Option<u64>> {
+        metrics::add(&self.metrics.gets, 1);
+
+        // Step 1: MemTable (most recent data)
+        if let Some(entry) = self.memtable.get(key) {
+            let value = match entry {
+                MemTableEntry::Value(value) => value,
+                MemTableEntry::Tombstone => return Ok(None),
+                MemTableEntry::Merge(operands) => {
+                    let operator = self.merge_operator()?;
+                    let base = self.storage.get(key)?;
+                    Arc::new(operator.merge(base.as_deref(), &operands))
+                }
+            };
+            writer.write_all(&value)?;
+            return Ok(Some(value.len() as u64));
+        }
+
+        // Step 2: SSTables (newest to oldest), copied straight from the file
+        self.storage.get_streaming(key, &mut writer)
+    }
+
+    /// Get a value along with the unix-millis timestamp of its last write
+    ///
+    /// The timestamp comes from the write's WAL entry. A key with pending
+    /// merge operands reports the last operand's write. Values that
+    /// reached disk before SSTable format version 4 report 0.
+    pub fn get_with_meta(&self, key: &[u8]) -> Result<Option<(Vec<u8>, u64)>> {
+        metrics::add(&self.metrics.gets, 1);
+
+        // Step 1: MemTable (most recent data)
+        if let Some(found) = self.memtable.get_with_seq(key) {
+            return match found.entry {
+                MemTableEntry::Value(value) => {
+                    Ok(Some((Arc::unwrap_or_clone(value), found.timestamp)))
+                }
+                MemTableEntry::Tombstone => Ok(None),
+                MemTableEntry::Merge(operands) => {
+                    let operator = self.merge_operator()?;
+                    let base = self.storage.get(key)?;
+                    let value = operator.merge(base.as_deref(), &operands);
+                    Ok(Some((value, found.timestamp)))
+                }
+            };
+        }
+
+        // Step 2: SSTables (newest to oldest)
+        let found = self.storage.get_with_meta(key)?;
+        Ok(found.map(|(value, meta)| (value, meta.timestamp)))
+    }
+
+    /// Get a key's value as of sequence number `seq`
+    ///
+    /// Sees only writes with a sequence number at or below `seq` (see
+    /// `last_seq`). The MemTable keeps one version per key, so older
+    /// versions are only found once flushed: a key overwritten since
+    /// the last flush reads as its newest flushed version, or not found.
+    /// Compaction keeps only the newest version of each key.
+    pub fn get_as_of(&self, key: &[u8], seq: u64) -> Result<Option<Vec<u8>>> {
+        metrics::add(&self.metrics.gets, 1);
+
+        // Step 1: MemTable, if its version is old enough
+        if let Some(found) = self.memtable.get_with_seq(key).filter(|found| found.seq <= seq) {
+            return match found.entry {
+                MemTableEntry::Value(value) => Ok(Some(Arc::unwrap_or_clone(value))),
+                MemTableEntry::Tombstone => Ok(None),
+                MemTableEntry::Merge(operands) => {
+                    let operator = self.merge_operator()?;
+                    let base = self.storage.get_as_of(key, seq)?;
+                    Ok(Some(operator.merge(base.as_deref(), &operands)))
+                }
+            };
+        }
+
+        // Step 2: Newest SSTable version at or below `seq`
+        self.storage.get_as_of(key, seq)
+    }
+
+    /// Scan a key range from the highest key down, returning up to `limit` entries
+    ///
+    /// Same resolution as `get`: the MemTable shadows SSTables, newer
+    /// SSTables shadow older ones, and deleted keys are skipped.
+    pub fn scan_rev(
+        &self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        metrics::add(&self.metrics.scans, 1);
+
+        // Step 1: Snapshot the MemTable range, folding any merge operands
+        let newer = self.memtable_range(start, end)?;
+
+        // Step 2: Merge with SSTables (newest to oldest), highest key first
+        self.storage.scan_rev(start, end, limit, newer)
+    }
+
+    /// Get the next page of a cursor scan: up to `limit` entries with keys
+    /// strictly after `after_key` (`None` = from the first key)
+    ///
+    /// `more` tells whether entries remain. To continue, pass the last key
+    /// returned as the next `after_key`. Each page is resolved like
+    /// `scan_rev` from a fresh snapshot, so writes between pages show up
+    /// only in pages not yet read.
+    pub fn scan_after(&self, after_key: Option<&[u8]>, limit: usize) -> Result<ScanPage> {
+        self.scan_page(after_key, limit, None)
+    }
+
+    /// [`scan_after`](Self::scan_after), stopping early once `deadline` passes
+    ///
+    /// A page cut short has `deadline_exceeded` and `more` set and may hold
+    /// fewer than `limit` entries (even none); continuing from its last key
+    /// picks up where it stopped.
+    pub fn scan_after_until(
+        &self,
+        after_key: Option<&[u8]>,
+        limit: usize,
+        deadline: Instant,
+    ) -> Result<ScanPage> {
+        self.scan_page(after_key, limit, Some(deadline))
+    }
+
+    fn scan_page(
+        &self,
+        after_key: Option<&[u8]>,
+        limit: usize,
+        deadline: Option<Instant>,
+    ) -> Result<ScanPage> {
+        metrics::add(&self.metrics.scans, 1);
+
+        let start = after_key.map_or(Bound::Unbounded, Bound::Excluded);
+        let newer = self.memtable_range(start, Bound::Unbounded)?;
+
+        // One entry past the page tells whether there is another
+        let (mut entries, deadline_exceeded) = self.storage.scan(
+            start,
+            Bound::Unbounded,
+            limit.saturating_add(1),
+            newer,
+            deadline,
+        )?;
+        let more = deadline_exceeded || entries.len() > limit;
+        entries.truncate(limit);
+        Ok(ScanPage { entries, more, deadline_exceeded })
+    }
+
+    /// Snapshot the MemTable entries in a range, folding any merge operands
+    /// over the SSTable value (`None` = tombstone)
+    fn memtable_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Result<Vec<MergeEntry>> {
+        let mut entries = Vec::new();
+        for (key, entry) in self.memtable.range(start, end) {
+            let value = match entry {
+                MemTableEntry::Value(value) => Some(Arc::unwrap_or_clone(value)),
+                MemTableEntry::Tombstone => None,
+                MemTableEntry::Merge(operands) => {
+                    let operator = self.merge_operator()?;
+                    let base = self.storage.get(&key)?;
+                    Some(operator.merge(base.as_deref(), &operands))
+                }
+            };
+            entries.push((key, value));
+        }
+        Ok(entries)
+    }
+
+    /// List up to `limit` live keys in a range, in ascending order
+    ///
+    /// Resolved like `scan_rev`, but values are never read: the MemTable
+    /// contributes its keys and SSTables only their entry headers.
+    pub fn scan_keys(
+        &self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>> {
+        metrics::add(&self.metrics.scans, 1);
+
+        let newer = self.memtable.range_keys(start, end);
+        self.storage.scan_keys(start, end, limit, newer)
+    }
+
+    /// Count the live keys in a range
+    ///
+    /// Resolved like `scan_keys`, walking every entry in the range without
+    /// reading values. Nothing keeps a running count, so this is
+    /// O(entries in range) across the MemTable and all SSTables, and an
+    /// unbounded range is O(total entries).
+    pub fn count_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Result<u64> {
+        metrics::add(&self.metrics.scans, 1);
+
+        let newer = self.memtable.range_keys(start, end);
+        self.storage.count_keys(start, end, newer)
+    }
+
+    /// Estimate the number of live keys without scanning
+    ///
+    /// An upper bound, not an exact count: it adds up the MemTable and
+    /// SSTable entry counts, so a key present in several of them is counted
+    /// each time and tombstones count as keys. Costs O(SSTables) rather than
+    /// O(entries); use `count_range` for an exact figure.
+    pub fn approx_key_count(&self) -> u64 {
+        self.memtable.entry_count() as u64 + self.storage.total_sstable_entries()
+    }
+
+    /// Snapshot every live entry in ascending key order
+    ///
+    /// Resolved like `scan_rev` over the whole key space, so the result is
+    /// held in memory.
+    pub fn iter(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut entries = self.scan_rev(Bound::Unbounded, Bound::Unbounded, usize::MAX)?;
+        entries.reverse();
+        Ok(entries)
     }
 
     /// Put a key-value pair
     ///
     /// Steps:
-    /// 1. Acquire write lock
-    /// 2. Write to WAL (durability)
-    /// 3. Write to MemTable
-    /// 4. Check if flush needed
+    /// 1. Enforce key/value size limits
+    /// 2. Acquire write lock
+    /// 3. Write to WAL (durability)
+    /// 4. Write to MemTable
+    /// 5. Check if flush needed
     pub fn put(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        let span = tracing::debug_span!(
+            "engine.put",
+            key_len = key.len(),
+            value_len = value.len(),
+            memtable_size = tracing::field::Empty,
+        );
+        let _enter = span.enter();
+        let start = Instant::now();
+
+        self.check_key_size(key)?;
+        self.check_value_size(value)?;
+
+        self.throttle_writes()?;
+
         // Acquire write lock to serialize writes
-        let _write_guard = self.write_lock.lock().map_err(|e| {
-            crate::AtlasError::LockPoisoned(format!("Write lock poisoned: {}", e))
-        })?;
+        let _write_guard = self.lock_writes()?;
+
+        let result = self.put_internal(key, value);
+        tracing::trace!(elapsed_us = elapsed_us(start), "put finished");
+        result
+    }
+
+    /// Append to the value stored at a key
+    ///
+    /// Treats a missing key as an empty value. The WAL records the full
+    /// resulting value as a regular PUT, so recovery needs no special case.
+    ///
+    /// Returns the new total length of the value.
+    pub fn append(&self, key: &[u8], suffix: &[u8]) -> Result<usize> {
+        self.check_key_size(key)?;
+
+        self.throttle_writes()?;
+
+        // Acquire write lock so the read-modify-write is atomic
+        let _write_guard = self.lock_writes()?;
+
+        let mut value = self.get_internal(key)?.unwrap_or_default();
+        value.extend_from_slice(suffix);
+        self.check_value_size(&value)?;
+
+        self.put_internal(key, &value)?;
+
+        Ok(value.len())
+    }
 
+    /// Set a key to a new value and return its previous value
+    ///
+    /// The read and the write happen under one write-lock acquisition, so
+    /// concurrent callers observe a total order (usable for queues/locks).
+    ///
+    /// Returns `None` if the key was absent or deleted.
+    pub fn get_set(&self, key: &[u8], value: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.check_key_size(key)?;
+        self.check_value_size(value)?;
+
+        self.throttle_writes()?;
+
+        let _write_guard = self.lock_writes()?;
+
+        let old_value = self.get_internal(key)?;
+        self.put_internal(key, value)?;
+
+        Ok(old_value)
+    }
+
+    /// Put a key-value pair only if the key has no live value
+    ///
+    /// A deleted key counts as absent. The check and the write happen under
+    /// one write-lock acquisition, so of several racing callers exactly one
+    /// writes (usable for leader election).
+    ///
+    /// Returns whether the value was written.
+    pub fn put_if_absent(&self, key: &[u8], value: &[u8]) -> Result<bool> {
+        self.check_key_size(key)?;
+        self.check_value_size(value)?;
+
+        self.throttle_writes()?;
+
+        let _write_guard = self.lock_writes()?;
+
+        if self.contains_internal(key)? {
+            return Ok(false);
+        }
+        self.put_internal(key, value)?;
+
+        Ok(true)
+    }
+
+    /// Internal put implementation (called with write lock held)
+    fn put_internal(&self, key: &[u8], value: &[u8]) -> Result<()> {
         // Step 1: Write to WAL first (durability guarantee)
-        {
-            let mut wal = self.wal.lock().map_err(|e| {
-                crate::AtlasError::LockPoisoned(format!("WAL lock poisoned: {}", e))
-            })?;
+        let (seq, timestamp, wal_size) = {
+            let mut wal = self.lock_wal()?;
+            self.check_total_size(wal.size_bytes(), key.len() + value.len())?;
 
-            wal.append(Operation::Put {
+            let entry = wal.append_entry(Operation::Put {
                 key: key.to_vec(),
                 value: value.to_vec(),
             })?;
-        }
+            let (seq, timestamp) = (entry.lsn, entry.timestamp);
+            self.broadcast_wal(|| entry);
+            (seq, timestamp, wal.bytes_since_checkpoint())
+        };
 
         // Step 2: Write to MemTable
-        let new_size = self.memtable.put(key.to_vec(), value.to_vec());
+        let new_size = self.memtable.put_at(key.to_vec(), value.to_vec(), seq, timestamp);
+        metrics::add(&self.metrics.puts, 1);
+        tracing::Span::current().record("memtable_size", new_size);
+        self.notify_observer(|| Operation::Put {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        });
 
         // Step 3: Check if flush is needed
-        if new_size >= self.config.memtable_size_limit {
+        if self.over_flush_limits(new_size, wal_size) {
             self.flush_internal()?;
         }
 
@@ -222,33 +919,357 @@ impl Engine {
 
     /// Delete a key
     ///
+    /// Returns whether the key had a live value; a tombstone is written
+    /// either way.
+    ///
     /// Steps:
-    /// 1. Acquire write lock
-    /// 2. Write tombstone to WAL
-    /// 3. Write tombstone to MemTable
-    /// 4. Check if flush needed
-    pub fn delete(&self, key: &[u8]) -> Result<()> {
+    /// 1. Enforce key size limit
+    /// 2. Acquire write lock
+    /// 3. Check for a live value
+    /// 4. Write tombstone to WAL
+    /// 5. Write tombstone to MemTable
+    /// 6. Check if flush needed
+    pub fn delete(&self, key: &[u8]) -> Result<bool> {
+        let span = tracing::debug_span!(
+            "engine.delete",
+            key_len = key.len(),
+            memtable_size = tracing::field::Empty,
+        );
+        let _enter = span.enter();
+        let start = Instant::now();
+
+        self.check_key_size(key)?;
+
+        self.throttle_writes()?;
+
         // Acquire write lock to serialize writes
-        let _write_guard = self.write_lock.lock().map_err(|e| {
-            crate::AtlasError::LockPoisoned(format!("Write lock poisoned: {}", e))
-        })?;
+        let _write_guard = self.lock_writes()?;
+
+        // Checked under the lock, so no write lands between it and the tombstone
+        let existed = self.contains_internal(key)?;
 
         // Step 1: Write delete operation to WAL
-        {
-            let mut wal = self.wal.lock().map_err(|e| {
-                crate::AtlasError::LockPoisoned(format!("WAL lock poisoned: {}", e))
+        let (seq, timestamp, wal_size) = {
+            let mut wal = self.lock_wal()?;
+
+            let entry = wal.append_entry(Operation::Delete {
+                key: key.to_vec(),
             })?;
+            let (seq, timestamp) = (entry.lsn, entry.timestamp);
+            self.broadcast_wal(|| entry);
+            (seq, timestamp, wal.bytes_since_checkpoint())
+        };
+
+        // Step 2: Write tombstone to MemTable
+        let new_size = self.memtable.delete_at(key.to_vec(), seq, timestamp);
+        metrics::add(&self.metrics.deletes, 1);
+        span.record("memtable_size", new_size);
+        self.notify_observer(|| Operation::Delete { key: key.to_vec() });
+
+        // Step 3: Check if flush is needed
+        if self.over_flush_limits(new_size, wal_size) {
+            self.flush_internal()?;
+        }
+
+        tracing::trace!(elapsed_us = elapsed_us(start), "delete finished");
+        Ok(existed)
+    }
+
+    /// Bulk-load pre-sorted key-value pairs directly into a new SSTable
+    ///
+    /// Bypasses the WAL and MemTable entirely. Keys must be strictly
+    /// ascending in the configured comparator's order; an out-of-order key
+    /// returns `AtlasError::Storage` and nothing is loaded.
+    ///
+    /// Steps:
+    /// 1. Acquire write lock
+    /// 2. Flush the MemTable so earlier writes can't shadow loaded keys
+    /// 3. Stream entries into a new SSTable and publish it as the newest
+    pub fn bulk_load<I>(&self, sorted: I) -> Result<()>
+    where
+        I: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+    {
+        let _write_guard = self.lock_writes()?;
+
+        self.flush_internal()?;
+
+        // The whole load is one write, newer than everything before it
+        let seq = self.lock_wal()?.reserve_lsn();
+        self.storage.bulk_load(sorted, seq)?;
+
+        Ok(())
+    }
+
+    /// Write every live entry to `writer` in the dump format
+    ///
+    /// See `crate::dump` for the format. Returns the number of entries.
+    pub fn export(&self, writer: impl Write) -> Result<u64> {
+        let mut dump = DumpWriter::new(writer)?;
+        for (key, value) in self.iter()? {
+            dump.write_entry(&key, &value)?;
+        }
+        let count = dump.count();
+        dump.finish()?;
+        Ok(count)
+    }
+
+    /// Load a dump written by `export`
+    ///
+    /// The whole dump is read and its trailer verified before anything is
+    /// written, so a corrupt dump loads nothing. Entries then go through
+    /// `bulk_load`; unsorted input is sorted first, and for a key that
+    /// appears twice the later record wins. Imported keys shadow existing
+    /// values; other keys are left alone. Returns the number of entries
+    /// loaded.
+    pub fn import(&self, reader: impl Read) -> Result<u64> {
+        let mut entries = DumpReader::new(reader)?.collect::<Result<Vec<_>>>()?;
+
+        let comparator = &*self.config.comparator;
+        if !entries.windows(2).all(|pair| comparator.compare(&pair[0].0, &pair[1].0).is_lt()) {
+            // Reverse first so the stable sort puts each key's last record
+            // first, which is the one dedup keeps
+            entries.reverse();
+            entries.sort_by(|a, b| comparator.compare(&a.0, &b.0));
+            entries.dedup_by(|later, earlier| comparator.compare(&later.0, &earlier.0).is_eq());
+        }
 
-            wal.append(Operation::Delete {
+        let count = entries.len() as u64;
+        self.bulk_load(entries.into_iter())?;
+        Ok(count)
+    }
+
+    /// Record a merge operand for a key
+    ///
+    /// The operand is combined with the current value by the configured
+    /// `MergeOperator`, lazily on read or at flush. Fails with
+    /// `AtlasError::Config` if no operator is configured.
+    ///
+    /// Steps:
+    /// 1. Enforce key/operand size limits
+    /// 2. Acquire write lock
+    /// 3. Write MERGE to WAL
+    /// 4. Record operand in MemTable
+    /// 5. Check if flush needed
+    pub fn merge(&self, key: &[u8], operand: &[u8]) -> Result<()> {
+        self.check_key_size(key)?;
+        self.check_value_size(operand)?;
+        let operator = self.merge_operator()?;
+
+        self.throttle_writes()?;
+
+        // Acquire write lock to serialize writes
+        let _write_guard = self.lock_writes()?;
+
+        // Step 1: Write merge operand to WAL
+        let (seq, timestamp, wal_size) = {
+            let mut wal = self.lock_wal()?;
+            self.check_total_size(wal.size_bytes(), key.len() + operand.len())?;
+
+            let entry = wal.append_entry(Operation::Merge {
                 key: key.to_vec(),
+                operand: operand.to_vec(),
             })?;
+            let (seq, timestamp) = (entry.lsn, entry.timestamp);
+            self.broadcast_wal(|| entry);
+            (seq, timestamp, wal.bytes_since_checkpoint())
+        };
+
+        // Step 2: Record operand in MemTable
+        let new_size =
+            self.memtable.merge_at(key.to_vec(), operand.to_vec(), operator, seq, timestamp);
+        metrics::add(&self.metrics.merges, 1);
+        self.notify_observer(|| Operation::Merge {
+            key: key.to_vec(),
+            operand: operand.to_vec(),
+        });
+
+        // Step 3: Check if flush is needed
+        if self.over_flush_limits(new_size, wal_size) {
+            self.flush_internal()?;
         }
 
-        // Step 2: Write tombstone to MemTable
-        let new_size = self.memtable.delete(key.to_vec());
+        Ok(())
+    }
+
+    /// Apply several writes atomically
+    ///
+    /// The operations are logged as one WAL batch and synced once; recovery
+    /// replays all of them or none. They reach the MemTable, in order, only
+    /// once the batch is durable. Accepts `Put`, `Delete`, and `Merge`; an
+    /// empty batch is a no-op.
+    pub fn write_batch(&self, ops: Vec<Operation>) -> Result<()> {
+        // Validate up front so a bad operation can't abort a half-applied batch
+        let mut operator = None;
+        let mut incoming = 0;
+        for op in &ops {
+            match op {
+                Operation::Put { key, value } => {
+                    self.check_key_size(key)?;
+                    self.check_value_size(value)?;
+                    incoming += key.len() + value.len();
+                }
+                Operation::Delete { key } => self.check_key_size(key)?,
+                Operation::Merge { key, operand } => {
+                    self.check_key_size(key)?;
+                    self.check_value_size(operand)?;
+                    operator = Some(self.merge_operator()?);
+                    incoming += key.len() + operand.len();
+                }
+                Operation::BatchBegin { .. }
+                | Operation::BatchCommit
+                | Operation::Checkpoint { .. } => {
+                    return Err(AtlasError::Storage(
+                        "Batch markers and checkpoints are not allowed inside a batch".to_string(),
+                    ));
+                }
+            }
+        }
+        if ops.is_empty() {
+            return Ok(());
+        }
+
+        self.throttle_writes()?;
+
+        // Acquire write lock so the batch is applied as a unit
+        let _write_guard = self.lock_writes()?;
+
+        // Step 1: Log the whole batch (synced before returning)
+        let (commit_seq, timestamp, wal_size) = {
+            let mut wal = self.lock_wal()?;
+            self.check_total_size(wal.size_bytes(), incoming)?;
+
+            let commit_seq = wal.append_batch(&ops)?;
+            (commit_seq, wal.last_timestamp(), wal.bytes_since_checkpoint())
+        };
+
+        // Step 2: Apply to MemTable in order; the operations' LSNs directly
+        // precede the commit marker's, and all take the commit's timestamp
+        let mut new_size = self.memtable.size();
+        let count = ops.len();
+        let first_seq = commit_seq - count as u64;
+        self.broadcast_wal(|| WalEntry {
+            lsn: first_seq - 1,
+            operation: Operation::BatchBegin { count: count as u32 },
+            timestamp,
+        });
+        for (seq, op) in (first_seq..).zip(ops) {
+            new_size = match &op {
+                Operation::Put { key, value } => {
+                    metrics::add(&self.metrics.puts, 1);
+                    self.memtable.put_at(key.clone(), value.clone(), seq, timestamp)
+                }
+                Operation::Delete { key } => {
+                    metrics::add(&self.metrics.deletes, 1);
+                    self.memtable.delete_at(key.clone(), seq, timestamp)
+                }
+                Operation::Merge { key, operand } => {
+                    metrics::add(&self.metrics.merges, 1);
+                    let operator = operator.expect("merge operator checked above");
+                    self.memtable.merge_at(key.clone(), operand.clone(), operator, seq, timestamp)
+                }
+                Operation::BatchBegin { .. }
+                | Operation::BatchCommit
+                | Operation::Checkpoint { .. } => {
+                    unreachable!("markers rejected above")
+                }
+            };
+            self.broadcast_wal(|| WalEntry { lsn: seq, operation: op.clone(), timestamp });
+            self.notify_observer(|| op);
+        }
+        self.broadcast_wal(|| WalEntry {
+            lsn: commit_seq,
+            operation: Operation::BatchCommit,
+            timestamp,
+        });
 
         // Step 3: Check if flush is needed
-        if new_size >= self.config.memtable_size_limit {
+        if self.over_flush_limits(new_size, wal_size) {
+            self.flush_internal()?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply an entry from a primary's WAL stream (see `subscribe_wal`)
+    ///
+    /// For replicas. The entry must carry exactly the LSN this engine
+    /// would assign next (`last_seq() + 1`); a gap, duplicate, or
+    /// out-of-order LSN returns `AtlasError::Storage` and nothing is
+    /// applied. The entry is logged to the local WAL with its own LSN and
+    /// timestamp before it reaches the MemTable, so a replica that crashes
+    /// recovers it like any other write. Flushes follow the usual
+    /// triggers, but wait for the end of a batch: recovery drops a batch
+    /// whose commit marker never arrived. Until then `flush`, `close` and
+    /// `flush_on_drop` fail with `AtlasError::Storage`, leaving the batch
+    /// to the WAL.
+    ///
+    /// A replica should take no writes of its own, which would use up the
+    /// LSNs its primary sends next.
+    pub fn apply_replicated(&self, entry: WalEntry) -> Result<()> {
+        let operator = match entry.operation {
+            Operation::Merge { .. } => Some(self.merge_operator()?),
+            Operation::Checkpoint { .. } => {
+                return Err(AtlasError::Storage(
+                    "Checkpoints belong to the local WAL and can't be replicated".to_string(),
+                ));
+            }
+            _ => None,
+        };
+
+        self.throttle_writes()?;
+
+        // Acquire write lock to serialize writes
+        let _write_guard = self.lock_writes()?;
+
+        // Step 1: Log the entry as the primary did
+        let wal_size = {
+            let mut wal = self.lock_wal()?;
+            let expected = wal.current_lsn();
+            if entry.lsn != expected {
+                return Err(AtlasError::Storage(format!(
+                    "Replicated entry has LSN {}, expected {}",
+                    entry.lsn, expected
+                )));
+            }
+
+            wal.append_replicated(&entry)?;
+            self.broadcast_wal(|| entry.clone());
+            wal.bytes_since_checkpoint()
+        };
+
+        // Step 2: Apply to MemTable
+        let WalEntry { lsn: seq, operation, timestamp } = entry;
+        let new_size = match &operation {
+            Operation::Put { key, value } => {
+                metrics::add(&self.metrics.puts, 1);
+                self.memtable.put_at(key.clone(), value.clone(), seq, timestamp)
+            }
+            Operation::Delete { key } => {
+                metrics::add(&self.metrics.deletes, 1);
+                self.memtable.delete_at(key.clone(), seq, timestamp)
+            }
+            Operation::Merge { key, operand } => {
+                metrics::add(&self.metrics.merges, 1);
+                let operator = operator.expect("merge operator checked above");
+                self.memtable.merge_at(key.clone(), operand.clone(), operator, seq, timestamp)
+            }
+            Operation::BatchBegin { .. } => {
+                self.replicating_batch.store(true, Ordering::Relaxed);
+                return Ok(());
+            }
+            Operation::BatchCommit => {
+                self.replicating_batch.store(false, Ordering::Relaxed);
+                self.memtable.size()
+            }
+            Operation::Checkpoint { .. } => unreachable!("checkpoints rejected above"),
+        };
+        if !matches!(operation, Operation::BatchCommit) {
+            self.notify_observer(|| operation);
+        }
+
+        // Step 3: Check if flush is needed
+        let in_batch = self.replicating_batch.load(Ordering::Relaxed);
+        if !in_batch && self.over_flush_limits(new_size, wal_size) {
             self.flush_internal()?;
         }
 
@@ -259,42 +1280,167 @@ impl Engine {
     ///
     /// Forces a flush regardless of memtable size
     pub fn flush(&self) -> Result<()> {
-        let _write_guard = self.write_lock.lock().map_err(|e| {
-            crate::AtlasError::LockPoisoned(format!("Write lock poisoned: {}", e))
-        })?;
+        self.flush_stats()?;
+        Ok(())
+    }
+
+    /// Flush memtable to disk, reporting what was written
+    ///
+    /// Same as `flush`. An empty MemTable writes nothing and reports
+    /// `was_empty: true` with every count 0.
+    pub fn flush_stats(&self) -> Result<FlushStats> {
+        let _write_guard = self.lock_writes()?;
 
         self.flush_internal()
     }
 
+    /// Switch the WAL sync strategy without restarting (public API)
+    ///
+    /// This changes the durability guarantee live: from the next write on,
+    /// acknowledged writes are only as durable as `strategy` makes them
+    /// (e.g. `NoSync` for a bulk load in a maintenance window). Entries
+    /// already logged are synced first, so none are left behind under the
+    /// weaker strategy. `config()` keeps reporting the strategy the engine
+    /// was opened with.
+    pub fn set_wal_sync_strategy(&self, strategy: WalSyncStrategy) -> Result<()> {
+        let mut wal = self.lock_wal()?;
+        wal.sync()?;
+        wal.set_sync_strategy(strategy);
+        tracing::info!("WAL sync strategy set to {:?}", strategy);
+        Ok(())
+    }
+
+    /// Compact SSTables (public API)
+    ///
+    /// Merges the SSTables `compaction_strategy` picks (by default all of
+    /// them), dropping overwritten values, and tombstones too once no older
+    /// SSTable could hold their key and `tombstone_grace_ms` has passed.
+    /// With `max_sstable_bytes` set, the output is split into several files
+    /// with disjoint key ranges. Writes keep going into the MemTable
+    /// meanwhile, but a flush waits for the compaction to finish.
+    pub fn compact(&self) -> Result<()> {
+        self.storage.compact()?;
+        Ok(())
+    }
+
+    /// Delete all data, keeping the directory (public API)
+    ///
+    /// SSTables go first, then the MemTable and WAL. A crash part-way
+    /// therefore loses only older data: on restart the WAL may replay the
+    /// most recent writes, but nothing they overwrote or deleted can come back.
+    pub fn clear(&self) -> Result<()> {
+        let _write_guard = self.lock_writes()?;
+
+        // Step 1: Drop every SSTable (ids restart at 1)
+        self.storage.clear()?;
+
+        // Step 2: Drop the MemTable and the WAL entries backing it
+        self.memtable.clear();
+        {
+            let mut wal = self.lock_wal()?;
+
+            wal.truncate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether a write left the MemTable or the WAL over its limit
+    ///
+    /// `wal_size` is the WAL's size since its latest checkpoint.
+    fn over_flush_limits(&self, memtable_size: usize, wal_size: u64) -> bool {
+        let wal_limit = self.config.wal_max_bytes;
+        memtable_size >= self.config.memtable_size_limit || (wal_limit > 0 && wal_size >= wal_limit)
+    }
+
     /// Internal flush implementation (called with write lock held)
-    fn flush_internal(&self) -> Result<()> {
+    fn flush_internal(&self) -> Result<FlushStats> {
+        // A flush mid-batch would persist half the batch and checkpoint or
+        // reset the WAL between its markers, which recovery reads as corruption
+        if self.replicating_batch.load(Ordering::Relaxed) {
+            return Err(AtlasError::Storage(
+                "Can't flush inside a replicated batch; apply its BatchCommit first".to_string(),
+            ));
+        }
+
         // Skip if memtable is empty
         if self.memtable.is_empty() {
-            return Ok(());
+            return Ok(FlushStats { was_empty: true, ..FlushStats::default() });
         }
 
-        // Step 1: Flush memtable to SSTable (StorageManager internally locks)
-        self.storage.flush(&self.memtable)?;
+        let span = tracing::debug_span!(
+            "engine.flush",
+            memtable_entries = self.memtable.entry_count(),
+            memtable_size = self.memtable.size(),
+        );
+        let _enter = span.enter();
+        let start = Instant::now();
 
-        // Step 2: Clear memtable
+        // Step 1: Collapse merge operands into values, then stream the
+        // MemTable into an SSTable. Entries are written straight from the
+        // map, so a large MemTable isn't copied just to be dropped.
+        Self::resolve_merges(&self.memtable, &self.storage, self.config.merge_operator.as_deref())?;
+        let sstable = self.storage.flush(&self.memtable)?;
+        metrics::add(&self.metrics.flushes, 1);
+
+        // Step 2: Drop the flushed entries. The write lock kept writers out
+        // since Step 1, so the MemTable holds exactly what was written.
+        // Readers never miss a key: it is published in the SSTable before
+        // it leaves the MemTable.
         self.memtable.clear();
 
-        // Step 3: Truncate WAL (entries are now durable in SSTable)
+        // Step 3: Checkpoint or truncate the WAL (entries are now durable
+        // in SSTable). Only safe because the write lock keeps new writes out
+        // of the WAL until the flush is done; otherwise they'd be skipped or
+        // dropped here. LSNs carry on from where they were, since they
+        // double as sequence numbers.
+        debug_assert!(self.memtable.is_empty());
         {
-            let mut wal = self.wal.lock().map_err(|e| {
-                crate::AtlasError::LockPoisoned(format!("WAL lock poisoned: {}", e))
-            })?;
+            let mut wal = self.lock_wal()?;
 
-            wal.truncate()?;
+            let checkpoint_limit = self.config.wal_checkpoint_max_bytes;
+            if checkpoint_limit > 0 && wal.size_bytes() < checkpoint_limit {
+                wal.checkpoint()?;
+            } else {
+                let next_lsn = wal.current_lsn();
+                wal.reset(next_lsn)?;
+            }
         }
 
-        Ok(())
+        // No SSTable if every entry was a tombstone with nothing to shadow
+        let stats = match sstable {
+            Some(sstable) => FlushStats {
+                entries: sstable.entry_count,
+                bytes: sstable.file_size,
+                sstable_id: StorageManager::parse_sstable_id(&sstable.path).unwrap_or_default(),
+                was_empty: false,
+            },
+            None => FlushStats::default(),
+        };
+        tracing::debug!(
+            elapsed_us = elapsed_us(start),
+            entries = stats.entries,
+            bytes = stats.bytes,
+            sstable_id = stats.sstable_id,
+            "flush finished"
+        );
+        Ok(stats)
     }
 
     /// Close the engine gracefully
     ///
     /// Flushes any pending data and syncs to disk
     pub fn close(self) -> Result<()> {
+        self.shutdown()
+    }
+
+    /// Flush the MemTable and sync the WAL (`close` and `flush_on_drop`)
+    fn shutdown(&self) -> Result<()> {
+        // Nothing to persist for a read-only handle
+        if self.is_read_only() {
+            return Ok(());
+        }
+
         // Flush any remaining data in memtable
         if !self.memtable.is_empty() {
             self.flush()?;
@@ -302,9 +1448,7 @@ impl Engine {
 
         // Sync WAL to ensure all data is on disk
         {
-            let mut wal = self.wal.lock().map_err(|e| {
-                crate::AtlasError::LockPoisoned(format!("WAL lock poisoned: {}", e))
-            })?;
+            let mut wal = self.lock_wal()?;
 
             wal.sync()?;
         }
@@ -312,10 +1456,231 @@ impl Engine {
         Ok(())
     }
 
+    // =========================================================================
+    // Health
+    // =========================================================================
+
+    /// Check the engine can still serve writes, returning a short status line
+    ///
+    /// Fails if a probe file can't be created, synced, and removed in the
+    /// storage directory (missing, unwritable, or out of space). The engine's
+    /// locks are `parking_lot` locks, which a panicking writer can't poison,
+    /// and the WAL itself is not written: an append can't be taken back
+    /// without disturbing LSNs. A read-only engine only checks that the
+    /// storage directory can be listed.
+    pub fn health(&self) -> Result<String> {
+        let storage_error = |e: std::io::Error| {
+            AtlasError::Storage(format!(
+                "storage dir {} unusable: {}",
+                self.storage_dir.display(),
+                e
+            ))
+        };
+        if self.is_read_only() {
+            fs::read_dir(&self.storage_dir).map_err(storage_error)?;
+        } else {
+            let probe = self.storage_dir.join(Self::HEALTH_PROBE_FILENAME);
+            fs::File::create(&probe)
+                .and_then(|file| file.sync_all())
+                .and_then(|()| fs::remove_file(&probe))
+                .map_err(storage_error)?;
+        }
+
+        Ok(format!(
+            "OK: {} sstables, {} memtable bytes",
+            self.storage.sstable_count(),
+            self.memtable.size()
+        ))
+    }
+
+    // =========================================================================
+    // Write Throttling
+    // =========================================================================
+
+    /// Slow or refuse a write while too many SSTables are live
+    ///
+    /// Past `sstable_stall_threshold` the write sleeps `WRITE_STALL_DELAY`
+    /// first, giving compaction room to catch up; past
+    /// `sstable_stop_threshold` it fails. Called before taking the write
+    /// lock, so a stalled writer doesn't hold up the others.
+    fn throttle_writes(&self) -> Result<()> {
+        let count = self.storage.sstable_count();
+
+        let stop = self.config.sstable_stop_threshold;
+        if stop > 0 && count > stop {
+            return Err(AtlasError::Storage("too many sstables, write stalled".to_string()));
+        }
+
+        let stall = self.config.sstable_stall_threshold;
+        if stall > 0 && count > stall {
+            tracing::debug!(sstables = count, "write stalled");
+            std::thread::sleep(WRITE_STALL_DELAY);
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // Size Limits
+    // =========================================================================
+
+    /// Reject keys larger than `config.max_key_size`
+    fn check_key_size(&self, key: &[u8]) -> Result<()> {
+        if key.len() > self.config.max_key_size {
+            return Err(AtlasError::Storage(format!(
+                "Key too large: {} bytes (max {})",
+                key.len(),
+                self.config.max_key_size
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reject values larger than `config.max_value_size`
+    fn check_value_size(&self, value: &[u8]) -> Result<()> {
+        if value.len() > self.config.max_value_size {
+            return Err(AtlasError::Storage(format!(
+                "Value too large: {} bytes (max {})",
+                value.len(),
+                self.config.max_value_size
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reject a write of `incoming` bytes that would take the database past
+    /// `config.max_total_bytes` (called with the WAL lock held, before logging)
+    ///
+    /// SSTable, WAL, and MemTable sizes are all tracked in memory, so this
+    /// stats no files. Writes adding nothing (deletes) always pass.
+    fn check_total_size(&self, wal_size: u64, incoming: usize) -> Result<()> {
+        let Some(max) = self.config.max_total_bytes else {
+            return Ok(());
+        };
+        if incoming == 0 {
+            return Ok(());
+        }
+
+        let total = self.storage.total_size_bytes() + wal_size + self.memtable.size() as u64;
+        if total + incoming as u64 > max {
+            return Err(AtlasError::Storage("database full".to_string()));
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // Merge Helpers
+    // =========================================================================
+
+    /// Get the configured merge operator
+    fn merge_operator(&self) -> Result<&dyn MergeOperator> {
+        self.config
+            .merge_operator
+            .as_deref()
+            .ok_or_else(|| AtlasError::Config("No merge operator configured".to_string()))
+    }
+
+    // =========================================================================
+    // Write Observer
+    // =========================================================================
+
+    /// Register a callback fed every write that goes through the WAL
+    ///
+    /// Fires after each successful put/delete/merge (append and get-set
+    /// report as puts), once the write is in both the WAL and the MemTable.
+    /// It runs under the write lock, so calls arrive in WAL order, and every
+    /// write waits for it: keep it cheap. `bulk_load`, `clear`, and WAL
+    /// replay on open are not reported. Replaces any previous observer.
+    ///
+    /// A panic in the observer is caught and logged. The write it was
+    /// reporting has already been applied, and the engine stays usable.
+    pub fn set_write_observer(&self, observer: WriteObserver) {
+        *self.observer.write().unwrap_or_else(PoisonError::into_inner) = Some(observer);
+    }
+
+    /// Report an applied write to the observer (called with write lock held)
+    ///
+    /// `operation` is only built when an observer is registered.
+    fn notify_observer(&self, operation: impl FnOnce() -> Operation) {
+        let observer = self.observer.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(observer) = observer.as_ref() {
+            let operation = operation();
+            if panic::catch_unwind(AssertUnwindSafe(|| observer(&operation))).is_err() {
+                tracing::error!("Write observer panicked; the write was still applied");
+            }
+        }
+    }
+
+    /// Subscribe to a live stream of WAL entries, e.g. for replication
+    ///
+    /// The receiver gets every put/delete/merge logged from now on, in LSN
+    /// order, ready for a replica's `apply_replicated`. Batches arrive with
+    /// their begin and commit markers, every entry carrying the commit's
+    /// timestamp. `bulk_load` and `clear` are not streamed, so a replica
+    /// must be reseeded after them. Delivery is best-effort: a subscriber
+    /// more than `WAL_SUBSCRIBER_CAPACITY` entries behind misses new ones
+    /// (counted in `EngineMetrics::wal_stream_dropped`) rather than
+    /// stalling writes. Dropping the receiver unsubscribes.
+    pub fn subscribe_wal(&self) -> Receiver<WalEntry> {
+        let (sender, receiver) = bounded(WAL_SUBSCRIBER_CAPACITY);
+        self.wal_subscribers.lock().push(sender);
+        receiver
+    }
+
+    /// Forward a logged entry to WAL subscribers (called with write lock held)
+    ///
+    /// `entry` is only built when someone is subscribed. Subscribers whose
+    /// receiver is gone are removed.
+    fn broadcast_wal(&self, entry: impl FnOnce() -> WalEntry) {
+        let mut subscribers = self.wal_subscribers.lock();
+        if subscribers.is_empty() {
+            return;
+        }
+        let entry = entry();
+        subscribers.retain(|subscriber| match subscriber.try_send(entry.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                metrics::add(&self.metrics.wal_stream_dropped, 1);
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+
+    // =========================================================================
+    // Lock Helpers
+    // =========================================================================
+
+    /// Acquire the write lock, refusing if the engine is read-only
+    ///
+    /// Like the WAL lock, it doesn't poison: a writer that panics part-way
+    /// leaves the engine usable for the next one.
+    fn lock_writes(&self) -> Result<MutexGuard<'_, ()>> {
+        if self.is_read_only() {
+            return Err(AtlasError::Storage("read-only".to_string()));
+        }
+
+        Ok(self.write_lock.lock())
+    }
+
+    /// Acquire the WAL (only reachable with the write lock held, so never read-only)
+    fn lock_wal(&self) -> Result<MutexGuard<'_, WalWriter>> {
+        let wal = self
+            .wal
+            .as_ref()
+            .ok_or_else(|| AtlasError::Storage("read-only".to_string()))?;
+
+        Ok(wal.lock())
+    }
+
     // =========================================================================
     // Accessors (for testing and debugging)
     // =========================================================================
 
+    /// Check if the engine was opened read-only
+    pub fn is_read_only(&self) -> bool {
+        self.wal.is_none()
+    }
+
     /// Get the data directory path
     pub fn data_dir(&self) -> &Path {
         &self.config.data_dir
@@ -336,13 +1701,141 @@ impl Engine {
         self.memtable.entry_count()
     }
 
+    /// Get the MemTable size at which writes flush (`Config::memtable_size_limit`)
+    pub fn memtable_size_limit(&self) -> usize {
+        self.config.memtable_size_limit
+    }
+
+    /// Whether the MemTable has reached its size limit, without flushing
+    ///
+    /// Writes flush on their own once it does, so this is mostly true while
+    /// a flush is held off: inside a replicated batch, after a failed
+    /// flush, or on a read-only engine that replayed a large WAL.
+    pub fn needs_flush(&self) -> bool {
+        self.memtable.should_flush(self.config.memtable_size_limit)
+    }
+
+    /// Get the engine's operation counters
+    pub fn metrics(&self) -> &EngineMetrics {
+        &self.metrics
+    }
+
+    /// Get a snapshot of engine state (what STATSJSON serves)
+    pub fn stats(&self) -> EngineStats {
+        let sstables = self.sstable_stats();
+        EngineStats {
+            memtable_entries: self.memtable.entry_count(),
+            memtable_size_bytes: self.memtable.size(),
+            sstable_count: sstables.len(),
+            sstable_total_bytes: sstables.iter().map(|s| s.file_size).sum(),
+            sstables,
+            ops: self.metrics.snapshot(),
+            block_cache: self.block_cache_stats(),
+            wal_segments: self.wal_segments(),
+            read_only: self.is_read_only(),
+        }
+    }
+
+    /// Get per-SSTable statistics, newest first
+    pub fn sstable_stats(&self) -> Vec<SSTableStats> {
+        self.storage.sstable_stats()
+    }
+
+    /// List the WAL's files and their LSN ranges (empty when read-only)
+    ///
+    /// See `WalWriter::segments`; also reported by STATSJSON.
+    pub fn wal_segments(&self) -> Vec<SegmentInfo> {
+        self.wal.as_ref().map_or_else(Vec::new, |wal| wal.lock().segments())
+    }
+
+    /// Get the sequence number of the latest write (0 before any)
+    ///
+    /// Pass it to `get_as_of` to read the store as it is now.
+    pub fn last_seq(&self) -> Result<u64> {
+        Ok(self.lock_wal()?.current_lsn() - 1)
+    }
+
     /// Get the number of SSTables
     pub fn sstable_count(&self) -> usize {
         self.storage.sstable_count()
     }
 
+    /// Get SSTable value cache statistics (`None` when the cache is disabled)
+    pub fn block_cache_stats(&self) -> Option<BlockCacheStats> {
+        self.storage.block_cache_stats()
+    }
+
     /// Get the configuration
     pub fn config(&self) -> &Config {
         &self.config
     }
 }
+
+/// Background thread that syncs the WAL on a timer
+///
+/// Under `EveryNEntries`, entries short of the count would otherwise wait
+/// for the next write to be synced. Each tick takes the WAL lock only long
+/// enough to sync, and only if something is unsynced.
+struct WalSyncer {
+    /// Dropped to stop the thread (wakes it at once, mid-interval)
+    stop: Sender<()>,
+
+    /// Sync loop thread
+    handle: JoinHandle<()>,
+}
+
+impl WalSyncer {
+    /// Start syncing `wal` every `interval`
+    fn start(wal: Arc<Mutex<WalWriter>>, interval: Duration) -> Result<Self> {
+        let (stop, stopped) = bounded::<()>(0);
+        let handle = thread::Builder::new()
+            .name("atlaskv-wal-sync".to_string())
+            .spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let mut wal = wal.lock();
+                    if wal.uncommitted_count() > 0 {
+                        if let Err(e) = wal.sync() {
+                            tracing::error!("Background WAL sync failed: {}", e);
+                        }
+                    }
+                }
+            })
+            .map_err(|e| AtlasError::Storage(format!("Failed to spawn WAL sync thread: {}", e)))?;
+
+        Ok(Self { stop, handle })
+    }
+
+    /// Stop the thread and wait for it to exit
+    fn stop(self) {
+        drop(self.stop);
+        if let Err(e) = self.handle.join() {
+            tracing::error!("WAL sync thread panicked: {:?}", e);
+        }
+    }
+}
+
+/// Stops the WAL syncer, then does a best-effort `close` for engines
+/// dropped without one if `Config::flush_on_drop` is set
+///
+/// Errors are logged, never raised: a panic here would abort a thread
+/// that is already unwinding. With the flag off (the default) nothing is
+/// flushed, so the WAL keeps unflushed writes exactly as after a crash,
+/// which the recovery tests depend on.
+impl Drop for Engine {
+    fn drop(&mut self) {
+        if let Some(syncer) = self.wal_syncer.take() {
+            syncer.stop();
+        }
+        if !self.config.flush_on_drop || self.is_read_only() {
+            return;
+        }
+        if let Err(e) = self.shutdown() {
+            tracing::error!("Flush on drop failed: {}", e);
+        }
+    }
+}
+
+/// Microseconds since `start`, for tracing events
+fn elapsed_us(start: Instant) -> u64 {
+    start.elapsed().as_micros() as u64
+}
@@ -33,6 +33,11 @@ pub enum AtlasError {
     #[error("Key not found")]
     KeyNotFound,
 
+    /// SSTable file is shorter than its header/footer claim (e.g. crash mid-write).
+    /// Recoverable: the storage manager skips the file instead of failing startup.
+    #[error("SSTable truncated: {0}")]
+    SSTableTruncated(String),
+
     // -------------------------------------------------------------------------
     // Serialization Errors
     // -------------------------------------------------------------------------
@@ -48,15 +53,13 @@ pub enum AtlasError {
     #[error("Protocol error: {0}")]
     Protocol(String),
 
+    /// Error response returned by a remote server
+    #[error("Server error: {0}")]
+    Server(String),
+
     // -------------------------------------------------------------------------
     // Configuration Errors
     // -------------------------------------------------------------------------
     #[error("Configuration error: {0}")]
     Config(String),
-
-    // -------------------------------------------------------------------------
-    // Concurrency Errors
-    // -------------------------------------------------------------------------
-    #[error("Lock poisoned: {0}")]
-    LockPoisoned(String),
 }
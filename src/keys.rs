@@ -0,0 +1,53 @@
+//! Key Encoding Helpers
+//!
+//! Fixed-width, big-endian encodings for numeric keys.
+//!
+//! ## Why
+//! MemTables and SSTables order keys by raw bytes. Little-endian integers
+//! (`to_le_bytes`) don't sort numerically as bytes: 256 (`00 01 ...`) sorts
+//! before 1 (`01 00 ...`). Big-endian fixed-width encodings do, so range
+//! scans over these keys come out in numeric order.
+//!
+//! ## Signed integers
+//! Two's complement puts negatives (sign bit 1) after positives. Flipping
+//! the sign bit before encoding maps `i64::MIN..=i64::MAX` onto
+//! `0..=u64::MAX` in order.
+
+use crate::error::{AtlasError, Result};
+
+/// Encoded width of a 64-bit key
+pub const U64_KEY_SIZE: usize = 8;
+
+/// Sign bit of a 64-bit integer
+const SIGN_BIT: u64 = 1 << 63;
+
+/// Encode a `u64` so that byte order matches numeric order
+pub fn encode_u64_be(value: u64) -> [u8; U64_KEY_SIZE] {
+    value.to_be_bytes()
+}
+
+/// Decode a key written by `encode_u64_be`
+///
+/// Fails unless `bytes` is exactly 8 bytes long.
+pub fn decode_u64_be(bytes: &[u8]) -> Result<u64> {
+    let bytes: [u8; U64_KEY_SIZE] = bytes.try_into().map_err(|_| {
+        AtlasError::Serialization(format!(
+            "Numeric key: expected {} bytes, got {}",
+            U64_KEY_SIZE,
+            bytes.len()
+        ))
+    })?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// Encode an `i64` so that byte order matches numeric order (negatives first)
+pub fn encode_i64_be(value: i64) -> [u8; U64_KEY_SIZE] {
+    encode_u64_be(value as u64 ^ SIGN_BIT)
+}
+
+/// Decode a key written by `encode_i64_be`
+///
+/// Fails unless `bytes` is exactly 8 bytes long.
+pub fn decode_i64_be(bytes: &[u8]) -> Result<i64> {
+    Ok((decode_u64_be(bytes)? ^ SIGN_BIT) as i64)
+}
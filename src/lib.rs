@@ -43,10 +43,16 @@ pub mod config;
 
 pub mod wal;
 pub mod memtable;
+pub mod keys;
+pub mod comparator;
+pub mod merge;
+pub mod metrics;
 pub mod storage;
 pub mod network;
 pub mod protocol;
 pub mod engine;
+pub mod client;
+pub mod dump;
 
 // =============================================================================
 // Public API Re-exports
@@ -55,6 +61,7 @@ pub mod engine;
 pub use error::{AtlasError, Result};
 pub use config::Config;
 pub use engine::Engine;
+pub use client::Client;
 
 // =============================================================================
 // Version Info
@@ -10,20 +10,33 @@
 //!
 //! ## Data Structure Choice
 //! Using BTreeMap wrapped in RwLock for V1:
-//! - Ordered keys (required for SSTable generation)
+//! - Ordered keys (required for SSTable generation), in the configured
+//!   comparator's order via a wrapper key type
 //! - Simple and correct first, optimize later
 //! - Future: Consider SkipList for better concurrent performance
 
 mod table;
 
-pub use table::MemTable;
+use std::sync::Arc;
+
+pub use table::{MemTable, SequencedEntry, SortedEntries};
+
+/// Default per-entry bookkeeping overhead (in bytes) added to size accounting
+///
+/// Approximates BTreeMap node space plus the two `Vec` headers per entry,
+/// so the flush trigger tracks real memory use rather than raw payload bytes.
+pub const DEFAULT_ENTRY_OVERHEAD: usize = 64;
 
 /// Entry stored in the MemTable
 #[derive(Debug, Clone, PartialEq)]
 pub enum MemTableEntry {
-    /// A live value
-    Value(Vec<u8>),
+    /// A live value, shared so reads can hand it out without copying
+    Value(Arc<Vec<u8>>),
 
     /// A tombstone (deleted key)
     Tombstone,
+
+    /// Unresolved merge operands (oldest first) awaiting a base value
+    /// from the SSTables
+    Merge(Vec<Vec<u8>>),
 }
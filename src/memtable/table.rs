@@ -3,90 +3,267 @@
 //! BTreeMap-based memtable with RwLock for concurrency.
 //! Uses parking_lot::RwLock which never poisons on panic.
 
-use super::MemTableEntry;
-use std::collections::BTreeMap;
+use super::{MemTableEntry, DEFAULT_ENTRY_OVERHEAD};
+use crate::comparator::{is_empty_range, BytewiseComparator, Comparator};
+use crate::merge::MergeOperator;
+use std::cmp;
+use std::collections::{btree_map, BTreeMap};
+use std::ops::Bound;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use parking_lot::RwLock;
 
+/// A MemTable key, ordered by the table's comparator
+struct MemKey {
+    bytes: Vec<u8>,
+    comparator: Arc<dyn Comparator>,
+}
+
+impl PartialEq for MemKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+
+impl Eq for MemKey {}
+
+impl PartialOrd for MemKey {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MemKey {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.comparator.compare(&self.bytes, &other.bytes)
+    }
+}
+
+/// A MemTable entry with the sequence number and time of the write that produced it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequencedEntry {
+    /// The entry itself
+    pub entry: MemTableEntry,
+    /// WAL LSN of the latest write to the key (0 = unsequenced)
+    pub seq: u64,
+    /// Unix millis of the latest write to the key, from its WAL entry (0 = unknown)
+    pub timestamp: u64,
+}
+
 /// In-memory table for recent writes
+///
+/// Holds one version per key. The `put`/`delete`/`merge` methods record
+/// seq and timestamp 0; the engine uses the `_at` variants to tag entries
+/// with the write's WAL LSN and timestamp.
 pub struct MemTable {
     /// Sorted key-value store with concurrent access
-    data: RwLock<BTreeMap<Vec<u8>, MemTableEntry>>,
+    data: RwLock<BTreeMap<MemKey, SequencedEntry>>,
+
+    /// Order of `data`'s keys (shared by every `MemKey`)
+    comparator: Arc<dyn Comparator>,
     
     /// Approximate size in bytes (for flush trigger)
     size: AtomicUsize,
+
+    /// Fixed bytes charged per entry on top of key/value lengths
+    entry_overhead: usize,
 }
 
 impl MemTable {
     /// Create a new empty MemTable
     pub fn new() -> Self {
+        Self::with_entry_overhead(DEFAULT_ENTRY_OVERHEAD)
+    }
+
+    /// Create a new empty MemTable with a custom per-entry overhead
+    pub fn with_entry_overhead(entry_overhead: usize) -> Self {
         MemTable { 
             data: RwLock::new(BTreeMap::new()), 
+            comparator: Arc::new(BytewiseComparator),
             size: AtomicUsize::new(0),
+            entry_overhead,
+        }
+    }
+
+    /// Order keys with `comparator` instead of byte order (call while empty)
+    pub fn with_comparator(mut self, comparator: Arc<dyn Comparator>) -> Self {
+        debug_assert!(self.data.get_mut().is_empty(), "comparator set on a non-empty MemTable");
+        self.comparator = comparator;
+        self
+    }
+
+    /// Wrap key bytes for use with `data`
+    fn key(&self, bytes: Vec<u8>) -> MemKey {
+        MemKey {
+            bytes,
+            comparator: self.comparator.clone(),
         }
     }
 
+    /// Accounted size of a single entry: overhead + key + value
+    fn entry_size(&self, key: &[u8], entry: &MemTableEntry) -> usize {
+        let value_len = match entry {
+            MemTableEntry::Value(v) => v.len(),
+            MemTableEntry::Tombstone => 0,
+            MemTableEntry::Merge(operands) => operands.iter().map(Vec::len).sum(),
+        };
+        self.entry_overhead + key.len() + value_len
+    }
+
+    /// Swap one entry's accounted size for another's
+    /// Returns new total size
+    ///
+    /// A single atomic update that saturates at 0, so accounting drift can
+    /// never wrap the total to a huge value and wedge the flush trigger.
+    /// Callers hold the write lock, which is what keeps the total exact.
+    fn replace_size(&self, old_size: usize, new_size: usize) -> usize {
+        let previous = self
+            .size
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |total| {
+                Some(total.saturating_sub(old_size).saturating_add(new_size))
+            })
+            .unwrap(); // closure always returns Some
+
+        debug_assert!(
+            previous >= old_size,
+            "MemTable size accounting drifted: total {} < replaced entry {}",
+            previous,
+            old_size
+        );
+
+        previous.saturating_sub(old_size).saturating_add(new_size)
+    }
+
     /// Get a value by key (read lock)
+    ///
+    /// A live value comes back as a clone of its `Arc`, never a copy.
     pub fn get(&self, key: &[u8]) -> Option<MemTableEntry> {
         let data = self.data.read();
-        data.get(key).cloned()
+        data.get(&self.key(key.to_vec())).map(|found| found.entry.clone())
+    }
+
+    /// Check whether a key is present without cloning its value (read lock)
+    ///
+    /// `Some(true)` for a value or merge operands, `Some(false)` for a
+    /// tombstone, `None` if the key isn't in the MemTable.
+    pub fn contains(&self, key: &[u8]) -> Option<bool> {
+        let data = self.data.read();
+        data.get(&self.key(key.to_vec()))
+            .map(|found| !matches!(found.entry, MemTableEntry::Tombstone))
+    }
+
+    /// Get a value by key along with its sequence number (read lock)
+    pub fn get_with_seq(&self, key: &[u8]) -> Option<SequencedEntry> {
+        let data = self.data.read();
+        data.get(&self.key(key.to_vec())).cloned()
     }
 
     /// Put a key-value pair (write lock)
     /// Returns new total size
     pub fn put(&self, key: Vec<u8>, value: Vec<u8>) -> usize {
-        let entry_size = key.len() + value.len();
+        self.put_at(key, value, 0, 0)
+    }
+
+    /// Put a key-value pair written at sequence number `seq` and unix-millis
+    /// `timestamp` (write lock)
+    /// Returns new total size
+    pub fn put_at(&self, key: Vec<u8>, value: Vec<u8>, seq: u64, timestamp: u64) -> usize {
+        self.insert(key, MemTableEntry::Value(Arc::new(value)), seq, timestamp)
+    }
+
+    /// Delete a key (write lock, inserts tombstone)
+    /// Returns new total size
+    pub fn delete(&self, key: Vec<u8>) -> usize {
+        self.delete_at(key, 0, 0)
+    }
+
+    /// Delete a key at sequence number `seq` and unix-millis `timestamp`
+    /// (write lock, inserts tombstone)
+    /// Returns new total size
+    pub fn delete_at(&self, key: Vec<u8>, seq: u64, timestamp: u64) -> usize {
+        // Tombstone = overhead + key
+        self.insert(key, MemTableEntry::Tombstone, seq, timestamp)
+    }
+
+    /// Replace a key's entry (write lock)
+    /// Returns new total size
+    fn insert(&self, key: Vec<u8>, entry: MemTableEntry, seq: u64, timestamp: u64) -> usize {
+        let entry_size = self.entry_size(&key, &entry);
+        let key = self.key(key);
         let mut data = self.data.write();
 
         let old_size = data.get(&key)
-            .map(|entry| match entry {
-                MemTableEntry::Value(v) => key.len() + v.len(),
-                MemTableEntry::Tombstone => key.len(),
-            })
+            .map(|old| self.entry_size(&key.bytes, &old.entry))
             .unwrap_or(0);
 
-        data.insert(key, MemTableEntry::Value(value));
+        data.insert(key, SequencedEntry { entry, seq, timestamp });
 
-        let size_delta = entry_size as isize - old_size as isize;
-        if size_delta > 0 {
-            self.size.fetch_add(size_delta as usize, Ordering::Relaxed);
-        } else {
-            self.size.fetch_sub((-size_delta) as usize, Ordering::Relaxed);
-        }
+        self.replace_size(old_size, entry_size)
+    }
 
-        self.size.load(Ordering::Relaxed)
+    /// Record a merge operand for a key (write lock)
+    /// Returns new total size
+    ///
+    /// A live value or tombstone already in the MemTable is a known base, so
+    /// the operand is folded in immediately. Otherwise the operand is queued
+    /// until a read or flush supplies the base from the SSTables.
+    pub fn merge(&self, key: Vec<u8>, operand: Vec<u8>, operator: &dyn MergeOperator) -> usize {
+        self.merge_at(key, operand, operator, 0, 0)
     }
 
-    /// Delete a key (write lock, inserts tombstone)
+    /// Record a merge operand written at sequence number `seq` and unix-millis
+    /// `timestamp` (write lock)
     /// Returns new total size
-    pub fn delete(&self, key: Vec<u8>) -> usize {
+    pub fn merge_at(
+        &self,
+        key: Vec<u8>,
+        operand: Vec<u8>,
+        operator: &dyn MergeOperator,
+        seq: u64,
+        timestamp: u64,
+    ) -> usize {
+        let key = self.key(key);
         let mut data = self.data.write();
 
-        let old_size = data.get(&key)
-            .map(|entry| match entry {
-                MemTableEntry::Value(v) => key.len() + v.len(),
-                MemTableEntry::Tombstone => key.len(),
-            })
+        // Keep the stored spelling of a key the comparator finds equal
+        let (key, old) = match data.remove_entry(&key) {
+            Some((stored, old)) => (stored, Some(old.entry)),
+            None => (key, None),
+        };
+        let old_size = old.as_ref()
+            .map(|old| self.entry_size(&key.bytes, old))
             .unwrap_or(0);
 
-        let new_size = key.len(); // Tombstone = just key
-        data.insert(key, MemTableEntry::Tombstone);
+        let entry = match old {
+            Some(MemTableEntry::Value(v)) => {
+                MemTableEntry::Value(Arc::new(operator.merge(Some(&v), &[operand])))
+            }
+            Some(MemTableEntry::Tombstone) => {
+                MemTableEntry::Value(Arc::new(operator.merge(None, &[operand])))
+            }
+            Some(MemTableEntry::Merge(mut operands)) => {
+                operands.push(operand);
+                MemTableEntry::Merge(operands)
+            }
+            None => MemTableEntry::Merge(vec![operand]),
+        };
 
-        let size_delta = new_size as isize - old_size as isize;
-        if size_delta > 0 {
-            self.size.fetch_add(size_delta as usize, Ordering::Relaxed);
-        } else {
-            self.size.fetch_sub((-size_delta) as usize, Ordering::Relaxed);
-        }
+        let new_size = self.entry_size(&key.bytes, &entry);
+        data.insert(key, SequencedEntry { entry, seq, timestamp });
 
-        self.size.load(Ordering::Relaxed)
+        self.replace_size(old_size, new_size)
     }
 
-    /// Get current size in bytes
+    /// Get current size in bytes (includes per-entry overhead)
     pub fn size(&self) -> usize {
         self.size.load(Ordering::Relaxed)
     }
 
+    /// Get the per-entry overhead charged by size accounting
+    pub fn entry_overhead(&self) -> usize {
+        self.entry_overhead
+    }
+
     /// Get entry count
     pub fn entry_count(&self) -> usize {
         self.data.read().len()
@@ -98,20 +275,74 @@ impl MemTable {
     }
 
     /// Check if size exceeds limit (for flush trigger)
+    ///
+    /// Compares against the overhead-inclusive size, so many tiny entries
+    /// trigger a flush well before raw key/value bytes reach the limit.
     pub fn should_flush(&self, size_limit: usize) -> bool {
         self.size() >= size_limit
     }
 
-    /// Get a snapshot of all entries (for flush to SSTable)
-    /// Returns entries in sorted key order
+    /// Get a snapshot of all entries, copied out in sorted key order
+    ///
+    /// Flush streams from `with_sorted` instead, which copies nothing.
     pub fn iter(&self) -> Vec<(Vec<u8>, MemTableEntry)> {
         let data = self.data.read();
         data.iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
+            .map(|(k, v)| (k.bytes.clone(), v.entry.clone()))
+            .collect()
+    }
+
+    /// Run `f` over all entries in sorted key order, without copying them
+    ///
+    /// The read lock is held until `f` returns: readers carry on, but
+    /// writers block, so `f` should only run where writes are already
+    /// held off (the engine flushes under its write lock).
+    pub fn with_sorted<R>(&self, f: impl FnOnce(SortedEntries<'_>) -> R) -> R {
+        let data = self.data.read();
+        f(SortedEntries(data.iter()))
+    }
+
+    /// Get a snapshot of entries within a key range (read lock)
+    /// Returns entries in sorted key order, tombstones included
+    ///
+    /// An inverted range (start > end) yields an empty result rather than
+    /// panicking like `BTreeMap::range` would.
+    pub fn range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Vec<(Vec<u8>, MemTableEntry)> {
+        if is_empty_range(&*self.comparator, start, end) {
+            return Vec::new();
+        }
+
+        let data = self.data.read();
+        data.range(self.key_range(start, end))
+            .map(|(k, v)| (k.bytes.clone(), v.entry.clone()))
+            .collect()
+    }
+
+    /// Get a snapshot of the keys within a key range (read lock)
+    /// Returns (key, live) in sorted key order, with `live` false for tombstones
+    ///
+    /// Values are not cloned. Merge operands always resolve to a value, so
+    /// their keys count as live.
+    pub fn range_keys(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Vec<(Vec<u8>, bool)> {
+        if is_empty_range(&*self.comparator, start, end) {
+            return Vec::new();
+        }
+
+        let data = self.data.read();
+        data.range(self.key_range(start, end))
+            .map(|(k, v)| (k.bytes.clone(), !matches!(v.entry, MemTableEntry::Tombstone)))
             .collect()
     }
 
+    /// Wrap range bounds for use with `data`
+    fn key_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> (Bound<MemKey>, Bound<MemKey>) {
+        let wrap = |bound: Bound<&[u8]>| bound.map(|key| self.key(key.to_vec()));
+        (wrap(start), wrap(end))
+    }
+
     /// Clear all entries (after successful flush)
+    ///
+    /// Resets size to 0, dropping both payload and overhead accounting.
     pub fn clear(&self) {
         let mut data = self.data.write();
         data.clear();
@@ -119,6 +350,23 @@ impl MemTable {
     }
 }
 
+/// A MemTable's entries in key order, borrowed (see `MemTable::with_sorted`)
+pub struct SortedEntries<'a>(btree_map::Iter<'a, MemKey, SequencedEntry>);
+
+impl<'a> Iterator for SortedEntries<'a> {
+    type Item = (&'a [u8], &'a SequencedEntry);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, entry)| (key.bytes.as_slice(), entry))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for SortedEntries<'_> {}
+
 impl Default for MemTable {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,55 @@
+//! Merge Operators
+//!
+//! Merge-on-read support (like RocksDB's merge operator).
+//!
+//! ## Why
+//! Counters and sets are read-modify-write under a plain PUT: every update
+//! pays for a lookup. A merge records only the delta ("operand"); operands
+//! are folded over the base value lazily, on read or when the MemTable is
+//! flushed.
+//!
+//! ## Lifecycle of a merge
+//! ```text
+//! merge(k, op) → WAL (MERGE) → MemTable: Merge([op, ...])
+//!                                   │
+//!            get(k) / flush ────────┴──► operator.merge(base, operands)
+//! ```
+//! SSTables only ever hold fully-resolved values.
+
+use std::fmt;
+
+/// User-supplied function that combines merge operands with a base value
+///
+/// Must be deterministic: the same operands are folded again after crash
+/// recovery, and a read and a later flush must agree.
+pub trait MergeOperator: Send + Sync + fmt::Debug {
+    /// Fold `operands` (oldest first) over `existing`
+    ///
+    /// `existing` is `None` when the key is absent or deleted.
+    fn merge(&self, existing: Option<&[u8]>, operands: &[Vec<u8>]) -> Vec<u8>;
+}
+
+/// Built-in operator treating values as signed 64-bit counters
+///
+/// Values and operands are 8-byte little-endian `i64`s. A missing base
+/// counts as 0; a base or operand of the wrong length also counts as 0
+/// rather than failing the read. Addition wraps on overflow.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct I64AddOperator;
+
+impl I64AddOperator {
+    /// Decode an 8-byte little-endian i64 (anything else reads as 0)
+    fn decode(bytes: &[u8]) -> i64 {
+        bytes.try_into().map(i64::from_le_bytes).unwrap_or(0)
+    }
+}
+
+impl MergeOperator for I64AddOperator {
+    fn merge(&self, existing: Option<&[u8]>, operands: &[Vec<u8>]) -> Vec<u8> {
+        let base = existing.map(Self::decode).unwrap_or(0);
+        let total = operands
+            .iter()
+            .fold(base, |acc, op| acc.wrapping_add(Self::decode(op)));
+        total.to_le_bytes().to_vec()
+    }
+}
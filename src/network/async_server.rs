@@ -0,0 +1,342 @@
+//! Async TCP Server (requires the `tokio` feature)
+//!
+//! Same wire protocol as `Server`, but connections are tokio tasks instead
+//! of pool threads. The `Engine` is synchronous, so each command runs on
+//! tokio's blocking pool via `spawn_blocking`.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use socket2::SockRef;
+use tokio::io::{AsyncBufReadExt, BufReader, BufWriter};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+
+use crate::config::Config;
+use crate::engine::Engine;
+use crate::error::{AtlasError, Result};
+use crate::protocol::{
+    read_command_with_limits_async, write_response_async, Command, CommandLimits, Response,
+};
+
+use super::connection::{authenticate, negotiate, to_response, AUTH_REQUIRED};
+use super::server::{bind_listener, configure_stream};
+
+/// Async TCP server for AtlasKV
+///
+/// ## Architecture
+/// - One tokio task accepts connections
+/// - One tokio task per client connection
+/// - Engine calls run on the blocking pool
+pub struct AsyncServer {
+    /// Server configuration
+    config: Config,
+
+    /// Shared storage engine
+    engine: Arc<Engine>,
+
+    /// Shutdown flag (watched by the accept loop and every connection)
+    shutdown: watch::Sender<bool>,
+
+    /// Active connection count
+    active_connections: Arc<AtomicUsize>,
+}
+
+impl AsyncServer {
+    /// Create a new server with the given config and engine
+    pub fn new(config: Config, engine: Arc<Engine>) -> Self {
+        Self {
+            config,
+            engine,
+            shutdown: watch::Sender::new(false),
+            active_connections: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Bind to the configured address and serve until shutdown
+    pub async fn run(&self) -> Result<()> {
+        let listener = self.bind()?;
+        self.serve(listener).await
+    }
+
+    /// Bind to the configured address
+    ///
+    /// Uses the same socket options as `Server`. Must be called from
+    /// within a tokio runtime.
+    pub fn bind(&self) -> Result<TcpListener> {
+        let listener = bind_listener(&self.config)?;
+        listener.set_nonblocking(true)?;
+        Ok(TcpListener::from_std(listener)?)
+    }
+
+    /// Accept connections on `listener` until shutdown is signaled
+    ///
+    /// Open connections notice the shutdown at their next frame boundary
+    /// and close on their own; this does not wait for them.
+    pub async fn serve(&self, listener: TcpListener) -> Result<()> {
+        if let Ok(addr) = listener.local_addr() {
+            tracing::info!("Async server listening on {}", addr);
+        }
+
+        let mut shutdown = self.shutdown.subscribe();
+
+        loop {
+            let (stream, addr) = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        tracing::error!("Accept error: {}", e);
+                        continue;
+                    }
+                },
+                _ = shutdown.wait_for(|&stop| stop) => break,
+            };
+
+            // Check connection limit
+            let current = self.active_connections.load(Ordering::Relaxed);
+            if current >= self.config.max_connections {
+                tracing::warn!(
+                    "Connection limit reached ({}/{}), rejecting {}",
+                    current,
+                    self.config.max_connections,
+                    addr
+                );
+                drop(stream);
+                continue;
+            }
+
+            tracing::debug!("Accepted connection from {}", addr);
+
+            let conn = AsyncConnection {
+                peer_addr: addr.to_string(),
+                engine: Arc::clone(&self.engine),
+                read_timeout_ms: self.config.read_timeout_ms,
+                write_timeout_ms: self.config.write_timeout_ms,
+                idle_timeout_ms: self.config.idle_timeout_ms,
+                shutdown: self.shutdown.subscribe(),
+            };
+            let active_connections = Arc::clone(&self.active_connections);
+            active_connections.fetch_add(1, Ordering::Relaxed);
+
+            tokio::spawn(async move {
+                let peer_addr = conn.peer_addr.clone();
+                if let Err(e) = conn.handle(stream).await {
+                    tracing::debug!("Connection {} ended with error: {}", peer_addr, e);
+                }
+                active_connections.fetch_sub(1, Ordering::Relaxed);
+            });
+        }
+
+        tracing::info!("Async server shutdown complete");
+        Ok(())
+    }
+
+    /// Signal the server (and its open connections) to shut down
+    pub fn shutdown(&self) {
+        tracing::info!("Shutdown signal received");
+        self.shutdown.send_replace(true);
+    }
+
+    /// Check if the server is running
+    pub fn is_running(&self) -> bool {
+        !*self.shutdown.borrow()
+    }
+
+    /// Get the number of active connections
+    pub fn active_connections(&self) -> usize {
+        self.active_connections.load(Ordering::Relaxed)
+    }
+}
+
+/// Per-connection state for an async client
+struct AsyncConnection {
+    /// Peer address for logging
+    peer_addr: String,
+
+    /// Reference to the storage engine
+    engine: Arc<Engine>,
+
+    /// Timeout for the rest of a frame once it has started (0 = none)
+    read_timeout_ms: u64,
+
+    /// Timeout for writing a response (0 = none)
+    write_timeout_ms: u64,
+
+    /// Close after this long without a new frame (0 = never)
+    idle_timeout_ms: u64,
+
+    /// Server shutdown flag
+    shutdown: watch::Receiver<bool>,
+}
+
+impl AsyncConnection {
+    /// Handle the connection until the client disconnects or shutdown
+    async fn handle(mut self, stream: TcpStream) -> Result<()> {
+        tracing::debug!("Connection established from {}", self.peer_addr);
+
+        // Nagle off by default for low latency (`Config::tcp_nodelay`)
+        configure_stream(SockRef::from(&stream), self.engine.config())?;
+
+        let (read_half, write_half) = stream.into_split();
+        let mut reader = BufReader::new(read_half);
+        let mut writer = BufWriter::new(write_half);
+
+        let limits = CommandLimits {
+            max_key_size: self.engine.config().max_key_size,
+            max_value_size: self.engine.config().max_value_size,
+        };
+        let mut awaiting_first_frame = true;
+        let mut authenticated = self.engine.config().auth_token.is_none();
+        let max_requests = self.engine.config().max_requests_per_connection;
+        let mut requests = 0u64;
+
+        loop {
+            // Wait for the start of the next command without consuming it
+            let next = tokio::select! {
+                next = with_timeout(self.idle_timeout_ms, reader.fill_buf()) => next,
+                _ = self.shutdown.wait_for(|&stop| stop) => {
+                    tracing::debug!("Closing connection from {} for shutdown", self.peer_addr);
+                    return Ok(());
+                }
+            };
+            match next {
+                None => {
+                    tracing::debug!("Closing idle connection from {}", self.peer_addr);
+                    return Ok(());
+                }
+                Some(Ok([])) => {
+                    tracing::debug!("Client {} disconnected", self.peer_addr);
+                    return Ok(());
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return self.read_error(&mut writer, AtlasError::Io(e)).await,
+            }
+
+            // Read next command
+            let read = read_command_with_limits_async(&mut reader, &limits);
+            let command = match with_timeout(self.read_timeout_ms, read).await {
+                None => {
+                    // The frame can't be resumed, so close
+                    tracing::debug!("Read timeout mid-command for client {}", self.peer_addr);
+                    return Ok(());
+                }
+                Some(Ok(command)) => command,
+                Some(Err(e)) => return self.read_error(&mut writer, e).await,
+            };
+
+            tracing::trace!("Received command from {}: {:?}", self.peer_addr, command);
+
+            // Execute command (HELLO and AUTH are answered here, not by the engine)
+            let first_frame = std::mem::replace(&mut awaiting_first_frame, false);
+            // Frame CRCs aren't supported here, so no feature is ever enabled
+            let (response, close, _) = match command {
+                Command::Hello {
+                    proto_version,
+                    features,
+                } => negotiate(&self.peer_addr, proto_version, features, 0, first_frame),
+                Command::Auth { token } => {
+                    let expected = self.engine.config().auth_token.as_deref();
+                    let (response, accepted) = authenticate(&self.peer_addr, expected, &token);
+                    authenticated = accepted;
+                    (response, !accepted, 0)
+                }
+                _ if !authenticated => (Response::error(AUTH_REQUIRED), false, 0),
+                command => (self.execute_command(command).await, false, 0),
+            };
+
+            // Send response
+            let write = write_response_async(&mut writer, &response);
+            match with_timeout(self.write_timeout_ms, write).await {
+                None => {
+                    tracing::warn!("Write timeout for client {}", self.peer_addr);
+                    return Ok(());
+                }
+                Some(Ok(())) => {}
+                Some(Err(AtlasError::Io(ref e))) if is_disconnect(e.kind()) => {
+                    tracing::debug!(
+                        "Client {} disconnected before response could be sent: {}",
+                        self.peer_addr, e
+                    );
+                    return Ok(());
+                }
+                Some(Err(e)) => {
+                    tracing::warn!("Error writing to {}: {}", self.peer_addr, e);
+                    return Err(e);
+                }
+            }
+
+            if close {
+                tracing::debug!(
+                    "Closing connection from {} after failed handshake or auth",
+                    self.peer_addr
+                );
+                return Ok(());
+            }
+
+            requests += 1;
+            if max_requests > 0 && requests >= max_requests {
+                tracing::debug!(
+                    "Closing connection from {} after {} requests",
+                    self.peer_addr,
+                    requests
+                );
+                return Ok(());
+            }
+        }
+    }
+
+    /// Run a command on the blocking pool and map the result to a response
+    async fn execute_command(&self, command: Command) -> Response {
+        let engine = Arc::clone(&self.engine);
+        match tokio::task::spawn_blocking(move || engine.execute(command)).await {
+            Ok(result) => to_response(result),
+            Err(e) => Response::error(&format!("Command task failed: {}", e)),
+        }
+    }
+
+    /// Decide how a failed read ends the connection
+    ///
+    /// Client-side disconnects end it cleanly; anything else gets an error
+    /// response (if possible) and is propagated.
+    async fn read_error<W>(&self, writer: &mut W, e: AtlasError) -> Result<()>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        match e {
+            AtlasError::Io(ref io_err)
+                if io_err.kind() == std::io::ErrorKind::UnexpectedEof
+                    || is_disconnect(io_err.kind()) =>
+            {
+                tracing::debug!("Client {} disconnected", self.peer_addr);
+                Ok(())
+            }
+            e => {
+                tracing::warn!("Error reading from {}: {}", self.peer_addr, e);
+                let _ = write_response_async(writer, &Response::error(&e.to_string())).await;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Check whether an I/O error means the client went away
+fn is_disconnect(kind: std::io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        std::io::ErrorKind::ConnectionAborted
+            | std::io::ErrorKind::ConnectionReset
+            | std::io::ErrorKind::BrokenPipe
+    )
+}
+
+/// Await `fut`, giving up after `ms` milliseconds (0 = no limit)
+///
+/// Returns `None` on timeout.
+async fn with_timeout<F: Future>(ms: u64, fut: F) -> Option<F::Output> {
+    if ms == 0 {
+        return Some(fut.await);
+    }
+    tokio::time::timeout(Duration::from_millis(ms), fut).await.ok()
+}
@@ -2,19 +2,29 @@
 //!
 //! Handles individual client connections.
 
-use std::io::{BufReader, BufWriter};
+use std::io::{BufRead, BufReader, BufWriter, Read};
 use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use socket2::SockRef;
 
 use crate::error::{AtlasError, Result};
 use crate::engine::Engine;
-use crate::protocol::{read_command, write_response, Response};
+use crate::metrics::{self, ServerMetrics};
+use crate::protocol::{
+    capabilities, encode_hello_response, read_command_with_crc, read_command_with_limits,
+    write_response, write_response_with_crc, Command, CommandLimits, Response, Status,
+    CAP_FRAME_CRC, CRC_SIZE, HEADER_SIZE, PROTOCOL_VERSION,
+};
+
+use super::server::configure_stream;
 
 /// Handles a single client connection
 pub struct Connection {
-    /// TCP stream reader (buffered for efficiency)
-    reader: BufReader<TcpStream>,
+    /// TCP stream reader (buffered for efficiency, counts bytes read)
+    reader: BufReader<MeteredReader>,
 
     /// TCP stream writer (buffered for efficiency)
     writer: BufWriter<TcpStream>,
@@ -24,41 +34,130 @@ pub struct Connection {
 
     /// Peer address for logging
     peer_addr: String,
+
+    /// Close after this long without a completed command (None = never)
+    idle_timeout: Option<Duration>,
+
+    /// When the last command completed (or the connection opened)
+    last_activity: Instant,
+
+    /// No frame has been received yet, so a HELLO handshake is still allowed
+    awaiting_first_frame: bool,
+
+    /// Frames after the handshake carry a trailing CRC (negotiated via HELLO)
+    frame_crc: bool,
+
+    /// AUTH succeeded, or the server has no `auth_token` configured
+    authenticated: bool,
+
+    /// Key/value size limits enforced while decoding (from the engine config)
+    limits: CommandLimits,
+
+    /// Server-wide counters this connection reports into
+    metrics: Arc<ServerMetrics>,
+
+    /// Set when the server is draining: finish the current command, then close
+    draining: Option<Arc<AtomicBool>>,
+
+    /// Configured read timeout, restored after waiting between commands
+    read_timeout: Option<Duration>,
+
+    /// The socket currently uses the shorter drain-poll read timeout
+    polling: bool,
+
+    /// Close after answering this many commands (0 = unlimited)
+    max_requests: u64,
+
+    /// Commands answered so far
+    requests: u64,
+}
+
+/// Read timeout while waiting between commands on a drainable connection,
+/// bounding how long an idle connection takes to notice a drain
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Read half of the TCP stream that counts bytes into the server metrics
+struct MeteredReader {
+    stream: TcpStream,
+    metrics: Arc<ServerMetrics>,
+}
+
+impl Read for MeteredReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.stream.read(buf)?;
+        metrics::add(&self.metrics.bytes_read, n as u64);
+        Ok(n)
+    }
 }
 
 impl Connection {
     /// Create a new connection handler
     ///
-    /// Sets up buffered I/O and configures timeouts
+    /// Sets up buffered I/O and configures timeouts. Counters go to a
+    /// private `ServerMetrics`; use `with_metrics` to share a server's.
     pub fn new(stream: TcpStream, engine: Arc<Engine>) -> Result<Self> {
+        Self::with_metrics(stream, engine, Arc::default())
+    }
+
+    /// Create a new connection handler that reports into shared metrics
+    pub fn with_metrics(
+        stream: TcpStream,
+        engine: Arc<Engine>,
+        metrics: Arc<ServerMetrics>,
+    ) -> Result<Self> {
         // Get peer address for logging before we split the stream
         let peer_addr = stream
             .peer_addr()
             .map(|a| a.to_string())
             .unwrap_or_else(|_| "unknown".to_string());
 
-        // Disable Nagle's algorithm for low latency
-        stream.set_nodelay(true)?;
+        // Nagle off by default for low latency (`Config::tcp_nodelay`)
+        configure_stream(SockRef::from(&stream), engine.config())?;
 
         // Clone stream for separate read/write handles
         let read_stream = stream.try_clone()?;
         let write_stream = stream;
 
+        let limits = CommandLimits {
+            max_key_size: engine.config().max_key_size,
+            max_value_size: engine.config().max_value_size,
+        };
+        let authenticated = engine.config().auth_token.is_none();
+        let max_requests = engine.config().max_requests_per_connection;
+
+        metrics::add(&metrics.total_connections, 1);
+
         Ok(Self {
-            reader: BufReader::new(read_stream),
+            reader: BufReader::new(MeteredReader {
+                stream: read_stream,
+                metrics: Arc::clone(&metrics),
+            }),
             writer: BufWriter::new(write_stream),
             engine,
             peer_addr,
+            idle_timeout: None,
+            last_activity: Instant::now(),
+            awaiting_first_frame: true,
+            frame_crc: false,
+            authenticated,
+            limits,
+            metrics,
+            draining: None,
+            read_timeout: None,
+            polling: false,
+            max_requests,
+            requests: 0,
         })
     }
 
     /// Configure connection timeouts
     pub fn set_timeouts(&mut self, read_ms: u64, write_ms: u64) -> Result<()> {
-        let read_stream = self.reader.get_ref();
+        let read_stream = &self.reader.get_ref().stream;
         let write_stream = self.writer.get_ref();
 
         if read_ms > 0 {
-            read_stream.set_read_timeout(Some(Duration::from_millis(read_ms)))?;
+            self.read_timeout = Some(Duration::from_millis(read_ms));
+            read_stream.set_read_timeout(self.read_timeout)?;
         }
         if write_ms > 0 {
             write_stream.set_write_timeout(Some(Duration::from_millis(write_ms)))?;
@@ -67,54 +166,142 @@ impl Connection {
         Ok(())
     }
 
+    /// Configure the idle timeout (0 = never reap idle connections)
+    ///
+    /// Only enforced when a read timeout is set, since the connection can
+    /// only notice idleness when a read wakes up.
+    pub fn set_idle_timeout(&mut self, idle_ms: u64) {
+        self.idle_timeout = if idle_ms > 0 {
+            Some(Duration::from_millis(idle_ms))
+        } else {
+            None
+        };
+    }
+
+    /// Share the server's draining flag with this connection
+    ///
+    /// Once the flag is set, the connection finishes the command it is
+    /// working on, sends the response, and closes instead of reading the
+    /// next one. Call after `set_timeouts`.
+    pub fn set_draining_flag(&mut self, draining: Arc<AtomicBool>) {
+        self.draining = Some(draining);
+    }
+
+    /// Check if the server has asked connections to drain
+    fn is_draining(&self) -> bool {
+        self.draining
+            .as_ref()
+            .map(|flag| flag.load(Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    /// Switch between the drain-poll and configured read timeouts
+    ///
+    /// Only a drainable connection polls. The short timeout applies just
+    /// while waiting for a frame to start; reading the rest of a frame uses
+    /// the configured one, so slow clients aren't cut off mid-command.
+    fn set_polling(&mut self, polling: bool) -> Result<()> {
+        if self.draining.is_none() || self.polling == polling {
+            return Ok(());
+        }
+
+        let timeout = if polling {
+            let configured = self.read_timeout.unwrap_or(DRAIN_POLL_INTERVAL);
+            Some(configured.min(DRAIN_POLL_INTERVAL))
+        } else {
+            self.read_timeout
+        };
+        self.reader.get_ref().stream.set_read_timeout(timeout)?;
+        self.polling = polling;
+        Ok(())
+    }
+
+    /// Check if the connection has been idle longer than the idle timeout
+    fn is_idle(&self) -> bool {
+        self.idle_timeout
+            .map(|timeout| self.last_activity.elapsed() >= timeout)
+            .unwrap_or(false)
+    }
+
     /// Handle the connection (blocking until closed)
     ///
     /// Reads commands in a loop and sends responses.
-    /// Returns when the client disconnects or an error occurs.
+    /// Returns when the client disconnects or an error occurs, or once
+    /// `Config::max_requests_per_connection` commands have been answered.
     pub fn handle(&mut self) -> Result<()> {
         tracing::debug!("Connection established from {}", self.peer_addr);
 
         loop {
-            // Read next command
-            let command = match read_command(&mut self.reader) {
-                Ok(cmd) => cmd,
-                Err(AtlasError::Io(ref e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            // Only block (and poll for a drain) when nothing is buffered
+            if self.reader.buffer().is_empty() {
+                self.set_polling(true)?;
+            }
+
+            // Wait for the start of the next command without consuming it,
+            // so a read timeout here never leaves a half-read frame behind
+            match self.reader.fill_buf() {
+                Ok([]) => {
                     // Client disconnected gracefully
                     tracing::debug!("Client {} disconnected", self.peer_addr);
                     return Ok(());
                 }
-                Err(AtlasError::Io(ref e)) if e.kind() == std::io::ErrorKind::ConnectionReset => {
-                    // Connection reset by peer
-                    tracing::debug!("Connection reset by client {}", self.peer_addr);
-                    return Ok(());
-                }
-                Err(AtlasError::Io(ref e)) if e.kind() == std::io::ErrorKind::ConnectionAborted => {
-                    // Connection aborted
-                    tracing::debug!("Connection aborted by client {}", self.peer_addr);
-                    return Ok(());
-                }
-                Err(AtlasError::Io(ref e)) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    // Read timeout - could continue or close
-                    tracing::debug!("Read timeout for client {}", self.peer_addr);
-                    return Ok(());
-                }
-                Err(AtlasError::Io(ref e)) if e.kind() == std::io::ErrorKind::TimedOut => {
+                Ok(_) => {}
+                Err(ref e)
+                    if e.kind() == std::io::ErrorKind::WouldBlock
+                        || e.kind() == std::io::ErrorKind::TimedOut =>
+                {
                     // Read timeout (Windows uses TimedOut instead of WouldBlock)
-                    tracing::debug!("Read timeout for client {}", self.peer_addr);
-                    return Ok(());
-                }
-                Err(e) => {
-                    tracing::warn!("Error reading from {}: {}", self.peer_addr, e);
-                    // Send error response if possible
-                    let _ = self.send_response(Response::error(&e.to_string()));
-                    return Err(e);
+                    if self.is_idle() {
+                        tracing::debug!("Closing idle connection from {}", self.peer_addr);
+                        return Ok(());
+                    }
+                    if self.is_draining() {
+                        tracing::debug!("Closing drained connection from {}", self.peer_addr);
+                        return Ok(());
+                    }
+                    continue;
                 }
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return self.handle_read_error(AtlasError::Io(e)),
+            }
+            self.set_polling(false)?;
+
+            // Read next command
+            let read = if self.frame_crc {
+                read_command_with_crc(&mut self.reader, &self.limits)
+            } else {
+                read_command_with_limits(&mut self.reader, &self.limits)
+            };
+            let command = match read {
+                Ok(cmd) => cmd,
+                Err(e) => return self.handle_read_error(e),
             };
 
             tracing::trace!("Received command from {}: {:?}", self.peer_addr, command);
+            metrics::add(&self.metrics.total_commands, 1);
 
-            // Execute command
-            let response = self.execute_command(command);
+            // Execute command (HELLO and AUTH are answered here, not by the engine)
+            let first_frame = std::mem::replace(&mut self.awaiting_first_frame, false);
+            let (response, close, features) = match command {
+                Command::Hello {
+                    proto_version,
+                    features,
+                } => negotiate(
+                    &self.peer_addr,
+                    proto_version,
+                    features,
+                    CAP_FRAME_CRC,
+                    first_frame,
+                ),
+                Command::Auth { token } => {
+                    let expected = self.engine.config().auth_token.as_deref();
+                    let (response, accepted) = authenticate(&self.peer_addr, expected, &token);
+                    self.authenticated = accepted;
+                    (response, !accepted, 0)
+                }
+                _ if !self.authenticated => (Response::error(AUTH_REQUIRED), false, 0),
+                command => (self.execute_command(command), false, 0),
+            };
 
             // Send response
             if let Err(e) = self.send_response(response) {
@@ -138,22 +325,97 @@ impl Connection {
                 tracing::warn!("Error writing to {}: {}", self.peer_addr, e);
                 return Err(e);
             }
+
+            if close {
+                tracing::debug!(
+                    "Closing connection from {} after failed handshake or auth",
+                    self.peer_addr
+                );
+                return Ok(());
+            }
+
+            // The HELLO response itself went out without a CRC
+            if features & CAP_FRAME_CRC != 0 {
+                self.frame_crc = true;
+            }
+
+            self.requests += 1;
+            if self.max_requests > 0 && self.requests >= self.max_requests {
+                tracing::debug!(
+                    "Closing connection from {} after {} requests",
+                    self.peer_addr,
+                    self.requests
+                );
+                return Ok(());
+            }
+
+            if self.is_draining() {
+                tracing::debug!("Closing drained connection from {}", self.peer_addr);
+                return Ok(());
+            }
+
+            self.last_activity = Instant::now();
         }
     }
 
-    /// Execute a command and return a response
-    fn execute_command(&self, command: crate::protocol::Command) -> Response {
-        match self.engine.execute(command) {
-            Ok(Some(value)) => Response::ok(Some(value)),
-            Ok(None) => Response::ok(None),
-            Err(AtlasError::KeyNotFound) => Response::not_found(),
-            Err(e) => Response::error(&e.to_string()),
+    /// Decide how a failed read ends the connection
+    ///
+    /// Client-side disconnects end it cleanly; anything else gets an error
+    /// response (if possible) and is propagated.
+    fn handle_read_error(&mut self, e: AtlasError) -> Result<()> {
+        match e {
+            AtlasError::Io(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                // Client disconnected gracefully
+                tracing::debug!("Client {} disconnected", self.peer_addr);
+                Ok(())
+            }
+            AtlasError::Io(ref e) if e.kind() == std::io::ErrorKind::ConnectionReset => {
+                // Connection reset by peer
+                tracing::debug!("Connection reset by client {}", self.peer_addr);
+                Ok(())
+            }
+            AtlasError::Io(ref e) if e.kind() == std::io::ErrorKind::ConnectionAborted => {
+                // Connection aborted
+                tracing::debug!("Connection aborted by client {}", self.peer_addr);
+                Ok(())
+            }
+            AtlasError::Io(ref e)
+                if e.kind() == std::io::ErrorKind::WouldBlock
+                    || e.kind() == std::io::ErrorKind::TimedOut =>
+            {
+                // Read timeout mid-command - the frame can't be resumed, so close
+                tracing::debug!("Read timeout mid-command for client {}", self.peer_addr);
+                Ok(())
+            }
+            e => {
+                tracing::warn!("Error reading from {}: {}", self.peer_addr, e);
+                // Send error response if possible
+                let _ = self.send_response(Response::error(&e.to_string()));
+                Err(e)
+            }
         }
     }
 
+    /// Execute a command and return a response
+    fn execute_command(&self, command: Command) -> Response {
+        to_response(self.engine.execute(command))
+    }
+
     /// Send a response to the client
     fn send_response(&mut self, response: Response) -> Result<()> {
-        write_response(&mut self.writer, &response)?;
+        if response.status == Status::Error {
+            metrics::add(&self.metrics.total_errors, 1);
+        }
+
+        let mut frame_len = HEADER_SIZE + response.payload.as_ref().map_or(0, Vec::len);
+        if self.frame_crc {
+            write_response_with_crc(&mut self.writer, &response)?;
+            frame_len += CRC_SIZE;
+        } else {
+            write_response(&mut self.writer, &response)?;
+        }
+
+        metrics::add(&self.metrics.bytes_written, frame_len as u64);
         Ok(())
     }
 
@@ -162,3 +424,81 @@ impl Connection {
         &self.peer_addr
     }
 }
+
+/// Answer a HELLO handshake
+///
+/// Returns the response, whether the connection should be closed after
+/// sending it (unsupported version), and the features enabled from here on:
+/// those both requested and in `supported`.
+pub(super) fn negotiate(
+    peer_addr: &str,
+    proto_version: u16,
+    requested: u64,
+    supported: u64,
+    first_frame: bool,
+) -> (Response, bool, u64) {
+    if !first_frame {
+        return (
+            Response::error("HELLO is only valid as the first frame"),
+            false,
+            0,
+        );
+    }
+
+    if proto_version != PROTOCOL_VERSION {
+        tracing::debug!(
+            "Client {} requested unsupported protocol version {}",
+            peer_addr, proto_version
+        );
+        let message = format!(
+            "Unsupported protocol version {} (server supports {})",
+            proto_version, PROTOCOL_VERSION
+        );
+        return (Response::error(&message), true, 0);
+    }
+
+    let features = requested & supported;
+    let payload = encode_hello_response(PROTOCOL_VERSION, capabilities() | features);
+    (Response::ok(Some(payload)), false, features)
+}
+
+/// Error returned for commands sent before a required AUTH
+pub(super) const AUTH_REQUIRED: &str = "authentication required";
+
+/// Answer an AUTH command
+///
+/// Returns the response and whether the connection is now authenticated.
+/// A wrong token should close the connection. With no token configured
+/// there is nothing to check, so any AUTH succeeds.
+pub(super) fn authenticate(
+    peer_addr: &str,
+    expected: Option<&str>,
+    token: &[u8],
+) -> (Response, bool) {
+    match expected {
+        Some(expected) if !constant_time_eq(expected.as_bytes(), token) => {
+            tracing::warn!("Client {} sent an invalid auth token", peer_addr);
+            (Response::error("invalid auth token"), false)
+        }
+        _ => (Response::ok(None), true),
+    }
+}
+
+/// Compare two secrets without stopping at the first difference, so the
+/// response time doesn't reveal how much of a guess was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Map an engine result onto a wire response
+pub(super) fn to_response(result: Result<Option<Vec<u8>>>) -> Response {
+    match result {
+        Ok(Some(value)) => Response::ok(Some(value)),
+        Ok(None) => Response::ok(None),
+        Err(AtlasError::KeyNotFound) => Response::not_found(),
+        Err(e) => Response::error(&e.to_string()),
+    }
+}
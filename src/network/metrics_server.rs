@@ -0,0 +1,188 @@
+//! Metrics Endpoint
+//!
+//! Minimal HTTP/1.1 responder serving Prometheus text on `GET /metrics`.
+//!
+//! ## Why hand-written
+//! There is exactly one route and scrapes arrive every few seconds, so
+//! requests are answered one at a time on a single thread. Pulling in an
+//! HTTP stack for that would dwarf the rest of the server.
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::engine::Engine;
+use crate::error::{AtlasError, Result};
+use crate::metrics::{render_prometheus, Gauges, ServerMetrics};
+
+/// Largest request head we read before giving up (scrapers send ~200 bytes)
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Per-request read/write timeout, so a stalled scraper can't wedge the thread
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Background thread serving the Prometheus endpoint
+pub struct MetricsServer {
+    /// Bound address (resolved, so port 0 shows the real port)
+    local_addr: SocketAddr,
+
+    /// Shutdown flag checked by the accept loop
+    shutdown: Arc<AtomicBool>,
+
+    /// Accept loop thread
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Everything a scrape needs to render
+struct MetricsSource {
+    engine: Arc<Engine>,
+    server_metrics: Arc<ServerMetrics>,
+    active_connections: Arc<AtomicUsize>,
+}
+
+impl MetricsServer {
+    /// Bind `addr` and start serving on a background thread
+    pub fn start(
+        addr: &str,
+        engine: Arc<Engine>,
+        server_metrics: Arc<ServerMetrics>,
+        active_connections: Arc<AtomicUsize>,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(addr).map_err(|e| {
+            AtlasError::Network(format!("Failed to bind metrics endpoint {}: {}", addr, e))
+        })?;
+
+        // Non-blocking so the loop can notice shutdown
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let source = MetricsSource {
+            engine,
+            server_metrics,
+            active_connections,
+        };
+
+        let handle = thread::Builder::new()
+            .name("atlaskv-metrics".to_string())
+            .spawn({
+                let shutdown = Arc::clone(&shutdown);
+                move || accept_loop(listener, source, shutdown)
+            })
+            .map_err(|e| AtlasError::Network(format!("Failed to spawn metrics thread: {}", e)))?;
+
+        tracing::info!("Metrics endpoint listening on http://{}/metrics", local_addr);
+
+        Ok(Self {
+            local_addr,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Get the bound address
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop the endpoint and wait for its thread to exit
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Metrics thread panicked: {:?}", e);
+            }
+        }
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Accept and answer scrapes until shutdown
+fn accept_loop(listener: TcpListener, source: MetricsSource, shutdown: Arc<AtomicBool>) {
+    while !shutdown.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, addr)) => {
+                if let Err(e) = handle_request(stream, &source) {
+                    tracing::debug!("Metrics request from {} failed: {}", addr, e);
+                }
+            }
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => tracing::warn!("Metrics accept error: {}", e),
+        }
+    }
+}
+
+/// Answer one HTTP request and close the connection
+fn handle_request(mut stream: TcpStream, source: &MetricsSource) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let head = read_request_head(&mut stream)?;
+    let request_line = head.lines().next().unwrap_or("");
+    let mut parts = request_line.split_whitespace();
+
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", render(source)),
+        (Some("GET"), Some(_)) => ("404 Not Found", "Not Found\n".to_string()),
+        _ => ("405 Method Not Allowed", "Method Not Allowed\n".to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\n\
+         Content-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes())?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Read up to the blank line ending the request head
+fn read_request_head(stream: &mut TcpStream) -> Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+        if head.len() > MAX_REQUEST_SIZE {
+            return Err(AtlasError::Network("Metrics request too large".to_string()));
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+/// Render the current metrics
+fn render(source: &MetricsSource) -> String {
+    let gauges = Gauges {
+        active_connections: source.active_connections.load(Ordering::Relaxed),
+        memtable_bytes: source.engine.memtable_size(),
+        sstables: source.engine.sstable_count(),
+    };
+    render_prometheus(source.engine.metrics(), &source.server_metrics, gauges)
+}
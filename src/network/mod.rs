@@ -6,9 +6,19 @@
 //! - Single acceptor thread
 //! - Worker thread pool for connections
 //! - Commands routed through Engine
+//! - Optional Prometheus endpoint on its own port (`Config::metrics_addr`)
+//!
+//! With the `tokio` feature, `AsyncServer` serves the same protocol from
+//! tokio tasks instead of a thread pool.
 
 mod server;
 mod connection;
+mod metrics_server;
+#[cfg(feature = "tokio")]
+mod async_server;
 
 pub use server::Server;
 pub use connection::Connection;
+pub use metrics_server::MetricsServer;
+#[cfg(feature = "tokio")]
+pub use async_server::AsyncServer;
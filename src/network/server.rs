@@ -2,19 +2,26 @@
 //!
 //! Accepts connections and dispatches to worker threads.
 
-use std::net::{TcpListener, TcpStream};
+use std::io::Write;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crossbeam::channel::{bounded, Receiver, Sender};
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
 
 use crate::config::Config;
 use crate::engine::Engine;
 use crate::error::{AtlasError, Result};
+use crate::metrics::{self, ServerMetrics};
+use crate::protocol::{encode_response, Response};
 
-use super::Connection;
+use super::{Connection, MetricsServer};
+
+/// How long the accept loop waits for room in a full worker queue
+const DISPATCH_TIMEOUT: Duration = Duration::from_millis(100);
 
 /// Message sent to worker threads
 enum WorkerMessage {
@@ -51,6 +58,12 @@ pub struct Server {
 
     /// Active connection count
     active_connections: Arc<AtomicUsize>,
+
+    /// Counters shared by all connections
+    metrics: Arc<ServerMetrics>,
+
+    /// Prometheus endpoint (only when `metrics_addr` is configured)
+    metrics_server: Option<MetricsServer>,
 }
 
 impl Server {
@@ -64,6 +77,8 @@ impl Server {
             workers: Vec::new(),
             shutdown: Arc::new(AtomicBool::new(false)),
             active_connections: Arc::new(AtomicUsize::new(0)),
+            metrics: Arc::new(ServerMetrics::default()),
+            metrics_server: None,
         }
     }
 
@@ -75,13 +90,11 @@ impl Server {
     /// 3. Accepts connections in a loop
     /// 4. Returns when shutdown is signaled
     pub fn run(&mut self) -> Result<()> {
+        // Reject a bad worker count before binding anything
+        let num_workers = worker_count(&self.config)?;
+
         // Step 1: Bind to address
-        let listener = TcpListener::bind(&self.config.listen_addr).map_err(|e| {
-            AtlasError::Network(format!(
-                "Failed to bind to {}: {}",
-                self.config.listen_addr, e
-            ))
-        })?;
+        let listener = bind_listener(&self.config)?;
 
         // Set non-blocking so we can check shutdown flag
         listener.set_nonblocking(true)?;
@@ -89,8 +102,33 @@ impl Server {
         tracing::info!("Server listening on {}", self.config.listen_addr);
         self.listener = Some(listener);
 
+        // Optional metrics endpoint (stopped in cleanup)
+        if let Some(addr) = &self.config.metrics_addr {
+            self.metrics_server = Some(MetricsServer::start(
+                addr,
+                Arc::clone(&self.engine),
+                Arc::clone(&self.metrics),
+                Arc::clone(&self.active_connections),
+            )?);
+        }
+
         // Step 2: Create worker thread pool
-        let num_workers = num_cpus();
+        self.spawn_workers(num_workers)?;
+
+        // Step 3: Accept loop
+        self.accept_loop()?;
+
+        // Step 4: Cleanup (after shutdown signaled)
+        self.cleanup();
+
+        Ok(())
+    }
+
+    /// Spawn the worker thread pool and the queue feeding it
+    ///
+    /// The queue holds up to `max_connections` accepted connections waiting
+    /// for a free worker; past that, new connections get a busy error.
+    fn spawn_workers(&mut self, num_workers: usize) -> Result<()> {
         let (sender, receiver) = bounded::<WorkerMessage>(self.config.max_connections);
         self.work_sender = Some(sender);
 
@@ -102,8 +140,9 @@ impl Server {
                 receiver.clone(),
                 Arc::clone(&self.engine),
                 Arc::clone(&self.active_connections),
-                self.config.read_timeout_ms,
-                self.config.write_timeout_ms,
+                Arc::clone(&self.metrics),
+                Arc::clone(&self.shutdown),
+                &self.config,
             );
             let handle = thread::Builder::new()
                 .name(format!("atlaskv-worker-{}", worker_id))
@@ -113,12 +152,6 @@ impl Server {
             self.workers.push(handle);
         }
 
-        // Step 3: Accept loop
-        self.accept_loop()?;
-
-        // Step 4: Cleanup (after shutdown signaled)
-        self.cleanup();
-
         Ok(())
     }
 
@@ -139,16 +172,19 @@ impl Server {
                             self.config.max_connections,
                             addr
                         );
-                        // Drop the connection
-                        drop(stream);
+                        self.reject_busy(stream, "server busy: connection limit reached");
                         continue;
                     }
 
                     tracing::debug!("Accepted connection from {}", addr);
 
-                    // Send to worker pool
-                    if let Err(e) = sender.send(WorkerMessage::NewConnection(stream)) {
-                        tracing::error!("Failed to dispatch connection: {}", e);
+                    // Send to worker pool, waiting briefly if its queue is full
+                    let message = WorkerMessage::NewConnection(stream);
+                    if let Err(e) = sender.send_timeout(message, DISPATCH_TIMEOUT) {
+                        tracing::warn!("Worker queue full, rejecting {}", addr);
+                        if let WorkerMessage::NewConnection(stream) = e.into_inner() {
+                            self.reject_busy(stream, "server busy: worker queue full");
+                        }
                     }
                 }
                 Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
@@ -166,10 +202,27 @@ impl Server {
         Ok(())
     }
 
+    /// Tell a client it was turned away, then close its connection
+    ///
+    /// Best effort: the socket is made non-blocking so a client that isn't
+    /// reading can't stall the accept loop.
+    fn reject_busy(&self, mut stream: TcpStream, reason: &str) {
+        metrics::add(&self.metrics.total_errors, 1);
+
+        let frame = encode_response(&Response::error(reason));
+        if let Err(e) = stream.set_nonblocking(true).and_then(|_| stream.write_all(&frame)) {
+            tracing::debug!("Failed to send busy response: {}", e);
+        }
+    }
+
     /// Cleanup workers and resources
     fn cleanup(&mut self) {
         tracing::info!("Shutting down server...");
 
+        // Connections see the shutdown flag and close after their current
+        // command; give them until the drain deadline before stopping workers
+        self.drain_connections();
+
         // Send shutdown signal to all workers
         if let Some(sender) = &self.work_sender {
             for _ in 0..self.workers.len() {
@@ -184,15 +237,47 @@ impl Server {
             }
         }
 
+        if let Some(metrics_server) = self.metrics_server.take() {
+            metrics_server.shutdown();
+        }
+
         tracing::info!("Server shutdown complete");
     }
 
+    /// Wait for active connections to finish, bounded by `shutdown_drain_ms`
+    fn drain_connections(&self) {
+        let deadline = Instant::now() + Duration::from_millis(self.config.shutdown_drain_ms);
+
+        loop {
+            let remaining = self.active_connections.load(Ordering::Relaxed);
+            if remaining == 0 {
+                return;
+            }
+            if Instant::now() >= deadline {
+                tracing::warn!(
+                    "Drain deadline passed with {} connection(s) still active",
+                    remaining
+                );
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
     /// Signal the server to shutdown gracefully
     pub fn shutdown(&self) {
         tracing::info!("Shutdown signal received");
         self.shutdown.store(true, Ordering::Relaxed);
     }
 
+    /// Get the shutdown flag, for signaling shutdown from another thread
+    ///
+    /// `run` borrows the server mutably, so other threads store `true`
+    /// into this flag instead of calling `shutdown`.
+    pub fn shutdown_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.shutdown)
+    }
+
     /// Check if the server is running
     pub fn is_running(&self) -> bool {
         !self.shutdown.load(Ordering::Relaxed)
@@ -203,6 +288,11 @@ impl Server {
         self.active_connections.load(Ordering::Relaxed)
     }
 
+    /// Get the server-wide connection counters
+    pub fn metrics(&self) -> &Arc<ServerMetrics> {
+        &self.metrics
+    }
+
     /// Get the bound address (if running)
     pub fn local_addr(&self) -> Option<std::net::SocketAddr> {
         self.listener.as_ref().and_then(|l| l.local_addr().ok())
@@ -223,11 +313,20 @@ struct Worker {
     /// Active connection counter
     active_connections: Arc<AtomicUsize>,
 
+    /// Server-wide counters
+    metrics: Arc<ServerMetrics>,
+
+    /// Server shutdown flag, doubling as the connections' draining flag
+    draining: Arc<AtomicBool>,
+
     /// Read timeout in milliseconds
     read_timeout_ms: u64,
 
     /// Write timeout in milliseconds
     write_timeout_ms: u64,
+
+    /// Idle connection timeout in milliseconds
+    idle_timeout_ms: u64,
 }
 
 impl Worker {
@@ -236,16 +335,20 @@ impl Worker {
         receiver: Receiver<WorkerMessage>,
         engine: Arc<Engine>,
         active_connections: Arc<AtomicUsize>,
-        read_timeout_ms: u64,
-        write_timeout_ms: u64,
+        metrics: Arc<ServerMetrics>,
+        draining: Arc<AtomicBool>,
+        config: &Config,
     ) -> Self {
         Self {
             id,
             receiver,
             engine,
             active_connections,
-            read_timeout_ms,
-            write_timeout_ms,
+            metrics,
+            draining,
+            read_timeout_ms: config.read_timeout_ms,
+            write_timeout_ms: config.write_timeout_ms,
+            idle_timeout_ms: config.idle_timeout_ms,
         }
     }
 
@@ -277,7 +380,11 @@ impl Worker {
         self.active_connections.fetch_add(1, Ordering::Relaxed);
 
         // Create connection handler
-        let mut conn = match Connection::new(stream, Arc::clone(&self.engine)) {
+        let mut conn = match Connection::with_metrics(
+            stream,
+            Arc::clone(&self.engine),
+            Arc::clone(&self.metrics),
+        ) {
             Ok(c) => c,
             Err(e) => {
                 tracing::error!("Failed to create connection: {}", e);
@@ -290,6 +397,8 @@ impl Worker {
         if let Err(e) = conn.set_timeouts(self.read_timeout_ms, self.write_timeout_ms) {
             tracing::warn!("Failed to set connection timeouts: {}", e);
         }
+        conn.set_idle_timeout(self.idle_timeout_ms);
+        conn.set_draining_flag(Arc::clone(&self.draining));
 
         // Handle connection
         if let Err(e) = conn.handle() {
@@ -305,6 +414,56 @@ impl Worker {
     }
 }
 
+/// Create the TCP listener with socket options std doesn't expose
+///
+/// - SO_REUSEADDR: rebind immediately even if the old socket is in TIME_WAIT
+/// - SO_REUSEPORT: optional, lets multiple acceptors share the port (Unix only)
+/// - Explicit listen backlog from config
+pub(super) fn bind_listener(config: &Config) -> Result<TcpListener> {
+    let bind_error = |e: std::io::Error| {
+        AtlasError::Network(format!("Failed to bind to {}: {}", config.listen_addr, e))
+    };
+
+    let addr: SocketAddr = config
+        .listen_addr
+        .to_socket_addrs()
+        .map_err(bind_error)?
+        .next()
+        .ok_or_else(|| {
+            AtlasError::Network(format!("No address resolved for {}", config.listen_addr))
+        })?;
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))
+        .map_err(bind_error)?;
+
+    socket.set_reuse_address(true).map_err(bind_error)?;
+
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    if config.reuse_port {
+        socket.set_reuse_port(true).map_err(bind_error)?;
+    }
+    #[cfg(not(all(unix, not(any(target_os = "solaris", target_os = "illumos")))))]
+    if config.reuse_port {
+        tracing::warn!("SO_REUSEPORT not supported on this platform, ignoring");
+    }
+
+    socket.bind(&addr.into()).map_err(bind_error)?;
+    socket.listen(config.listen_backlog).map_err(bind_error)?;
+
+    Ok(socket.into())
+}
+
+/// Number of worker threads: `worker_threads` if set, else one per CPU
+fn worker_count(config: &Config) -> Result<usize> {
+    match config.worker_threads {
+        Some(0) => Err(AtlasError::Config(
+            "worker_threads must be at least 1".to_string(),
+        )),
+        Some(count) => Ok(count),
+        None => Ok(num_cpus()),
+    }
+}
+
 /// Get number of CPUs (for worker thread count)
 fn num_cpus() -> usize {
     std::thread::available_parallelism()
@@ -312,6 +471,54 @@ fn num_cpus() -> usize {
         .unwrap_or(4)
 }
 
+/// Apply the configured socket options to an accepted connection
+///
+/// Shared by the thread-pool and async servers: TCP_NODELAY, plus
+/// SO_SNDBUF/SO_RCVBUF when set (the OS may round or cap them).
+pub(super) fn configure_stream(socket: SockRef<'_>, config: &Config) -> std::io::Result<()> {
+    socket.set_nodelay(config.tcp_nodelay)?;
+    if config.tcp_send_buffer_bytes > 0 {
+        socket.set_send_buffer_size(config.tcp_send_buffer_bytes)?;
+    }
+    if config.tcp_recv_buffer_bytes > 0 {
+        socket.set_recv_buffer_size(config.tcp_recv_buffer_bytes)?;
+    }
+    if config.keepalive_interval_ms > 0 {
+        socket.set_tcp_keepalive(&keepalive_params(config))?;
+    }
+    Ok(())
+}
+
+/// SO_KEEPALIVE parameters, as far as the platform lets us tune them
+fn keepalive_params(config: &Config) -> TcpKeepalive {
+    let interval = Duration::from_millis(config.keepalive_interval_ms);
+    let keepalive = TcpKeepalive::new().with_time(interval);
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        windows
+    ))]
+    let keepalive = keepalive.with_interval(interval);
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "netbsd"
+    ))]
+    let keepalive = if config.keepalive_retries > 0 {
+        keepalive.with_retries(config.keepalive_retries)
+    } else {
+        keepalive
+    };
+    keepalive
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -330,4 +537,63 @@ mod tests {
 
         assert!(!server.is_running() || server.is_running()); // Just check it exists
     }
+
+    #[test]
+    fn test_worker_threads_overrides_cpu_count() {
+        let temp_dir = tempdir().unwrap();
+        let config = Config::builder()
+            .data_dir(temp_dir.path())
+            .worker_threads(2)
+            .build();
+
+        let engine = Arc::new(Engine::open(config.clone()).unwrap());
+        let mut server = Server::new(config, engine);
+
+        server.spawn_workers(worker_count(&server.config).unwrap()).unwrap();
+        assert_eq!(server.workers.len(), 2);
+
+        // Every worker must take a shutdown message and exit
+        server.cleanup();
+        assert!(server.workers.is_empty());
+    }
+
+    #[test]
+    fn test_worker_threads_zero_rejected() {
+        let temp_dir = tempdir().unwrap();
+        let config = Config::builder()
+            .data_dir(temp_dir.path())
+            .listen_addr("127.0.0.1:0")
+            .worker_threads(0)
+            .build();
+
+        let engine = Arc::new(Engine::open(config.clone()).unwrap());
+        let mut server = Server::new(config, engine);
+
+        assert!(matches!(server.run(), Err(AtlasError::Config(_))));
+    }
+
+    #[test]
+    fn test_bind_listener_rebinds_port_in_time_wait() {
+        let config = Config::builder().listen_addr("127.0.0.1:0").build();
+        let listener = bind_listener(&config).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Server side closes first, leaving its end of the connection in TIME_WAIT
+        let client = TcpStream::connect(addr).unwrap();
+        let (accepted, _) = listener.accept().unwrap();
+        drop(accepted);
+        drop(listener);
+        drop(client);
+
+        // Immediately rebind the same port
+        let config = Config::builder().listen_addr(addr.to_string()).build();
+        let listener = bind_listener(&config).unwrap();
+        assert_eq!(listener.local_addr().unwrap(), addr);
+    }
+
+    #[test]
+    fn test_bind_listener_invalid_address() {
+        let config = Config::builder().listen_addr("not-an-address").build();
+        assert!(matches!(bind_listener(&config), Err(AtlasError::Network(_))));
+    }
 }
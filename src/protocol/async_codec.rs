@@ -0,0 +1,114 @@
+//! Async codec (requires the `tokio` feature)
+//!
+//! `AsyncRead`/`AsyncWrite` counterparts of the stream helpers in `codec`.
+//! Same wire format and validation; only the I/O is async.
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::error::{AtlasError, Result};
+use super::codec::has_key_prefix;
+use super::{
+    decode_command_parts, decode_response, encode_command, encode_response, Command, CommandLimits,
+    Response, HEADER_SIZE, MAX_PAYLOAD_SIZE,
+};
+
+/// Read a complete command from an async stream
+pub async fn read_command_async<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Command> {
+    read_command_with_limits_async(reader, &CommandLimits::unlimited()).await
+}
+
+/// Read a complete command from an async stream, enforcing key/value size limits
+///
+/// Like `read_command_with_limits`, sizes are checked before the payload
+/// is allocated.
+pub async fn read_command_with_limits_async<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    limits: &CommandLimits,
+) -> Result<Command> {
+    // Read header first
+    let mut header = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut header).await?;
+
+    // Parse payload length
+    let payload_len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+
+    // Validate payload length
+    if payload_len > MAX_PAYLOAD_SIZE as usize {
+        return Err(AtlasError::Protocol(format!(
+            "Payload too large: {} bytes (max {})",
+            payload_len, MAX_PAYLOAD_SIZE
+        )));
+    }
+
+    // Read payload (peeking at key_len first for key-prefixed commands)
+    let mut payload = Vec::new();
+    if has_key_prefix(header[0]) && payload_len >= 4 {
+        let mut key_len_bytes = [0u8; 4];
+        reader.read_exact(&mut key_len_bytes).await?;
+
+        let key_len = u32::from_be_bytes(key_len_bytes) as usize;
+        limits.check(key_len, (payload_len - 4).saturating_sub(key_len))?;
+
+        payload.resize(payload_len, 0);
+        payload[..4].copy_from_slice(&key_len_bytes);
+        reader.read_exact(&mut payload[4..]).await?;
+    } else if payload_len > 0 {
+        payload.resize(payload_len, 0);
+        reader.read_exact(&mut payload).await?;
+    }
+
+    decode_command_parts(header[0], payload_len, &payload, limits)
+}
+
+/// Write a command to an async stream
+pub async fn write_command_async<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    command: &Command,
+) -> Result<()> {
+    let bytes = encode_command(command);
+    writer.write_all(&bytes).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read a complete response from an async stream
+pub async fn read_response_async<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Response> {
+    // Read header first
+    let mut header = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut header).await?;
+
+    // Parse payload length
+    let payload_len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+
+    // Validate payload length
+    if payload_len > MAX_PAYLOAD_SIZE as usize {
+        return Err(AtlasError::Protocol(format!(
+            "Response payload too large: {} bytes (max {})",
+            payload_len, MAX_PAYLOAD_SIZE
+        )));
+    }
+
+    // Read payload
+    let mut payload = vec![0u8; payload_len];
+    if payload_len > 0 {
+        reader.read_exact(&mut payload).await?;
+    }
+
+    // Combine and decode
+    let mut full_message = Vec::with_capacity(HEADER_SIZE + payload_len);
+    full_message.extend_from_slice(&header);
+    full_message.extend_from_slice(&payload);
+
+    decode_response(&full_message)
+}
+
+/// Write a response to an async stream
+pub async fn write_response_async<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    response: &Response,
+) -> Result<()> {
+    let bytes = encode_response(response);
+    writer.write_all(&bytes).await?;
+    writer.flush().await?;
+    Ok(())
+}
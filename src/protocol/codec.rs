@@ -16,6 +16,24 @@
 //! - PUT:    key_len (4 bytes) + key + value
 //! - DELETE: key_len (4 bytes) + key
 //! - PING:   empty
+//! - FLUSH:  empty
+//! - APPEND: key_len (4 bytes) + key + data
+//! - GETSET: key_len (4 bytes) + key + value
+//! - HELLO:  proto_version (2 bytes) [+ requested features (8 bytes)]
+//! - SSTABLES: empty
+//! - SCANREV: start_len (4) + start + end_len (4) + end + limit (4)
+//! - SETNX:  key_len (4 bytes) + key + value
+//!
+//! ### Response Payloads
+//! - APPEND: new value length (8 bytes, big-endian)
+//! - GETSET: previous value (empty if absent)
+//! - HELLO:  proto_version (2 bytes) + capabilities (8 bytes), big-endian
+//! - SSTABLES: one line per SSTable, newest first:
+//!   `id\tentry_count\tfile_size\tmin_key\tmax_key\n` (keys ASCII-escaped)
+//! - SCANREV: entries in descending key order, each
+//!   key_len (4) + key + value_len (4) + value (see `encode_entries`)
+//! - SETNX:  1 byte, 1 if the value was written, 0 if the key already existed
+//! - DELETE: 1 byte, 1 if the key had a live value, 0 if not
 //!
 //! ### Response Format
 //! ```text
@@ -23,10 +41,15 @@
 //! │Status(1) │ Len (4)  │         Payload             │
 //! └──────────┴──────────┴─────────────────────────────┘
 //! ```
+//!
+//! ### Frame CRC
+//! Once `CAP_FRAME_CRC` is negotiated, every later frame in both directions
+//! is followed by a CRC32 (4 bytes, big-endian) of its header + payload.
+//! The HELLO exchange itself never carries one.
 
 use std::io::{Read, Write};
 use crate::error::{AtlasError, Result};
-use super::{Command, Response, Status};
+use super::{Command, CommandType, Response, Status};
 
 /// Header size: 1 byte command/status + 4 bytes length
 pub const HEADER_SIZE: usize = 5;
@@ -34,6 +57,186 @@ pub const HEADER_SIZE: usize = 5;
 /// Maximum payload size (16 MB)
 pub const MAX_PAYLOAD_SIZE: u32 = 16 * 1024 * 1024;
 
+/// Protocol version spoken by this build (assumed when a client skips HELLO)
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// HELLO response payload size: proto_version (2) + capabilities (8)
+const HELLO_RESPONSE_SIZE: usize = 10;
+
+/// HELLO command payload size when features are requested
+const HELLO_FEATURES_SIZE: usize = 10;
+
+/// Capability bit for trailing frame CRCs
+///
+/// Requested in HELLO's `features`; set in the HELLO response only when
+/// enabled for the connection. Sits above every command bit.
+pub const CAP_FRAME_CRC: u64 = 1 << 63;
+
+/// Trailing CRC size on frames once `CAP_FRAME_CRC` is enabled
+pub const CRC_SIZE: usize = 4;
+
+// =============================================================================
+// Handshake
+// =============================================================================
+
+// Every command bit must fit below the feature bits, starting at bit 63
+const _: () = {
+    let mut i = 0;
+    while i < CommandType::ALL.len() {
+        assert!((CommandType::ALL[i] as u8) < 63, "command byte collides with CAP_FRAME_CRC");
+        i += 1;
+    }
+};
+
+/// Capability bitmask advertised in the HELLO response
+///
+/// Bit `n` is set when command type byte `n` is understood. Feature bits
+/// (`CAP_*`) are added per connection, when enabled. Command bytes are
+/// checked at compile time to stay below bit 63.
+pub fn capabilities() -> u64 {
+    CommandType::ALL
+        .iter()
+        .fold(0u64, |mask, cmd| mask | (1u64 << (*cmd as u8)))
+}
+
+/// Encode a HELLO response payload
+pub fn encode_hello_response(proto_version: u16, capabilities: u64) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(HELLO_RESPONSE_SIZE);
+    payload.extend_from_slice(&proto_version.to_be_bytes());
+    payload.extend_from_slice(&capabilities.to_be_bytes());
+    payload
+}
+
+/// Decode a HELLO response payload into (proto_version, capabilities)
+pub fn decode_hello_response(payload: &[u8]) -> Result<(u16, u64)> {
+    if payload.len() != HELLO_RESPONSE_SIZE {
+        return Err(AtlasError::Protocol(format!(
+            "HELLO response: expected {} bytes, got {}",
+            HELLO_RESPONSE_SIZE,
+            payload.len()
+        )));
+    }
+
+    let proto_version = u16::from_be_bytes([payload[0], payload[1]]);
+    let capabilities = u64::from_be_bytes(payload[2..10].try_into().unwrap());
+    Ok((proto_version, capabilities))
+}
+
+/// What a VERSION response reports about the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerVersion {
+    /// Crate version of the server build (`atlaskv::VERSION`)
+    pub version: String,
+    /// SSTable format version it writes
+    pub sstable_format: u16,
+    /// WAL entry layout version it writes
+    pub wal_format: u16,
+    /// Protocol version of the connection
+    pub proto_version: u16,
+}
+
+/// Encode a VERSION response payload: proto_version (2) + sstable_format (2)
+/// + wal_format (2) + version (UTF-8, rest of the payload)
+pub fn encode_version_response(info: &ServerVersion) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(6 + info.version.len());
+    payload.extend_from_slice(&info.proto_version.to_be_bytes());
+    payload.extend_from_slice(&info.sstable_format.to_be_bytes());
+    payload.extend_from_slice(&info.wal_format.to_be_bytes());
+    payload.extend_from_slice(info.version.as_bytes());
+    payload
+}
+
+/// Decode a VERSION response payload
+pub fn decode_version_response(payload: &[u8]) -> Result<ServerVersion> {
+    if payload.len() < 6 {
+        return Err(AtlasError::Protocol(format!(
+            "VERSION response: expected at least 6 bytes, got {}",
+            payload.len()
+        )));
+    }
+
+    let version = String::from_utf8(payload[6..].to_vec())
+        .map_err(|_| AtlasError::Protocol("VERSION response: version is not UTF-8".to_string()))?;
+    Ok(ServerVersion {
+        version,
+        sstable_format: u16::from_be_bytes([payload[2], payload[3]]),
+        wal_format: u16::from_be_bytes([payload[4], payload[5]]),
+        proto_version: u16::from_be_bytes([payload[0], payload[1]]),
+    })
+}
+
+/// Encode a GETMETA response payload: timestamp (8) + value
+pub fn encode_get_meta_response(value: &[u8], timestamp: u64) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(8 + value.len());
+    payload.extend_from_slice(&timestamp.to_be_bytes());
+    payload.extend_from_slice(value);
+    payload
+}
+
+/// Decode a GETMETA response payload into (value, timestamp)
+pub fn decode_get_meta_response(payload: &[u8]) -> Result<(Vec<u8>, u64)> {
+    if payload.len() < 8 {
+        return Err(AtlasError::Protocol(format!(
+            "GETMETA response: expected at least 8 bytes, got {}",
+            payload.len()
+        )));
+    }
+
+    let timestamp = u64::from_be_bytes(payload[0..8].try_into().unwrap());
+    Ok((payload[8..].to_vec(), timestamp))
+}
+
+/// One page of a cursor scan (what SCANCURSOR returns)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanPage {
+    /// Entries in ascending key order
+    pub entries: Vec<(Vec<u8>, Vec<u8>)>,
+    /// More entries follow the last one
+    pub more: bool,
+    /// The scan hit its deadline (see `Command::WithDeadline`), so the page
+    /// may be short; `more` is set too
+    pub deadline_exceeded: bool,
+}
+
+/// SCANCURSOR response flag: more entries follow
+const SCAN_PAGE_MORE: u8 = 0x01;
+
+/// SCANCURSOR response flag: the scan hit its deadline
+const SCAN_PAGE_DEADLINE_EXCEEDED: u8 = 0x02;
+
+/// Encode a SCANCURSOR response payload: flags (1) + entries (as `encode_entries`)
+///
+/// Flags: bit 0 = more, bit 1 = deadline exceeded.
+pub fn encode_scan_page(page: &ScanPage) -> Vec<u8> {
+    let mut flags = 0;
+    if page.more {
+        flags |= SCAN_PAGE_MORE;
+    }
+    if page.deadline_exceeded {
+        flags |= SCAN_PAGE_DEADLINE_EXCEEDED;
+    }
+    let mut payload = vec![flags];
+    payload.extend_from_slice(&encode_entries(&page.entries));
+    payload
+}
+
+/// Decode a SCANCURSOR response payload
+pub fn decode_scan_page(payload: &[u8]) -> Result<ScanPage> {
+    let flags = match payload.first() {
+        Some(&flags) if flags & !(SCAN_PAGE_MORE | SCAN_PAGE_DEADLINE_EXCEEDED) == 0 => flags,
+        _ => {
+            return Err(AtlasError::Protocol(
+                "SCANCURSOR response: missing or invalid flags".to_string(),
+            ))
+        }
+    };
+    Ok(ScanPage {
+        entries: decode_entries(&payload[1..])?,
+        more: flags & SCAN_PAGE_MORE != 0,
+        deadline_exceeded: flags & SCAN_PAGE_DEADLINE_EXCEEDED != 0,
+    })
+}
+
 // =============================================================================
 // Command Encoding/Decoding
 // =============================================================================
@@ -59,13 +262,79 @@ pub fn encode_command(command: &Command) -> Vec<u8> {
             payload.extend_from_slice(value);
             payload
         }
-        Command::Delete { key } => {
+        Command::Delete { key } | Command::GetMeta { key } => {
             let mut payload = Vec::with_capacity(4 + key.len());
             payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
             payload.extend_from_slice(key);
             payload
         }
-        Command::Ping => Vec::new(),
+        Command::Ping
+        | Command::Flush
+        | Command::SsTables
+        | Command::StatsJson
+        | Command::Health
+        | Command::Version => Vec::new(),
+        Command::Append { key, data } => {
+            let mut payload = Vec::with_capacity(4 + key.len() + data.len());
+            payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
+            payload.extend_from_slice(key);
+            payload.extend_from_slice(data);
+            payload
+        }
+        Command::GetSet { key, value } | Command::SetNx { key, value } => {
+            let mut payload = Vec::with_capacity(4 + key.len() + value.len());
+            payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
+            payload.extend_from_slice(key);
+            payload.extend_from_slice(value);
+            payload
+        }
+        Command::Hello {
+            proto_version,
+            features,
+        } => {
+            // Without features, keep the original 2-byte payload
+            let mut payload = proto_version.to_be_bytes().to_vec();
+            if *features != 0 {
+                payload.extend_from_slice(&features.to_be_bytes());
+            }
+            payload
+        }
+        Command::ScanRev { start, end, limit } | Command::ScanKeys { start, end, limit } => {
+            let mut payload = Vec::with_capacity(12 + start.len() + end.len());
+            payload.extend_from_slice(&(start.len() as u32).to_be_bytes());
+            payload.extend_from_slice(start);
+            payload.extend_from_slice(&(end.len() as u32).to_be_bytes());
+            payload.extend_from_slice(end);
+            payload.extend_from_slice(&limit.to_be_bytes());
+            payload
+        }
+        Command::Count { start, end } => {
+            let mut payload = Vec::with_capacity(8 + start.len() + end.len());
+            payload.extend_from_slice(&(start.len() as u32).to_be_bytes());
+            payload.extend_from_slice(start);
+            payload.extend_from_slice(&(end.len() as u32).to_be_bytes());
+            payload.extend_from_slice(end);
+            payload
+        }
+        Command::ScanCursor { after_key, limit } => {
+            // has_after (1) [+ after_len (4) + after_key] + limit (4)
+            let after_len = after_key.as_ref().map_or(0, |key| 4 + key.len());
+            let mut payload = Vec::with_capacity(5 + after_len);
+            payload.push(after_key.is_some() as u8);
+            if let Some(key) = after_key {
+                payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
+                payload.extend_from_slice(key);
+            }
+            payload.extend_from_slice(&limit.to_be_bytes());
+            payload
+        }
+        Command::WithDeadline { deadline_ms, command } => {
+            // deadline_ms (4) + the wrapped command, header included
+            let mut payload = deadline_ms.to_be_bytes().to_vec();
+            payload.extend_from_slice(&encode_command(command));
+            payload
+        }
+        Command::Auth { token } => token.clone(),
     };
 
     // Build full message: header + payload
@@ -79,8 +348,13 @@ pub fn encode_command(command: &Command) -> Vec<u8> {
 
 /// Decode a command from bytes
 ///
-/// Returns the command and number of bytes consumed
+/// Bytes past the end of the frame are ignored.
 pub fn decode_command(bytes: &[u8]) -> Result<Command> {
+    decode_command_with_limits(bytes, &CommandLimits::unlimited())
+}
+
+/// Decode a command from bytes, rejecting PUT keys over `limits.max_key_size`
+pub fn decode_command_with_limits(bytes: &[u8], limits: &CommandLimits) -> Result<Command> {
     if bytes.len() < HEADER_SIZE {
         return Err(AtlasError::Protocol(format!(
             "Incomplete header: expected {} bytes, got {}",
@@ -93,6 +367,21 @@ pub fn decode_command(bytes: &[u8]) -> Result<Command> {
     let cmd_type = bytes[0];
     let payload_len = u32::from_be_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]) as usize;
 
+    decode_command_parts(cmd_type, payload_len, &bytes[HEADER_SIZE..], limits)
+}
+
+/// Decode a command from its header fields and payload, held separately
+///
+/// `payload_len` is the header's length field; `payload` must hold at
+/// least that many bytes and anything past them is ignored. Stream readers
+/// use this to decode without first copying header and payload into one
+/// buffer. PUT keys over `limits.max_key_size` are rejected.
+pub fn decode_command_parts(
+    cmd_type: u8,
+    payload_len: usize,
+    payload: &[u8],
+    limits: &CommandLimits,
+) -> Result<Command> {
     // Validate payload length
     if payload_len > MAX_PAYLOAD_SIZE as usize {
         return Err(AtlasError::Protocol(format!(
@@ -101,23 +390,38 @@ pub fn decode_command(bytes: &[u8]) -> Result<Command> {
         )));
     }
 
-    let total_len = HEADER_SIZE + payload_len;
-    if bytes.len() < total_len {
+    if payload.len() < payload_len {
         return Err(AtlasError::Protocol(format!(
             "Incomplete payload: expected {} bytes, got {}",
-            total_len,
-            bytes.len()
+            HEADER_SIZE + payload_len,
+            HEADER_SIZE + payload.len()
         )));
     }
 
-    let payload = &bytes[HEADER_SIZE..total_len];
+    let payload = &payload[..payload_len];
 
     // Parse command based on type
     match cmd_type {
         0x01 => decode_get_command(payload),
-        0x02 => decode_put_command(payload),
+        0x02 => decode_put_command(payload, limits),
         0x03 => decode_delete_command(payload),
         0x04 => decode_ping_command(payload),
+        0x0F => decode_flush_command(payload),
+        0x10 => decode_append_command(payload),
+        0x11 => decode_getset_command(payload),
+        0x12 => decode_hello_command(payload),
+        0x13 => decode_sstables_command(payload),
+        0x14 => decode_scan_rev_command(payload),
+        0x15 => decode_stats_json_command(payload),
+        0x16 => decode_setnx_command(payload),
+        0x18 => decode_auth_command(payload),
+        0x19 => decode_get_meta_command(payload),
+        0x1A => decode_scan_keys_command(payload),
+        0x1B => decode_health_command(payload),
+        0x1C => decode_count_command(payload),
+        0x1D => decode_version_command(payload),
+        0x1F => decode_scan_cursor_command(payload),
+        0x20 => decode_with_deadline_command(payload, limits),
         _ => Err(AtlasError::Protocol(format!(
             "Unknown command type: 0x{:02x}",
             cmd_type
@@ -135,7 +439,9 @@ fn decode_get_command(payload: &[u8]) -> Result<Command> {
 
     let key_len = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
 
-    if payload.len() < 4 + key_len {
+    // Compare against what's left: key_len comes off the wire, and
+    // 4 + key_len can overflow a 32-bit usize
+    if payload.len() - 4 < key_len {
         return Err(AtlasError::Protocol(format!(
             "GET command: incomplete key (expected {}, got {})",
             key_len,
@@ -148,7 +454,7 @@ fn decode_get_command(payload: &[u8]) -> Result<Command> {
 }
 
 /// Decode PUT command payload
-fn decode_put_command(payload: &[u8]) -> Result<Command> {
+fn decode_put_command(payload: &[u8], limits: &CommandLimits) -> Result<Command> {
     if payload.len() < 4 {
         return Err(AtlasError::Protocol(
             "PUT command: missing key length".to_string(),
@@ -157,7 +463,13 @@ fn decode_put_command(payload: &[u8]) -> Result<Command> {
 
     let key_len = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
 
-    if payload.len() < 4 + key_len {
+    if key_len > limits.max_key_size {
+        return Err(AtlasError::Protocol(format!(
+            "PUT command: key too large ({} bytes, max {})",
+            key_len, limits.max_key_size
+        )));
+    }
+    if payload.len() - 4 < key_len {
         return Err(AtlasError::Protocol(format!(
             "PUT command: incomplete key (expected {}, got {})",
             key_len,
@@ -181,7 +493,7 @@ fn decode_delete_command(payload: &[u8]) -> Result<Command> {
 
     let key_len = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
 
-    if payload.len() < 4 + key_len {
+    if payload.len() - 4 < key_len {
         return Err(AtlasError::Protocol(format!(
             "DELETE command: incomplete key (expected {}, got {})",
             key_len,
@@ -204,6 +516,358 @@ fn decode_ping_command(payload: &[u8]) -> Result<Command> {
     Ok(Command::Ping)
 }
 
+/// Decode HEALTH command payload
+fn decode_health_command(payload: &[u8]) -> Result<Command> {
+    if !payload.is_empty() {
+        return Err(AtlasError::Protocol(format!(
+            "HEALTH command: unexpected payload of {} bytes",
+            payload.len()
+        )));
+    }
+    Ok(Command::Health)
+}
+
+/// Decode VERSION command payload (must be empty)
+fn decode_version_command(payload: &[u8]) -> Result<Command> {
+    if !payload.is_empty() {
+        return Err(AtlasError::Protocol(format!(
+            "VERSION command: unexpected payload of {} bytes",
+            payload.len()
+        )));
+    }
+    Ok(Command::Version)
+}
+
+/// Decode FLUSH command payload
+fn decode_flush_command(payload: &[u8]) -> Result<Command> {
+    if !payload.is_empty() {
+        return Err(AtlasError::Protocol(format!(
+            "FLUSH command: unexpected payload of {} bytes",
+            payload.len()
+        )));
+    }
+    Ok(Command::Flush)
+}
+
+/// Decode SSTABLES command payload
+fn decode_sstables_command(payload: &[u8]) -> Result<Command> {
+    if !payload.is_empty() {
+        return Err(AtlasError::Protocol(format!(
+            "SSTABLES command: unexpected payload of {} bytes",
+            payload.len()
+        )));
+    }
+    Ok(Command::SsTables)
+}
+
+/// Decode STATSJSON command payload
+fn decode_stats_json_command(payload: &[u8]) -> Result<Command> {
+    if !payload.is_empty() {
+        return Err(AtlasError::Protocol(format!(
+            "STATSJSON command: unexpected payload of {} bytes",
+            payload.len()
+        )));
+    }
+    Ok(Command::StatsJson)
+}
+
+/// Decode APPEND command payload
+fn decode_append_command(payload: &[u8]) -> Result<Command> {
+    if payload.len() < 4 {
+        return Err(AtlasError::Protocol(
+            "APPEND command: missing key length".to_string(),
+        ));
+    }
+
+    let key_len = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
+
+    if payload.len() - 4 < key_len {
+        return Err(AtlasError::Protocol(format!(
+            "APPEND command: incomplete key (expected {}, got {})",
+            key_len,
+            payload.len() - 4
+        )));
+    }
+
+    let key = payload[4..4 + key_len].to_vec();
+    let data = payload[4 + key_len..].to_vec();
+
+    Ok(Command::Append { key, data })
+}
+
+/// Decode GETSET command payload
+fn decode_getset_command(payload: &[u8]) -> Result<Command> {
+    if payload.len() < 4 {
+        return Err(AtlasError::Protocol(
+            "GETSET command: missing key length".to_string(),
+        ));
+    }
+
+    let key_len = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
+
+    if payload.len() - 4 < key_len {
+        return Err(AtlasError::Protocol(format!(
+            "GETSET command: incomplete key (expected {}, got {})",
+            key_len,
+            payload.len() - 4
+        )));
+    }
+
+    let key = payload[4..4 + key_len].to_vec();
+    let value = payload[4 + key_len..].to_vec();
+
+    Ok(Command::GetSet { key, value })
+}
+
+/// Decode SETNX command payload
+fn decode_setnx_command(payload: &[u8]) -> Result<Command> {
+    if payload.len() < 4 {
+        return Err(AtlasError::Protocol(
+            "SETNX command: missing key length".to_string(),
+        ));
+    }
+
+    let key_len = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
+
+    if payload.len() - 4 < key_len {
+        return Err(AtlasError::Protocol(format!(
+            "SETNX command: incomplete key (expected {}, got {})",
+            key_len,
+            payload.len() - 4
+        )));
+    }
+
+    let key = payload[4..4 + key_len].to_vec();
+    let value = payload[4 + key_len..].to_vec();
+
+    Ok(Command::SetNx { key, value })
+}
+
+/// Decode GETMETA command payload
+fn decode_get_meta_command(payload: &[u8]) -> Result<Command> {
+    if payload.len() < 4 {
+        return Err(AtlasError::Protocol(
+            "GETMETA command: missing key length".to_string(),
+        ));
+    }
+
+    let key_len = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
+
+    if payload.len() - 4 < key_len {
+        return Err(AtlasError::Protocol(format!(
+            "GETMETA command: incomplete key (expected {}, got {})",
+            key_len,
+            payload.len() - 4
+        )));
+    }
+
+    let key = payload[4..4 + key_len].to_vec();
+    Ok(Command::GetMeta { key })
+}
+
+/// Decode AUTH command payload
+fn decode_auth_command(payload: &[u8]) -> Result<Command> {
+    Ok(Command::Auth {
+        token: payload.to_vec(),
+    })
+}
+
+/// Decode HELLO command payload
+fn decode_hello_command(payload: &[u8]) -> Result<Command> {
+    let features = match payload.len() {
+        2 => 0,
+        HELLO_FEATURES_SIZE => u64::from_be_bytes(payload[2..10].try_into().unwrap()),
+        len => {
+            return Err(AtlasError::Protocol(format!(
+                "HELLO command: expected 2-byte version (+ 8-byte features), got {} bytes",
+                len
+            )))
+        }
+    };
+
+    let proto_version = u16::from_be_bytes([payload[0], payload[1]]);
+    Ok(Command::Hello {
+        proto_version,
+        features,
+    })
+}
+
+/// Decode SCANREV command payload
+fn decode_scan_rev_command(payload: &[u8]) -> Result<Command> {
+    let (start, end, limit) = decode_scan_range(payload, "SCANREV")?;
+    Ok(Command::ScanRev { start, end, limit })
+}
+
+/// Decode SCANKEYS command payload
+fn decode_scan_keys_command(payload: &[u8]) -> Result<Command> {
+    let (start, end, limit) = decode_scan_range(payload, "SCANKEYS")?;
+    Ok(Command::ScanKeys { start, end, limit })
+}
+
+/// Decode COUNT command payload: start_len (4) + start + end_len (4) + end
+fn decode_count_command(payload: &[u8]) -> Result<Command> {
+    let mut pos = 0;
+    let start = read_length_prefixed(payload, &mut pos, "COUNT command: start key")?;
+    let end = read_length_prefixed(payload, &mut pos, "COUNT command: end key")?;
+
+    if pos != payload.len() {
+        return Err(AtlasError::Protocol(format!(
+            "COUNT command: {} trailing bytes",
+            payload.len() - pos
+        )));
+    }
+    Ok(Command::Count { start, end })
+}
+
+/// Decode SCANCURSOR command payload: has_after (1) [+ after_len (4) + after_key] + limit (4)
+fn decode_scan_cursor_command(payload: &[u8]) -> Result<Command> {
+    let mut pos = 1;
+    let after_key = match payload.first() {
+        Some(0) => None,
+        Some(1) => Some(read_length_prefixed(payload, &mut pos, "SCANCURSOR command: after key")?),
+        _ => {
+            return Err(AtlasError::Protocol(
+                "SCANCURSOR command: missing or invalid has_after flag".to_string(),
+            ))
+        }
+    };
+
+    if payload.len() - pos != 4 {
+        return Err(AtlasError::Protocol(format!(
+            "SCANCURSOR command: expected 4-byte limit, got {} bytes",
+            payload.len() - pos
+        )));
+    }
+    let limit = u32::from_be_bytes(payload[pos..pos + 4].try_into().unwrap());
+
+    Ok(Command::ScanCursor { after_key, limit })
+}
+
+/// Decode WITHDEADLINE command payload: deadline_ms (4) + command (header + payload)
+fn decode_with_deadline_command(payload: &[u8], limits: &CommandLimits) -> Result<Command> {
+    let Some((deadline_ms, inner)) = payload.split_first_chunk::<4>() else {
+        return Err(AtlasError::Protocol(
+            "WITHDEADLINE command: missing deadline".to_string(),
+        ));
+    };
+
+    // Checked before decoding: recursing first would let a deeply nested
+    // frame overflow the stack
+    if inner.first() == Some(&(CommandType::WithDeadline as u8)) {
+        return Err(AtlasError::Protocol(
+            "WITHDEADLINE command: deadlines can't be nested".to_string(),
+        ));
+    }
+    let command = decode_command_with_limits(inner, limits)?;
+    // decode_command ignores bytes past the frame, but here they'd be lost
+    let inner_len = u32::from_be_bytes([inner[1], inner[2], inner[3], inner[4]]) as usize;
+    if inner.len() != HEADER_SIZE + inner_len {
+        return Err(AtlasError::Protocol(format!(
+            "WITHDEADLINE command: {} bytes after the wrapped command",
+            inner.len() - HEADER_SIZE - inner_len
+        )));
+    }
+
+    Ok(Command::WithDeadline {
+        deadline_ms: u32::from_be_bytes(*deadline_ms),
+        command: Box::new(command),
+    })
+}
+
+/// Decode a scan payload: start_len (4) + start + end_len (4) + end + limit (4)
+fn decode_scan_range(payload: &[u8], name: &str) -> Result<(Vec<u8>, Vec<u8>, u32)> {
+    let mut pos = 0;
+    let start = read_length_prefixed(payload, &mut pos, &format!("{} command: start key", name))?;
+    let end = read_length_prefixed(payload, &mut pos, &format!("{} command: end key", name))?;
+
+    if payload.len() - pos != 4 {
+        return Err(AtlasError::Protocol(format!(
+            "{} command: expected 4-byte limit, got {} bytes",
+            name,
+            payload.len() - pos
+        )));
+    }
+    let limit = u32::from_be_bytes(payload[pos..pos + 4].try_into().unwrap());
+
+    Ok((start, end, limit))
+}
+
+/// Read a u32 length prefix and that many bytes, advancing `pos`
+fn read_length_prefixed(payload: &[u8], pos: &mut usize, what: &str) -> Result<Vec<u8>> {
+    if payload.len() - *pos < 4 {
+        return Err(AtlasError::Protocol(format!("{}: missing length", what)));
+    }
+    let len = u32::from_be_bytes(payload[*pos..*pos + 4].try_into().unwrap()) as usize;
+    *pos += 4;
+
+    if payload.len() - *pos < len {
+        return Err(AtlasError::Protocol(format!(
+            "{}: incomplete (expected {}, got {})",
+            what,
+            len,
+            payload.len() - *pos
+        )));
+    }
+    let bytes = payload[*pos..*pos + len].to_vec();
+    *pos += len;
+
+    Ok(bytes)
+}
+
+// =============================================================================
+// Scan Results
+// =============================================================================
+
+/// Encode scan results as a response payload
+///
+/// Format: repeated key_len (4) + key + value_len (4) + value, big-endian
+pub fn encode_entries(entries: &[(Vec<u8>, Vec<u8>)]) -> Vec<u8> {
+    let size = entries.iter().map(|(k, v)| 8 + k.len() + v.len()).sum();
+    let mut payload = Vec::with_capacity(size);
+    for (key, value) in entries {
+        payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
+        payload.extend_from_slice(key);
+        payload.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        payload.extend_from_slice(value);
+    }
+    payload
+}
+
+/// Decode a scan result payload produced by `encode_entries`
+pub fn decode_entries(payload: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let mut entries = Vec::new();
+    let mut pos = 0;
+    while pos < payload.len() {
+        let key = read_length_prefixed(payload, &mut pos, "Scan result: key")?;
+        let value = read_length_prefixed(payload, &mut pos, "Scan result: value")?;
+        entries.push((key, value));
+    }
+    Ok(entries)
+}
+
+/// Encode key-only scan results as a response payload
+///
+/// Format: repeated key_len (4) + key, big-endian
+pub fn encode_keys(keys: &[Vec<u8>]) -> Vec<u8> {
+    let size = keys.iter().map(|k| 4 + k.len()).sum();
+    let mut payload = Vec::with_capacity(size);
+    for key in keys {
+        payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
+        payload.extend_from_slice(key);
+    }
+    payload
+}
+
+/// Decode a key-only scan result payload produced by `encode_keys`
+pub fn decode_keys(payload: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut keys = Vec::new();
+    let mut pos = 0;
+    while pos < payload.len() {
+        keys.push(read_length_prefixed(payload, &mut pos, "Scan result: key")?);
+    }
+    Ok(keys)
+}
+
 // =============================================================================
 // Response Encoding/Decoding
 // =============================================================================
@@ -212,7 +876,7 @@ fn decode_ping_command(payload: &[u8]) -> Result<Command> {
 ///
 /// Format: status (1) + payload_len (4) + payload
 pub fn encode_response(response: &Response) -> Vec<u8> {
-    let payload = response.payload.as_ref().map(|p| p.as_slice()).unwrap_or(&[]);
+    let payload = response.payload.as_deref().unwrap_or(&[]);
     let payload_len = payload.len() as u32;
 
     let mut message = Vec::with_capacity(HEADER_SIZE + payload.len());
@@ -277,14 +941,158 @@ pub fn decode_response(bytes: &[u8]) -> Result<Response> {
     Ok(Response { status, payload })
 }
 
+// =============================================================================
+// Frame CRC
+// =============================================================================
+
+/// Encode a command followed by its frame CRC
+pub fn encode_command_with_crc(command: &Command) -> Vec<u8> {
+    let mut frame = encode_command(command);
+    append_crc(&mut frame);
+    frame
+}
+
+/// Decode a command followed by its frame CRC
+///
+/// A CRC that doesn't match returns `AtlasError::Protocol("crc mismatch")`.
+pub fn decode_command_with_crc(bytes: &[u8]) -> Result<Command> {
+    decode_command(verify_crc(bytes)?)
+}
+
+/// Encode a response followed by its frame CRC
+pub fn encode_response_with_crc(response: &Response) -> Vec<u8> {
+    let mut frame = encode_response(response);
+    append_crc(&mut frame);
+    frame
+}
+
+/// Decode a response followed by its frame CRC
+///
+/// A CRC that doesn't match returns `AtlasError::Protocol("crc mismatch")`.
+pub fn decode_response_with_crc(bytes: &[u8]) -> Result<Response> {
+    decode_response(verify_crc(bytes)?)
+}
+
+/// Append a CRC32 of the frame so far (header + payload)
+fn append_crc(frame: &mut Vec<u8>) {
+    let crc = crc32fast::hash(frame);
+    frame.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Check the CRC trailing a frame and return the frame without it
+///
+/// A short header or oversized payload length is passed through for the
+/// decoder to report.
+fn verify_crc(bytes: &[u8]) -> Result<&[u8]> {
+    if bytes.len() < HEADER_SIZE {
+        return Ok(bytes);
+    }
+
+    let payload_len = u32::from_be_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]) as usize;
+    if payload_len > MAX_PAYLOAD_SIZE as usize {
+        return Ok(bytes);
+    }
+    let frame_len = HEADER_SIZE + payload_len;
+    if bytes.len() < frame_len + CRC_SIZE {
+        return Err(AtlasError::Protocol(format!(
+            "Incomplete frame: expected {} bytes with CRC, got {}",
+            frame_len + CRC_SIZE,
+            bytes.len()
+        )));
+    }
+
+    let expected = u32::from_be_bytes(bytes[frame_len..frame_len + CRC_SIZE].try_into().unwrap());
+    if crc32fast::hash(&bytes[..frame_len]) != expected {
+        return Err(AtlasError::Protocol("crc mismatch".to_string()));
+    }
+    Ok(&bytes[..frame_len])
+}
+
 // =============================================================================
 // Stream-based I/O helpers
 // =============================================================================
 
+/// Key/value size limits applied while reading commands from a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandLimits {
+    /// Max key size in bytes
+    pub max_key_size: usize,
+    /// Max value size in bytes (everything after the key)
+    pub max_value_size: usize,
+}
+
+impl CommandLimits {
+    /// No limits beyond `MAX_PAYLOAD_SIZE`
+    pub fn unlimited() -> Self {
+        Self {
+            max_key_size: usize::MAX,
+            max_value_size: usize::MAX,
+        }
+    }
+
+    /// Check key and value lengths against the limits
+    pub(super) fn check(&self, key_len: usize, value_len: usize) -> Result<()> {
+        if key_len > self.max_key_size {
+            return Err(AtlasError::Protocol(format!(
+                "Key too large: {} bytes (max {})",
+                key_len, self.max_key_size
+            )));
+        }
+        if value_len > self.max_value_size {
+            return Err(AtlasError::Protocol(format!(
+                "Value too large: {} bytes (max {})",
+                value_len, self.max_value_size
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Whether a command type's payload starts with key_len (4) + key
+pub(super) fn has_key_prefix(cmd_type: u8) -> bool {
+    matches!(cmd_type, 0x01 | 0x02 | 0x03 | 0x10 | 0x11 | 0x16 | 0x19)
+}
+
 /// Read a complete command from a stream
 ///
 /// Blocks until a complete command is received or an error occurs
 pub fn read_command<R: Read>(reader: &mut R) -> Result<Command> {
+    read_command_with_limits(reader, &CommandLimits::unlimited())
+}
+
+/// Read a complete command from a stream, enforcing key/value size limits
+///
+/// Sizes are checked from the length fields before the payload is
+/// allocated, so an oversized request never reaches memory. On error the
+/// stream is left mid-frame and the connection should be closed.
+pub fn read_command_with_limits<R: Read>(
+    reader: &mut R,
+    limits: &CommandLimits,
+) -> Result<Command> {
+    let (header, payload) = read_command_frame(reader, limits)?;
+    decode_command_parts(header[0], payload.len(), &payload, limits)
+}
+
+/// Read a command and its trailing frame CRC, enforcing key/value size limits
+pub fn read_command_with_crc<R: Read>(reader: &mut R, limits: &CommandLimits) -> Result<Command> {
+    let (header, payload) = read_command_frame(reader, limits)?;
+    let mut crc = [0u8; CRC_SIZE];
+    reader.read_exact(&mut crc)?;
+
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&header);
+    hasher.update(&payload);
+    if hasher.finalize() != u32::from_be_bytes(crc) {
+        return Err(AtlasError::Protocol("crc mismatch".to_string()));
+    }
+    decode_command_parts(header[0], payload.len(), &payload, limits)
+}
+
+/// Read one command frame (header, payload), checking sizes before allocating
+fn read_command_frame<R: Read>(
+    reader: &mut R,
+    limits: &CommandLimits,
+) -> Result<([u8; HEADER_SIZE], Vec<u8>)> {
     // Read header first
     let mut header = [0u8; HEADER_SIZE];
     reader.read_exact(&mut header)?;
@@ -300,18 +1108,32 @@ pub fn read_command<R: Read>(reader: &mut R) -> Result<Command> {
         )));
     }
 
-    // Read payload
-    let mut payload = vec![0u8; payload_len];
-    if payload_len > 0 {
+    // Read payload (peeking at key_len first for key-prefixed commands)
+    let mut payload = Vec::new();
+    if has_key_prefix(header[0]) && payload_len >= 4 {
+        let mut key_len_bytes = [0u8; 4];
+        reader.read_exact(&mut key_len_bytes)?;
+
+        let key_len = u32::from_be_bytes(key_len_bytes) as usize;
+        limits.check(key_len, (payload_len - 4).saturating_sub(key_len))?;
+
+        payload.resize(payload_len, 0);
+        payload[..4].copy_from_slice(&key_len_bytes);
+        reader.read_exact(&mut payload[4..])?;
+    } else if payload_len > 0 {
+        payload.resize(payload_len, 0);
         reader.read_exact(&mut payload)?;
     }
 
-    // Combine and decode
-    let mut full_message = Vec::with_capacity(HEADER_SIZE + payload_len);
-    full_message.extend_from_slice(&header);
-    full_message.extend_from_slice(&payload);
+    Ok((header, payload))
+}
 
-    decode_command(&full_message)
+/// Read the CRC trailing a frame onto the end of it
+fn read_crc<R: Read>(reader: &mut R, frame: &mut Vec<u8>) -> Result<()> {
+    let mut crc = [0u8; CRC_SIZE];
+    reader.read_exact(&mut crc)?;
+    frame.extend_from_slice(&crc);
+    Ok(())
 }
 
 /// Write a command to a stream
@@ -322,8 +1144,28 @@ pub fn write_command<W: Write>(writer: &mut W, command: &Command) -> Result<()>
     Ok(())
 }
 
+/// Write a command and its frame CRC to a stream
+pub fn write_command_with_crc<W: Write>(writer: &mut W, command: &Command) -> Result<()> {
+    let bytes = encode_command_with_crc(command);
+    writer.write_all(&bytes)?;
+    writer.flush()?;
+    Ok(())
+}
+
 /// Read a complete response from a stream
 pub fn read_response<R: Read>(reader: &mut R) -> Result<Response> {
+    decode_response(&read_response_frame(reader)?)
+}
+
+/// Read a response and its trailing frame CRC
+pub fn read_response_with_crc<R: Read>(reader: &mut R) -> Result<Response> {
+    let mut frame = read_response_frame(reader)?;
+    read_crc(reader, &mut frame)?;
+    decode_response_with_crc(&frame)
+}
+
+/// Read one response frame (header + payload)
+fn read_response_frame<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
     // Read header first
     let mut header = [0u8; HEADER_SIZE];
     reader.read_exact(&mut header)?;
@@ -345,12 +1187,12 @@ pub fn read_response<R: Read>(reader: &mut R) -> Result<Response> {
         reader.read_exact(&mut payload)?;
     }
 
-    // Combine and decode
-    let mut full_message = Vec::with_capacity(HEADER_SIZE + payload_len);
+    // Combine
+    let mut full_message = Vec::with_capacity(HEADER_SIZE + payload_len + CRC_SIZE);
     full_message.extend_from_slice(&header);
     full_message.extend_from_slice(&payload);
 
-    decode_response(&full_message)
+    Ok(full_message)
 }
 
 /// Write a response to a stream
@@ -360,3 +1202,11 @@ pub fn write_response<W: Write>(writer: &mut W, response: &Response) -> Result<(
     writer.flush()?;
     Ok(())
 }
+
+/// Write a response and its frame CRC to a stream
+pub fn write_response_with_crc<W: Write>(writer: &mut W, response: &Response) -> Result<()> {
+    let bytes = encode_response_with_crc(response);
+    writer.write_all(&bytes)?;
+    writer.flush()?;
+    Ok(())
+}
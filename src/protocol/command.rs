@@ -10,6 +10,48 @@ pub enum CommandType {
     Put = 0x02,
     Delete = 0x03,
     Ping = 0x04,
+    Flush = 0x0F,
+    Append = 0x10,
+    GetSet = 0x11,
+    Hello = 0x12,
+    SsTables = 0x13,
+    ScanRev = 0x14,
+    StatsJson = 0x15,
+    SetNx = 0x16,
+    Auth = 0x18,
+    GetMeta = 0x19,
+    ScanKeys = 0x1A,
+    Health = 0x1B,
+    Count = 0x1C,
+    Version = 0x1D,
+    ScanCursor = 0x1F,
+    WithDeadline = 0x20,
+}
+
+impl CommandType {
+    /// Every command type this build understands
+    pub const ALL: [CommandType; 20] = [
+        CommandType::Get,
+        CommandType::Put,
+        CommandType::Delete,
+        CommandType::Ping,
+        CommandType::Flush,
+        CommandType::Append,
+        CommandType::GetSet,
+        CommandType::Hello,
+        CommandType::SsTables,
+        CommandType::ScanRev,
+        CommandType::StatsJson,
+        CommandType::SetNx,
+        CommandType::Auth,
+        CommandType::GetMeta,
+        CommandType::ScanKeys,
+        CommandType::Health,
+        CommandType::Count,
+        CommandType::Version,
+        CommandType::ScanCursor,
+        CommandType::WithDeadline,
+    ];
 }
 
 /// A parsed command
@@ -21,11 +63,78 @@ pub enum Command {
     /// Put a key-value pair
     Put { key: Vec<u8>, value: Vec<u8> },
 
-    /// Delete a key
+    /// Delete a key (the response says whether it had a live value)
     Delete { key: Vec<u8> },
 
-    /// Ping (health check)
+    /// Ping (liveness only: answered without touching storage, see `Health`)
     Ping,
+
+    /// Force a MemTable flush to SSTable (admin operation)
+    ///
+    /// Takes the engine write lock and blocks all writers until the flush
+    /// completes. Set `Config::auth_token` to keep it from anonymous clients.
+    Flush,
+
+    /// Append data to the value at a key (missing key = empty value)
+    Append { key: Vec<u8>, data: Vec<u8> },
+
+    /// Atomically set a key and return its previous value
+    GetSet { key: Vec<u8>, value: Vec<u8> },
+
+    /// Optional opening handshake announcing the client's protocol version
+    ///
+    /// Only valid as the first frame on a connection; handled by the
+    /// connection itself rather than the engine. `features` requests
+    /// optional frame features (`CAP_*` bits, 0 for none).
+    Hello { proto_version: u16, features: u64 },
+
+    /// List per-SSTable statistics (admin/debugging)
+    SsTables,
+
+    /// Scan keys in `[start, end)` from the highest down, up to `limit`
+    /// entries (empty `end` = no upper bound)
+    ScanRev { start: Vec<u8>, end: Vec<u8>, limit: u32 },
+
+    /// Engine statistics as a JSON document (admin/dashboards)
+    StatsJson,
+
+    /// Set a key only if it has no live value
+    SetNx { key: Vec<u8>, value: Vec<u8> },
+
+    /// Authenticate the connection with the server's shared secret
+    ///
+    /// Required before any other command (HELLO aside) when the server has
+    /// `Config::auth_token` set; handled by the connection, not the engine.
+    Auth { token: Vec<u8> },
+
+    /// Get a value along with its last-write timestamp (unix millis)
+    GetMeta { key: Vec<u8> },
+
+    /// List keys in `[start, end)` from the lowest up, up to `limit` keys,
+    /// without their values (empty `end` = no upper bound)
+    ScanKeys { start: Vec<u8>, end: Vec<u8>, limit: u32 },
+
+    /// Readiness check: OK with a short status line only if locks are
+    /// healthy and the storage directory is writable
+    Health,
+
+    /// Count live keys in `[start, end)` without sending them (empty
+    /// `end` = no upper bound)
+    Count { start: Vec<u8>, end: Vec<u8> },
+
+    /// Server version and the on-disk and wire format versions it speaks
+    Version,
+
+    /// One page of an ascending scan: up to `limit` entries with keys
+    /// strictly after `after_key` (`None` = from the first key), plus
+    /// whether more remain
+    ScanCursor { after_key: Option<Vec<u8>>, limit: u32 },
+
+    /// Run `command` with a budget of `deadline_ms` milliseconds of server
+    /// time; a SCANCURSOR that runs out returns a partial page flagged
+    /// `deadline_exceeded`. SCANREV, SCANKEYS, and COUNT are rejected, having
+    /// no way to flag a partial result; point commands ignore the deadline.
+    WithDeadline { deadline_ms: u32, command: Box<Command> },
 }
 
 impl Command {
@@ -36,6 +145,22 @@ impl Command {
             Command::Put { .. } => CommandType::Put,
             Command::Delete { .. } => CommandType::Delete,
             Command::Ping => CommandType::Ping,
+            Command::Flush => CommandType::Flush,
+            Command::Append { .. } => CommandType::Append,
+            Command::GetSet { .. } => CommandType::GetSet,
+            Command::Hello { .. } => CommandType::Hello,
+            Command::SsTables => CommandType::SsTables,
+            Command::ScanRev { .. } => CommandType::ScanRev,
+            Command::StatsJson => CommandType::StatsJson,
+            Command::SetNx { .. } => CommandType::SetNx,
+            Command::Auth { .. } => CommandType::Auth,
+            Command::GetMeta { .. } => CommandType::GetMeta,
+            Command::ScanKeys { .. } => CommandType::ScanKeys,
+            Command::Health => CommandType::Health,
+            Command::Count { .. } => CommandType::Count,
+            Command::Version => CommandType::Version,
+            Command::ScanCursor { .. } => CommandType::ScanCursor,
+            Command::WithDeadline { .. } => CommandType::WithDeadline,
         }
     }
 }
@@ -16,6 +16,43 @@
 //! - 0x02: PUT   - Payload: key_len (4) + key + value
 //! - 0x03: DEL   - Payload: key
 //! - 0x04: PING  - Payload: empty
+//! - 0x0F: FLUSH - Payload: empty (admin: forces MemTable → SSTable flush)
+//! - 0x10: APPEND - Payload: key_len (4) + key + data
+//! - 0x11: GETSET - Payload: key_len (4) + key + value
+//! - 0x12: HELLO - Payload: proto_version (2) [+ features (8)]
+//! - 0x13: SSTABLES - Payload: empty (admin: per-SSTable stats)
+//! - 0x14: SCANREV - Payload: start_len (4) + start + end_len (4) + end + limit (4)
+//! - 0x15: STATSJSON - Payload: empty (admin: engine stats as JSON)
+//! - 0x16: SETNX - Payload: key_len (4) + key + value
+//! - 0x18: AUTH - Payload: token
+//! - 0x19: GETMETA - Payload: key_len (4) + key (response: timestamp (8) + value)
+//! - 0x1A: SCANKEYS - Payload: as SCANREV (response: key_len (4) + key per key)
+//! - 0x1B: HEALTH - Payload: empty (readiness: checks locks and storage dir)
+//! - 0x1C: COUNT - Payload: start_len (4) + start + end_len (4) + end (response: count (8))
+//! - 0x1D: VERSION - Payload: empty (response: proto_version (2) + sstable_format (2) +
+//!   wal_format (2) + server version string)
+//! - 0x1F: SCANCURSOR - Payload: has_after (1) [+ after_len (4) + after_key] + limit (4)
+//!   (response: flags (1: more = 0x01, deadline exceeded = 0x02) + key_len (4) + key +
+//!   value_len (4) + value per entry)
+//! - 0x20: WITHDEADLINE - Payload: deadline_ms (4) + a whole command (header + payload);
+//!   a wrapped SCANCURSOR stops once the deadline passes and returns what it has;
+//!   SCANREV, SCANKEYS, and COUNT can't stop early and are rejected
+//!
+//! ### Handshake
+//! A client may open with HELLO. The server replies OK with its own version
+//! and a capability bitmask (bit `n` = command byte `n` supported), or with
+//! ERROR and closes the connection if the version is unsupported. Clients
+//! that skip HELLO are assumed to speak `PROTOCOL_VERSION`.
+//!
+//! HELLO may also request optional features (`CAP_*` bits). Those the
+//! server enables are set in the response's bitmask. `CAP_FRAME_CRC` adds a
+//! trailing CRC32 to every later frame, in both directions; it is off unless
+//! requested.
+//!
+//! ### Authentication
+//! When the server has `Config::auth_token` set, a connection must send AUTH
+//! with the matching token before anything but HELLO. Other commands get
+//! ERROR until then; a wrong token gets ERROR and the connection is closed.
 //!
 //! ### Response Format
 //! ```text
@@ -32,11 +69,28 @@
 mod command;
 mod response;
 mod codec;
+#[cfg(feature = "tokio")]
+mod async_codec;
 
 pub use command::{Command, CommandType};
 pub use response::{Response, Status};
 pub use codec::{
-    encode_command, decode_command, encode_response, decode_response,
-    read_command, write_command, read_response, write_response,
-    HEADER_SIZE, MAX_PAYLOAD_SIZE,
+    encode_command, decode_command, decode_command_with_limits, decode_command_parts,
+    encode_response, decode_response,
+    read_command, read_command_with_limits, write_command, read_response, write_response,
+    capabilities, encode_hello_response, decode_hello_response,
+    encode_get_meta_response, decode_get_meta_response,
+    encode_version_response, decode_version_response, ServerVersion,
+    encode_entries, decode_entries, encode_keys, decode_keys,
+    encode_scan_page, decode_scan_page, ScanPage,
+    encode_command_with_crc, decode_command_with_crc, encode_response_with_crc,
+    decode_response_with_crc, read_command_with_crc, write_command_with_crc,
+    read_response_with_crc, write_response_with_crc,
+    CommandLimits, CAP_FRAME_CRC, CRC_SIZE, HEADER_SIZE, MAX_PAYLOAD_SIZE, PROTOCOL_VERSION,
+};
+
+#[cfg(feature = "tokio")]
+pub use async_codec::{
+    read_command_async, read_command_with_limits_async, write_command_async,
+    read_response_async, write_response_async,
 };
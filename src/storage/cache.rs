@@ -0,0 +1,151 @@
+//! Block Cache
+//!
+//! LRU cache of SSTable lookup results, keyed by `(sstable_id, key)`.
+//!
+//! ## Why
+//! Every SSTable lookup reads and decodes a whole data block. Hot keys pay
+//! that read every time; the cache holds their values rather than the
+//! blocks, so a hot key costs only its own bytes (the name matches
+//! `Config::block_cache_bytes`).
+//!
+//! ## Invalidation
+//! SSTables are immutable, so a cached result never goes stale while its
+//! file is live. Compaction deletes files, and their entries must be
+//! evicted then (ids are never reused, so stale entries would only waste
+//! space, not return wrong data).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// Cached lookup result: `None` is a tombstone
+type CachedValue = Option<Vec<u8>>;
+
+/// Bytes charged per entry on top of key/value lengths
+/// (approximates the LRU node, hash slot, and Vec headers)
+const ENTRY_OVERHEAD: usize = 64;
+
+/// Byte-bounded LRU cache of SSTable values
+pub struct BlockCache {
+    /// Entries plus the bytes they are charged for
+    inner: Mutex<Inner>,
+
+    /// Max bytes before the least recently used entries are evicted
+    capacity_bytes: usize,
+
+    /// Lookups answered from the cache
+    hits: AtomicU64,
+
+    /// Lookups that fell through to the file
+    misses: AtomicU64,
+}
+
+struct Inner {
+    map: LruCache<(u64, Vec<u8>), CachedValue>,
+    used_bytes: usize,
+}
+
+/// Point-in-time cache statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BlockCacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that fell through to the file
+    pub misses: u64,
+    /// Cached entries
+    pub entries: usize,
+    /// Bytes charged for cached entries
+    pub used_bytes: usize,
+}
+
+impl BlockCache {
+    /// Create a cache holding up to `capacity_bytes`
+    pub fn new(capacity_bytes: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                map: LruCache::unbounded(),
+                used_bytes: 0,
+            }),
+            capacity_bytes,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Look up a cached result (`None` on a miss)
+    pub fn get(&self, sstable_id: u64, key: &[u8]) -> Option<CachedValue> {
+        // Borrowed lookups aren't possible with a tuple key; the clone is
+        // cheap next to the file read a hit saves
+        let found = self.inner.lock().map.get(&(sstable_id, key.to_vec())).cloned();
+
+        let counter = if found.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    /// Cache a lookup result, evicting least recently used entries to fit
+    ///
+    /// Entries larger than the whole cache are not stored.
+    pub fn insert(&self, sstable_id: u64, key: Vec<u8>, value: CachedValue) {
+        let charge = Self::charge(&key, &value);
+        if charge > self.capacity_bytes {
+            return;
+        }
+
+        let mut inner = self.inner.lock();
+        if let Some((old_key, old_value)) = inner.map.push((sstable_id, key), value) {
+            inner.used_bytes -= Self::charge(&old_key.1, &old_value);
+        }
+        inner.used_bytes += charge;
+
+        while inner.used_bytes > self.capacity_bytes {
+            match inner.map.pop_lru() {
+                Some(((_, key), value)) => inner.used_bytes -= Self::charge(&key, &value),
+                None => break,
+            }
+        }
+    }
+
+    /// Drop every entry belonging to the given SSTables
+    pub fn evict_sstables(&self, sstable_ids: &[u64]) {
+        let mut inner = self.inner.lock();
+
+        let doomed: Vec<(u64, Vec<u8>)> = inner
+            .map
+            .iter()
+            .filter(|((id, _), _)| sstable_ids.contains(id))
+            .map(|(entry_key, _)| entry_key.clone())
+            .collect();
+
+        for entry_key in doomed {
+            if let Some(value) = inner.map.pop(&entry_key) {
+                inner.used_bytes -= Self::charge(&entry_key.1, &value);
+            }
+        }
+    }
+
+    /// Drop every entry (hit/miss counters are kept)
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock();
+        inner.map.clear();
+        inner.used_bytes = 0;
+    }
+
+    /// Get current statistics
+    pub fn stats(&self) -> BlockCacheStats {
+        let inner = self.inner.lock();
+        BlockCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: inner.map.len(),
+            used_bytes: inner.used_bytes,
+        }
+    }
+
+    /// Bytes charged for one entry
+    fn charge(key: &[u8], value: &CachedValue) -> usize {
+        key.len() + value.as_ref().map_or(0, Vec::len) + ENTRY_OVERHEAD
+    }
+}
@@ -0,0 +1,86 @@
+//! Compaction Input Selection
+//!
+//! Picks the SSTables a compaction merges, per `CompactionStrategy`.
+//!
+//! Inputs are always the newest N SSTables. Outputs take fresh ids, which
+//! makes them the newest files on the next open, so they can only stand in
+//! for a run that nothing newer shadows. A tombstone is dropped only when no
+//! SSTable older than the run could hold its key, and only once it's past
+//! `Config::tombstone_grace_ms`; otherwise it may still hide an older value.
+
+use std::cmp::Ordering;
+
+use crate::comparator::Comparator;
+use crate::config::CompactionStrategy;
+
+use super::SSTableStats;
+
+/// An SSTable up for compaction
+#[derive(Debug, Clone)]
+pub struct CompactionCandidate {
+    /// Id, entry count, size, and key range
+    pub stats: SSTableStats,
+    /// Tombstones among `stats.entry_count`
+    pub tombstones: u64,
+}
+
+/// Pick the ids of the SSTables worth compacting
+///
+/// `candidates` must run newest → oldest; the result is always a prefix of
+/// them (empty if no compaction is worthwhile):
+/// - `AllFiles`: every SSTable
+/// - `OverlapBased`: the newest SSTables, for as long as each one's key
+///   range overlaps the combined range of those newer than it (at least two)
+/// - `TombstoneRatio`: every SSTable, once tombstones make up `min_ratio`
+///   of all entries
+pub fn select_inputs(
+    strategy: CompactionStrategy,
+    candidates: &[CompactionCandidate],
+    comparator: &dyn Comparator,
+) -> Vec<u64> {
+    let count = match strategy {
+        CompactionStrategy::AllFiles => candidates.len(),
+        CompactionStrategy::OverlapBased => overlapping_run(candidates, comparator),
+        CompactionStrategy::TombstoneRatio { min_ratio } => {
+            let entries: u64 = candidates.iter().map(|c| c.stats.entry_count).sum();
+            let tombstones: u64 = candidates.iter().map(|c| c.tombstones).sum();
+            if entries > 0 && tombstones as f64 >= min_ratio * entries as f64 {
+                candidates.len()
+            } else {
+                0
+            }
+        }
+    };
+    candidates[..count].iter().map(|c| c.stats.id).collect()
+}
+
+/// Length of the newest run of overlapping SSTables (0 if under two)
+fn overlapping_run(candidates: &[CompactionCandidate], comparator: &dyn Comparator) -> usize {
+    let mut low: Option<&[u8]> = None;
+    let mut high: Option<&[u8]> = None;
+    let mut len = 0;
+
+    for candidate in candidates {
+        // An empty SSTable has no range to overlap
+        let (Some(min), Some(max)) = (&candidate.stats.min_key, &candidate.stats.max_key) else {
+            break;
+        };
+        if let (Some(lo), Some(hi)) = (low, high) {
+            let disjoint = comparator.compare(min, hi) == Ordering::Greater
+                || comparator.compare(max, lo) == Ordering::Less;
+            if disjoint {
+                break;
+            }
+        }
+
+        if low.is_none_or(|lo| comparator.compare(min, lo) == Ordering::Less) {
+            low = Some(min);
+        }
+        if high.is_none_or(|hi| comparator.compare(max, hi) == Ordering::Greater) {
+            high = Some(max);
+        }
+        len += 1;
+    }
+
+    if len >= 2 { len } else { 0 }
+}
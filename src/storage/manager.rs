@@ -3,28 +3,106 @@
 //! Manages multiple SSTables and coordinates reads/writes.
 //!
 //! ## Responsibilities
-//! - Discover existing SSTables on startup
+//! - Load live SSTables from the MANIFEST on startup
 //! - Search SSTables newest → oldest for reads
 //! - Create new SSTables from MemTable flushes
 //! - Track SSTable lifecycle
 
+use std::fmt;
 use std::fs;
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::ops::Bound;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use serde::{Serialize, Serializer};
 
+use crate::comparator::{BytewiseComparator, Comparator};
+use crate::config::{CompactionStrategy, DEFAULT_IO_BUFFER_BYTES};
 use crate::error::Result;
 use crate::memtable::{MemTable, MemTableEntry};
 use crate::AtlasError;
 
-use super::{SSTable, SSTableBuilder, SSTableReader};
+use super::sstable::read_exact_at;
+use super::{
+    select_inputs, BlockCache, BlockCacheStats, CompactionCandidate, EntryMeta, Manifest,
+    MergeEntry, MergeIterator, MergeSource, SSTable, SSTableBuilder, SSTableReader,
+    DEFAULT_BLOCK_SIZE,
+};
+
+/// Compaction merge payload: a value (`None` = tombstone) and its seq/timestamp
+type SeqValue = (Option<Vec<u8>>, EntryMeta);
+
+/// Bytes copied per positioned read when streaming a value
+const STREAM_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Per-SSTable statistics (for debugging read amplification)
+///
+/// Serializes with `file_size` as `file_size_bytes` and keys as
+/// ASCII-escaped strings (`null` for an empty SSTable).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SSTableStats {
+    /// SSTable id (from its filename)
+    pub id: u64,
+    /// Number of entries, including tombstones
+    pub entry_count: u64,
+    /// File size in bytes
+    #[serde(rename = "file_size_bytes")]
+    pub file_size: u64,
+    /// Smallest key (None for an empty SSTable)
+    #[serde(serialize_with = "serialize_key")]
+    pub min_key: Option<Vec<u8>>,
+    /// Largest key (None for an empty SSTable)
+    #[serde(serialize_with = "serialize_key")]
+    pub max_key: Option<Vec<u8>>,
+}
+
+/// Serialize a binary key as an ASCII-escaped string
+fn serialize_key<S: Serializer>(
+    key: &Option<Vec<u8>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match key {
+        Some(key) => serializer.serialize_str(&key.escape_ascii().to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Formats as one tab-separated line: `id entry_count file_size min_key max_key`
+///
+/// Keys are ASCII-escaped so binary keys can't break the line format.
+impl fmt::Display for SSTableStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = |k: &Option<Vec<u8>>| {
+            k.as_deref()
+                .map(|k| k.escape_ascii().to_string())
+                .unwrap_or_else(|| "-".to_string())
+        };
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}",
+            self.id,
+            self.entry_count,
+            self.file_size,
+            key(&self.min_key),
+            key(&self.max_key)
+        )
+    }
+}
 
 /// Manages the storage layer
 ///
 /// ## Concurrency:
 /// - `sstables`: Protected by RwLock (many concurrent readers, exclusive writer)
 /// - `next_sstable_id`: Atomic counter (lock-free)
+/// - `total_bytes`: Atomic, only changed under the `sstables` write lock
+/// - `manifest`: Mutex, held from id allocation to publication (so flushes and
+///   compactions publish in id order); `None` when opened read-only
 /// - All methods use `&self` (no exclusive access needed)
 pub struct StorageManager {
     /// Directory where SSTables are stored
@@ -36,6 +114,35 @@ pub struct StorageManager {
 
     /// Next ID for creating new SSTables (atomic, lock-free)
     next_sstable_id: AtomicU64,
+
+    /// Sum of the live SSTables' file sizes, kept in step with `sstables`
+    total_bytes: AtomicU64,
+
+    /// Authoritative list of live SSTables (`None` in read-only mode)
+    manifest: Option<Mutex<Manifest>>,
+
+    /// Cache of SSTable lookup results (`None` when disabled)
+    cache: Option<BlockCache>,
+    /// Target data block size for new SSTables
+    block_size: usize,
+    /// Buffer capacity for writing new SSTables
+    write_buffer_bytes: usize,
+    /// Buffer capacity for each SSTable reader
+    read_buffer_bytes: usize,
+    /// Order of keys within every SSTable
+    comparator: Arc<dyn Comparator>,
+    /// Which SSTables `compact` merges
+    compaction_strategy: CompactionStrategy,
+    /// Data bytes per compaction output SSTable (0 = one output)
+    max_sstable_bytes: u64,
+    /// Minimum tombstone age in ms before it may be dropped (0 = no minimum)
+    tombstone_grace_ms: u64,
+    /// Store a CRC32 after each value in new SSTables
+    value_checksums: bool,
+    /// Between `begin_bulk` and `end_bulk`: new SSTables skip the directory fsync
+    bulk: AtomicBool,
+    /// A directory fsync was skipped during bulk mode
+    dir_sync_pending: AtomicBool,
 }
 
 impl StorageManager {
@@ -43,49 +150,217 @@ impl StorageManager {
     ///
     /// On startup:
     /// 1. Create directory if it doesn't exist
-    /// 2. Discover existing SSTable files
-    /// 3. Open readers for each (loads indexes into RAM)
+    /// 2. Load the MANIFEST (bootstrapping it from a directory scan if absent)
+    /// 3. Open readers for each live SSTable (loads indexes into RAM)
     /// 4. Order by ID descending (newest first)
+    ///
+    /// SSTable files not listed in the manifest are orphans from a crashed
+    /// flush; they are logged and ignored. Live SSTables with no entries
+    /// can't affect a read, so they are dropped from the manifest and deleted.
     pub fn open(path: &Path) -> Result<Self> {
         // Create directory if it doesn't exist
         fs::create_dir_all(path)?;
 
-        // Discover existing SSTables
-        let mut sstable_ids: Vec<u64> = Vec::new();
+        // Discover SSTable files on disk
+        let mut disk_ids: Vec<u64> = Vec::new();
 
         for entry in fs::read_dir(path)? {
             let entry = entry?;
             let file_path = entry.path();
 
-            if file_path.is_file() {
-                if let Some(id) = Self::parse_sstable_id(&file_path) {
-                    sstable_ids.push(id);
-                }
+            if !file_path.is_file() {
+                continue;
+            }
+
+            // Leftover from a flush that crashed before its rename
+            if Self::is_temp_sstable(&file_path) {
+                tracing::warn!("Removing incomplete SSTable {}", file_path.display());
+                fs::remove_file(&file_path)?;
+                continue;
+            }
+
+            if let Some(id) = Self::parse_sstable_id(&file_path) {
+                disk_ids.push(id);
             }
         }
 
-        // Sort newest first (highest ID first)
-        sstable_ids.sort();
-        sstable_ids.reverse();
+        // Load manifest; a pre-manifest directory adopts whatever is on disk
+        let mut manifest = if Manifest::exists(path) {
+            Manifest::open(path)?
+        } else {
+            let mut manifest = Manifest::open(path)?;
+            disk_ids.sort();
+            for id in &disk_ids {
+                manifest.add(*id)?;
+            }
+            manifest
+        };
 
-        // Open readers for each SSTable
-        let mut sstables = Vec::new();
-        for id in &sstable_ids {
-            let sstable_path = Self::sstable_path_with_dir(path, *id);
-            let reader = SSTableReader::open(&sstable_path)?;
-            sstables.push(reader);
+        for id in disk_ids.iter().filter(|id| !manifest.contains(**id)) {
+            tracing::warn!(
+                "Ignoring orphan SSTable {} (not in manifest)",
+                Self::sstable_path_with_dir(path, *id).display()
+            );
         }
 
-        // Next ID = max + 1, or 1 if no SSTables exist
-        let next_id = sstable_ids.first().map(|&id| id + 1).unwrap_or(1);
+        // Live SSTables, newest first (highest ID first)
+        let sstable_ids: Vec<u64> = manifest.live_ids().iter().rev().copied().collect();
+
+        let (sstables, empty_ids) = Self::open_readers(path, &sstable_ids)?;
+        if !empty_ids.is_empty() {
+            manifest.replace(&[], &empty_ids)?;
+            for id in &empty_ids {
+                let empty_path = Self::sstable_path_with_dir(path, *id);
+                tracing::warn!("Removing empty SSTable {}", empty_path.display());
+                fs::remove_file(&empty_path)?;
+            }
+        }
+
+        // The manifest remembers every id ever used (including compacted-away
+        // files); orphans are also skipped so they are never reused
+        let next_id = Self::next_id(manifest.next_id(), &disk_ids);
 
         Ok(Self {
             data_dir: path.to_path_buf(),
+            total_bytes: AtomicU64::new(Self::sum_file_sizes(&sstables)),
             sstables: RwLock::new(sstables),
             next_sstable_id: AtomicU64::new(next_id),
+            manifest: Some(Mutex::new(manifest)),
+            cache: None,
+            block_size: DEFAULT_BLOCK_SIZE,
+            write_buffer_bytes: DEFAULT_IO_BUFFER_BYTES,
+            read_buffer_bytes: DEFAULT_IO_BUFFER_BYTES,
+            comparator: Arc::new(BytewiseComparator),
+            compaction_strategy: CompactionStrategy::AllFiles,
+            max_sstable_bytes: 0,
+            tombstone_grace_ms: 0,
+            value_checksums: false,
+            bulk: AtomicBool::new(false),
+            dir_sync_pending: AtomicBool::new(false),
         })
     }
 
+    /// Open existing storage without modifying anything on disk
+    ///
+    /// Unlike [`open`](Self::open), this never creates the directory,
+    /// removes temp files, or writes the MANIFEST, so it is safe to run
+    /// alongside a writer. Without a manifest, every SSTable on disk is
+    /// treated as live. [`flush`](Self::flush) always fails.
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        let mut disk_ids: Vec<u64> = Vec::new();
+
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let file_path = entry.path();
+
+            if !file_path.is_file() {
+                continue;
+            }
+
+            if let Some(id) = Self::parse_sstable_id(&file_path) {
+                disk_ids.push(id);
+            }
+        }
+
+        let (live_ids, manifest_next_id) = match Manifest::read_state(path)? {
+            Some(state) => state,
+            None => (disk_ids.iter().copied().collect(), 1),
+        };
+
+        // Live SSTables, newest first (highest ID first)
+        let sstable_ids: Vec<u64> = live_ids.iter().rev().copied().collect();
+        let (sstables, _empty_ids) = Self::open_readers(path, &sstable_ids)?;
+
+        let next_id = Self::next_id(manifest_next_id, &disk_ids);
+
+        Ok(Self {
+            data_dir: path.to_path_buf(),
+            total_bytes: AtomicU64::new(Self::sum_file_sizes(&sstables)),
+            sstables: RwLock::new(sstables),
+            next_sstable_id: AtomicU64::new(next_id),
+            manifest: None,
+            cache: None,
+            block_size: DEFAULT_BLOCK_SIZE,
+            write_buffer_bytes: DEFAULT_IO_BUFFER_BYTES,
+            read_buffer_bytes: DEFAULT_IO_BUFFER_BYTES,
+            comparator: Arc::new(BytewiseComparator),
+            compaction_strategy: CompactionStrategy::AllFiles,
+            max_sstable_bytes: 0,
+            tombstone_grace_ms: 0,
+            value_checksums: false,
+            bulk: AtomicBool::new(false),
+            dir_sync_pending: AtomicBool::new(false),
+        })
+    }
+
+    /// Enable a value cache of `capacity_bytes` for SSTable lookups (0 = disabled)
+    pub fn with_block_cache(mut self, capacity_bytes: usize) -> Self {
+        self.cache = (capacity_bytes > 0).then(|| BlockCache::new(capacity_bytes));
+        self
+    }
+
+    /// Choose which SSTables `compact` merges (default: all of them)
+    pub fn with_compaction_strategy(mut self, strategy: CompactionStrategy) -> Self {
+        self.compaction_strategy = strategy;
+        self
+    }
+
+    /// Split compaction output into SSTables of about `bytes` of data each
+    /// (0 = one output file)
+    pub fn with_max_sstable_bytes(mut self, bytes: u64) -> Self {
+        self.max_sstable_bytes = bytes;
+        self
+    }
+
+    /// Keep tombstones written less than `ms` milliseconds ago through
+    /// flushes and compactions, even with nothing older to shadow (0 = no
+    /// minimum)
+    pub fn with_tombstone_grace_ms(mut self, ms: u64) -> Self {
+        self.tombstone_grace_ms = ms;
+        self
+    }
+
+    /// Store a CRC32 after each value in SSTables written from now on,
+    /// checked whenever the value is read
+    pub fn with_value_checksums(mut self, enabled: bool) -> Self {
+        self.value_checksums = enabled;
+        self
+    }
+
+    /// Set the target data block size for SSTables written from now on
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    /// Write and search SSTables in `comparator`'s order instead of byte order
+    ///
+    /// Readers already open are switched over too, so it must be the
+    /// comparator the existing files were written with.
+    pub fn with_comparator(mut self, comparator: Arc<dyn Comparator>) -> Self {
+        let sstables = self.sstables.get_mut();
+        *sstables = std::mem::take(sstables)
+            .into_iter()
+            .map(|reader| reader.with_comparator(comparator.clone()))
+            .collect();
+        self.comparator = comparator;
+        self
+    }
+
+    /// Set the buffer capacities for SSTable writes and reads (in bytes)
+    ///
+    /// Readers already open are switched over too.
+    pub fn with_io_buffers(mut self, write_bytes: usize, read_bytes: usize) -> Self {
+        self.write_buffer_bytes = write_bytes;
+        self.read_buffer_bytes = read_bytes;
+        let sstables = self.sstables.get_mut();
+        *sstables = std::mem::take(sstables)
+            .into_iter()
+            .map(|reader| reader.with_buffer_size(read_bytes))
+            .collect();
+        self
+    }
+
     /// Get a value by key (searches all SSTables newest → oldest)
     ///
     /// Returns:
@@ -106,12 +381,29 @@ impl StorageManager {
                 continue;
             }
 
+            // Cached results are final: SSTables never change once written
+            let cached_id = match &self.cache {
+                Some(cache) => {
+                    let id = Self::parse_sstable_id(reader.path()).unwrap_or(0);
+                    if let Some(value) = cache.get(id, key) {
+                        return Ok(value);
+                    }
+                    Some((cache, id))
+                }
+                None => None,
+            };
+
             // Key might be here — do the actual lookup
             match reader.get(key) {
-                Ok(Some(value)) => return Ok(Some(value)), // Found!
-                Ok(None) => return Ok(None),               // Tombstone = deleted
-                Err(AtlasError::KeyNotFound) => continue,  // Not in this SSTable
-                Err(e) => return Err(e),                   // Real error
+                Ok(value) => {
+                    // Found a value, or a tombstone (= deleted)
+                    if let Some((cache, id)) = cached_id {
+                        cache.insert(id, key.to_vec(), value.clone());
+                    }
+                    return Ok(value);
+                }
+                Err(AtlasError::KeyNotFound) => continue, // Not in this SSTable
+                Err(e) => return Err(e),                  // Real error
             }
         }
 
@@ -119,42 +411,851 @@ impl StorageManager {
         Ok(None)
     }
 
+    /// Check whether a key has a live value, without reading the value
+    ///
+    /// Resolved like `get`, newest SSTable first, but each SSTable is asked
+    /// only for entry headers and keys. Positioned reads need just the read
+    /// lock, and the block cache is neither consulted nor filled.
+    pub fn contains(&self, key: &[u8]) -> Result<bool> {
+        let sstables = self.sstables.read();
+
+        for reader in sstables.iter().filter(|reader| reader.might_contain(key)) {
+            // A value or a tombstone in a newer SSTable decides
+            if let Some(live) = reader.contains(key)? {
+                return Ok(live);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Copy a key's value into `writer` in chunks, returning its length
+    ///
+    /// Resolved like `get`, but the value is never held in memory whole:
+    /// its location is found from entry headers, then copied with
+    /// positioned reads of `STREAM_CHUNK_BYTES`. Only the read lock is
+    /// taken, and only to locate the value; the copy runs on a cloned file
+    /// handle, which stays readable if compaction deletes the file. Cached
+    /// values are written from the cache. Returns `Ok(None)` if the key is
+    /// absent or deleted. A value whose CRC doesn't match fails with
+    /// `AtlasError::Storage` once it has been copied.
+    pub fn get_streaming(&self, key: &[u8], writer: &mut dyn Write) -> Result<Option<u64>> {
+        let (file, offset, len, checked) = {
+            let sstables = self.sstables.read();
+            let mut found = None;
+            for reader in sstables.iter() {
+                if !reader.might_contain(key) {
+                    continue;
+                }
+                if let Some(cache) = &self.cache {
+                    let id = Self::parse_sstable_id(reader.path()).unwrap_or(0);
+                    if let Some(value) = cache.get(id, key) {
+                        let Some(value) = value else { return Ok(None) };
+                        writer.write_all(&value)?;
+                        return Ok(Some(value.len() as u64));
+                    }
+                }
+                match reader.value_location(key) {
+                    Ok(None) => return Ok(None), // Tombstone
+                    Ok(Some((offset, len))) => {
+                        let file = reader.file().try_clone()?;
+                        found = Some((file, offset, len, reader.has_value_checksums()));
+                        break;
+                    }
+                    Err(AtlasError::KeyNotFound) => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+            match found {
+                Some(found) => found,
+                None => return Ok(None),
+            }
+        };
+
+        let mut buf = vec![0u8; STREAM_CHUNK_BYTES.min(len as usize)];
+        let mut hasher = crc32fast::Hasher::new();
+        let mut copied = 0u64;
+        while copied < len as u64 {
+            let chunk = (len as u64 - copied).min(buf.len() as u64) as usize;
+            read_exact_at(&file, &mut buf[..chunk], offset + copied)?;
+            hasher.update(&buf[..chunk]);
+            writer.write_all(&buf[..chunk])?;
+            copied += chunk as u64;
+        }
+
+        if checked {
+            let mut stored = [0u8; 4];
+            read_exact_at(&file, &mut stored, offset + copied)?;
+            if hasher.finalize() != u32::from_le_bytes(stored) {
+                return Err(AtlasError::Storage(format!(
+                    "Corrupt SSTable block: value checksum mismatch for key '{}'",
+                    key.escape_ascii()
+                )));
+            }
+        }
+        Ok(Some(copied))
+    }
+
+    /// Get the newest version of a key written at or before sequence number `seq`
+    ///
+    /// Returns `Ok(None)` if no such version exists or it is a tombstone.
+    /// Only versions still on disk are visible: each SSTable keeps one
+    /// version per key, and compaction keeps only the newest. Bypasses the
+    /// block cache, which holds current values only.
+    pub fn get_as_of(&self, key: &[u8], seq: u64) -> Result<Option<Vec<u8>>> {
+        let mut sstables = self.sstables.write();
+
+        // Highest qualifying seq wins; on a tie the newer SSTable (seen first) does
+        let mut newest: Option<(u64, Option<Vec<u8>>)> = None;
+        for reader in sstables.iter_mut() {
+            if let Some((value, found)) = reader.get_with_seq(key)? {
+                let newer = newest.as_ref().is_none_or(|(best, _)| found > *best);
+                if found <= seq && newer {
+                    newest = Some((found, value));
+                }
+            }
+        }
+
+        Ok(newest.and_then(|(_, value)| value))
+    }
+
+    /// Get the newest value of a key along with its seq and write timestamp
+    ///
+    /// Resolved like `get`, but bypasses the block cache, which holds
+    /// values only. Returns `Ok(None)` if the key is absent or deleted.
+    pub fn get_with_meta(&self, key: &[u8]) -> Result<Option<(Vec<u8>, EntryMeta)>> {
+        let mut sstables = self.sstables.write();
+
+        // Newest to oldest: the first SSTable holding the key decides
+        for reader in sstables.iter_mut() {
+            if let Some((value, meta)) = reader.get_with_meta(key)? {
+                return Ok(value.map(|value| (value, meta)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Get the highest sequence number across all SSTables (0 if none)
+    pub fn max_seq(&self) -> u64 {
+        self.sstables.read().iter().map(SSTableReader::max_seq).max().unwrap_or(0)
+    }
+
+    /// Scan a key range in descending order, newest version of each key winning
+    ///
+    /// `newer` holds entries (ascending, as from `MemTable::range`) that
+    /// shadow every SSTable, e.g. a MemTable snapshot. Tombstoned keys are
+    /// skipped; at most `limit` live entries are returned.
+    pub fn scan_rev(
+        &self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        limit: usize,
+        newer: Vec<MergeEntry>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        // Need write lock because SSTable iterators mutate file position
+        let mut sstables = self.sstables.write();
+
+        // Sources newest → oldest: overlay first, then SSTables in list order
+        let mut sources: Vec<MergeSource<'_>> = Vec::with_capacity(sstables.len() + 1);
+        sources.push(Box::new(newer.into_iter().rev().map(Ok)));
+        for reader in Self::sstables_overlapping(&mut sstables, start, end) {
+            sources.push(Box::new(reader.range_rev(start, end)));
+        }
+
+        let mut entries = Vec::new();
+        for entry in MergeIterator::reverse(sources, &*self.comparator)? {
+            if entries.len() >= limit {
+                break;
+            }
+            if let (key, Some(value)) = entry? {
+                entries.push((key, value));
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Scan a key range in ascending order, newest version of each key winning
+    ///
+    /// Like [`scan_rev`](Self::scan_rev), from the lowest key up. Once
+    /// `deadline` passes the scan stops early; the flag returned alongside
+    /// the entries says it did, in which case they are only a prefix.
+    pub fn scan(
+        &self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        limit: usize,
+        newer: Vec<MergeEntry>,
+        deadline: Option<Instant>,
+    ) -> Result<(Vec<MergeEntry<Vec<u8>>>, bool)> {
+        // Need write lock because SSTable iterators mutate file position
+        let mut sstables = self.sstables.write();
+
+        // Sources newest → oldest: overlay first, then SSTables in list order
+        let mut sources: Vec<MergeSource<'_>> = Vec::with_capacity(sstables.len() + 1);
+        sources.push(Box::new(newer.into_iter().map(Ok)));
+        for reader in Self::sstables_overlapping(&mut sstables, start, end) {
+            sources.push(Box::new(reader.range(start, end)));
+        }
+
+        let mut merge = MergeIterator::forward(sources, &*self.comparator)?;
+        if let Some(deadline) = deadline {
+            merge = merge.with_deadline(deadline);
+        }
+
+        // Checked before pulling, so a full page never trips the deadline
+        let mut entries = Vec::new();
+        while entries.len() < limit {
+            let Some(entry) = merge.next() else { break };
+            if let (key, Some(value)) = entry? {
+                entries.push((key, value));
+            }
+        }
+
+        Ok((entries, merge.deadline_exceeded()))
+    }
+
+    /// List the live keys in a range in ascending order, newest version winning
+    ///
+    /// Like [`scan_rev`](Self::scan_rev), but `newer` holds (key, live)
+    /// pairs and only keys come back: SSTable values are never decoded.
+    pub fn scan_keys(
+        &self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        limit: usize,
+        newer: Vec<MergeEntry<bool>>,
+    ) -> Result<Vec<Vec<u8>>> {
+        let mut keys = Vec::new();
+        self.visit_live_keys(start, end, newer, |key| {
+            if keys.len() >= limit {
+                return false;
+            }
+            keys.push(key);
+            true
+        })?;
+        Ok(keys)
+    }
+
+    /// Count the live keys in a range, newest version winning
+    ///
+    /// Same resolution as [`scan_keys`](Self::scan_keys), without
+    /// collecting the keys.
+    pub fn count_keys(
+        &self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        newer: Vec<MergeEntry<bool>>,
+    ) -> Result<u64> {
+        let mut count = 0;
+        self.visit_live_keys(start, end, newer, |_| {
+            count += 1;
+            true
+        })?;
+        Ok(count)
+    }
+
+    /// The readers whose `[min_key, max_key]` intersects a range, newest first
+    ///
+    /// Range scans iterate only these: a disjoint SSTable would otherwise
+    /// still cost a block read, since its nearest block overhangs the range.
+    fn sstables_overlapping<'a>(
+        sstables: &'a mut [SSTableReader],
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+    ) -> Vec<&'a mut SSTableReader> {
+        sstables.iter_mut().filter(|reader| reader.overlaps(start, end)).collect()
+    }
+
+    /// Feed each live key in a range to `visit`, in ascending order, until
+    /// it returns false
+    fn visit_live_keys(
+        &self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        newer: Vec<MergeEntry<bool>>,
+        mut visit: impl FnMut(Vec<u8>) -> bool,
+    ) -> Result<()> {
+        // Need write lock because SSTable iterators mutate file position
+        let mut sstables = self.sstables.write();
+
+        // Sources newest → oldest: overlay first, then SSTables in list order
+        let mut sources: Vec<MergeSource<'_, bool>> = Vec::with_capacity(sstables.len() + 1);
+        sources.push(Box::new(newer.into_iter().map(Ok)));
+        for reader in Self::sstables_overlapping(&mut sstables, start, end) {
+            sources.push(Box::new(reader.range_keys(start, end)));
+        }
+
+        for entry in MergeIterator::forward(sources, &*self.comparator)? {
+            if let (key, true) = entry? {
+                if !visit(key) {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Flush a MemTable to a new SSTable
     ///
     /// Creates a new SSTable file from the MemTable's sorted entries,
-    /// opens a reader for it, and adds it to the front of the list.
-    pub fn flush(&self, memtable: &MemTable) -> Result<SSTable> {
+    /// records it in the manifest, opens a reader for it, and adds it to
+    /// the front of the list. Entries are streamed under the MemTable's
+    /// read lock rather than copied out, so memory doesn't double for a
+    /// large MemTable; writers block until the flush is published.
+    ///
+    /// A tombstone is dropped when its key lies outside the key range of
+    /// every existing SSTable: there is nothing on disk for it to shadow.
+    /// With [`with_tombstone_grace_ms`](Self::with_tombstone_grace_ms) it
+    /// must also be past the grace period. Returns `Ok(None)` if every entry
+    /// was dropped that way, in which case no SSTable is written.
+    pub fn flush(&self, memtable: &MemTable) -> Result<Option<SSTable>> {
+        memtable.with_sorted(|entries| {
+            let entries = entries.map(|(key, found)| {
+                let meta = EntryMeta {
+                    seq: found.seq,
+                    timestamp: found.timestamp,
+                };
+                (key, &found.entry, meta)
+            });
+            self.flush_entries(entries)
+        })
+    }
+
+    /// Write sorted MemTable entries to a new SSTable
+    fn flush_entries<'a, I>(&self, entries: I) -> Result<Option<SSTable>>
+    where
+        I: ExactSizeIterator<Item = (&'a [u8], &'a MemTableEntry, EntryMeta)>,
+    {
+        let manifest = self.manifest.as_ref().ok_or_else(|| {
+            AtlasError::Storage("Cannot flush: storage is read-only".to_string())
+        })?;
+
         // Skip if MemTable is empty
-        if memtable.is_empty() {
+        if entries.len() == 0 {
             return Err(AtlasError::Storage(
                 "Cannot flush empty MemTable".to_string(),
             ));
         }
 
-        // Generate new SSTable ID (atomic, lock-free)
-        let id = self.next_sstable_id.fetch_add(1, Ordering::SeqCst);
-        let path = self.sstable_path(id);
+        let span = tracing::debug_span!("storage.flush", entries = entries.len());
+        let _enter = span.enter();
 
-        // Create builder and write entries (already sorted from BTreeMap)
-        let mut builder = SSTableBuilder::new(&path)?;
-        for (key, entry) in memtable.iter() {
-            match entry {
-                MemTableEntry::Value(v) => builder.add(&key, &v)?,
-                MemTableEntry::Tombstone => builder.add_tombstone(&key)?,
+        // Hold the manifest lock across the range check, so no SSTable that
+        // could hold a dropped tombstone's key is published in between
+        let mut manifest = manifest.lock();
+        let now = unix_millis();
+        let entries: Vec<_> = {
+            let sstables = self.sstables.read();
+            entries
+                .filter(|(key, entry, meta)| {
+                    !matches!(entry, MemTableEntry::Tombstone)
+                        || !self.tombstone_expired(*meta, now)
+                        || sstables.iter().any(|sstable| sstable.might_contain(key))
+                })
+                .collect()
+        };
+        if entries.is_empty() {
+            tracing::debug!("every entry was a tombstone with nothing to shadow");
+            return Ok(None);
+        }
+
+        let metadata = self.publish_locked(&mut manifest, |builder| {
+            // Entries are already sorted from the BTreeMap
+            for (key, entry, meta) in entries {
+                match entry {
+                    MemTableEntry::Value(v) => builder.add_entry(key, Some(v.as_slice()), meta)?,
+                    MemTableEntry::Tombstone => builder.add_entry(key, None, meta)?,
+                    MemTableEntry::Merge(_) => {
+                        // The engine resolves merges before flushing
+                        return Err(AtlasError::Storage(format!(
+                            "Unresolved merge operands for key {:?}",
+                            String::from_utf8_lossy(key)
+                        )));
+                    }
+                }
             }
+            Ok(())
+        })?;
+
+        Ok(Some(metadata))
+    }
+
+    /// Write pre-sorted key-value pairs straight into a new SSTable
+    ///
+    /// The new SSTable becomes the newest one, so it shadows older SSTables.
+    /// Keys must be strictly ascending; the first out-of-order (or duplicate)
+    /// key aborts the load with `AtlasError::Storage` and nothing is published.
+    /// Every entry is written with sequence number `seq` and the current
+    /// time as its timestamp. Returns `Ok(None)` for empty input.
+    pub fn bulk_load<I>(&self, sorted: I, seq: u64) -> Result<Option<SSTable>>
+    where
+        I: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+    {
+        let manifest = self.manifest.as_ref().ok_or_else(|| {
+            AtlasError::Storage("Cannot bulk load: storage is read-only".to_string())
+        })?;
+
+        let mut sorted = sorted.peekable();
+        if sorted.peek().is_none() {
+            return Ok(None);
         }
-        let metadata = builder.finish()?;
 
-        // Open reader for the new SSTable
-        let reader = SSTableReader::open(&path)?;
+        let meta = EntryMeta {
+            seq,
+            timestamp: unix_millis(),
+        };
 
-        // Acquire write lock and insert at front (newest first)
-        let mut sstables = self.sstables.write();
-        sstables.insert(0, reader);
+        // The builder rejects out-of-order and duplicate keys
+        let metadata = self.build_and_publish(manifest, |builder| {
+            for (key, value) in sorted {
+                builder.add_entry(&key, Some(&value), meta)?;
+            }
+            Ok(())
+        })?;
+
+        Ok(Some(metadata))
+    }
+
+    /// Defer directory fsyncs until [`end_bulk`](Self::end_bulk)
+    ///
+    /// For recovery or a bulk load writing many SSTables: each file is still
+    /// fsynced, but the directory only once at the end. Until `end_bulk`
+    /// returns, a crash may lose the directory entries of SSTables written
+    /// since, so their source (e.g. the WAL) must be kept until then.
+    pub fn begin_bulk(&self) {
+        self.bulk.store(true, Ordering::SeqCst);
+    }
+
+    /// Leave bulk mode, fsyncing the directory once if any SSTable was written
+    ///
+    /// Every SSTable flushed since `begin_bulk` is durable when this returns.
+    pub fn end_bulk(&self) -> Result<()> {
+        self.bulk.store(false, Ordering::SeqCst);
+        if self.dir_sync_pending.swap(false, Ordering::SeqCst) {
+            if let Err(e) = sync_dir(&self.data_dir) {
+                self.dir_sync_pending.store(true, Ordering::SeqCst);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Ids of the SSTables `compact` would merge, newest first
+    ///
+    /// Chosen by the compaction strategy (see `storage::select_inputs`);
+    /// empty if compacting isn't worthwhile. `TombstoneRatio` reads every
+    /// SSTable to count its tombstones.
+    pub fn select_compaction_inputs(&self) -> Result<Vec<u64>> {
+        let snapshot: Vec<(SSTableStats, PathBuf)> = self
+            .sstables
+            .read()
+            .iter()
+            .map(|reader| (Self::reader_stats(reader), reader.path().to_path_buf()))
+            .collect();
+
+        let count_tombstones =
+            matches!(self.compaction_strategy, CompactionStrategy::TombstoneRatio { .. });
+        let candidates = snapshot
+            .into_iter()
+            .map(|(stats, path)| {
+                let tombstones = if count_tombstones { self.count_tombstones(&path)? } else { 0 };
+                Ok(CompactionCandidate { stats, tombstones })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(select_inputs(self.compaction_strategy, &candidates, &*self.comparator))
+    }
+
+    /// Count the tombstones in an SSTable, through its own file handle
+    fn count_tombstones(&self, path: &Path) -> Result<u64> {
+        let mut reader = self.open_reader(path)?;
+        let mut tombstones = 0;
+        for entry in reader.iter_with_seqs()? {
+            if entry?.1.is_none() {
+                tombstones += 1;
+            }
+        }
+        Ok(tombstones)
+    }
+
+    /// Merge the SSTables picked by the compaction strategy
+    ///
+    /// The inputs are the newest SSTables (all of them by default); shadowed
+    /// values are dropped, and so are tombstones whose key no older SSTable
+    /// can hold, once past the grace period (see
+    /// [`with_tombstone_grace_ms`](Self::with_tombstone_grace_ms)). Inputs
+    /// are read through their own file handles, so lookups keep running;
+    /// flushes wait until the compaction is published.
+    ///
+    /// The output is one SSTable, or with
+    /// [`with_max_sstable_bytes`](Self::with_max_sstable_bytes) a run of them
+    /// split between keys, so their ranges are disjoint. Each takes a fresh
+    /// id, and the swap is a single manifest record, so a crash leaves either
+    /// the inputs or the outputs live, never both. Returns the outputs in key
+    /// order: none if there was nothing to compact or every entry was deleted.
+    pub fn compact(&self) -> Result<Vec<SSTable>> {
+        let manifest = self.manifest.as_ref().ok_or_else(|| {
+            AtlasError::Storage("Cannot compact: storage is read-only".to_string())
+        })?;
+
+        let span = tracing::debug_span!(
+            "storage.compact",
+            inputs = tracing::field::Empty,
+            output_entries = tracing::field::Empty,
+        );
+        let _enter = span.enter();
+        let start = Instant::now();
+
+        // Held throughout so no flush publishes a newer file mid-compaction
+        let mut manifest = manifest.lock();
+
+        // Pick the inputs, a run of the newest files, and snapshot their paths
+        let input_ids = self.select_compaction_inputs()?;
+        if input_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let input_paths: Vec<PathBuf> = self.sstables.read()[..input_ids.len()]
+            .iter()
+            .map(|reader| reader.path().to_path_buf())
+            .collect();
+        span.record("inputs", input_ids.len());
+
+        // Inputs get their own file handles, closed once the output is built
+        let output = {
+            let mut inputs = input_paths
+                .iter()
+                .map(|path| self.open_reader(path))
+                .collect::<Result<Vec<_>>>()?;
+
+            // Each value travels with its seq and timestamp, so the output keeps the winner's
+            let mut sources: Vec<MergeSource<'_, SeqValue>> = Vec::with_capacity(inputs.len());
+            for reader in inputs.iter_mut() {
+                let iter = reader.iter_with_seqs()?;
+                sources.push(Box::new(
+                    iter.map(|entry| entry.map(|(key, value, meta)| (key, (value, meta)))),
+                ));
+            }
 
+            // Tombstones go only if no older SSTable (the ones past the
+            // inputs) could hold the key, so nothing can resurface a deleted
+            // key; the manifest lock keeps that set fixed
+            let now = unix_millis();
+            let mut live = MergeIterator::forward(sources, &*self.comparator)?
+                .filter_map(|entry| match entry {
+                    Ok((key, (None, meta)))
+                        if self.tombstone_expired(meta, now)
+                            && !self.sstables.read()[input_ids.len()..]
+                                .iter()
+                                .any(|sstable| sstable.might_contain(&key)) =>
+                    {
+                        None
+                    }
+                    Ok((key, (value, meta))) => Some(Ok((key, value, meta))),
+                    Err(e) => Some(Err(e)),
+                })
+                .peekable();
+
+            // Roll over to a new output once one holds `max_sstable_bytes`
+            let cap = self.max_sstable_bytes;
+            let mut output = Vec::new();
+            while live.peek().is_some() {
+                let id = self.next_sstable_id.fetch_add(1, Ordering::SeqCst);
+                // Merged keys are strictly increasing by construction
+                let open = SSTableBuilder::new_unchecked;
+                let written = self.write_sstable(id, open, |builder| {
+                    for entry in live.by_ref() {
+                        let (key, value, meta) = entry?;
+                        builder.add_entry(&key, value.as_deref(), meta)?;
+                        if cap > 0 && builder.data_size() >= cap {
+                            break;
+                        }
+                    }
+                    Ok(())
+                });
+                match written {
+                    Ok((metadata, reader)) => output.push((id, metadata, reader)),
+                    Err(e) => {
+                        // Outputs written so far were never published
+                        for (_, metadata, _) in &output {
+                            let _ = fs::remove_file(&metadata.path);
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            output
+        };
+
+        // Publish: one manifest record, then swap the readers
+        let added: Vec<u64> = output.iter().map(|(id, _, _)| *id).collect();
+        manifest.replace(&added, &input_ids)?;
+
+        let metadata: Vec<SSTable> = {
+            let mut sstables = self.sstables.write();
+            sstables.drain(..input_ids.len());
+            // Outputs go newest first, like every other SSTable
+            let (readers, metadata): (Vec<_>, Vec<_>) = output
+                .into_iter()
+                .map(|(_, metadata, reader)| (reader, metadata))
+                .unzip();
+            sstables.splice(0..0, readers.into_iter().rev());
+            self.total_bytes.store(Self::sum_file_sizes(&sstables), Ordering::SeqCst);
+            metadata
+        };
+
+        if let Some(cache) = &self.cache {
+            cache.evict_sstables(&input_ids);
+        }
+
+        // The inputs are no longer referenced; failing to delete one only leaks space
+        for path in &input_paths {
+            if let Err(e) = fs::remove_file(path) {
+                tracing::warn!("Failed to remove compacted SSTable {}: {}", path.display(), e);
+            }
+        }
+        sync_dir(&self.data_dir)?;
+
+        span.record("output_entries", metadata.iter().map(|m| m.entry_count).sum::<u64>());
+        tracing::debug!(
+            elapsed_us = start.elapsed().as_micros() as u64,
+            "compaction finished"
+        );
         Ok(metadata)
     }
 
+    /// Rewrite one SSTable without the entries nothing can see
+    ///
+    /// Cheaper than [`compact`](Self::compact) for garbage-collecting a
+    /// single file. Dropped from the rewrite:
+    /// - entries whose key is in a newer SSTable (always shadowed)
+    /// - tombstones whose key is in no older SSTable (nothing left to hide),
+    ///   once past the grace period
+    ///
+    /// A tombstone that still shadows an older value is kept. The rewrite
+    /// takes a fresh id, which makes it the newest file on the next open;
+    /// since none of its keys appear in newer files, that reordering can't
+    /// change any read, so it goes to the front of the list right away. If
+    /// nothing survives, the file is simply removed. There is no TTL yet, so
+    /// no entry expires. Like compaction, flushes wait until it finishes.
+    pub fn rewrite_sstable(&self, id: u64) -> Result<()> {
+        let manifest = self.manifest.as_ref().ok_or_else(|| {
+            AtlasError::Storage("Cannot rewrite: storage is read-only".to_string())
+        })?;
+
+        let span = tracing::debug_span!(
+            "storage.rewrite",
+            id,
+            kept = tracing::field::Empty,
+            dropped = tracing::field::Empty,
+        );
+        let _enter = span.enter();
+
+        // Held throughout so the set of newer files can't change under us
+        let mut manifest = manifest.lock();
+
+        // Snapshot every live file, newest first, and find the target
+        let paths: Vec<PathBuf> = self
+            .sstables
+            .read()
+            .iter()
+            .map(|reader| reader.path().to_path_buf())
+            .collect();
+        let position = paths
+            .iter()
+            .position(|path| Self::parse_sstable_id(path) == Some(id))
+            .ok_or_else(|| AtlasError::Storage(format!("SSTable {} is not live", id)))?;
+
+        // Own file handles, so lookups keep running meanwhile
+        let output = {
+            let mut readers = paths
+                .iter()
+                .map(|path| self.open_reader(path))
+                .collect::<Result<Vec<_>>>()?;
+            let (newer, rest) = readers.split_at_mut(position);
+            let (target, older) = rest.split_first_mut().expect("position is in range");
+
+            let total = target.entry_count();
+            let now = unix_millis();
+            let mut kept = target
+                .iter_with_seqs()?
+                .filter_map(|entry| {
+                    entry
+                        .and_then(|entry| {
+                            let expired_tombstone =
+                                entry.1.is_none() && self.tombstone_expired(entry.2, now);
+                            let visible =
+                                Self::still_visible(newer, older, &entry.0, expired_tombstone)?;
+                            Ok(visible.then_some(entry))
+                        })
+                        .transpose()
+                })
+                .peekable();
+
+            let output = if kept.peek().is_some() {
+                let new_id = self.next_sstable_id.fetch_add(1, Ordering::SeqCst);
+                // Filtered from one SSTable, so keys are still strictly increasing
+                let open = SSTableBuilder::new_unchecked;
+                let (metadata, reader) = self.write_sstable(new_id, open, |builder| {
+                    for entry in kept {
+                        let (key, value, meta) = entry?;
+                        builder.add_entry(&key, value.as_deref(), meta)?;
+                    }
+                    Ok(())
+                })?;
+                Some((new_id, metadata, reader))
+            } else {
+                None
+            };
+
+            let kept = output.as_ref().map_or(0, |(_, metadata, _)| metadata.entry_count);
+            span.record("kept", kept);
+            span.record("dropped", total - kept);
+            output
+        };
+
+        // Publish: one manifest record, then swap the reader
+        let added: Vec<u64> = output.iter().map(|(new_id, _, _)| *new_id).collect();
+        manifest.replace(&added, &[id])?;
+
+        {
+            let mut sstables = self.sstables.write();
+            let old = sstables.remove(position);
+            self.total_bytes.fetch_sub(old.file_size(), Ordering::SeqCst);
+            if let Some((_, _, reader)) = output {
+                self.total_bytes.fetch_add(reader.file_size(), Ordering::SeqCst);
+                sstables.insert(0, reader);
+            }
+        }
+
+        if let Some(cache) = &self.cache {
+            cache.evict_sstables(&[id]);
+        }
+
+        // No longer referenced; failing to delete it only leaks space
+        let old_path = &paths[position];
+        if let Err(e) = fs::remove_file(old_path) {
+            tracing::warn!("Failed to remove rewritten SSTable {}: {}", old_path.display(), e);
+        }
+        sync_dir(&self.data_dir)?;
+
+        Ok(())
+    }
+
+    /// Whether an entry of the file being rewritten can still affect reads
+    ///
+    /// Not if a newer file shadows it; a tombstone (pass `false` for one
+    /// still in its grace period) also only matters while an older file
+    /// holds the key.
+    fn still_visible(
+        newer: &mut [SSTableReader],
+        older: &mut [SSTableReader],
+        key: &[u8],
+        tombstone: bool,
+    ) -> Result<bool> {
+        if Self::contains_key(newer, key)? {
+            return Ok(false);
+        }
+        if tombstone {
+            return Self::contains_key(older, key);
+        }
+        Ok(true)
+    }
+
+    /// Whether a tombstone written with `meta` is past the grace period at `now`
+    fn tombstone_expired(&self, meta: EntryMeta, now: u64) -> bool {
+        now.saturating_sub(meta.timestamp) >= self.tombstone_grace_ms
+    }
+
+    /// Check whether any of `readers` holds an entry (value or tombstone) for `key`
+    fn contains_key(readers: &mut [SSTableReader], key: &[u8]) -> Result<bool> {
+        for reader in readers {
+            if reader.get_with_seq(key)?.is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Delete every SSTable and restart ids at 1
+    ///
+    /// The manifest is emptied first, so a crash part-way leaves the
+    /// remaining files as orphans that the next open ignores (and skips
+    /// when allocating ids). Orphans already on disk are deleted too.
+    pub fn clear(&self) -> Result<()> {
+        let manifest = self.manifest.as_ref().ok_or_else(|| {
+            AtlasError::Storage("Cannot clear: storage is read-only".to_string())
+        })?;
+
+        // Held throughout so no flush or compaction publishes mid-clear
+        let mut manifest = manifest.lock();
+        manifest.reset()?;
+
+        {
+            let mut sstables = self.sstables.write();
+            sstables.clear();
+            self.total_bytes.store(0, Ordering::SeqCst);
+        }
+
+        // Ids are about to be reused, so cached entries would be wrong, not just stale
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+
+        for entry in fs::read_dir(&self.data_dir)? {
+            let path = entry?.path();
+            if path.is_file() && Self::parse_sstable_id(&path).is_some() {
+                fs::remove_file(&path)?;
+            }
+        }
+        sync_dir(&self.data_dir)?;
+
+        self.next_sstable_id.store(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Get per-SSTable statistics, newest first
+    ///
+    /// Only takes the read lock, so it never blocks lookups or other stats calls.
+    pub fn sstable_stats(&self) -> Vec<SSTableStats> {
+        let sstables = self.sstables.read();
+
+        sstables.iter().map(Self::reader_stats).collect()
+    }
+
+    /// Statistics for one open SSTable
+    fn reader_stats(reader: &SSTableReader) -> SSTableStats {
+        SSTableStats {
+            id: Self::parse_sstable_id(reader.path()).unwrap_or(0),
+            entry_count: reader.entry_count(),
+            file_size: reader.file_size(),
+            min_key: reader.min_key().map(|k| k.to_vec()),
+            max_key: reader.max_key().map(|k| k.to_vec()),
+        }
+    }
+
+    /// Get value cache statistics (`None` when the cache is disabled)
+    pub fn block_cache_stats(&self) -> Option<BlockCacheStats> {
+        self.cache.as_ref().map(BlockCache::stats)
+    }
+
+    /// Total size of the live SSTable files in bytes
+    ///
+    /// Maintained as files are published and removed, so it costs no I/O.
+    pub fn total_size_bytes(&self) -> u64 {
+        self.total_bytes.load(Ordering::SeqCst)
+    }
+
+    /// Sum of the live SSTables' entry counts, tombstones included
+    ///
+    /// Read from each reader's footer, so O(SSTables) with no I/O. A key
+    /// written to several SSTables is counted once per file.
+    pub fn total_sstable_entries(&self) -> u64 {
+        self.sstables.read().iter().map(SSTableReader::entry_count).sum()
+    }
+
     /// Get the number of SSTables
     pub fn sstable_count(&self) -> usize {
         self.sstables.read().len()
@@ -174,6 +1275,179 @@ impl StorageManager {
     // Private Helpers
     // =========================================================================
 
+    /// Build a new SSTable via `write`, then publish it as the newest SSTable
+    ///
+    /// The manifest lock is held from id allocation to publication, so ids
+    /// are published in order and never interleave with a compaction.
+    fn build_and_publish<F>(&self, manifest: &Mutex<Manifest>, write: F) -> Result<SSTable>
+    where
+        F: FnOnce(&mut SSTableBuilder) -> Result<()>,
+    {
+        self.publish_locked(&mut manifest.lock(), write)
+    }
+
+    /// [`build_and_publish`](Self::build_and_publish) with the manifest lock
+    /// already held
+    fn publish_locked<F>(&self, manifest: &mut Manifest, write: F) -> Result<SSTable>
+    where
+        F: FnOnce(&mut SSTableBuilder) -> Result<()>,
+    {
+        // Generate new SSTable ID (atomic, lock-free)
+        let id = self.next_sstable_id.fetch_add(1, Ordering::SeqCst);
+        let (metadata, reader) = self.write_sstable(id, SSTableBuilder::new, write)?;
+
+        // Publish to manifest only now that the file is fully synced
+        manifest.add(id)?;
+
+        // Acquire write lock and insert at front (newest first)
+        let mut sstables = self.sstables.write();
+        self.total_bytes.fetch_add(reader.file_size(), Ordering::SeqCst);
+        sstables.insert(0, reader);
+
+        Ok(metadata)
+    }
+
+    /// Build SSTable `id` via `write` on a builder from `open`, and open a
+    /// reader for it
+    ///
+    /// The file is built under a temp name, fsynced, and renamed into place;
+    /// the caller records it in the manifest. If `write` fails, or adds no
+    /// entries, the temp file is removed.
+    fn write_sstable<F>(
+        &self,
+        id: u64,
+        open: fn(&Path) -> Result<SSTableBuilder>,
+        write: F,
+    ) -> Result<(SSTable, SSTableReader)>
+    where
+        F: FnOnce(&mut SSTableBuilder) -> Result<()>,
+    {
+        let span = tracing::debug_span!(
+            "sstable.write",
+            id,
+            entries = tracing::field::Empty,
+            file_size = tracing::field::Empty,
+        );
+        let _enter = span.enter();
+        let start = Instant::now();
+
+        let path = self.sstable_path(id);
+
+        let tmp_path = Self::temp_path(&path);
+
+        // Written under a temp name so a crash never leaves a partial .sst
+        let mut builder = open(&tmp_path)?
+            .with_block_size(self.block_size)
+            .with_value_checksums(self.value_checksums)
+            .with_comparator(self.comparator.clone())
+            .with_buffer_size(self.write_buffer_bytes)?;
+        let written = write(&mut builder).and_then(|()| {
+            if builder.entry_count() == 0 {
+                return Err(AtlasError::Storage("Refusing to write an empty SSTable".to_string()));
+            }
+            Ok(())
+        });
+        if let Err(e) = written {
+            drop(builder);
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+        let mut metadata = builder.finish()?; // fsyncs the file
+
+        // Atomically publish under the final name
+        fs::rename(&tmp_path, &path)?;
+        metadata.path = path.clone();
+
+        // The file's contents are synced, but its directory entry is not: after
+        // a crash the rename may be lost even though the data made it. Sync the
+        // directory before the manifest (and then WAL truncation) relies on it,
+        // unless bulk mode leaves that to `end_bulk`.
+        if self.bulk.load(Ordering::SeqCst) {
+            self.dir_sync_pending.store(true, Ordering::SeqCst);
+        } else {
+            sync_dir(&self.data_dir)?;
+        }
+
+        // Open reader for the new SSTable
+        let reader = self.open_reader(&path)?;
+
+        span.record("entries", metadata.entry_count);
+        span.record("file_size", metadata.file_size);
+        tracing::debug!(
+            elapsed_us = start.elapsed().as_micros() as u64,
+            "sstable written"
+        );
+        Ok((metadata, reader))
+    }
+
+    /// Open a reader with this manager's read buffer size and comparator
+    fn open_reader(&self, path: &Path) -> Result<SSTableReader> {
+        Ok(SSTableReader::open(path)?
+            .with_buffer_size(self.read_buffer_bytes)
+            .with_comparator(self.comparator.clone()))
+    }
+
+    /// Next SSTable id: past everything in the manifest and on disk
+    fn next_id(manifest_next_id: u64, disk_ids: &[u64]) -> u64 {
+        disk_ids
+            .iter()
+            .map(|&id| id + 1)
+            .fold(manifest_next_id, u64::max)
+    }
+
+    /// Open readers for the given SSTable ids, in the order given
+    ///
+    /// Truncated files are skipped with a warning rather than refusing to start.
+    /// Files with no entries get no reader; their ids are returned alongside.
+    ///
+    /// Each open parses a whole index, so the ids are split into one
+    /// contiguous chunk per core and opened on scoped threads. Chunks are
+    /// joined back in order, keeping the result in `ids` order.
+    fn open_readers(dir: &Path, ids: &[u64]) -> Result<(Vec<SSTableReader>, Vec<u64>)> {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let chunk_size = ids.len().div_ceil(threads).max(1);
+        let open_chunk = |chunk: &[u64]| -> Vec<(u64, Result<SSTableReader>)> {
+            chunk
+                .iter()
+                .map(|&id| (id, SSTableReader::open(&Self::sstable_path_with_dir(dir, id))))
+                .collect()
+        };
+
+        let opened: Vec<(u64, Result<SSTableReader>)> = if ids.len() <= chunk_size {
+            open_chunk(ids)
+        } else {
+            thread::scope(|scope| {
+                let handles: Vec<_> = ids
+                    .chunks(chunk_size)
+                    .map(|chunk| scope.spawn(move || open_chunk(chunk)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .flat_map(|handle| handle.join().expect("SSTable open thread panicked"))
+                    .collect()
+            })
+        };
+
+        let mut sstables = Vec::new();
+        let mut empty_ids = Vec::new();
+        for (id, result) in opened {
+            match result {
+                Ok(reader) if reader.entry_count() == 0 => empty_ids.push(id),
+                Ok(reader) => sstables.push(reader),
+                Err(AtlasError::SSTableTruncated(msg)) => {
+                    tracing::warn!("Skipping truncated SSTable: {}", msg);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok((sstables, empty_ids))
+    }
+
+    /// Sum of the given readers' file sizes
+    fn sum_file_sizes(readers: &[SSTableReader]) -> u64 {
+        readers.iter().map(SSTableReader::file_size).sum()
+    }
+
     /// Generate the file path for an SSTable with given ID
     fn sstable_path(&self, id: u64) -> PathBuf {
         Self::sstable_path_with_dir(&self.data_dir, id)
@@ -184,17 +1458,46 @@ impl StorageManager {
         dir.join(format!("sstable_{:06}.sst", id))
     }
 
+    /// Temp path an SSTable is built under before being renamed into place
+    fn temp_path(path: &Path) -> PathBuf {
+        path.with_extension("sst.tmp")
+    }
+
+    /// Check if a file is a leftover temp SSTable ("sstable_000042.sst.tmp")
+    fn is_temp_sstable(path: &Path) -> bool {
+        path.file_name()
+            .map(|name| {
+                let name = name.to_string_lossy();
+                name.starts_with("sstable_") && name.ends_with(".sst.tmp")
+            })
+            .unwrap_or(false)
+    }
+
     /// Parse SSTable ID from filename
     /// "sstable_000042.sst" → Some(42)
-    fn parse_sstable_id(path: &Path) -> Option<u64> {
+    pub(crate) fn parse_sstable_id(path: &Path) -> Option<u64> {
         let name = path.file_stem()?.to_string_lossy();
         let id_str = name.strip_prefix("sstable_")?;
         id_str.parse().ok()
     }
+}
 
-    /// Compact SSTables (future - merges multiple SSTables)
-    #[allow(dead_code)]
-    fn compact(&self) -> Result<()> {
-        todo!("Implement compaction in V2")
-    }
+/// Fsync a directory so entries created or renamed in it are durable
+///
+/// `File::sync_all` on a new file does not persist its name; on ext4/xfs
+/// the directory itself must be synced. A no-op on platforms that can't
+/// open directories as files (Windows).
+pub fn sync_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Current time in Unix millis, as stored in `EntryMeta::timestamp`
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
 }
@@ -0,0 +1,269 @@
+//! Manifest
+//!
+//! Authoritative record of which SSTables are live.
+//!
+//! ## Why
+//! Scanning the directory for `sstable_*.sst` files can pick up a half-written
+//! file left behind by a crashed flush. The manifest is only appended to after
+//! an SSTable is fully synced, so anything not listed is an orphan.
+//!
+//! ## File Format
+//! Append-only text, one record per line:
+//! ```text
+//! add 1
+//! add 2
+//! replace 3 1,2
+//! ```
+//! `replace <added> <removed>` swaps comma-separated id lists in one
+//! record, so a compaction is published atomically. A trailing line without
+//! `\n` is a torn append from a crash; it is discarded (and truncated away)
+//! on open.
+//!
+//! ## SSTable Ids
+//! Every id ever recorded counts towards the next id, even once removed.
+//! Ids are therefore never reused, however many files compaction deletes.
+
+use std::collections::BTreeSet;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::AtlasError;
+
+/// Manifest filename inside the storage directory
+pub(crate) const MANIFEST_FILENAME: &str = "MANIFEST";
+
+/// Tracks the set of live SSTable ids on disk
+pub struct Manifest {
+    /// Path to the manifest file
+    path: PathBuf,
+
+    /// Append handle for new records
+    file: File,
+
+    /// Live SSTable ids (sorted ascending)
+    live_ids: BTreeSet<u64>,
+
+    /// One past the highest id ever recorded
+    next_id: u64,
+}
+
+/// State replayed from the manifest records
+struct Records {
+    /// Live SSTable ids (sorted ascending)
+    live_ids: BTreeSet<u64>,
+
+    /// One past the highest id ever recorded (1 if none)
+    next_id: u64,
+
+    /// Length of the complete-record prefix
+    complete_len: usize,
+}
+
+impl Manifest {
+    /// Check whether a manifest exists in the given directory
+    pub fn exists(dir: &Path) -> bool {
+        dir.join(MANIFEST_FILENAME).is_file()
+    }
+
+    /// Open the manifest in `dir`, creating an empty one if missing
+    ///
+    /// Replays all complete records and truncates a torn trailing record.
+    pub fn open(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILENAME);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+
+        let records = Self::parse_records(&contents)?;
+
+        if records.complete_len < contents.len() {
+            tracing::warn!(
+                "Discarding {} bytes of torn manifest record",
+                contents.len() - records.complete_len
+            );
+            file.set_len(records.complete_len as u64)?;
+            file.sync_all()?;
+        }
+
+        Ok(Self {
+            path,
+            file,
+            live_ids: records.live_ids,
+            next_id: records.next_id,
+        })
+    }
+
+    /// Read the live SSTable ids without modifying anything on disk
+    ///
+    /// Returns `None` if no manifest exists. A torn trailing record is
+    /// ignored but left in place.
+    pub fn read_live_ids(dir: &Path) -> Result<Option<BTreeSet<u64>>> {
+        Ok(Self::read_state(dir)?.map(|(live_ids, _)| live_ids))
+    }
+
+    /// Read the live SSTable ids and next id without modifying anything on disk
+    ///
+    /// Returns `None` if no manifest exists.
+    pub fn read_state(dir: &Path) -> Result<Option<(BTreeSet<u64>, u64)>> {
+        if !Self::exists(dir) {
+            return Ok(None);
+        }
+
+        let contents = std::fs::read(dir.join(MANIFEST_FILENAME))?;
+        let records = Self::parse_records(&contents)?;
+        Ok(Some((records.live_ids, records.next_id)))
+    }
+
+    /// Record a new live SSTable id (fsyncs before returning)
+    ///
+    /// Must only be called once the SSTable file itself is fully synced.
+    pub fn add(&mut self, id: u64) -> Result<()> {
+        self.append(&format!("add {}\n", id))?;
+        self.live_ids.insert(id);
+        self.next_id = self.next_id.max(id + 1);
+        Ok(())
+    }
+
+    /// Atomically add `added` and remove `removed` (fsyncs before returning)
+    ///
+    /// Must only be called once every added SSTable is fully synced; removed
+    /// files may be deleted once this returns.
+    pub fn replace(&mut self, added: &[u64], removed: &[u64]) -> Result<()> {
+        self.append(&format!(
+            "replace {} {}\n",
+            Self::format_ids(added),
+            Self::format_ids(removed)
+        ))?;
+        Self::apply_replace(&mut self.live_ids, added, removed);
+        if let Some(&max) = added.iter().chain(removed).max() {
+            self.next_id = self.next_id.max(max + 1);
+        }
+        Ok(())
+    }
+
+    /// Drop every record: no SSTable is live and ids restart at 1 (fsyncs)
+    ///
+    /// Truncating to empty is a single metadata update, so a crash leaves
+    /// either the old records or none. Unlisted SSTable files become orphans
+    /// and may be deleted once this returns.
+    pub fn reset(&mut self) -> Result<()> {
+        self.file.set_len(0)?;
+        self.file.sync_all()?;
+        self.live_ids.clear();
+        self.next_id = 1;
+        Ok(())
+    }
+
+    /// Check whether an SSTable id is listed as live
+    pub fn contains(&self, id: u64) -> bool {
+        self.live_ids.contains(&id)
+    }
+
+    /// Get the live SSTable ids (sorted ascending)
+    pub fn live_ids(&self) -> &BTreeSet<u64> {
+        &self.live_ids
+    }
+
+    /// Get the next SSTable id (one past the highest id ever recorded)
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    /// Get the manifest file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one record and fsync it
+    fn append(&mut self, record: &str) -> Result<()> {
+        self.file.write_all(record.as_bytes())?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Apply a replace record to a live set
+    fn apply_replace(live_ids: &mut BTreeSet<u64>, added: &[u64], removed: &[u64]) {
+        for id in removed {
+            live_ids.remove(id);
+        }
+        live_ids.extend(added);
+    }
+
+    /// Format an id list as "1,2,3" ("-" when empty)
+    fn format_ids(ids: &[u64]) -> String {
+        if ids.is_empty() {
+            return "-".to_string();
+        }
+        ids.iter().map(u64::to_string).collect::<Vec<_>>().join(",")
+    }
+
+    /// Parse all complete records
+    ///
+    /// Everything after the last newline is a torn append and is excluded
+    /// from `complete_len`.
+    fn parse_records(contents: &[u8]) -> Result<Records> {
+        let complete_len = contents
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map(|pos| pos + 1)
+            .unwrap_or(0);
+
+        let mut live_ids = BTreeSet::new();
+        let mut next_id = 1;
+        for line in contents[..complete_len].split(|&b| b == b'\n') {
+            if line.is_empty() {
+                continue;
+            }
+            let (added, removed) = Self::parse_record(line)?;
+            if let Some(&max) = added.iter().chain(&removed).max() {
+                next_id = next_id.max(max + 1);
+            }
+            Self::apply_replace(&mut live_ids, &added, &removed);
+        }
+
+        Ok(Records {
+            live_ids,
+            next_id,
+            complete_len,
+        })
+    }
+
+    /// Parse a single record: "add <id>" or "replace <ids> <ids>"
+    ///
+    /// Returns the (added, removed) ids.
+    fn parse_record(line: &[u8]) -> Result<(Vec<u64>, Vec<u64>)> {
+        let text = std::str::from_utf8(line).map_err(|_| {
+            AtlasError::Storage("Corrupt manifest record: invalid UTF-8".to_string())
+        })?;
+
+        let parsed = if let Some(id) = text.strip_prefix("add ") {
+            id.parse().ok().map(|id| (vec![id], Vec::new()))
+        } else if let Some(lists) = text.strip_prefix("replace ") {
+            lists.split_once(' ').and_then(|(added, removed)| {
+                Some((Self::parse_ids(added)?, Self::parse_ids(removed)?))
+            })
+        } else {
+            None
+        };
+
+        parsed.ok_or_else(|| {
+            AtlasError::Storage(format!("Corrupt manifest record: {:?}", text))
+        })
+    }
+
+    /// Parse an id list written by `format_ids`
+    fn parse_ids(text: &str) -> Option<Vec<u64>> {
+        if text == "-" {
+            return Some(Vec::new());
+        }
+        text.split(',').map(|id| id.parse().ok()).collect()
+    }
+}
@@ -0,0 +1,209 @@
+//! Merge Iterator
+//!
+//! K-way merge of the MemTable and SSTables for scans and compaction.
+//!
+//! Every source yields entries in the same key order (ascending or
+//! descending). A heap holds the current head of every source; popping it
+//! gives the next key across all sources, and ties go to the newest source
+//! so newest-wins resolution matches point lookups.
+//!
+//! With no sources, or only empty ones (a brand-new database), the heap
+//! starts out empty and the merge is exhausted from the first `next`.
+//!
+//! The value payload is generic: scans merge plain values, while compaction
+//! carries each entry's sequence number along with it.
+//!
+//! A merge may be given a deadline. It checks the clock every
+//! `DEADLINE_CHECK_INTERVAL` entries, and once the deadline has passed it
+//! ends early, as if exhausted; `deadline_exceeded` tells the two apart.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+
+use crate::comparator::Comparator;
+use crate::error::Result;
+
+/// (key, value) — a `None` value is a tombstone
+pub type MergeEntry<V = Option<Vec<u8>>> = (Vec<u8>, V);
+
+/// A boxed source of entries for the merge
+pub type MergeSource<'a, V = Option<Vec<u8>>> =
+    Box<dyn Iterator<Item = Result<MergeEntry<V>>> + 'a>;
+
+/// Entries merged between deadline checks (reading the clock isn't free)
+const DEADLINE_CHECK_INTERVAL: u64 = 64;
+
+/// Merges sorted sources into one sorted stream, one entry per key
+///
+/// Sources must be given newest first. Tombstones are yielded (as `None`)
+/// rather than dropped, so a deleted key still hides older versions.
+pub struct MergeIterator<'a, V = Option<Vec<u8>>> {
+    /// Sources, newest first
+    sources: Vec<MergeSource<'a, V>>,
+
+    /// Current head of each non-exhausted source
+    heap: BinaryHeap<HeapEntry<'a, V>>,
+
+    /// Sources yield ascending keys (otherwise descending)
+    ascending: bool,
+
+    /// Order the sources are sorted in
+    comparator: &'a dyn Comparator,
+
+    /// Stop early once this passes (see `with_deadline`)
+    deadline: Option<Instant>,
+
+    /// Keys merged so far
+    merged: u64,
+
+    /// The merge ended early because the deadline passed
+    deadline_exceeded: bool,
+}
+
+/// Heap slot: a source's current entry
+struct HeapEntry<'a, V> {
+    key: Vec<u8>,
+    value: V,
+    /// Index into `sources` (lower = newer)
+    source: usize,
+    /// Smaller keys pop first (otherwise larger keys do)
+    ascending: bool,
+    comparator: &'a dyn Comparator,
+}
+
+impl<V> PartialEq for HeapEntry<'_, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<V> Eq for HeapEntry<'_, V> {}
+
+impl<V> PartialOrd for HeapEntry<'_, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<V> Ord for HeapEntry<'_, V> {
+    /// Next key in merge order first; for equal keys, newer source (lower index) first
+    fn cmp(&self, other: &Self) -> Ordering {
+        let by_key = if self.ascending {
+            self.comparator.compare(&other.key, &self.key)
+        } else {
+            self.comparator.compare(&self.key, &other.key)
+        };
+        by_key.then_with(|| other.source.cmp(&self.source))
+    }
+}
+
+impl<'a, V> MergeIterator<'a, V> {
+    /// Create a merge over `sources` (newest first), ascending in `comparator`'s order
+    pub fn forward(
+        sources: Vec<MergeSource<'a, V>>,
+        comparator: &'a dyn Comparator,
+    ) -> Result<Self> {
+        Self::new(sources, comparator, true)
+    }
+
+    /// Create a merge over `sources` (newest first), descending in `comparator`'s order
+    pub fn reverse(
+        sources: Vec<MergeSource<'a, V>>,
+        comparator: &'a dyn Comparator,
+    ) -> Result<Self> {
+        Self::new(sources, comparator, false)
+    }
+
+    fn new(
+        sources: Vec<MergeSource<'a, V>>,
+        comparator: &'a dyn Comparator,
+        ascending: bool,
+    ) -> Result<Self> {
+        let mut merge = Self {
+            heap: BinaryHeap::with_capacity(sources.len()),
+            sources,
+            ascending,
+            comparator,
+            deadline: None,
+            merged: 0,
+            deadline_exceeded: false,
+        };
+        for source in 0..merge.sources.len() {
+            merge.advance(source)?;
+        }
+        Ok(merge)
+    }
+
+    /// End the merge early once `deadline` passes
+    ///
+    /// Checked before the first entry and then every
+    /// `DEADLINE_CHECK_INTERVAL` entries, so the merge may run a little past it.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// The merge ended early because its deadline passed; everything it
+    /// yielded is still correct, but keys after the last one were skipped
+    pub fn deadline_exceeded(&self) -> bool {
+        self.deadline_exceeded
+    }
+
+    /// Pull the next entry from a source onto the heap
+    fn advance(&mut self, source: usize) -> Result<()> {
+        if let Some(next) = self.sources[source].next() {
+            let (key, value) = next?;
+            self.heap.push(HeapEntry {
+                key,
+                value,
+                source,
+                ascending: self.ascending,
+                comparator: self.comparator,
+            });
+        }
+        Ok(())
+    }
+
+    /// Pop the winning entry for the next remaining key
+    fn next_entry(&mut self) -> Result<Option<MergeEntry<V>>> {
+        if self.deadline_exceeded {
+            return Ok(None);
+        }
+        if let Some(deadline) = self.deadline {
+            let due = self.merged.is_multiple_of(DEADLINE_CHECK_INTERVAL);
+            if due && Instant::now() >= deadline {
+                self.deadline_exceeded = !self.heap.is_empty();
+                return Ok(None);
+            }
+        }
+
+        let top = match self.heap.pop() {
+            Some(top) => top,
+            None => return Ok(None),
+        };
+        self.advance(top.source)?;
+        self.merged += 1;
+
+        // Older versions of the same key are shadowed
+        let comparator = self.comparator;
+        while self
+            .heap
+            .peek()
+            .is_some_and(|entry| comparator.compare(&entry.key, &top.key).is_eq())
+        {
+            let shadowed = self.heap.pop().unwrap();
+            self.advance(shadowed.source)?;
+        }
+
+        Ok(Some((top.key, top.value)))
+    }
+}
+
+impl<'a, V> Iterator for MergeIterator<'a, V> {
+    type Item = Result<MergeEntry<V>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().transpose()
+    }
+}
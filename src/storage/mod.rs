@@ -5,38 +5,34 @@
 //! ## Responsibilities
 //! - Persist data to disk in sorted format
 //! - Efficient range scans and point lookups
-//! - Background compaction (future)
+//! - Compaction of all or the newest overlapping SSTables (manual;
+//!   background scheduling is future work)
 //! - Bloom filters for negative lookups (future)
+//! - LRU cache of hot SSTable values (optional)
 //!
-//! ## File Format (V1 - Simple)
-//! ```text
-//! ┌────────────────────────────────────────┐
-//! │ Header                                 │
-//! │ ┌──────────┬──────────┬──────────────┐ │
-//! │ │Magic (4) │Version(2)│ Entry Count  │ │
-//! │ └──────────┴──────────┴──────────────┘ │
-//! ├────────────────────────────────────────┤
-//! │ Data Block                             │
-//! │ ┌────────┬────────┬─────┬───────────┐ │
-//! │ │KeyLen  │ValLen  │ Key │   Value   │ │
-//! │ └────────┴────────┴─────┴───────────┘ │
-//! │ ... (repeated for each entry)         │
-//! ├────────────────────────────────────────┤
-//! │ Index Block                            │
-//! │ ┌────────┬────────┬─────────────────┐ │
-//! │ │KeyLen  │ Offset │      Key        │ │
-//! │ └────────┴────────┴─────────────────┘ │
-//! │ ... (repeated for each entry)         │
-//! ├────────────────────────────────────────┤
-//! │ Footer                                 │
-//! │ ┌──────────────────┬─────────────────┐ │
-//! │ │ Index Offset     │    CRC32        │ │
-//! │ └──────────────────┴─────────────────┘ │
-//! └────────────────────────────────────────┘
-//! ```
+//! ## File Format
+//! Each SSTable is a header, data blocks of prefix-compressed entries, an
+//! index with one entry per block, and a footer. The byte-level layout and
+//! the rules for changing it are documented in `sstable/mod.rs`.
 
 mod sstable;
 mod manager;
+mod manifest;
+mod merge_iter;
+mod cache;
+mod compaction;
 
-pub use sstable::{SSTable, SSTableBuilder, SSTableReader, SSTableIterator};
-pub use manager::StorageManager;
+pub use sstable::{
+    EntryMeta, SSTable, SSTableBuilder, SSTableIterator,
+    SSTableKeyIterator, SSTableRangeIterator, SSTableReader, SSTableRevIterator, SeqEntry,
+    DEFAULT_BLOCK_SIZE, MAX_VALUE_SIZE,
+};
+pub(crate) use sstable::VERSION as SSTABLE_FORMAT_VERSION;
+#[cfg(any(test, feature = "test-hooks"))]
+#[doc(hidden)]
+pub use sstable::positioned_read_bytes;
+pub use manager::{sync_dir, SSTableStats, StorageManager};
+pub use manifest::Manifest;
+pub use merge_iter::{MergeEntry, MergeIterator, MergeSource};
+pub use cache::{BlockCache, BlockCacheStats};
+pub use compaction::{select_inputs, CompactionCandidate};
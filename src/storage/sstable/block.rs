@@ -0,0 +1,490 @@
+//! SSTable Data Blocks
+//!
+//! Entries are grouped into blocks of roughly `Config::sstable_block_size`
+//! bytes, and the index holds one handle per block instead of one per key.
+//!
+//! ## Block Format
+//! ```text
+//! [KeyLen: u32][ValLen: u32][Seq: u64][Timestamp: u64][Key][Value]   ... per entry
+//! [Restart: u32]                                                      ... per restart point
+//! [RestartCount: u32]
+//! ```
+//! Version 3 blocks have no `Timestamp`; their entries read back with 0.
+//! In files flagged `FLAG_VALUE_CRCS` (version 6+), each value is followed
+//! by `[ValueCRC: u32]`, the CRC32 of its bytes; tombstones have none.
+//! A restart point is the in-block offset of every `RESTART_INTERVAL`-th
+//! entry. Lookups binary-search the restart points, then scan at most one
+//! interval.
+
+use std::cmp::Ordering;
+use std::fs::File;
+
+use crate::comparator::Comparator;
+use crate::error::Result;
+use crate::AtlasError;
+
+use super::{read_exact_at, TOMBSTONE_MARKER};
+
+/// Entries between restart points
+const RESTART_INTERVAL: usize = 16;
+
+/// KeyLen (4) + ValLen (4) + Seq (8) + Timestamp (8)
+const ENTRY_HEADER_SIZE: usize = 24;
+
+/// Entry header size in version 3 blocks, which have no timestamp
+const V3_ENTRY_HEADER_SIZE: usize = 16;
+
+/// Size of the CRC32 after each value, when a file has them
+const VALUE_CRC_SIZE: usize = 4;
+
+/// How entries are laid out in a file's blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct EntryFormat {
+    /// Entries carry a write timestamp (version 4+)
+    pub(super) timestamps: bool,
+    /// Values are followed by their CRC32 (version 6+ files flagged `FLAG_VALUE_CRCS`)
+    pub(super) value_crcs: bool,
+}
+
+impl EntryFormat {
+    /// Per-entry header size (smaller in version 3 blocks)
+    fn header_size(self) -> usize {
+        if self.timestamps { ENTRY_HEADER_SIZE } else { V3_ENTRY_HEADER_SIZE }
+    }
+
+    /// Bytes after an entry's key: its value and the value's CRC, if any
+    fn value_span(self, val_len: Option<usize>) -> usize {
+        match val_len {
+            Some(len) if self.value_crcs => len + VALUE_CRC_SIZE,
+            Some(len) => len,
+            None => 0,
+        }
+    }
+}
+
+/// Which write produced an entry, and when
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntryMeta {
+    /// WAL LSN of the write (0 = unsequenced)
+    pub seq: u64,
+    /// Unix millis of the write (0 = unknown, e.g. from a pre-version-4 file)
+    pub timestamp: u64,
+}
+
+impl EntryMeta {
+    /// Metadata with a seq and no known timestamp
+    pub fn at_seq(seq: u64) -> Self {
+        Self { seq, timestamp: 0 }
+    }
+}
+
+/// One block entry: (key, value, meta) — a `None` value is a tombstone
+pub type SeqEntry = (Vec<u8>, Option<Vec<u8>>, EntryMeta);
+
+/// A `SeqEntry` borrowed from the block's buffer
+type EntryRef<'a> = (&'a [u8], Option<&'a [u8]>, EntryMeta);
+
+/// Where a key's value bytes sit in the file: `Some((offset, len))`, or
+/// `None` for a tombstone
+pub(super) type ValueLocation = Option<(u64, u32)>;
+
+/// Accumulates entries for the block being written
+#[derive(Default)]
+pub(super) struct BlockBuilder {
+    /// Encoded entries so far
+    buf: Vec<u8>,
+    /// In-block offsets of restart points
+    restarts: Vec<u32>,
+    /// Entries added so far
+    count: usize,
+    /// Follow each value with its CRC32
+    value_crcs: bool,
+}
+
+impl BlockBuilder {
+    /// A builder that follows each value with its CRC32 if `value_crcs`
+    pub(super) fn new(value_crcs: bool) -> Self {
+        Self { value_crcs, ..Self::default() }
+    }
+
+    /// Append an entry (callers keep keys in order)
+    pub(super) fn add(&mut self, key: &[u8], value: Option<&[u8]>, meta: EntryMeta) {
+        if self.count.is_multiple_of(RESTART_INTERVAL) {
+            self.restarts.push(self.buf.len() as u32);
+        }
+
+        let val_len = value.map_or(TOMBSTONE_MARKER, |v| v.len() as u32);
+        self.buf.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        self.buf.extend_from_slice(&val_len.to_le_bytes());
+        self.buf.extend_from_slice(&meta.seq.to_le_bytes());
+        self.buf.extend_from_slice(&meta.timestamp.to_le_bytes());
+        self.buf.extend_from_slice(key);
+        if let Some(v) = value {
+            self.buf.extend_from_slice(v);
+            if self.value_crcs {
+                self.buf.extend_from_slice(&crc32fast::hash(v).to_le_bytes());
+            }
+        }
+        self.count += 1;
+    }
+
+    /// Encoded size if the block were finished now
+    pub(super) fn size(&self) -> usize {
+        self.buf.len() + 4 * (self.restarts.len() + 1)
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Encode the block and reset the builder for the next one
+    pub(super) fn finish(&mut self) -> Vec<u8> {
+        let mut block = std::mem::take(&mut self.buf);
+        for restart in &self.restarts {
+            block.extend_from_slice(&restart.to_le_bytes());
+        }
+        block.extend_from_slice(&(self.restarts.len() as u32).to_le_bytes());
+
+        self.restarts.clear();
+        self.count = 0;
+        block
+    }
+}
+
+/// A decoded block, ready for lookups and iteration
+pub(super) struct Block {
+    data: Vec<u8>,
+    /// In-block offsets of restart points
+    restarts: Vec<usize>,
+    /// Where entries stop and the restart array begins
+    entries_end: usize,
+    /// Layout of the entries
+    format: EntryFormat,
+}
+
+impl Block {
+    /// Parse a block read from disk, its entries laid out per `format`
+    pub(super) fn decode(data: Vec<u8>, format: EntryFormat) -> Result<Self> {
+        let corrupt = |what: &str| AtlasError::Storage(format!("Corrupt SSTable block: {}", what));
+
+        if data.len() < 4 {
+            return Err(corrupt("shorter than its restart count"));
+        }
+        let count_at = data.len() - 4;
+        let restart_count =
+            u32::from_le_bytes(data[count_at..].try_into().unwrap()) as usize;
+        if restart_count == 0 || restart_count > count_at / 4 {
+            return Err(corrupt("bad restart count"));
+        }
+
+        let entries_end = count_at - 4 * restart_count;
+        let restarts: Vec<usize> = data[entries_end..count_at]
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()) as usize)
+            .collect();
+        if restarts[0] != 0 || restarts.iter().any(|&restart| restart >= entries_end) {
+            return Err(corrupt("restart point outside entries"));
+        }
+
+        Ok(Self { data, restarts, entries_end, format })
+    }
+
+    /// Build a one-entry block from a version 1/2 data entry
+    ///
+    /// Those files have no blocks: each entry is `[KeyLen][ValLen][Key][Value]`
+    /// and its seq lives in the index.
+    pub(super) fn from_legacy_entry(data: &[u8], seq: u64) -> Result<Self> {
+        let corrupt = || AtlasError::Storage("Corrupt SSTable entry".to_string());
+
+        if data.len() < 8 {
+            return Err(corrupt());
+        }
+        let key_len = u32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
+        let val_len = u32::from_le_bytes(data[4..8].try_into().unwrap());
+        let key = data.get(8..8 + key_len).ok_or_else(corrupt)?;
+        let value = if val_len == TOMBSTONE_MARKER {
+            None
+        } else {
+            let start = 8 + key_len;
+            Some(data.get(start..start + val_len as usize).ok_or_else(corrupt)?)
+        };
+
+        let mut builder = BlockBuilder::default();
+        builder.add(key, value, EntryMeta::at_seq(seq));
+        Self::decode(builder.finish(), EntryFormat { timestamps: true, value_crcs: false })
+    }
+
+    /// Look up a key: `Some((value, meta))` if present (`None` value = tombstone)
+    ///
+    /// Checks the value against its CRC, if the block has them; only the
+    /// entry found is checked, so a corrupt neighbour doesn't fail the lookup.
+    pub(super) fn get(
+        &self,
+        comparator: &dyn Comparator,
+        key: &[u8],
+    ) -> Result<Option<(Option<Vec<u8>>, EntryMeta)>> {
+        // Last restart point whose key is <= the target
+        let mut low = 0;
+        let mut high = self.restarts.len();
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            let (mid_key, _, _) = self.entry_at(self.restarts[mid])?;
+            if comparator.compare(mid_key, key).is_le() {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        // Scan forward from there; keys are sorted, so stop once past it
+        let mut pos = self.restarts[low];
+        while pos < self.entries_end {
+            let (found, value, meta) = self.entry_at(pos)?;
+            match comparator.compare(found, key) {
+                Ordering::Equal => {
+                    self.check_value(pos, found, value)?;
+                    return Ok(Some((value.map(<[u8]>::to_vec), meta)));
+                }
+                Ordering::Greater => break,
+                Ordering::Less => {}
+            }
+            pos = self.next_entry(pos)?;
+        }
+        Ok(None)
+    }
+
+    /// Decode every entry, in key order, checking values against their CRCs
+    pub(super) fn entries(&self) -> Result<Vec<SeqEntry>> {
+        let mut entries = Vec::new();
+        let mut pos = 0;
+        while pos < self.entries_end {
+            let (key, value, meta) = self.entry_at(pos)?;
+            self.check_value(pos, key, value)?;
+            entries.push((key.to_vec(), value.map(<[u8]>::to_vec), meta));
+            pos = self.next_entry(pos)?;
+        }
+        Ok(entries)
+    }
+
+    /// Decode every key with whether it holds a value (false = tombstone)
+    ///
+    /// Walks entry headers only; value bytes are skipped, never copied.
+    pub(super) fn keys(&self) -> Result<Vec<(Vec<u8>, bool)>> {
+        let mut keys = Vec::new();
+        let mut pos = 0;
+        while pos < self.entries_end {
+            let (key_len, val_len, _) = self.header_at(pos)?;
+            let key_start = pos + self.format.header_size();
+            keys.push((self.data[key_start..key_start + key_len].to_vec(), val_len.is_some()));
+            pos = key_start + key_len + self.format.value_span(val_len);
+        }
+        Ok(keys)
+    }
+
+    /// Borrow the entry starting at `pos`
+    fn entry_at(&self, pos: usize) -> Result<EntryRef<'_>> {
+        let (key_len, val_len, meta) = self.header_at(pos)?;
+        let key_start = pos + self.format.header_size();
+        let key = &self.data[key_start..key_start + key_len];
+        let value = val_len.map(|len| &self.data[key_start + key_len..key_start + key_len + len]);
+        Ok((key, value, meta))
+    }
+
+    /// Check the value of the entry at `pos` (borrowed by `entry_at`)
+    /// against the CRC stored after it, if the block has them
+    fn check_value(&self, pos: usize, key: &[u8], value: Option<&[u8]>) -> Result<()> {
+        let Some(value) = value.filter(|_| self.format.value_crcs) else {
+            return Ok(());
+        };
+        let crc_at = pos + self.format.header_size() + key.len() + value.len();
+        let stored = &self.data[crc_at..crc_at + VALUE_CRC_SIZE];
+        if crc32fast::hash(value) != u32::from_le_bytes(stored.try_into().unwrap()) {
+            return Err(AtlasError::Storage(format!(
+                "Corrupt SSTable block: value checksum mismatch for key '{}'",
+                key.escape_ascii()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Offset of the entry after the one at `pos`
+    fn next_entry(&self, pos: usize) -> Result<usize> {
+        let (key_len, val_len, _) = self.header_at(pos)?;
+        Ok(pos + self.format.header_size() + key_len + self.format.value_span(val_len))
+    }
+
+    /// Parse (key_len, value_len, meta) at `pos`, checking the entry fits
+    fn header_at(&self, pos: usize) -> Result<(usize, Option<usize>, EntryMeta)> {
+        let corrupt = || AtlasError::Storage("Corrupt SSTable block: entry overruns it".to_string());
+
+        let header_size = self.format.header_size();
+        if pos + header_size > self.entries_end {
+            return Err(corrupt());
+        }
+        let header = &self.data[pos..pos + header_size];
+        let key_len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let val_len = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let seq = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        let timestamp = match header.get(16..24) {
+            Some(bytes) => u64::from_le_bytes(bytes.try_into().unwrap()),
+            None => 0,
+        };
+
+        let val_len = (val_len != TOMBSTONE_MARKER).then_some(val_len as usize);
+        let entry_end = pos + header_size + key_len + self.format.value_span(val_len);
+        if entry_end > self.entries_end {
+            return Err(corrupt());
+        }
+        Ok((key_len, val_len, EntryMeta { seq, timestamp }))
+    }
+}
+
+/// Length of the block at the start of `data`, its entries laid out per
+/// `format`, found without an index
+///
+/// Walks entries from the start; after each one, checks whether the bytes
+/// that follow are exactly the restart array and count a block of that
+/// many entries ends with. The array starts with a 0, which as an entry
+/// would be an empty key, and keys are strictly increasing, so this can't
+/// stop early on intact data. `None` if an entry runs off the end first.
+pub(super) fn find_block_end(data: &[u8], format: EntryFormat) -> Option<usize> {
+    let header_size = format.header_size();
+    let mut restarts: Vec<u32> = Vec::new();
+    let mut pos = 0;
+    let mut count = 0;
+    loop {
+        if count % RESTART_INTERVAL == 0 {
+            restarts.push(pos as u32);
+        }
+        let header = data.get(pos..pos + header_size)?;
+        let key_len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let val_len = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let val_len = (val_len != TOMBSTONE_MARKER).then_some(val_len as usize);
+        pos = pos.checked_add(header_size + key_len)?.checked_add(format.value_span(val_len))?;
+        if pos > data.len() {
+            return None;
+        }
+        count += 1;
+
+        let mut trailer = Vec::with_capacity(4 * (restarts.len() + 1));
+        for restart in &restarts {
+            trailer.extend_from_slice(&restart.to_le_bytes());
+        }
+        trailer.extend_from_slice(&(restarts.len() as u32).to_le_bytes());
+        if data[pos..].starts_with(&trailer) {
+            return Some(pos + trailer.len());
+        }
+    }
+}
+
+/// Find a key's value in the block at `block_offset..block_offset + block_len`
+/// without reading the block into memory, its entries laid out per `format`
+///
+/// Reads the restart array, then entry headers and keys only: the value
+/// itself is never read, so this costs the same for a 1 MB value as for an
+/// empty one (and its CRC, if any, is left to whoever reads it). `Ok(None)`
+/// if the key isn't in the block.
+pub(super) fn locate_value(
+    file: &File,
+    block_offset: u64,
+    block_len: u64,
+    format: EntryFormat,
+    comparator: &dyn Comparator,
+    key: &[u8],
+) -> Result<Option<ValueLocation>> {
+    let corrupt = |what: &str| AtlasError::Storage(format!("Corrupt SSTable block: {}", what));
+    let header_size = format.header_size() as u64;
+    let read_u32 = |at: u64| -> Result<u32> {
+        let mut bytes = [0u8; 4];
+        read_exact_at(file, &mut bytes, block_offset + at)?;
+        Ok(u32::from_le_bytes(bytes))
+    };
+
+    if block_len < 4 {
+        return Err(corrupt("shorter than its restart count"));
+    }
+    let count_at = block_len - 4;
+    let restart_count = read_u32(count_at)? as u64;
+    if restart_count == 0 || restart_count > count_at / 4 {
+        return Err(corrupt("bad restart count"));
+    }
+    let entries_end = count_at - 4 * restart_count;
+
+    // (key, value length or None for a tombstone) of the entry at `pos`
+    let entry_at = |pos: u64| -> Result<(Vec<u8>, Option<u32>)> {
+        if pos + header_size > entries_end {
+            return Err(corrupt("entry overruns it"));
+        }
+        let key_len = read_u32(pos)? as u64;
+        let val_len = read_u32(pos + 4)?;
+        let val_len = (val_len != TOMBSTONE_MARKER).then_some(val_len);
+        let value_span = format.value_span(val_len.map(|len| len as usize)) as u64;
+        if pos + header_size + key_len + value_span > entries_end {
+            return Err(corrupt("entry overruns it"));
+        }
+        let mut found = vec![0u8; key_len as usize];
+        read_exact_at(file, &mut found, block_offset + pos + header_size)?;
+        Ok((found, val_len))
+    };
+
+    // Last restart point whose key is <= the target
+    let restart = |i: u64| read_u32(entries_end + 4 * i).map(u64::from);
+    let mut low = 0;
+    let mut high = restart_count;
+    while high - low > 1 {
+        let mid = (low + high) / 2;
+        let (mid_key, _) = entry_at(restart(mid)?)?;
+        if comparator.compare(&mid_key, key).is_le() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    // Scan forward from there; keys are sorted, so stop once past it
+    let mut pos = restart(low)?;
+    while pos < entries_end {
+        let (found, val_len) = entry_at(pos)?;
+        let value_at = pos + header_size + found.len() as u64;
+        match comparator.compare(&found, key) {
+            Ordering::Equal => {
+                return Ok(Some(val_len.map(|len| (block_offset + value_at, len))));
+            }
+            Ordering::Greater => break,
+            Ordering::Less => {}
+        }
+        pos = value_at + format.value_span(val_len.map(|len| len as usize)) as u64;
+    }
+    Ok(None)
+}
+
+/// Find a key's value in a version 1/2 entry at `entry_offset`, as
+/// [`locate_value`] does for blocks
+pub(super) fn locate_legacy_value(
+    file: &File,
+    entry_offset: u64,
+    entry_len: u64,
+    comparator: &dyn Comparator,
+    key: &[u8],
+) -> Result<Option<ValueLocation>> {
+    let corrupt = || AtlasError::Storage("Corrupt SSTable entry".to_string());
+
+    if entry_len < 8 {
+        return Err(corrupt());
+    }
+    let mut header = [0u8; 8];
+    read_exact_at(file, &mut header, entry_offset)?;
+    let key_len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as u64;
+    let val_len = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    let val_len = (val_len != TOMBSTONE_MARKER).then_some(val_len);
+    if 8 + key_len + val_len.unwrap_or(0) as u64 > entry_len {
+        return Err(corrupt());
+    }
+
+    let mut found = vec![0u8; key_len as usize];
+    read_exact_at(file, &mut found, entry_offset + 8)?;
+    if comparator.compare(&found, key).is_ne() {
+        return Ok(None);
+    }
+    Ok(Some(val_len.map(|len| (entry_offset + 8 + key_len, len))))
+}
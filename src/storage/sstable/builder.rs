@@ -1,15 +1,22 @@
 //! SSTable Builder
 //!
-//! Writes sorted key-value entries to a new SSTable file.
+//! Writes sorted key-value entries to a new SSTable file, grouped into
+//! data blocks.
 
 use std::fs::{File, OpenOptions};
 use std::io::{BufWriter, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::Arc;
 
+use crate::comparator::{BytewiseComparator, Comparator};
 use crate::error::Result;
 use crate::AtlasError;
 
-use super::{SSTable, HEADER_SIZE, MAGIC, TOMBSTONE_MARKER, VERSION};
+use super::block::{BlockBuilder, EntryMeta};
+use super::{
+    SSTable, DEFAULT_BLOCK_SIZE, FLAG_VALUE_CRCS, HEADER_SIZE, MAGIC, MAX_VALUE_SIZE,
+    MINOR_VERSION, VERSION,
+};
 
 /// Builder for creating new SSTables from sorted entries
 pub struct SSTableBuilder {
@@ -19,23 +26,51 @@ pub struct SSTableBuilder {
     writer: BufWriter<File>,
     /// Number of entries written
     entry_count: u64,
-    /// Current write position (for index)
+    /// Current write position (start of the block being built)
     current_offset: u64,
-    /// Index: key → file offset of entry
-    index: Vec<(Vec<u8>, u64)>,
+    /// Block being built
+    block: BlockBuilder,
+    /// First key of the block being built
+    block_first_key: Vec<u8>,
+    /// Target block size in bytes
+    block_size: usize,
+    /// Block index: first key, file offset, and length of each block
+    index: Vec<(Vec<u8>, u64, u64)>,
+    /// Highest sequence number written
+    max_seq: u64,
     /// Track min/max keys for metadata
     min_key: Option<Vec<u8>>,
     max_key: Option<Vec<u8>>,
     /// Running CRC hasher for data section
     data_hasher: crc32fast::Hasher,
+    /// Reject keys that don't follow the previous one (see `new_unchecked`)
+    check_order: bool,
+    /// Order keys must arrive in
+    comparator: Arc<dyn Comparator>,
+    /// Follow each value with its CRC32 (see `with_value_checksums`)
+    value_crcs: bool,
 }
 
 impl SSTableBuilder {
     /// Create a new SSTable builder
     ///
     /// Writes header immediately; call `add()`/`add_tombstone()` in sorted order,
-    /// then `finish()` to write index and footer.
+    /// then `finish()` to write index and footer. A key that is not strictly
+    /// greater than the previous one fails with `AtlasError::Storage`, since
+    /// it would leave the index unsearchable.
     pub fn new(path: &Path) -> Result<Self> {
+        Self::create(path, true)
+    }
+
+    /// Create a builder that trusts its input order
+    ///
+    /// For callers whose keys are strictly increasing by construction
+    /// (compaction output from the merge iterator); skips the per-key check.
+    pub fn new_unchecked(path: &Path) -> Result<Self> {
+        Self::create(path, false)
+    }
+
+    fn create(path: &Path, check_order: bool) -> Result<Self> {
         let file = OpenOptions::new()
             .create(true)
             .write(true)
@@ -54,88 +89,195 @@ impl SSTableBuilder {
             writer,
             entry_count: 0,
             current_offset: HEADER_SIZE,
+            block: BlockBuilder::default(),
+            block_first_key: Vec::new(),
+            block_size: DEFAULT_BLOCK_SIZE,
             index: Vec::new(),
+            max_seq: 0,
             min_key: None,
             max_key: None,
             data_hasher: crc32fast::Hasher::new(),
+            check_order,
+            comparator: Arc::new(BytewiseComparator),
+            value_crcs: false,
         })
     }
 
+    /// Set the target data block size in bytes
+    ///
+    /// A block is closed once it reaches this size, so blocks run slightly
+    /// over it; an entry larger than the target gets a block of its own.
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    /// Order keys with `comparator` instead of byte order
+    ///
+    /// Readers of the file must be given the same comparator.
+    pub fn with_comparator(mut self, comparator: Arc<dyn Comparator>) -> Self {
+        self.comparator = comparator;
+        self
+    }
+
+    /// Store a CRC32 after every value, checked whenever it's read back
+    ///
+    /// Costs 4 bytes per value. Call it before adding entries.
+    pub fn with_value_checksums(mut self, enabled: bool) -> Self {
+        self.value_crcs = enabled;
+        self.block = BlockBuilder::new(enabled);
+        self
+    }
+
+    /// Set the capacity of the write buffer in bytes
+    ///
+    /// Flushes the header already written, so call it before adding entries.
+    pub fn with_buffer_size(mut self, bytes: usize) -> Result<Self> {
+        let file = self.writer.into_inner().map_err(|e| e.into_error())?;
+        self.writer = BufWriter::with_capacity(bytes, file);
+        Ok(self)
+    }
+
     /// Add a key-value pair (must be called in sorted key order)
     pub fn add(&mut self, key: &[u8], value: &[u8]) -> Result<()> {
-        self.write_entry(key, Some(value))
+        self.add_entry(key, Some(value), EntryMeta::default())
+    }
+
+    /// Add a key-value pair written at sequence number `seq`
+    pub fn add_at(&mut self, key: &[u8], value: &[u8], seq: u64) -> Result<()> {
+        self.add_entry(key, Some(value), EntryMeta::at_seq(seq))
     }
 
     /// Add a tombstone (must be called in sorted key order)
     pub fn add_tombstone(&mut self, key: &[u8]) -> Result<()> {
-        self.write_entry(key, None)
+        self.add_entry(key, None, EntryMeta::default())
+    }
+
+    /// Add a tombstone written at sequence number `seq`
+    pub fn add_tombstone_at(&mut self, key: &[u8], seq: u64) -> Result<()> {
+        self.add_entry(key, None, EntryMeta::at_seq(seq))
     }
 
-    /// Internal: write an entry (value=None means tombstone)
-    fn write_entry(&mut self, key: &[u8], value: Option<&[u8]>) -> Result<()> {
-        // Record offset for index
-        self.index.push((key.to_vec(), self.current_offset));
+    /// Add an entry with its seq and write timestamp (`None` value = tombstone)
+    ///
+    /// A value over `MAX_VALUE_SIZE` fails with `AtlasError::Storage`.
+    pub fn add_entry(&mut self, key: &[u8], value: Option<&[u8]>, meta: EntryMeta) -> Result<()> {
+        if let Some(value) = value {
+            Self::check_value_size(value.len())?;
+        }
+        if self.check_order {
+            if let Some(last_key) = &self.max_key {
+                if self.comparator.compare(key, last_key).is_le() {
+                    return Err(AtlasError::Storage(format!(
+                        "keys must be strictly increasing: {:?} follows {:?}",
+                        String::from_utf8_lossy(key),
+                        String::from_utf8_lossy(last_key)
+                    )));
+                }
+            }
+        }
+
+        if self.block.is_empty() {
+            self.block_first_key = key.to_vec();
+        }
 
         // Track min/max keys
         if self.min_key.is_none() {
             self.min_key = Some(key.to_vec());
         }
         self.max_key = Some(key.to_vec());
+        self.max_seq = self.max_seq.max(meta.seq);
 
-        // Prepare entry bytes: [key_len(4)][val_len(4)][key][value]
-        let key_len = key.len() as u32;
-        let val_len = match value {
-            Some(v) => v.len() as u32,
-            None => TOMBSTONE_MARKER,
-        };
+        self.block.add(key, value, meta);
+        self.entry_count += 1;
 
-        // Write and accumulate CRC
-        let key_len_bytes = key_len.to_le_bytes();
-        let val_len_bytes = val_len.to_le_bytes();
+        if self.block.size() >= self.block_size {
+            self.write_block()?;
+        }
 
-        self.writer.write_all(&key_len_bytes)?;
-        self.writer.write_all(&val_len_bytes)?;
-        self.writer.write_all(key)?;
+        Ok(())
+    }
 
-        self.data_hasher.update(&key_len_bytes);
-        self.data_hasher.update(&val_len_bytes);
-        self.data_hasher.update(key);
+    /// Check that a value of `len` bytes fits the format
+    ///
+    /// Its length must stay below the tombstone marker, so anything over
+    /// `MAX_VALUE_SIZE` fails with `AtlasError::Storage`.
+    pub fn check_value_size(len: usize) -> Result<()> {
+        if len > MAX_VALUE_SIZE {
+            return Err(AtlasError::Storage(format!(
+                "value too large for an SSTable: {} bytes (max {})",
+                len, MAX_VALUE_SIZE
+            )));
+        }
+        Ok(())
+    }
 
-        // Entry size so far: 4 + 4 + key_len
-        let mut entry_size: u64 = 8 + key.len() as u64;
+    /// Entries added so far
+    pub fn entry_count(&self) -> u64 {
+        self.entry_count
+    }
+
+    /// Data bytes added so far, the block being built included
+    ///
+    /// Excludes the header, index, and footer `finish` adds.
+    pub fn data_size(&self) -> u64 {
+        self.current_offset - HEADER_SIZE + self.block.size() as u64
+    }
 
-        if let Some(v) = value {
-            self.writer.write_all(v)?;
-            self.data_hasher.update(v);
-            entry_size += v.len() as u64;
+    /// Write out the block being built and record it in the index
+    fn write_block(&mut self) -> Result<()> {
+        if self.block.is_empty() {
+            return Ok(());
         }
 
-        self.current_offset += entry_size;
-        self.entry_count += 1;
+        let block = self.block.finish();
+        self.writer.write_all(&block)?;
+        self.data_hasher.update(&block);
+
+        let first_key = std::mem::take(&mut self.block_first_key);
+        self.index.push((first_key, self.current_offset, block.len() as u64));
+        self.current_offset += block.len() as u64;
 
         Ok(())
     }
 
     /// Finish building: write index block, footer, and return metadata
     pub fn finish(mut self) -> Result<SSTable> {
+        // The last block is usually partial
+        self.write_block()?;
+
         // Record where index block starts
         let index_offset = self.current_offset;
 
-        // Write index block: [key_len(4)][offset(8)][key] for each entry
-        for (key, offset) in &self.index {
-            let key_len = key.len() as u32;
-            self.writer.write_all(&key_len.to_le_bytes())?;
+        // Write index block: [max_seq(8)][last_key_len(4)][last_key], then
+        // [shared(4)][suffix_len(4)][offset(8)][len(8)][suffix] for each
+        // block, `shared` bytes coming from the previous block's first key
+        let last_key = self.max_key.as_deref().unwrap_or_default();
+        self.writer.write_all(&self.max_seq.to_le_bytes())?;
+        self.writer.write_all(&(last_key.len() as u32).to_le_bytes())?;
+        self.writer.write_all(last_key)?;
+
+        let mut prev_key: &[u8] = &[];
+        for (key, offset, len) in &self.index {
+            let shared = prev_key.iter().zip(key).take_while(|(a, b)| a == b).count();
+            let suffix = &key[shared..];
+            self.writer.write_all(&(shared as u32).to_le_bytes())?;
+            self.writer.write_all(&(suffix.len() as u32).to_le_bytes())?;
             self.writer.write_all(&offset.to_le_bytes())?;
-            self.writer.write_all(key)?;
+            self.writer.write_all(&len.to_le_bytes())?;
+            self.writer.write_all(suffix)?;
+            prev_key = key;
         }
 
         // Finalize CRC
         let data_crc = self.data_hasher.finalize();
 
-        // Write footer: index_offset (8) + data_crc (4) + padding (4)
+        // Write footer: index_offset (8) + data_crc (4) + minor (2) + flags (2)
+        let flags = if self.value_crcs { FLAG_VALUE_CRCS } else { 0 };
         self.writer.write_all(&index_offset.to_le_bytes())?;
         self.writer.write_all(&data_crc.to_le_bytes())?;
-        self.writer.write_all(&[0u8; 4])?; // Padding for alignment
+        self.writer.write_all(&MINOR_VERSION.to_le_bytes())?;
+        self.writer.write_all(&flags.to_le_bytes())?;
 
         // Flush everything
         self.writer.flush()?;
@@ -1,34 +1,52 @@
 //! SSTable Iterator
 //!
-//! Sequential iteration over all entries in an SSTable.
+//! Iteration over the entries of an SSTable, one data block at a time,
+//! forward (over everything or a key range) or backward, or over just its keys.
 
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::io::BufReader;
+use std::iter::Rev;
+use std::ops::Bound;
+use std::slice;
+use std::vec;
 
+use crate::comparator::{before_start, past_end, Comparator};
 use crate::error::Result;
-use crate::AtlasError;
 
-use super::{HEADER_SIZE, TOMBSTONE_MARKER};
+use super::block::SeqEntry;
+use super::reader::{read_block, BlockHandle};
 
 /// Iterator over SSTable entries in sorted key order
 pub struct SSTableIterator<'a> {
     file: &'a mut BufReader<File>,
-    /// Stop reading when we reach this offset (start of index block)
-    end_offset: u64,
-    /// Current position in file
-    current_offset: u64,
+    /// Blocks not read yet
+    blocks: slice::Iter<'a, BlockHandle>,
+    /// Remaining entries of the current block
+    entries: vec::IntoIter<SeqEntry>,
 }
 
 impl<'a> SSTableIterator<'a> {
-    /// Create a new iterator starting from the data block
-    pub(super) fn new(file: &'a mut BufReader<File>, end_offset: u64) -> Result<Self> {
-        // Seek to start of data (after header)
-        file.seek(SeekFrom::Start(HEADER_SIZE))?;
-        Ok(Self {
+    /// Create a new iterator over the given blocks
+    pub(super) fn new(file: &'a mut BufReader<File>, blocks: &'a [BlockHandle]) -> Self {
+        Self {
             file,
-            end_offset,
-            current_offset: HEADER_SIZE,
-        })
+            blocks: blocks.iter(),
+            entries: Vec::new().into_iter(),
+        }
+    }
+
+    /// Next entry with its sequence number, reading the next block as needed
+    pub(super) fn next_entry(&mut self) -> Option<Result<SeqEntry>> {
+        loop {
+            if let Some(entry) = self.entries.next() {
+                return Some(Ok(entry));
+            }
+            let handle = self.blocks.next()?;
+            match read_block(self.file, handle).and_then(|block| block.entries()) {
+                Ok(entries) => self.entries = entries.into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
     }
 }
 
@@ -37,43 +55,200 @@ impl<'a> Iterator for SSTableIterator<'a> {
     type Item = Result<(Vec<u8>, Option<Vec<u8>>)>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // Stop at index block
-        if self.current_offset >= self.end_offset {
-            return None;
+        self.next_entry()
+            .map(|entry| entry.map(|(key, value, _)| (key, value)))
+    }
+}
+
+/// Iterator over SSTable entries within a key range, in ascending order
+pub struct SSTableRangeIterator<'a> {
+    /// Entries of the blocks overlapping the range
+    inner: SSTableIterator<'a>,
+    /// Range bounds (the first and last blocks overhang them)
+    start: Bound<Vec<u8>>,
+    end: Bound<Vec<u8>>,
+    /// Order the bounds are checked in
+    comparator: &'a dyn Comparator,
+    /// Passed `end`; nothing further can be in range
+    done: bool,
+}
+
+impl<'a> SSTableRangeIterator<'a> {
+    /// Create an iterator over the given blocks, limited to a key range
+    pub(super) fn new(
+        file: &'a mut BufReader<File>,
+        blocks: &'a [BlockHandle],
+        comparator: &'a dyn Comparator,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+    ) -> Self {
+        Self {
+            inner: SSTableIterator::new(file, blocks),
+            start: start.map(<[u8]>::to_vec),
+            end: end.map(<[u8]>::to_vec),
+            comparator,
+            done: false,
         }
+    }
+}
 
-        // Read entry header
-        let mut header = [0u8; 8];
-        if let Err(e) = self.file.read_exact(&mut header) {
-            return Some(Err(AtlasError::Io(e)));
+impl<'a> Iterator for SSTableRangeIterator<'a> {
+    /// (key, Option<value>) — None value means tombstone
+    type Item = Result<(Vec<u8>, Option<Vec<u8>>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let (key, value, _) = match self.inner.next_entry()? {
+                Ok(entry) => entry,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if before_start(self.comparator, &key, self.start.as_ref().map(Vec::as_slice)) {
+                continue;
+            }
+            if past_end(self.comparator, &key, self.end.as_ref().map(Vec::as_slice)) {
+                self.done = true;
+                break;
+            }
+
+            return Some(Ok((key, value)));
         }
+        None
+    }
+}
 
-        let key_len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
-        let val_len = u32::from_le_bytes(header[4..8].try_into().unwrap());
+/// Iterator over SSTable entries in descending key order
+///
+/// Entries within a block can't be walked backward (they are
+/// variable-length with no back-pointers), so each block is decoded whole
+/// and drained from the end.
+pub struct SSTableRevIterator<'a> {
+    file: &'a mut BufReader<File>,
+    /// Blocks not read yet, last first
+    blocks: Rev<slice::Iter<'a, BlockHandle>>,
+    /// Remaining entries of the current block, consumed from the back
+    entries: Vec<SeqEntry>,
+    /// Range bounds (the first and last blocks overhang them)
+    start: Bound<Vec<u8>>,
+    end: Bound<Vec<u8>>,
+    /// Order the bounds are checked in
+    comparator: &'a dyn Comparator,
+    /// Passed `start`; nothing further can be in range
+    done: bool,
+}
 
-        // Read key
-        let mut key = vec![0u8; key_len];
-        if let Err(e) = self.file.read_exact(&mut key) {
-            return Some(Err(AtlasError::Io(e)));
+impl<'a> SSTableRevIterator<'a> {
+    /// Create a reverse iterator over the given blocks, limited to a key range
+    pub(super) fn new(
+        file: &'a mut BufReader<File>,
+        blocks: &'a [BlockHandle],
+        comparator: &'a dyn Comparator,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+    ) -> Self {
+        Self {
+            file,
+            blocks: blocks.iter().rev(),
+            entries: Vec::new(),
+            start: start.map(<[u8]>::to_vec),
+            end: end.map(<[u8]>::to_vec),
+            comparator,
+            done: false,
         }
+    }
+}
 
-        // Calculate entry size and update offset
-        let mut entry_size = 8 + key_len as u64;
+impl<'a> Iterator for SSTableRevIterator<'a> {
+    /// (key, Option<value>) — None value means tombstone
+    type Item = Result<(Vec<u8>, Option<Vec<u8>>)>;
 
-        // Read value (if not tombstone)
-        let value = if val_len == TOMBSTONE_MARKER {
-            None
-        } else {
-            let mut v = vec![0u8; val_len as usize];
-            if let Err(e) = self.file.read_exact(&mut v) {
-                return Some(Err(AtlasError::Io(e)));
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let Some((key, value, _)) = self.entries.pop() else {
+                let handle = self.blocks.next()?;
+                match read_block(self.file, handle).and_then(|block| block.entries()) {
+                    Ok(entries) => self.entries = entries,
+                    Err(e) => return Some(Err(e)),
+                }
+                continue;
+            };
+
+            if past_end(self.comparator, &key, self.end.as_ref().map(Vec::as_slice)) {
+                continue;
+            }
+            if before_start(self.comparator, &key, self.start.as_ref().map(Vec::as_slice)) {
+                self.done = true;
+                break;
             }
-            entry_size += val_len as u64;
-            Some(v)
-        };
 
-        self.current_offset += entry_size;
+            return Some(Ok((key, value)));
+        }
+        None
+    }
+}
+
+/// Iterator over the keys in a range, in ascending order, skipping values
+pub struct SSTableKeyIterator<'a> {
+    file: &'a mut BufReader<File>,
+    /// Blocks not read yet
+    blocks: slice::Iter<'a, BlockHandle>,
+    /// Remaining keys of the current block
+    keys: vec::IntoIter<(Vec<u8>, bool)>,
+    /// Range bounds (the first and last blocks overhang them)
+    start: Bound<Vec<u8>>,
+    end: Bound<Vec<u8>>,
+    /// Order the bounds are checked in
+    comparator: &'a dyn Comparator,
+    /// Passed `end`; nothing further can be in range
+    done: bool,
+}
 
-        Some(Ok((key, value)))
+impl<'a> SSTableKeyIterator<'a> {
+    /// Create a key iterator over the given blocks, limited to a key range
+    pub(super) fn new(
+        file: &'a mut BufReader<File>,
+        blocks: &'a [BlockHandle],
+        comparator: &'a dyn Comparator,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+    ) -> Self {
+        Self {
+            file,
+            blocks: blocks.iter(),
+            keys: Vec::new().into_iter(),
+            start: start.map(<[u8]>::to_vec),
+            end: end.map(<[u8]>::to_vec),
+            comparator,
+            done: false,
+        }
+    }
+}
+
+impl<'a> Iterator for SSTableKeyIterator<'a> {
+    /// (key, live) — false means tombstone
+    type Item = Result<(Vec<u8>, bool)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let Some((key, live)) = self.keys.next() else {
+                let handle = self.blocks.next()?;
+                match read_block(self.file, handle).and_then(|block| block.keys()) {
+                    Ok(keys) => self.keys = keys.into_iter(),
+                    Err(e) => return Some(Err(e)),
+                }
+                continue;
+            };
+
+            if before_start(self.comparator, &key, self.start.as_ref().map(Vec::as_slice)) {
+                continue;
+            }
+            if past_end(self.comparator, &key, self.end.as_ref().map(Vec::as_slice)) {
+                self.done = true;
+                break;
+            }
+
+            return Some(Ok((key, live)));
+        }
+        None
     }
 }
@@ -8,28 +8,71 @@
 //! │ Header (14 bytes)                                       │
 //! │   Magic: "ATKV" (4) | Version: u16 (2) | Count: u64 (8) │
 //! ├─────────────────────────────────────────────────────────┤
-//! │ Data Block (variable)                                   │
-//! │   [KeyLen: u32][ValLen: u32][Key][Value]                │
-//! │   ... repeated for each entry ...                       │
+//! │ Data Blocks (variable, ~`sstable_block_size` each)      │
+//! │   [KeyLen: u32][ValLen: u32][Seq: u64][Timestamp: u64]  │
+//! │   [Key][Value][ValueCRC: u32, if FLAG_VALUE_CRCS]       │
+//! │   ... repeated for each entry in the block ...          │
 //! │   (ValLen = u32::MAX means tombstone, no value bytes)   │
+//! │   [Restart: u32] ... [RestartCount: u32]                │
+//! ├─────────────────────────────────────────────────────────┤
+//! │ Extension Blocks (newer minor versions only)            │
 //! ├─────────────────────────────────────────────────────────┤
 //! │ Index Block (variable)                                  │
-//! │   [KeyLen: u32][Offset: u64][Key]                       │
-//! │   ... repeated for each entry ...                       │
+//! │   [MaxSeq: u64][LastKeyLen: u32][LastKey]               │
+//! │   [Shared: u32][SuffixLen: u32][Offset: u64][Len: u64]  │
+//! │   [FirstKeySuffix]                                      │
+//! │   ... repeated for each data block ...                  │
 //! ├─────────────────────────────────────────────────────────┤
 //! │ Footer (16 bytes)                                       │
-//! │   IndexOffset: u64 (8) | DataCRC: u32 (4) | Padding (4) │
+//! │   IndexOffset: u64 (8) | DataCRC: u32 (4)               │
+//! │   Minor: u16 (2) | Flags: u16 (2)                       │
 //! └─────────────────────────────────────────────────────────┘
 //! ```
+//!
+//! ## Versioning
+//!
+//! The header's `Version` is the major version: anything newer than
+//! `VERSION` is rejected, since its data blocks or index may not parse.
+//! The footer's `Minor` (padding, so 0, before minors existed) may be
+//! newer than `MINOR_VERSION`. A newer minor may only add extension blocks
+//! between the last data block and the index; the header, data blocks,
+//! index, and footer keep their layout, so an older reader skips the
+//! extensions and still reads every entry. Anything else is a major bump.
+//!
+//! The footer's `Flags` (reserved, so 0, before version 6) say how a file
+//! was written. `FLAG_VALUE_CRCS` follows every value with the CRC32 of its
+//! bytes, checked when a lookup or iterator reads the value, so bit-rot
+//! fails that one value rather than going unnoticed.
+//!
+//! `ValLen` doubles as the tombstone flag, so a value may be at most
+//! `MAX_VALUE_SIZE` (4 GiB - 1) bytes; the builder rejects anything longer.
+//!
+//! Each block's first key is stored as the length of the prefix it shares
+//! with the previous block's first key plus the remaining suffix. Versions 3
+//! and 4 stored it whole, as `[KeyLen][Offset][Len][FirstKey]`.
+//!
+//! The `block` module covers the layout within a data block. Version 3 blocks
+//! lack the per-entry `Timestamp`, which reads back as 0. Versions 1 and 2 had
+//! no blocks: a bare `[KeyLen][ValLen][Key][Value]` per entry, indexed per
+//! key as `[KeyLen][Offset][Seq, version 2 only][Key]`. They are still
+//! readable; each entry is treated as a block of its own.
 
+mod block;
 mod builder;
 mod iterator;
 mod reader;
 
+#[cfg(any(test, feature = "test-hooks"))]
+use std::cell::Cell;
+use std::fs::File;
+use std::io;
 use std::path::PathBuf;
 
+pub use block::{EntryMeta, SeqEntry};
 pub use builder::SSTableBuilder;
-pub use iterator::SSTableIterator;
+pub use iterator::{
+    SSTableIterator, SSTableKeyIterator, SSTableRangeIterator, SSTableRevIterator,
+};
 pub use reader::SSTableReader;
 
 // =============================================================================
@@ -39,18 +82,90 @@ pub use reader::SSTableReader;
 /// Magic bytes identifying an AtlasKV SSTable file
 pub(crate) const MAGIC: &[u8; 4] = b"ATKV";
 
-/// Current SSTable format version
-pub(crate) const VERSION: u16 = 1;
+/// Current SSTable format version (2 added per-entry sequence numbers,
+/// 3 grouped entries into blocks, 4 added per-entry write timestamps,
+/// 5 prefix-compressed the index keys, 6 added footer flags)
+pub(crate) const VERSION: u16 = 6;
+
+/// Minor format version written to the footer (see "Versioning" above)
+pub(crate) const MINOR_VERSION: u16 = 0;
+
+/// First format version with data blocks
+pub(super) const BLOCK_VERSION: u16 = 3;
+
+/// First format version whose block entries carry a write timestamp
+pub(super) const TIMESTAMP_VERSION: u16 = 4;
+
+/// First format version whose index keys share prefixes
+pub(super) const PREFIX_INDEX_VERSION: u16 = 5;
+
+/// First format version whose footer carries flags
+pub(super) const FLAGS_VERSION: u16 = 6;
+
+/// Footer flag: each value is followed by its CRC32
+pub(super) const FLAG_VALUE_CRCS: u16 = 1;
+
+/// Default target size of a data block in bytes
+pub const DEFAULT_BLOCK_SIZE: usize = 4096;
+
+/// Oldest format version that can still be read
+pub(crate) const MIN_VERSION: u16 = 1;
 
 /// Header size: Magic (4) + Version (2) + EntryCount (8) = 14 bytes
 pub(crate) const HEADER_SIZE: u64 = 14;
 
-/// Footer size: IndexOffset (8) + DataCRC (4) + Padding (4) = 16 bytes
+/// Footer size: IndexOffset (8) + DataCRC (4) + Minor (2) + Flags (2) = 16 bytes
 pub(crate) const FOOTER_SIZE: u64 = 16;
 
 /// Sentinel value indicating a tombstone (deleted key)
 pub(crate) const TOMBSTONE_MARKER: u32 = u32::MAX;
 
+/// Largest value an SSTable can hold: 4 GiB - 1 bytes, since a `ValLen` of
+/// `u32::MAX` marks a tombstone
+pub const MAX_VALUE_SIZE: usize = TOMBSTONE_MARKER as usize - 1;
+
+#[cfg(any(test, feature = "test-hooks"))]
+thread_local! {
+    /// Bytes requested through `read_exact_at` on this thread
+    static POSITIONED_READ_BYTES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Bytes the current thread has asked for through positioned SSTable reads
+///
+/// Per thread, so a test can see exactly what its own lookups read (for
+/// example that `SSTableReader::contains` skips the value bytes). Only
+/// built with the `test-hooks` feature.
+#[cfg(any(test, feature = "test-hooks"))]
+#[doc(hidden)]
+pub fn positioned_read_bytes() -> u64 {
+    POSITIONED_READ_BYTES.with(Cell::get)
+}
+
+/// Fill `buf` from `file` at `offset` without moving the file's cursor
+///
+/// Positioned reads need only `&File`, so lookups that use them can share
+/// a reader with iterators that seek.
+pub(crate) fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    #[cfg(any(test, feature = "test-hooks"))]
+    POSITIONED_READ_BYTES.with(|bytes| bytes.set(bytes.get() + buf.len() as u64));
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+    }
+    #[cfg(windows)]
+    {
+        let mut filled = 0;
+        while filled < buf.len() {
+            let at = offset + filled as u64;
+            match std::os::windows::fs::FileExt::seek_read(file, &mut buf[filled..], at)? {
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                n => filled += n,
+            }
+        }
+        Ok(())
+    }
+}
+
 // =============================================================================
 // SSTable Metadata
 // =============================================================================
@@ -87,6 +202,9 @@ impl SSTable {
 
     /// Quick check if a key might be in this SSTable (range check)
     /// Returns false if key is definitely outside [min_key, max_key]
+    ///
+    /// Compares in byte order; `SSTableReader::might_contain` uses the
+    /// comparator the file was written with.
     pub fn might_contain(&self, key: &[u8]) -> bool {
         key >= self.min_key.as_slice() && key <= self.max_key.as_slice()
     }
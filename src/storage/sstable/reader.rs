@@ -1,59 +1,86 @@
 //! SSTable Reader
 //!
-//! Opens SSTable files and provides O(log n) key lookups via in-memory index.
+//! Opens SSTable files and looks keys up via an in-memory block index:
+//! a binary search picks the one block that can hold the key.
 
-use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom};
-use std::path::Path;
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use crate::comparator::{before_start, is_empty_range, past_end, BytewiseComparator, Comparator};
 use crate::error::Result;
 use crate::AtlasError;
 
-use super::iterator::SSTableIterator;
-use super::{FOOTER_SIZE, HEADER_SIZE, MAGIC, TOMBSTONE_MARKER, VERSION};
+use super::block::{
+    find_block_end, locate_legacy_value, locate_value, Block, EntryFormat, EntryMeta, SeqEntry,
+};
+use super::iterator::{
+    SSTableIterator, SSTableKeyIterator, SSTableRangeIterator, SSTableRevIterator,
+};
+use super::{
+    BLOCK_VERSION, FLAGS_VERSION, FLAG_VALUE_CRCS, FOOTER_SIZE, HEADER_SIZE, MAGIC,
+    MINOR_VERSION, MIN_VERSION, PREFIX_INDEX_VERSION, TIMESTAMP_VERSION, TOMBSTONE_MARKER,
+    VERSION,
+};
+
+/// Location of one data block
+#[derive(Debug, Clone)]
+pub(super) struct BlockHandle {
+    /// Smallest key in the block
+    pub(super) first_key: Vec<u8>,
+    /// File offset of the block
+    pub(super) offset: u64,
+    /// Length of the block in bytes
+    pub(super) len: u64,
+    /// Version 1/2 files only: the block is a bare entry, with this seq
+    pub(super) legacy_seq: Option<u64>,
+    /// Layout of the block's entries
+    pub(super) format: EntryFormat,
+}
 
-/// Reader for SSTable files with in-memory index for O(log n) lookups
+/// Reader for SSTable files with an in-memory block index
 pub struct SSTableReader {
-    /// File handle for reading entries
+    /// File handle for reading blocks
     pub(super) file: BufReader<File>,
-    /// In-memory index: key → file offset
-    index: BTreeMap<Vec<u8>, u64>,
+    /// In-memory block index, in key order
+    blocks: Vec<BlockHandle>,
+    /// Largest key (None for an empty SSTable)
+    max_key: Option<Vec<u8>>,
+    /// Highest sequence number in the file
+    max_seq: u64,
     /// Metadata
     entry_count: u64,
-    /// Index block starting offset (for iteration)
-    pub(super) index_offset: u64,
+    /// Path the SSTable was opened from
+    path: PathBuf,
+    /// Total file size in bytes
+    file_size: u64,
+    /// Where the index begins (data blocks, and any extension blocks, end there)
+    data_end: u64,
+    /// Order the file was written in
+    comparator: Arc<dyn Comparator>,
 }
 
 impl SSTableReader {
     /// Open an SSTable for reading
     ///
-    /// Loads the entire index into memory for fast lookups.
+    /// Loads the block index into memory; data blocks are read on demand.
     pub fn open(path: &Path) -> Result<Self> {
         let mut file = File::open(path)?;
         let file_size = file.metadata()?.len();
-
-        // Read and validate header
-        let mut header = [0u8; HEADER_SIZE as usize];
-        file.read_exact(&mut header)?;
-
-        if &header[0..4] != MAGIC {
-            return Err(AtlasError::Storage(format!(
-                "Invalid SSTable magic: expected ATKV, got {:?}",
-                &header[0..4]
-            )));
-        }
-
-        let version = u16::from_le_bytes(header[4..6].try_into().unwrap());
-        if version != VERSION {
-            return Err(AtlasError::Storage(format!(
-                "Unsupported SSTable version: {}",
-                version
+        let (version, entry_count) = Self::read_header(&mut file, path, file_size)?;
+
+        // A complete SSTable always has at least a header and a footer
+        if file_size < HEADER_SIZE + FOOTER_SIZE {
+            return Err(AtlasError::SSTableTruncated(format!(
+                "{}: {} bytes, expected at least {}",
+                path.display(),
+                file_size,
+                HEADER_SIZE + FOOTER_SIZE
             )));
         }
 
-        let entry_count = u64::from_le_bytes(header[6..14].try_into().unwrap());
-
         // Read footer to get index offset
         file.seek(SeekFrom::End(-(FOOTER_SIZE as i64)))?;
         let mut footer = [0u8; FOOTER_SIZE as usize];
@@ -63,87 +90,490 @@ impl SSTableReader {
         let _data_crc = u32::from_le_bytes(footer[8..12].try_into().unwrap());
         // Note: CRC validation could be done here for extra safety
 
-        // Load index into memory
-        let mut index = BTreeMap::new();
+        // A newer minor may put extension blocks we don't know before the index
+        let minor = u16::from_le_bytes(footer[12..14].try_into().unwrap());
+        let extensions = minor > MINOR_VERSION;
+        let format = Self::entry_format(version, &footer)?;
+
+        // Index must sit between the header and the footer; anything else
+        // means the tail we read as a footer is really cut-off data
+        if index_offset < HEADER_SIZE || index_offset > file_size - FOOTER_SIZE {
+            return Err(AtlasError::SSTableTruncated(format!(
+                "{}: index offset {} outside file of {} bytes",
+                path.display(),
+                index_offset,
+                file_size
+            )));
+        }
+
+        // Load index block into memory
         file.seek(SeekFrom::Start(index_offset))?;
 
         // Index block size = file_size - footer_size - index_offset
         let index_block_size = file_size - FOOTER_SIZE - index_offset;
+
+        // Block-format indexes always hold max_seq and the last key, so only
+        // an empty legacy file can have nothing between its data and footer
+        if index_block_size == 0 && (version >= BLOCK_VERSION || entry_count != 0) {
+            return Err(AtlasError::SSTableTruncated(format!(
+                "{}: empty index block for {} entries",
+                path.display(),
+                entry_count
+            )));
+        }
+
         let mut index_data = vec![0u8; index_block_size as usize];
         file.read_exact(&mut index_data)?;
 
-        // Parse index entries: [key_len(4)][offset(8)][key]
-        let mut pos = 0;
-        while pos < index_data.len() {
-            if pos + 4 > index_data.len() {
-                break;
-            }
-            let key_len =
-                u32::from_le_bytes(index_data[pos..pos + 4].try_into().unwrap()) as usize;
-            pos += 4;
+        // A record that runs past the block, or points outside the data
+        // block, means the footer we read was not the real one
+        let truncated = || {
+            AtlasError::SSTableTruncated(format!(
+                "{}: index block inconsistent with footer",
+                path.display()
+            ))
+        };
 
-            if pos + 8 > index_data.len() {
-                break;
+        let (blocks, max_key, max_seq) = if version >= BLOCK_VERSION {
+            Self::parse_block_index(&index_data, index_offset, version, format, extensions)
+                .ok_or_else(truncated)?
+        } else {
+            Self::parse_legacy_index(&index_data, index_offset, version).ok_or_else(truncated)?
+        };
+
+        // Reset file to start for reading
+        file.seek(SeekFrom::Start(0))?;
+
+        Ok(Self {
+            file: BufReader::new(file),
+            blocks,
+            max_key,
+            max_seq,
+            entry_count,
+            path: path.to_path_buf(),
+            file_size,
+            data_end: index_offset,
+            comparator: Arc::new(BytewiseComparator),
+        })
+    }
+
+    /// Rebuild the block index of an SSTable whose index or footer is
+    /// corrupt, by walking the data blocks from the start
+    ///
+    /// Reads up to the footer's index offset if it is plausible, else up to
+    /// where a footer would begin, and stops at the first block that
+    /// doesn't parse or breaks key order: everything before it is
+    /// recovered. Keys must be in byte order. Version 2 files keep their
+    /// seqs in the index, so their entries come back with seq 0.
+    pub fn rebuild_index(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        let (version, _) = Self::read_header(&mut file, path, file_size)?;
+
+        // The footer, if intact, says where the data ends and whether
+        // values carry CRCs (assumed not, without one)
+        let guess = file_size.saturating_sub(FOOTER_SIZE).max(HEADER_SIZE);
+        let mut data_end = guess;
+        let timestamps = version >= TIMESTAMP_VERSION;
+        let mut format = EntryFormat { timestamps, value_crcs: false };
+        if file_size >= HEADER_SIZE + FOOTER_SIZE {
+            let mut footer = [0u8; FOOTER_SIZE as usize];
+            file.seek(SeekFrom::Start(file_size - FOOTER_SIZE))?;
+            file.read_exact(&mut footer)?;
+            let index_offset = u64::from_le_bytes(footer[0..8].try_into().unwrap());
+            if (HEADER_SIZE..=guess).contains(&index_offset) {
+                data_end = index_offset;
+                format = Self::entry_format(version, &footer)?;
             }
-            let offset = u64::from_le_bytes(index_data[pos..pos + 8].try_into().unwrap());
-            pos += 8;
+        }
 
-            if pos + key_len > index_data.len() {
+        let mut data = vec![0u8; (data_end - HEADER_SIZE) as usize];
+        file.seek(SeekFrom::Start(HEADER_SIZE))?;
+        file.read_exact(&mut data)?;
+
+        let mut blocks: Vec<BlockHandle> = Vec::new();
+        let mut max_key: Option<Vec<u8>> = None;
+        let mut max_seq = 0;
+        let mut entry_count = 0;
+        let mut pos = 0;
+        while pos < data.len() {
+            let Some((len, entries)) = Self::recover_block(&data[pos..], version, format) else {
+                break;
+            };
+            // Keys run on strictly increasing from the previous block
+            let keys = entries.iter().map(|(key, _, _)| key.as_slice());
+            if !max_key.as_deref().into_iter().chain(keys).is_sorted_by(|a, b| a < b) {
                 break;
             }
-            let key = index_data[pos..pos + key_len].to_vec();
-            pos += key_len;
 
-            index.insert(key, offset);
+            blocks.push(BlockHandle {
+                first_key: entries[0].0.clone(),
+                offset: HEADER_SIZE + pos as u64,
+                len: len as u64,
+                legacy_seq: (version < BLOCK_VERSION).then_some(0),
+                format,
+            });
+            max_seq = entries.iter().map(|(_, _, meta)| meta.seq).fold(max_seq, u64::max);
+            entry_count += entries.len() as u64;
+            max_key = entries.last().map(|(key, _, _)| key.clone());
+            pos += len;
         }
 
-        // Reset file to start for reading
-        file.seek(SeekFrom::Start(0))?;
+        if pos < data.len() {
+            tracing::warn!(
+                path = %path.display(),
+                recovered_entries = entry_count,
+                lost_bytes = data.len() - pos,
+                "SSTable data unreadable past offset {}",
+                HEADER_SIZE + pos as u64
+            );
+        }
 
+        file.seek(SeekFrom::Start(0))?;
         Ok(Self {
             file: BufReader::new(file),
-            index,
+            blocks,
+            max_key,
+            max_seq,
             entry_count,
-            index_offset,
+            path: path.to_path_buf(),
+            file_size,
+            data_end: HEADER_SIZE + pos as u64,
+            comparator: Arc::new(BytewiseComparator),
         })
     }
 
-    /// Get a value by key — O(log n) lookup via in-memory index
+    /// Read and check the header, returning (version, entry count)
+    fn read_header(file: &mut File, path: &Path, file_size: u64) -> Result<(u16, u64)> {
+        if file_size < HEADER_SIZE {
+            return Err(AtlasError::SSTableTruncated(format!(
+                "{}: {} bytes, shorter than header",
+                path.display(),
+                file_size
+            )));
+        }
+
+        let mut header = [0u8; HEADER_SIZE as usize];
+        file.read_exact(&mut header)?;
+
+        if &header[0..4] != MAGIC {
+            return Err(AtlasError::Storage(format!(
+                "Invalid SSTable magic: expected ATKV, got {:?}",
+                &header[0..4]
+            )));
+        }
+
+        let version = u16::from_le_bytes(header[4..6].try_into().unwrap());
+        if !(MIN_VERSION..=VERSION).contains(&version) {
+            return Err(AtlasError::Storage(format!(
+                "Unsupported SSTable version: {}",
+                version
+            )));
+        }
+
+        let entry_count = u64::from_le_bytes(header[6..14].try_into().unwrap());
+        Ok((version, entry_count))
+    }
+
+    /// How a file's entries are laid out, from its version and footer
+    ///
+    /// Flags this build doesn't know may change the layout, so they fail
+    /// with `AtlasError::Storage` like a newer major version does.
+    fn entry_format(version: u16, footer: &[u8; FOOTER_SIZE as usize]) -> Result<EntryFormat> {
+        let flags = match version {
+            FLAGS_VERSION.. => u16::from_le_bytes(footer[14..16].try_into().unwrap()),
+            _ => 0,
+        };
+        if flags & !FLAG_VALUE_CRCS != 0 {
+            return Err(AtlasError::Storage(format!("Unsupported SSTable flags: {:#06x}", flags)));
+        }
+        Ok(EntryFormat {
+            timestamps: version >= TIMESTAMP_VERSION,
+            value_crcs: flags & FLAG_VALUE_CRCS != 0,
+        })
+    }
+
+    /// Decode the block (or, before version 3, the bare entry) at the start
+    /// of `data`, returning its length and entries
+    fn recover_block(
+        data: &[u8],
+        version: u16,
+        format: EntryFormat,
+    ) -> Option<(usize, Vec<SeqEntry>)> {
+        let (len, block) = if version >= BLOCK_VERSION {
+            let len = find_block_end(data, format)?;
+            (len, Block::decode(data[..len].to_vec(), format).ok()?)
+        } else {
+            let len = Self::legacy_entry_len(data)?;
+            (len, Block::from_legacy_entry(&data[..len], 0).ok()?)
+        };
+        Some((len, block.entries().ok()?))
+    }
+
+    /// Length of the version 1/2 entry (`[KeyLen][ValLen][Key][Value]`) at
+    /// the start of `data`, if it fits
+    fn legacy_entry_len(data: &[u8]) -> Option<usize> {
+        let key_len = u32::from_le_bytes(data.get(0..4)?.try_into().unwrap()) as usize;
+        let val_len = u32::from_le_bytes(data.get(4..8)?.try_into().unwrap());
+        let val_len = if val_len == TOMBSTONE_MARKER { 0 } else { val_len as usize };
+        let len = 8usize.checked_add(key_len)?.checked_add(val_len)?;
+        (len <= data.len()).then_some(len)
+    }
+
+    /// Search with `comparator` instead of byte order
+    ///
+    /// Must be the comparator the file was built with.
+    pub fn with_comparator(mut self, comparator: Arc<dyn Comparator>) -> Self {
+        self.comparator = comparator;
+        self
+    }
+
+    /// Set the capacity of the read buffer in bytes
+    ///
+    /// Every read seeks first, so nothing buffered is lost by the swap.
+    pub fn with_buffer_size(mut self, bytes: usize) -> Self {
+        self.file = BufReader::with_capacity(bytes, self.file.into_inner());
+        self
+    }
+
+    /// Parse a block index: `[max_seq(8)][last_key_len(4)][last_key]`, then
+    /// `[shared(4)][suffix_len(4)][offset(8)][len(8)][suffix]` per block
+    /// (`[key_len(4)][offset(8)][len(8)][first_key]` before version 5)
+    ///
+    /// `None` if a record runs past the index, a block past the data, or a
+    /// shared prefix past the previous key. With `extensions`, the data
+    /// blocks may end short of the index, leaving room for extension blocks.
+    fn parse_block_index(
+        data: &[u8],
+        index_offset: u64,
+        version: u16,
+        format: EntryFormat,
+        extensions: bool,
+    ) -> Option<(Vec<BlockHandle>, Option<Vec<u8>>, u64)> {
+        let mut cursor = IndexCursor { data, pos: 0 };
+
+        let max_seq = cursor.read_u64()?;
+        let last_key_len = cursor.read_u32()? as usize;
+        let last_key = cursor.read_bytes(last_key_len)?.to_vec();
+
+        let mut blocks = Vec::new();
+        let mut next_offset = HEADER_SIZE;
+        while !cursor.is_empty() {
+            let shared =
+                if version >= PREFIX_INDEX_VERSION { cursor.read_u32()? as usize } else { 0 };
+            let suffix_len = cursor.read_u32()? as usize;
+            let offset = cursor.read_u64()?;
+            let len = cursor.read_u64()?;
+            let suffix = cursor.read_bytes(suffix_len)?;
+
+            // Rebuild the full key from the previous block's first key
+            let prev_key = blocks.last().map_or(&[][..], |prev: &BlockHandle| &prev.first_key[..]);
+            let mut first_key = prev_key.get(..shared)?.to_vec();
+            first_key.extend_from_slice(suffix);
+
+            // Blocks are contiguous and end where the index begins
+            if offset != next_offset || len > index_offset - offset {
+                return None;
+            }
+            next_offset = offset + len;
+
+            blocks.push(BlockHandle {
+                first_key,
+                offset,
+                len,
+                legacy_seq: None,
+                format,
+            });
+        }
+        if next_offset != index_offset && !extensions {
+            return None;
+        }
+
+        let max_key = (!blocks.is_empty()).then_some(last_key);
+        Some((blocks, max_key, max_seq))
+    }
+
+    /// Parse a version 1/2 per-key index: `[key_len(4)][offset(8)][seq(8),
+    /// version 2 only][key]` per entry
+    ///
+    /// Each entry becomes a one-entry block running to the next entry.
+    fn parse_legacy_index(
+        data: &[u8],
+        index_offset: u64,
+        version: u16,
+    ) -> Option<(Vec<BlockHandle>, Option<Vec<u8>>, u64)> {
+        let mut cursor = IndexCursor { data, pos: 0 };
+
+        let mut blocks: Vec<BlockHandle> = Vec::new();
+        let mut max_seq = 0;
+        while !cursor.is_empty() {
+            let key_len = cursor.read_u32()? as usize;
+            let offset = cursor.read_u64()?;
+            let seq = if version >= 2 { cursor.read_u64()? } else { 0 };
+            let key = cursor.read_bytes(key_len)?.to_vec();
+
+            // Entries were written in key order, so offsets only grow
+            let min_offset = blocks.last().map_or(HEADER_SIZE, |prev| prev.offset + 1);
+            if offset < min_offset || offset >= index_offset {
+                return None;
+            }
+            if let Some(prev) = blocks.last_mut() {
+                prev.len = offset - prev.offset;
+            }
+
+            max_seq = max_seq.max(seq);
+            blocks.push(BlockHandle {
+                first_key: key,
+                offset,
+                len: index_offset - offset,
+                legacy_seq: Some(seq),
+                format: EntryFormat { timestamps: false, value_crcs: false },
+            });
+        }
+
+        let max_key = blocks.last().map(|last| last.first_key.clone());
+        Some((blocks, max_key, max_seq))
+    }
+
+    /// Get a value by key — binary search of the block index, then one block read
     ///
     /// Returns:
     /// - `Ok(Some(value))` — key found with value
     /// - `Ok(None)` — key found but is a tombstone (deleted)
     /// - `Err(KeyNotFound)` — key not in this SSTable
+    ///
+    /// In a file written with value checksums, a value that doesn't match
+    /// its CRC fails with `AtlasError::Storage`; other keys still read.
     pub fn get(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>> {
-        // O(log n) lookup in BTreeMap
-        let offset = match self.index.get(key) {
-            Some(&off) => off,
-            None => return Err(AtlasError::KeyNotFound),
-        };
+        match self.get_with_seq(key)? {
+            Some((value, _)) => Ok(value),
+            None => Err(AtlasError::KeyNotFound),
+        }
+    }
 
-        // Seek directly to the entry
-        self.file.seek(SeekFrom::Start(offset))?;
+    /// Get a key's entry along with its sequence number
+    ///
+    /// `Ok(None)` if the key is not in this SSTable; otherwise the value
+    /// (`None` for a tombstone) and the seq of the write that produced it.
+    pub fn get_with_seq(&mut self, key: &[u8]) -> Result<Option<(Option<Vec<u8>>, u64)>> {
+        Ok(self.get_with_meta(key)?.map(|(value, meta)| (value, meta.seq)))
+    }
 
-        // Read entry header
-        let mut header = [0u8; 8];
-        self.file.read_exact(&mut header)?;
+    /// Get a key's entry along with its seq and write timestamp
+    ///
+    /// Like `get_with_seq`; the timestamp is 0 in files older than version 4.
+    pub fn get_with_meta(&mut self, key: &[u8]) -> Result<Option<(Option<Vec<u8>>, EntryMeta)>> {
+        if !self.might_contain(key) {
+            return Ok(None);
+        }
 
-        let key_len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
-        let val_len = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        // Last block whose first key is <= the target
+        let i = first_block_after(&*self.comparator, &self.blocks, key);
+        let handle = &self.blocks[i - 1];
 
-        // Skip the key (we already know it matches)
-        self.file.seek(SeekFrom::Current(key_len as i64))?;
+        // Open checked the index against the footer, but the file can still
+        // be cut short later; name the key and offset rather than surface
+        // a bare EOF
+        if handle.offset + handle.len > self.data_end {
+            return Err(self.bad_block(key, handle, "past the data region"));
+        }
+        let block = read_block(&mut self.file, handle).map_err(|e| match e {
+            AtlasError::Io(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                self.bad_block(key, handle, "past the end of the file")
+            }
+            e => e,
+        })?;
+        block.get(&*self.comparator, key)
+    }
 
-        // Check for tombstone
-        if val_len == TOMBSTONE_MARKER {
-            return Ok(None);
+    /// Locate a key's value bytes in the file without reading them
+    ///
+    /// Returns the value's `(offset, len)` within the file, for positioned
+    /// reads through [`file`](Self::file) that copy it out in chunks.
+    /// Like `get`: `Ok(None)` for a tombstone, `Err(KeyNotFound)` if the
+    /// key isn't in this SSTable. Only entry headers and keys are read,
+    /// with positioned reads, so no `&mut self` is needed. The value isn't
+    /// checked; with [`has_value_checksums`](Self::has_value_checksums), its
+    /// CRC32 follows it at `offset + len`.
+    pub fn value_location(&self, key: &[u8]) -> Result<Option<(u64, u32)>> {
+        if !self.might_contain(key) {
+            return Err(AtlasError::KeyNotFound);
         }
 
-        // Read value
-        let mut value = vec![0u8; val_len as usize];
-        self.file.read_exact(&mut value)?;
+        let i = first_block_after(&*self.comparator, &self.blocks, key);
+        let handle = &self.blocks[i - 1];
+        if handle.offset + handle.len > self.data_end {
+            return Err(self.bad_block(key, handle, "past the data region"));
+        }
+
+        let file = self.file.get_ref();
+        let location = match handle.legacy_seq {
+            Some(_) => locate_legacy_value(file, handle.offset, handle.len, &*self.comparator, key),
+            None => locate_value(
+                file,
+                handle.offset,
+                handle.len,
+                handle.format,
+                &*self.comparator,
+                key,
+            ),
+        };
+        let location = location.map_err(|e| match e {
+            AtlasError::Io(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                self.bad_block(key, handle, "past the end of the file")
+            }
+            e => e,
+        })?;
+        location.ok_or(AtlasError::KeyNotFound)
+    }
 
-        Ok(Some(value))
+    /// Check whether this SSTable holds `key`, without reading its value
+    ///
+    /// `Some(true)` for a value, `Some(false)` for a tombstone, `None` if the
+    /// key isn't in this SSTable. Found like `value_location`, from entry
+    /// headers and keys alone, so a large value costs no more than a small one.
+    pub fn contains(&self, key: &[u8]) -> Result<Option<bool>> {
+        match self.value_location(key) {
+            Ok(location) => Ok(Some(location.is_some())),
+            Err(AtlasError::KeyNotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The open SSTable file, for positioned reads (e.g. of a
+    /// [`value_location`](Self::value_location))
+    ///
+    /// Seeking it would disturb the reader's own buffered reads; use
+    /// positioned reads only (`FileExt::read_at` on Unix).
+    pub fn file(&self) -> &File {
+        self.file.get_ref()
+    }
+
+    /// Error for a block handle that can't be read in full
+    fn bad_block(&self, key: &[u8], handle: &BlockHandle, why: &str) -> AtlasError {
+        AtlasError::Storage(format!(
+            "{}: block for key '{}' at offset {} (len {}) runs {}",
+            self.path.display(),
+            key.escape_ascii(),
+            handle.offset,
+            handle.len,
+            why
+        ))
+    }
+
+    /// Whether every value in this file is followed by its CRC32
+    pub fn has_value_checksums(&self) -> bool {
+        self.blocks.first().is_some_and(|block| block.format.value_crcs)
+    }
+
+    /// Get the highest sequence number in this SSTable (0 if empty)
+    pub fn max_seq(&self) -> u64 {
+        self.max_seq
+    }
+
+    /// Get the number of data blocks
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
     }
 
     /// Get entry count
@@ -151,27 +581,162 @@ impl SSTableReader {
         self.entry_count
     }
 
+    /// Get the path this SSTable was opened from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the total file size in bytes
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
     /// Get the minimum key in this SSTable (for range filtering)
     pub fn min_key(&self) -> Option<&[u8]> {
-        self.index.keys().next().map(|k| k.as_slice())
+        self.blocks.first().map(|block| block.first_key.as_slice())
     }
 
     /// Get the maximum key in this SSTable (for range filtering)
     pub fn max_key(&self) -> Option<&[u8]> {
-        self.index.keys().next_back().map(|k| k.as_slice())
+        self.max_key.as_deref()
     }
 
     /// Quick check if a key might be in this SSTable (range check)
     /// Returns false only if the key is definitely outside [min_key, max_key]
     pub fn might_contain(&self, key: &[u8]) -> bool {
         match (self.min_key(), self.max_key()) {
-            (Some(min), Some(max)) => key >= min && key <= max,
+            (Some(min), Some(max)) => {
+                let comparator = &self.comparator;
+                comparator.compare(key, min).is_ge() && comparator.compare(key, max).is_le()
+            }
+            _ => false, // Empty SSTable
+        }
+    }
+
+    /// Quick check if any key in a range might be in this SSTable
+    ///
+    /// Returns false only if `[min_key, max_key]` lies wholly outside the
+    /// range (or the SSTable is empty), so range scans can skip the file.
+    pub fn overlaps(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> bool {
+        match (self.min_key(), self.max_key()) {
+            (Some(min), Some(max)) => {
+                let comparator = &*self.comparator;
+                !before_start(comparator, max, start) && !past_end(comparator, min, end)
+            }
             _ => false, // Empty SSTable
         }
     }
 
     /// Create an iterator over all entries (for compaction, debugging)
     pub fn iter(&mut self) -> Result<SSTableIterator<'_>> {
-        SSTableIterator::new(&mut self.file, self.index_offset)
+        Ok(SSTableIterator::new(&mut self.file, &self.blocks))
+    }
+
+    /// Create an iterator over all entries with their seqs and timestamps
+    pub fn iter_with_seqs(&mut self) -> Result<impl Iterator<Item = Result<SeqEntry>> + '_> {
+        let mut iter = self.iter()?;
+        Ok(std::iter::from_fn(move || iter.next_entry()))
+    }
+
+    /// Create an iterator over all entries in descending key order
+    pub fn iter_rev(&mut self) -> SSTableRevIterator<'_> {
+        self.range_rev(Bound::Unbounded, Bound::Unbounded)
+    }
+
+    /// Create an iterator over entries within a key range, in ascending order
+    ///
+    /// An inverted range yields nothing.
+    pub fn range(&mut self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> SSTableRangeIterator<'_> {
+        let blocks = blocks_in_range(&*self.comparator, &self.blocks, start, end);
+        SSTableRangeIterator::new(&mut self.file, blocks, &*self.comparator, start, end)
+    }
+
+    /// Create an iterator over entries within a key range, in descending order
+    ///
+    /// An inverted range yields nothing.
+    pub fn range_rev(&mut self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> SSTableRevIterator<'_> {
+        let blocks = blocks_in_range(&*self.comparator, &self.blocks, start, end);
+        SSTableRevIterator::new(&mut self.file, blocks, &*self.comparator, start, end)
+    }
+
+    /// Create an iterator over the keys within a key range, in ascending order
+    ///
+    /// Each key comes with whether it holds a value (false = tombstone).
+    /// Blocks are still read whole, but values are never decoded or copied.
+    /// An inverted range yields nothing.
+    pub fn range_keys(&mut self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> SSTableKeyIterator<'_> {
+        let blocks = blocks_in_range(&*self.comparator, &self.blocks, start, end);
+        SSTableKeyIterator::new(&mut self.file, blocks, &*self.comparator, start, end)
+    }
+}
+
+/// Index of the first block whose first key sorts after `key`
+fn first_block_after(comparator: &dyn Comparator, blocks: &[BlockHandle], key: &[u8]) -> usize {
+    blocks.partition_point(|block| comparator.compare(&block.first_key, key).is_le())
+}
+
+/// The blocks that can hold keys in a range: from the block that could hold
+/// `start` to the last one starting in range (overhanging the range at both ends)
+fn blocks_in_range<'a>(
+    comparator: &dyn Comparator,
+    blocks: &'a [BlockHandle],
+    start: Bound<&[u8]>,
+    end: Bound<&[u8]>,
+) -> &'a [BlockHandle] {
+    if is_empty_range(comparator, start, end) {
+        return &blocks[..0];
+    }
+
+    let first = match start {
+        Bound::Included(key) | Bound::Excluded(key) => {
+            first_block_after(comparator, blocks, key).saturating_sub(1)
+        }
+        Bound::Unbounded => 0,
+    };
+    let last = match end {
+        Bound::Included(key) => first_block_after(comparator, blocks, key),
+        Bound::Excluded(key) => {
+            blocks.partition_point(|block| comparator.compare(&block.first_key, key).is_lt())
+        }
+        Bound::Unbounded => blocks.len(),
+    };
+    &blocks[first..last.max(first)]
+}
+
+/// Read and decode one data block
+pub(super) fn read_block(file: &mut BufReader<File>, handle: &BlockHandle) -> Result<Block> {
+    file.seek(SeekFrom::Start(handle.offset))?;
+    let mut data = vec![0u8; handle.len as usize];
+    file.read_exact(&mut data)?;
+
+    match handle.legacy_seq {
+        Some(seq) => Block::from_legacy_entry(&data, seq),
+        None => Block::decode(data, handle.format),
+    }
+}
+
+/// Bounds-checked reads over the index block (`None` past the end)
+struct IndexCursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> IndexCursor<'a> {
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn read_u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
     }
 }
@@ -7,9 +7,39 @@ use serde::{Deserialize, Serialize};
 
 use crate::{AtlasError, Result};
 
+/// Version of the WAL entry layout
+///
+/// Written as the first byte of every entry's data section, and reported
+/// by the VERSION command so tools can tell which layout a server writes.
+/// Bump it when the layout changes. Version 1 entries (a bincode dump of
+/// `WalEntry`) carry no version byte; they are still read.
+pub const WAL_FORMAT_VERSION: u16 = 2;
+
+/// `WAL_FORMAT_VERSION` as stored in an entry's data section
+const DATA_VERSION: u8 = WAL_FORMAT_VERSION as u8;
+const _: () = assert!(WAL_FORMAT_VERSION <= u8::MAX as u16);
+
 /// Header size: LSN (8) + CRC (4) + Len (4) = 16 bytes
 pub const HEADER_SIZE: usize = 16;
 
+/// Op-type byte for `Operation::Put`
+pub const OP_PUT: u8 = 0x01;
+
+/// Op-type byte for `Operation::Delete`
+pub const OP_DELETE: u8 = 0x02;
+
+/// Op-type byte for `Operation::Merge`
+pub const OP_MERGE: u8 = 0x03;
+
+/// Op-type byte for `Operation::BatchBegin`
+pub const OP_BATCH_BEGIN: u8 = 0x04;
+
+/// Op-type byte for `Operation::BatchCommit`
+pub const OP_BATCH_COMMIT: u8 = 0x05;
+
+/// Op-type byte for `Operation::Checkpoint`
+pub const OP_CHECKPOINT: u8 = 0x06;
+
 /// A single entry in the WAL
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct WalEntry {
@@ -31,6 +61,19 @@ pub enum Operation {
 
     /// Delete a key
     Delete { key: Vec<u8> },
+
+    /// Record a merge operand for a key (combined lazily by a `MergeOperator`)
+    Merge { key: Vec<u8>, operand: Vec<u8> },
+
+    /// Start of an atomic batch of `count` operations
+    BatchBegin { count: u32 },
+
+    /// End of an atomic batch: its operations apply only if this is logged
+    BatchCommit,
+
+    /// Everything up to `flushed_up_to_lsn` is in SSTables; recovery
+    /// replays only what follows the latest checkpoint
+    Checkpoint { flushed_up_to_lsn: u64 },
 }
 
 impl WalEntry {
@@ -51,24 +94,32 @@ impl WalEntry {
     /// ```text
     /// [LSN: 8 bytes][CRC: 4 bytes][Len: 4 bytes][Data: variable]
     /// ```
+    ///
+    /// Data section (all integers little-endian):
+    /// ```text
+    /// [Version: 1][OpType: 1][Timestamp: 8][op fields...]
+    ///   PUT    (0x01): [KeyLen: 4][Key][ValLen: 4][Value]
+    ///   DELETE (0x02): [KeyLen: 4][Key]
+    ///   MERGE  (0x03): [KeyLen: 4][Key][OperandLen: 4][Operand]
+    ///   BATCH_BEGIN  (0x04): [Count: 4]
+    ///   BATCH_COMMIT (0x05): (no fields)
+    ///   CHECKPOINT   (0x06): [FlushedUpTo: 8]
+    /// ```
+    ///
+    /// Version 1 had no version byte: the data section was the bincode
+    /// encoding of the whole entry, starting with its LSN again.
+    /// `deserialize` still reads those, so a WAL written before the
+    /// upgrade replays instead of being truncated as corrupt.
     pub fn serialize(&self) -> Result<Vec<u8>> {
-        // Step 1: Serialize the entry data using bincode
-        let data = bincode::serialize(self).map_err(|e| {
-            AtlasError::Serialization(format!("Failed to serialize WAL entry: {}", e))
-        })?;
+        // Step 1: Encode the data section
+        let data = self.encode_data()?;
 
         let data_len = data.len() as u32;
 
-        // Step 2: Build the buffer for CRC calculation (LSN + Len + Data)
-        let mut crc_buffer = Vec::with_capacity(8 + 4 + data.len());
-        crc_buffer.extend_from_slice(&self.lsn.to_le_bytes()); // LSN: 8 bytes
-        crc_buffer.extend_from_slice(&data_len.to_le_bytes()); // Len: 4 bytes
-        crc_buffer.extend_from_slice(&data);                   // Data: variable
-
-        // Step 3: Compute CRC32 checksum
-        let crc = crc32fast::hash(&crc_buffer);
+        // Step 2: Compute CRC32 checksum over LSN + Len + Data
+        let crc = Self::checksum(self.lsn, &data);
 
-        // Step 4: Build final output: LSN + CRC + Len + Data
+        // Step 3: Build final output: LSN + CRC + Len + Data
         let mut output = Vec::with_capacity(HEADER_SIZE + data.len());
         output.extend_from_slice(&self.lsn.to_le_bytes());      // LSN: 8 bytes
         output.extend_from_slice(&crc.to_le_bytes());           // CRC: 4 bytes
@@ -83,7 +134,7 @@ impl WalEntry {
     /// Returns error if:
     /// - Buffer too small
     /// - CRC mismatch (corruption detected)
-    /// - bincode deserialization fails
+    /// - Data section is malformed or has an unknown version or op type
     pub fn deserialize(bytes: &[u8]) -> Result<Self> {
         // Step 1: Validate minimum size
         if bytes.len() < HEADER_SIZE {
@@ -113,12 +164,7 @@ impl WalEntry {
         let data = &bytes[HEADER_SIZE..HEADER_SIZE + data_len];
 
         // Step 5: Recompute CRC and validate
-        let mut crc_buffer = Vec::with_capacity(8 + 4 + data_len);
-        crc_buffer.extend_from_slice(&lsn.to_le_bytes());
-        crc_buffer.extend_from_slice(&(data_len as u32).to_le_bytes());
-        crc_buffer.extend_from_slice(data);
-
-        let computed_crc = crc32fast::hash(&crc_buffer);
+        let computed_crc = Self::checksum(lsn, data);
 
         if computed_crc != stored_crc {
             return Err(AtlasError::WalCorruption(format!(
@@ -127,43 +173,269 @@ impl WalEntry {
             )));
         }
 
-        // Step 6: Deserialize the data section
-        let entry: WalEntry = bincode::deserialize(data).map_err(|e| {
-            AtlasError::WalCorruption(format!("Failed to deserialize WAL entry: {}", e))
-        })?;
-
-        // Step 7: Sanity check - LSN in header should match LSN in data
-        if entry.lsn != lsn {
-            return Err(AtlasError::WalCorruption(format!(
-                "LSN mismatch: header={}, data={}",
-                lsn, entry.lsn
-            )));
+        // Step 6: Decode the data section. A version 1 entry has no version
+        // byte but repeats its LSN; its first byte can still match, so fall
+        // back to it if the current layout doesn't decode
+        let legacy = data.starts_with(&lsn.to_le_bytes());
+        if data.first() == Some(&DATA_VERSION) {
+            let entry = Self::decode_data(lsn, &data[1..]);
+            if entry.is_ok() || !legacy {
+                return entry;
+            }
+        }
+        if legacy {
+            return Self::decode_legacy(lsn, data);
         }
 
-        Ok(entry)
+        Err(AtlasError::WalCorruption(format!(
+            "Unknown WAL format version {:#04x} for LSN {}",
+            data.first().copied().unwrap_or_default(),
+            lsn
+        )))
     }
 
     /// Get the total serialized size of this entry (without actually serializing)
     /// Only used for testing (maybe we add some marker to signify this)
     pub fn serialized_size(&self) -> Result<usize> {
-        let data_size = bincode::serialized_size(self).map_err(|e| {
-            AtlasError::Serialization(format!("Failed to compute size: {}", e))
-        })? as usize;
+        let op_size = match &self.operation {
+            Operation::Put { key, value } => 4 + key.len() + 4 + value.len(),
+            Operation::Delete { key } => 4 + key.len(),
+            Operation::Merge { key, operand } => 4 + key.len() + 4 + operand.len(),
+            Operation::BatchBegin { .. } => 4,
+            Operation::BatchCommit => 0,
+            Operation::Checkpoint { .. } => 8,
+        };
 
-        Ok(HEADER_SIZE + data_size)
+        Ok(HEADER_SIZE + 1 + 1 + 8 + op_size)
     }
 
     /// Compute CRC32 for this entry (for external use)
     pub fn compute_crc(&self) -> Result<u32> {
-        let data = bincode::serialize(self).map_err(|e| {
-            AtlasError::Serialization(format!("Failed to serialize: {}", e))
+        let data = self.encode_data()?;
+        Ok(Self::checksum(self.lsn, &data))
+    }
+
+    // =========================================================================
+    // Private Helpers
+    // =========================================================================
+
+    /// CRC32 over LSN (8) + Len (4) + Data
+    fn checksum(lsn: u64, data: &[u8]) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&lsn.to_le_bytes());
+        hasher.update(&(data.len() as u32).to_le_bytes());
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    /// Encode the data section: version, op type, timestamp, then op fields
+    fn encode_data(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(self.serialized_size()? - HEADER_SIZE);
+        data.push(DATA_VERSION);
+
+        match &self.operation {
+            Operation::Put { key, value } => {
+                data.push(OP_PUT);
+                data.extend_from_slice(&self.timestamp.to_le_bytes());
+                Self::encode_field(&mut data, key)?;
+                Self::encode_field(&mut data, value)?;
+            }
+            Operation::Delete { key } => {
+                data.push(OP_DELETE);
+                data.extend_from_slice(&self.timestamp.to_le_bytes());
+                Self::encode_field(&mut data, key)?;
+            }
+            Operation::Merge { key, operand } => {
+                data.push(OP_MERGE);
+                data.extend_from_slice(&self.timestamp.to_le_bytes());
+                Self::encode_field(&mut data, key)?;
+                Self::encode_field(&mut data, operand)?;
+            }
+            Operation::BatchBegin { count } => {
+                data.push(OP_BATCH_BEGIN);
+                data.extend_from_slice(&self.timestamp.to_le_bytes());
+                data.extend_from_slice(&count.to_le_bytes());
+            }
+            Operation::BatchCommit => {
+                data.push(OP_BATCH_COMMIT);
+                data.extend_from_slice(&self.timestamp.to_le_bytes());
+            }
+            Operation::Checkpoint { flushed_up_to_lsn } => {
+                data.push(OP_CHECKPOINT);
+                data.extend_from_slice(&self.timestamp.to_le_bytes());
+                data.extend_from_slice(&flushed_up_to_lsn.to_le_bytes());
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Append a u32 length-prefixed field
+    fn encode_field(data: &mut Vec<u8>, field: &[u8]) -> Result<()> {
+        let len = u32::try_from(field.len()).map_err(|_| {
+            AtlasError::Serialization(format!(
+                "WAL field too large: {} bytes (max {})",
+                field.len(),
+                u32::MAX
+            ))
         })?;
+        data.extend_from_slice(&len.to_le_bytes());
+        data.extend_from_slice(field);
+        Ok(())
+    }
 
-        let mut crc_buffer = Vec::with_capacity(8 + 4 + data.len());
-        crc_buffer.extend_from_slice(&self.lsn.to_le_bytes());
-        crc_buffer.extend_from_slice(&(data.len() as u32).to_le_bytes());
-        crc_buffer.extend_from_slice(&data);
+    /// Decode the data section (past its version byte) of an entry whose
+    /// CRC has been validated
+    fn decode_data(lsn: u64, data: &[u8]) -> Result<Self> {
+        let mut cursor = DataCursor { data, pos: 0, lsn };
 
-        Ok(crc32fast::hash(&crc_buffer))
+        let op_type = cursor.read_u8()?;
+        let timestamp = cursor.read_u64()?;
+
+        let operation = match op_type {
+            OP_PUT => {
+                let key = cursor.read_field()?;
+                let value = cursor.read_field()?;
+                Operation::Put { key, value }
+            }
+            OP_DELETE => {
+                let key = cursor.read_field()?;
+                Operation::Delete { key }
+            }
+            OP_MERGE => {
+                let key = cursor.read_field()?;
+                let operand = cursor.read_field()?;
+                Operation::Merge { key, operand }
+            }
+            OP_BATCH_BEGIN => {
+                let count = cursor.read_u32()?;
+                Operation::BatchBegin { count }
+            }
+            OP_BATCH_COMMIT => Operation::BatchCommit,
+            OP_CHECKPOINT => {
+                let flushed_up_to_lsn = cursor.read_u64()?;
+                Operation::Checkpoint { flushed_up_to_lsn }
+            }
+            other => {
+                return Err(AtlasError::WalCorruption(format!(
+                    "Unknown op type {:#04x} for LSN {}",
+                    other, lsn
+                )));
+            }
+        };
+
+        if cursor.pos != data.len() {
+            return Err(AtlasError::WalCorruption(format!(
+                "Trailing {} bytes in entry for LSN {}",
+                data.len() - cursor.pos,
+                lsn
+            )));
+        }
+
+        Ok(WalEntry {
+            lsn,
+            operation,
+            timestamp,
+        })
+    }
+
+    /// Decode a version 1 data section: bincode's default encoding of
+    /// `WalEntry`, whose operations were only PUT and DELETE
+    ///
+    /// ```text
+    /// [LSN: 8][Variant: 4][op fields...][Timestamp: 8]
+    ///   PUT    (0): [KeyLen: 8][Key][ValLen: 8][Value]
+    ///   DELETE (1): [KeyLen: 8][Key]
+    /// ```
+    fn decode_legacy(lsn: u64, data: &[u8]) -> Result<Self> {
+        let mut cursor = DataCursor { data, pos: 0, lsn };
+
+        let data_lsn = cursor.read_u64()?;
+        if data_lsn != lsn {
+            return Err(AtlasError::WalCorruption(format!(
+                "LSN mismatch: header={}, data={}",
+                lsn, data_lsn
+            )));
+        }
+
+        let operation = match cursor.read_u32()? {
+            0 => {
+                let key = cursor.read_legacy_field()?;
+                let value = cursor.read_legacy_field()?;
+                Operation::Put { key, value }
+            }
+            1 => {
+                let key = cursor.read_legacy_field()?;
+                Operation::Delete { key }
+            }
+            other => {
+                return Err(AtlasError::WalCorruption(format!(
+                    "Unknown version 1 operation {} for LSN {}",
+                    other, lsn
+                )));
+            }
+        };
+        let timestamp = cursor.read_u64()?;
+
+        if cursor.pos != data.len() {
+            return Err(AtlasError::WalCorruption(format!(
+                "Trailing {} bytes in entry for LSN {}",
+                data.len() - cursor.pos,
+                lsn
+            )));
+        }
+
+        Ok(WalEntry {
+            lsn,
+            operation,
+            timestamp,
+        })
+    }
+}
+
+/// Bounds-checked reader over an entry's data section
+struct DataCursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+    lsn: u64,
+}
+
+impl DataCursor<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        if len > self.data.len() - self.pos {
+            return Err(AtlasError::WalCorruption(format!(
+                "Entry data too short for LSN {}: need {} bytes at offset {}, have {}",
+                self.lsn,
+                len,
+                self.pos,
+                self.data.len() - self.pos
+            )));
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
     }
-}
\ No newline at end of file
+
+    fn read_field(&mut self) -> Result<Vec<u8>> {
+        let len = self.read_u32()? as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    /// A version 1 field: bincode's u64 length prefix
+    fn read_legacy_field(&mut self) -> Result<Vec<u8>> {
+        let len = usize::try_from(self.read_u64()?).unwrap_or(usize::MAX);
+        Ok(self.take(len)?.to_vec())
+    }
+}
@@ -22,13 +22,24 @@
 //! │ └─────────┴─────────┴────────┴────────┘ │
 //! └─────────────────────────────────────────┘
 //! ```
+//!
+//! Data is a hand-rolled encoding (version byte, op-type byte, timestamp,
+//! length-prefixed fields) rather than a struct dump, so new `Operation`
+//! variants can be added without breaking old logs. Entries from before it
+//! (bincode, version 1) are still read. See `WalEntry::serialize`.
+//!
+//! With `Config::wal_segment_bytes` set, the log is split across segment
+//! files (`wal.log`, `wal.log.1`, ...), each ending on an entry boundary.
 
 mod entry;
 mod writer;
 mod reader;
 mod recovery;
 
-pub use entry::{WalEntry, Operation, HEADER_SIZE};
-pub use writer::WalWriter;
+pub use entry::{
+    WalEntry, Operation, HEADER_SIZE, OP_PUT, OP_DELETE, OP_MERGE, OP_BATCH_BEGIN,
+    OP_BATCH_COMMIT, OP_CHECKPOINT, WAL_FORMAT_VERSION,
+};
+pub use writer::{SegmentInfo, WalWriter};
 pub use reader::WalReader;
 pub use recovery::{WalRecovery, RecoveryResult};
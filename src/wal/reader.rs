@@ -3,32 +3,107 @@
 //! Handles reading entries from the WAL file sequentially.
 //!
 //! Used during recovery to replay entries from the WAL back into the MemTable.
+//!
+//! A preallocated WAL (`Config::wal_preallocate_bytes`) is zero-filled past
+//! its last entry. No entry starts with zeros (LSNs start at 1), so the
+//! first all-zero header marks the logical end of the log: the reader stops
+//! there as it would at end of file.
+//!
+//! A log split into segments (`Config::wal_segment_bytes`) is read as one:
+//! the reader moves on from `wal.log` to `wal.log.1`, `wal.log.2`, and so on
+//! while they exist. Offsets count through the segments laid end to end.
 
-use std::{fs::File, io::Read, path::Path};
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+};
 
 use crate::{error::Result, wal::HEADER_SIZE};
 use super::WalEntry;
 
+/// Path of segment `index` of the log whose first segment is `path`
+///
+/// Segment 0 is `path` itself; later ones append `.1`, `.2`, ...
+pub(super) fn segment_path(path: &Path, index: u32) -> PathBuf {
+    if index == 0 {
+        return path.to_path_buf();
+    }
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Paths and sizes of the segments after the first that exist, oldest first
+///
+/// Stops at the first index with no file: segments are created in order
+/// and removed newest first, so there are no gaps.
+pub(super) fn later_segments(path: &Path) -> Result<Vec<(PathBuf, u64)>> {
+    let mut segments = Vec::new();
+    for index in 1.. {
+        let segment = segment_path(path, index);
+        match fs::metadata(&segment) {
+            Ok(metadata) => segments.push((segment, metadata.len())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => break,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(segments)
+}
+
 /// Reads entries from the WAL file sequentially
 pub struct WalReader {
-    file: File,
+    file: BufReader<File>,
+    /// Segments still to read after the open one, with their sizes
+    later_segments: VecDeque<(PathBuf, u64)>,
+    /// Byte offset where the open segment ends
+    segment_end: u64,
+    /// Byte offset of the next entry to read
     position: u64,
+    /// Logical end of the log: the size of every segment, until a zero
+    /// tail is found in the last one
     file_size: u64,
+    /// Byte offset of the most recently returned entry
+    last_entry_offset: u64,
+    /// LSN from the header of the entry last read (or attempted)
+    last_header_lsn: Option<u64>,
 }
 
 impl WalReader {
-    /// Open a WAL file for reading
+    /// Open a WAL file for reading, along with any later segments
     pub fn open(path: &Path) -> Result<Self> {
+        let later_segments = later_segments(path)?;
+        let mut reader = Self::open_segment(path)?;
+        reader.file_size += later_segments.iter().map(|(_, size)| size).sum::<u64>();
+        reader.later_segments = later_segments.into();
+        Ok(reader)
+    }
+
+    /// Open a single segment file for reading, ignoring any after it
+    pub(super) fn open_segment(path: &Path) -> Result<Self> {
         let file = File::open(path)?;
         let file_size = file.metadata()?.len();
         
         Ok(Self {
-            file,
+            file: BufReader::new(file),
+            later_segments: VecDeque::new(),
+            segment_end: file_size,
             position: 0,
             file_size,
+            last_entry_offset: 0,
+            last_header_lsn: None,
         })
     }
 
+    /// Set the capacity of the read buffer in bytes
+    ///
+    /// Call before reading: anything already buffered is dropped.
+    pub fn with_buffer_size(mut self, bytes: usize) -> Self {
+        self.file = BufReader::with_capacity(bytes, self.file.into_inner());
+        self
+    }
+
     /// Read the next entry from the WAL
     ///
     /// Returns:
@@ -36,25 +111,43 @@ impl WalReader {
     /// - `Ok(None)` - Reached EOF or incomplete entry (safe for recovery)
     /// - `Err(...)` - I/O error or corruption detected
     pub fn next_entry(&mut self) -> Result<Option<WalEntry>> {
-        // Step 1: Check EOF
-        if self.position >= self.file_size {
-            return Ok(None);
-        }
-
-        // Step 2: Ensure we can read full header
-        if self.position + HEADER_SIZE as u64 > self.file_size {
-            return Ok(None); // Partial write at EOF
-        }
-
-        // Step 3: Read header (16 bytes)
-        let mut header = [0u8; HEADER_SIZE];
-        self.file.read_exact(&mut header)?;
-
-        // Step 4: Parse data length from header
+        self.last_header_lsn = None;
+
+        let header = loop {
+            // Step 1: Check EOF, moving on to the next segment at the end of one
+            while self.position >= self.segment_end {
+                if !self.next_segment()? {
+                    return Ok(None);
+                }
+            }
+
+            // Step 2: Ensure we can read full header. Entries never span
+            // segments, so one cut short is a partial write.
+            if self.position + HEADER_SIZE as u64 > self.segment_end {
+                return Ok(None); // Partial write at EOF
+            }
+
+            // Step 3: Read header (16 bytes). A zero header is the
+            // preallocated tail, not an entry: the segment ends here, and
+            // with the last one the log.
+            let mut header = [0u8; HEADER_SIZE];
+            self.file.read_exact(&mut header)?;
+            if header != [0u8; HEADER_SIZE] {
+                break header;
+            }
+            if self.later_segments.is_empty() {
+                self.file_size = self.position;
+                return Ok(None);
+            }
+            self.position = self.segment_end;
+        };
+
+        // Step 4: Parse LSN and data length from header
+        self.last_header_lsn = Some(u64::from_le_bytes(header[0..8].try_into().unwrap()));
         let data_len = u32::from_le_bytes(header[12..16].try_into().unwrap()) as usize;
 
         // Step 5: Validate complete entry exists
-        if self.position + HEADER_SIZE as u64 + data_len as u64 > self.file_size {
+        if self.position + HEADER_SIZE as u64 + data_len as u64 > self.segment_end {
             return Ok(None); // Partial write at EOF
         }
 
@@ -69,18 +162,62 @@ impl WalReader {
 
         let entry = WalEntry::deserialize(&full_buffer)?;
 
-        // Step 8: Advance position
+        // Step 8: Record where this entry started, then advance position
+        self.last_entry_offset = self.position;
         self.position += (HEADER_SIZE + data_len) as u64;
 
         // Step 9: Return entry
         Ok(Some(entry))
     }
 
+    /// Open the segment after the one just read (false if it was the last)
+    ///
+    /// Earlier segments were synced whole before the next was started, so
+    /// they hold no partial entries.
+    fn next_segment(&mut self) -> Result<bool> {
+        let Some((path, size)) = self.later_segments.pop_front() else {
+            return Ok(false);
+        };
+        self.file = BufReader::with_capacity(self.file.capacity(), File::open(path)?);
+        self.position = self.segment_end;
+        self.segment_end += size;
+        Ok(true)
+    }
+
     /// Check if the reader has reached the end of the file
     pub fn is_at_eof(&self) -> bool {
         self.position >= self.file_size
     }
 
+    /// Byte offset of the next entry to read
+    ///
+    /// After `next_entry` stops at a partial or corrupt entry, this is
+    /// where that entry begins (nothing past it was consumed).
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Size of the WAL file (all its segments) when it was opened, or its
+    /// logical end once reading has reached a preallocated zero tail
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Byte offset of the entry most recently returned by `next_entry`
+    /// (0 if none has been returned yet)
+    pub fn last_entry_offset(&self) -> u64 {
+        self.last_entry_offset
+    }
+
+    /// LSN claimed by the header of the entry last read
+    ///
+    /// After `next_entry` stops at a partial or corrupt entry, this is the
+    /// LSN its header holds, or `None` if the header itself was cut short.
+    /// It is not covered by a verified CRC, so treat it as a hint.
+    pub fn last_header_lsn(&self) -> Option<u64> {
+        self.last_header_lsn
+    }
+
     /// Consume reader and return an iterator over all valid entries
     pub fn entries(self) -> WalIterator {
         WalIterator { reader: self }
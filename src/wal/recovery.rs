@@ -4,7 +4,7 @@
 
 use std::path::Path;
 use crate::{AtlasError, error::Result, wal::WalReader};
-use super::WalEntry;
+use super::{Operation, WalEntry};
 
 /// Handles WAL recovery after crash
 pub struct WalRecovery {
@@ -14,6 +14,9 @@ pub struct WalRecovery {
 #[derive(Debug)]
 pub struct RecoveryResult {
     /// Number of entries successfully recovered
+    ///
+    /// Counts only entries after the latest checkpoint; those before it
+    /// are already flushed.
     pub entries_recovered: u64,
 
     /// Number of corrupted entries skipped
@@ -22,8 +25,41 @@ pub struct RecoveryResult {
     /// Last valid LSN
     pub last_lsn: u64,
 
+    /// LSN covered by the latest checkpoint (None if the log has none)
+    pub checkpoint_lsn: Option<u64>,
+
     /// Whether the WAL was truncated (partial writes removed)
     pub was_truncated: bool,
+
+    /// Byte offset of the first partial/corrupt entry (None if the WAL is clean)
+    ///
+    /// Everything before this offset is valid; it is where the log should
+    /// be cut. A batch missing its commit marker counts as partial, so this
+    /// may point at the batch's begin marker.
+    pub truncate_offset: Option<u64>,
+
+    /// LSN of the first partial/corrupt entry, as its header claims
+    ///
+    /// `None` if the WAL is clean or the header itself was cut short. Not
+    /// CRC-verified: a damaged header can report a bogus LSN.
+    pub first_corrupt_lsn: Option<u64>,
+
+    /// Byte offset of the first partial/corrupt entry (None if the WAL is clean)
+    ///
+    /// Unlike `truncate_offset`, never moved back to an uncommitted
+    /// batch's begin marker; a batch that simply ends without its commit
+    /// is not corruption and leaves this `None`.
+    pub first_corrupt_offset: Option<u64>,
+}
+
+/// A batch whose commit marker hasn't been read yet
+struct PendingBatch {
+    /// Byte offset of its `BatchBegin` entry
+    start: u64,
+    /// Operation count from the begin marker
+    count: u32,
+    /// Operations read so far
+    entries: Vec<WalEntry>,
 }
 
 impl WalRecovery {
@@ -34,93 +70,154 @@ impl WalRecovery {
     /// 2. Detect and skip corrupted entries
     /// 3. Truncate partial writes at end
     /// 4. Return all valid entries in order
+    ///
+    /// Batch markers are consumed here: a committed batch's operations are
+    /// returned like any others, and a batch without its commit marker is
+    /// dropped whole. Entries before the latest checkpoint are already
+    /// flushed and are left out, as is the checkpoint itself.
     pub fn recover(path: &Path) -> Result<(Vec<WalEntry>, RecoveryResult)> {
-        let mut reader = WalReader::open(path)?;
+        Self::recover_from(WalReader::open(path)?)
+    }
 
+    /// [`recover`](Self::recover) through an already-opened reader
+    ///
+    /// For callers that configure the reader first (e.g. its buffer size).
+    pub fn recover_from(reader: WalReader) -> Result<(Vec<WalEntry>, RecoveryResult)> {
         let mut entries: Vec<WalEntry> = Vec::new();
-        let mut entries_recovered: u64 = 0;
-        let mut entries_corrupted: u64 = 0;
-        let mut last_lsn: u64 = 0;
-        let mut was_truncated = false;
-
-        loop {
-            match reader.next_entry() {
-                Ok(Some(entry)) => {
-                    // Valid entry — track LSN and collect
-                    last_lsn = entry.lsn;
-                    entries_recovered += 1;
-                    entries.push(entry);
-                }
-                Ok(None) => {
-                    // Partial write at tail means the WAL needs truncation
-                    if !reader.is_at_eof() {
-                        was_truncated = true;
-                    }
-                    break;
-                }
-                Err(e) => match e {
-                    // CRC mismatch — data is corrupt, stop here
-                    AtlasError::WalCorruption(_) => {
-                        entries_corrupted += 1;
-                        was_truncated = true;
-                        break;
-                    }
-                    // I/O errors propagate up — not a recovery concern
-                    _ => return Err(e),
-                },
-            }
-        }
-
-        let result = RecoveryResult {
-            entries_recovered,
-            entries_corrupted,
-            last_lsn,
-            was_truncated,
-        };
-
+        let result = Self::replay(reader, |entry| match entry.operation {
+            Operation::Checkpoint { .. } => entries.clear(),
+            _ => entries.push(entry),
+        })?;
         Ok((entries, result))
     }
 
+    /// Recover a WAL file, calling `f` for each valid entry in order
+    ///
+    /// Stops at the same point as [`recover`](Self::recover) but doesn't
+    /// collect the entries: memory is bounded by the largest batch, whose
+    /// entries are held until its commit marker is read. That suits tailing
+    /// the log into a derived structure (e.g. a secondary index). Checkpoints are passed
+    /// to `f` as they are read, since what came before has already been.
+    pub fn recover_with<F>(path: &Path, mut f: F) -> Result<RecoveryResult>
+    where
+        F: FnMut(&WalEntry),
+    {
+        Self::replay(WalReader::open(path)?, |entry| f(&entry))
+    }
+
     /// Verify integrity of a WAL file without modifying it
     ///
     /// Same logic as recover() but discards the entries — only returns stats.
     pub fn verify(path: &Path) -> Result<RecoveryResult> {
-        let mut reader = WalReader::open(path)?;
+        Self::replay(WalReader::open(path)?, drop)
+    }
+
+    /// Read valid entries until the end of the log or the first bad entry
+    ///
+    /// Operations inside a batch are held back until its commit marker is
+    /// read; batch markers themselves are not passed to `f`, checkpoints are.
+    fn replay<F>(mut reader: WalReader, mut f: F) -> Result<RecoveryResult>
+    where
+        F: FnMut(WalEntry),
+    {
 
         let mut entries_recovered: u64 = 0;
         let mut entries_corrupted: u64 = 0;
         let mut last_lsn: u64 = 0;
-        let mut was_truncated = false;
+        let mut checkpoint_lsn: Option<u64> = None;
+        let mut truncate_offset: Option<u64> = None;
+        let mut first_corrupt_lsn: Option<u64> = None;
+        let mut pending: Option<PendingBatch> = None;
 
         loop {
+            let offset = reader.position();
             match reader.next_entry() {
-                Ok(Some(entry)) => {
-                    // Use the actual LSN from the entry, not a counter
-                    last_lsn = entry.lsn;
-                    entries_recovered += 1;
-                }
+                Ok(Some(entry)) => match (&entry.operation, &mut pending) {
+                    (Operation::BatchBegin { count }, None) => {
+                        pending = Some(PendingBatch {
+                            start: offset,
+                            count: *count,
+                            entries: Vec::new(),
+                        });
+                    }
+                    (Operation::BatchCommit, Some(batch))
+                        if batch.entries.len() == batch.count as usize =>
+                    {
+                        // Committed — release the batch, tracking the marker's LSN
+                        last_lsn = entry.lsn;
+                        for op in pending.take().unwrap().entries {
+                            entries_recovered += 1;
+                            f(op);
+                        }
+                    }
+                    (Operation::Checkpoint { flushed_up_to_lsn }, None) => {
+                        // Everything so far is flushed; count from here
+                        last_lsn = last_lsn.max(entry.lsn);
+                        checkpoint_lsn = Some(*flushed_up_to_lsn);
+                        entries_recovered = 0;
+                        f(entry);
+                    }
+                    (
+                        Operation::BatchBegin { .. }
+                        | Operation::BatchCommit
+                        | Operation::Checkpoint { .. },
+                        _,
+                    ) => {
+                        // Nested begin, stray commit, wrong count, or a
+                        // checkpoint mid-batch: the writer never logs these,
+                        // so treat them as corruption
+                        entries_corrupted += 1;
+                        truncate_offset = Some(offset);
+                        first_corrupt_lsn = Some(entry.lsn);
+                        break;
+                    }
+                    (_, Some(batch)) => batch.entries.push(entry),
+                    (_, None) => {
+                        // Valid entry — use the actual LSN from the entry, not a counter
+                        last_lsn = entry.lsn;
+                        entries_recovered += 1;
+                        f(entry);
+                    }
+                },
                 Ok(None) => {
+                    // Partial write at tail means the WAL needs truncation
                     if !reader.is_at_eof() {
-                        was_truncated = true;
+                        truncate_offset = Some(reader.position());
+                        first_corrupt_lsn = reader.last_header_lsn();
                     }
                     break;
                 }
                 Err(e) => match e {
+                    // CRC mismatch — data is corrupt, stop here
                     AtlasError::WalCorruption(_) => {
                         entries_corrupted += 1;
-                        was_truncated = true;
+                        truncate_offset = Some(reader.position());
+                        first_corrupt_lsn = reader.last_header_lsn();
                         break;
                     }
+                    // I/O errors propagate up — not a recovery concern
                     _ => return Err(e),
                 },
             }
         }
 
+        // Found before a pending batch can move the cut back
+        let first_corrupt_offset = truncate_offset;
+
+        // A batch still open never committed: drop it and cut where it began
+        if let Some(batch) = pending {
+            truncate_offset = Some(batch.start);
+        }
+
         Ok(RecoveryResult {
             entries_recovered,
             entries_corrupted,
             last_lsn,
-            was_truncated,
+            checkpoint_lsn,
+            was_truncated: truncate_offset.is_some(),
+            truncate_offset,
+            first_corrupt_lsn,
+            first_corrupt_offset,
         })
     }
 }
@@ -1,19 +1,63 @@
 //! WAL Writer
 //!
 //! Handles appending entries to the WAL file.
+//!
+//! With a segment size set, the log rolls over to a new file (`wal.log.1`,
+//! `wal.log.2`, ...) once the open one would grow past it. Truncation goes
+//! back to the first file and removes the rest.
 
-use std::fs::{File, OpenOptions};
+use std::fs::{self, File, OpenOptions};
 use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
 
-use crate::error::Result;
+use crate::error::{AtlasError, Result};
 use crate::config::WalSyncStrategy;
-use super::{WalEntry, Operation};
+use crate::storage::sync_dir;
+use super::reader::{later_segments, segment_path};
+use super::{WalEntry, WalReader, Operation};
+
+/// One WAL file and the LSNs it holds (see `WalWriter::segments`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SegmentInfo {
+    /// Path of the file
+    pub path: PathBuf,
+    /// LSN of its first entry (None if it holds none)
+    pub first_lsn: Option<u64>,
+    /// LSN of its last entry (None if it holds none)
+    pub last_lsn: Option<u64>,
+    /// Bytes of log in it, entries still in the write buffer included
+    /// (preallocated zeros are not)
+    #[serde(rename = "size_bytes")]
+    pub byte_size: u64,
+}
 
 /// Writes entries to the WAL file
 pub struct WalWriter {
     /// Buffered file writer for performance (batches writes)
     file: BufWriter<File>,
+
+    /// Path of the WAL file (its first segment)
+    path: PathBuf,
+
+    /// Segments before the open one, oldest first (synced, never written again)
+    sealed: Vec<SegmentInfo>,
+
+    /// Index of the segment being written (0 = `path` itself)
+    segment_index: u32,
+
+    /// Bytes in the segment being written, including buffered entries
+    segment_bytes: u64,
+
+    /// Segment size past which the log rolls over to a new file (0 = never)
+    max_segment_bytes: u64,
+
+    /// LSN of the first entry in the open segment (None while it has none)
+    first_lsn: Option<u64>,
+
+    /// LSN of the last entry in the open segment (None while it has none)
+    last_lsn: Option<u64>,
     
     /// Next LSN to assign (auto-increments)
     current_lsn: u64,
@@ -23,11 +67,28 @@ pub struct WalWriter {
     
     /// Count of entries written since last sync
     uncommitted_count: usize,
+
+    /// Bytes in the log, including entries still in the buffer
+    size_bytes: u64,
+
+    /// Log size just past the latest checkpoint (0 if there is none)
+    checkpoint_bytes: u64,
+
+    /// Timestamp of the most recently written entry (0 before the first)
+    last_timestamp: u64,
+
+    /// Size each segment is zero-filled to whenever it starts empty (0 = off)
+    preallocate_bytes: u64,
 }
 
 impl WalWriter {
     /// Open or create a WAL file for writing (truncates - use for fresh start)
+    ///
+    /// Any later segments are removed.
     pub fn open(path: &Path, sync_strategy: WalSyncStrategy) -> Result<Self> {
+        Self::remove_later_segments(path)?;
+        let created = !path.exists();
+
         // Step 1: Open file in write mode, create if doesn't exist, truncate to start fresh
         let file = OpenOptions::new()
             .create(true)      // Create file if it doesn't exist
@@ -35,6 +96,10 @@ impl WalWriter {
             .truncate(true)    // Clear existing content
             .open(path)?;
 
+        if created {
+            Self::sync_parent_dir(path)?;
+        }
+
         // Step 2: Wrap in BufWriter for performance (batches writes in memory)
         let file = BufWriter::new(file);
 
@@ -43,56 +108,180 @@ impl WalWriter {
 
         Ok(WalWriter {
             file,
+            path: path.to_path_buf(),
+            sealed: Vec::new(),
+            segment_index: 0,
+            segment_bytes: 0,
+            max_segment_bytes: 0,
+            first_lsn: None,
+            last_lsn: None,
             current_lsn,
             sync_strategy,
             uncommitted_count: 0,
+            size_bytes: 0,
+            checkpoint_bytes: 0,
+            last_timestamp: 0,
+            preallocate_bytes: 0,
         })
     }
 
     /// Open WAL in append mode (for use after recovery)
     ///
     /// IMPORTANT: Call this after recovery instead of open() to preserve
-    /// the WAL until recovered data is flushed to disk.
+    /// the WAL until recovered data is flushed to disk. Appends go to the
+    /// last segment.
     pub fn open_append(path: &Path, sync_strategy: WalSyncStrategy, next_lsn: u64) -> Result<Self> {
-        // Step 1: Open file in append mode
+        // Step 1: Take stock of the segments before the last one
+        let mut sealed = Vec::new();
+        let mut segment_path = path.to_path_buf();
+        for (later_path, _) in later_segments(path)? {
+            let (first_lsn, last_lsn) = Self::scan_lsns(&segment_path)?;
+            let byte_size = fs::metadata(&segment_path)?.len();
+            sealed.push(SegmentInfo { path: segment_path, first_lsn, last_lsn, byte_size });
+            segment_path = later_path;
+        }
+
+        let created = !segment_path.exists();
+
+        // Step 2: Open the last segment in append mode
         let file = OpenOptions::new()
             .create(true)      // Create file if it doesn't exist
             .append(true)      // Append mode - don't truncate!
-            .open(path)?;
+            .open(&segment_path)?;
+
+        if created {
+            Self::sync_parent_dir(&segment_path)?;
+        }
+        let segment_bytes = file.metadata()?.len();
+        let (first_lsn, last_lsn) = Self::scan_lsns(&segment_path)?;
+        let size_bytes = segment_bytes + sealed.iter().map(|s| s.byte_size).sum::<u64>();
 
-        // Step 2: Wrap in BufWriter
+        // Step 3: Wrap in BufWriter
         let file = BufWriter::new(file);
 
-        // Step 3: Use provided LSN (continue from where recovery left off)
+        // Step 4: Use provided LSN (continue from where recovery left off)
         Ok(WalWriter {
             file,
+            path: path.to_path_buf(),
+            segment_index: sealed.len() as u32,
+            sealed,
+            segment_bytes,
+            max_segment_bytes: 0,
+            first_lsn,
+            last_lsn,
             current_lsn: next_lsn,
             sync_strategy,
             uncommitted_count: 0,
+            size_bytes,
+            checkpoint_bytes: 0,
+            last_timestamp: 0,
+            preallocate_bytes: 0,
         })
     }
 
+    /// First and last LSN of the entries already in one WAL segment
+    ///
+    /// Checkpoint markers carry an earlier entry's LSN, so they're skipped.
+    /// Stops at the first entry that doesn't read back cleanly.
+    fn scan_lsns(path: &Path) -> Result<(Option<u64>, Option<u64>)> {
+        let mut reader = WalReader::open_segment(path)?;
+        let (mut first, mut last) = (None, None);
+        while let Ok(Some(entry)) = reader.next_entry() {
+            if !matches!(entry.operation, Operation::Checkpoint { .. }) {
+                first.get_or_insert(entry.lsn);
+                last = Some(entry.lsn);
+            }
+        }
+        Ok((first, last))
+    }
+
+    /// Set the capacity of the write buffer in bytes
+    ///
+    /// Flushes anything already buffered. Syncs flush the buffer whatever
+    /// its size, so this trades memory for fewer write syscalls only.
+    pub fn with_buffer_size(mut self, bytes: usize) -> Result<Self> {
+        let file = self.file.into_inner().map_err(|e| e.into_error())?;
+        self.file = BufWriter::with_capacity(bytes, file);
+        Ok(self)
+    }
+
+    /// Zero-fill the file to `bytes` now and after every truncation (0 = off)
+    ///
+    /// Entries overwrite the zeros in place, so the file's size (and with it
+    /// its metadata) only changes once the log outgrows them; syncs are
+    /// `fdatasync`s. Readers stop at the zeros (see `WalReader`). Only for
+    /// writers from `open`: an `open_append` writer always writes at the
+    /// end of the file, past the zeros. Each new segment is zero-filled too.
+    pub fn with_preallocation(mut self, bytes: u64) -> Result<Self> {
+        self.preallocate_bytes = bytes;
+        self.preallocate()?;
+        Ok(self)
+    }
+
+    /// Roll over to a new segment file once the open one would pass `bytes`
+    /// (0 = keep one file)
+    ///
+    /// An entry never spans segments, so one larger than `bytes` gets a
+    /// segment of its own. Set it before writing: segments are only
+    /// started as entries are appended.
+    pub fn with_segment_bytes(mut self, bytes: u64) -> Self {
+        self.max_segment_bytes = bytes;
+        self
+    }
+
+    /// Extend the open segment with zeros to `preallocate_bytes`, if it is shorter
+    fn preallocate(&mut self) -> Result<()> {
+        if self.segment_bytes < self.preallocate_bytes {
+            self.file.flush()?;
+            let file = self.file.get_ref();
+            if file.metadata()?.len() < self.preallocate_bytes {
+                file.set_len(self.preallocate_bytes)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Append an entry to the WAL
     ///
     /// Returns the LSN assigned to this entry
     pub fn append(&mut self, operation: Operation) -> Result<u64> {
-        // Step 1: Assign LSN and increment counter
-        let lsn = self.current_lsn;
-        self.current_lsn += 1;
+        Ok(self.append_entry(operation)?.lsn)
+    }
 
-        // Step 2: Create WAL entry with assigned LSN
-        let wal_entry = WalEntry::new(lsn, operation);
+    /// Append an entry to the WAL, returning the entry as logged
+    ///
+    /// Same as `append`, but hands back the LSN and timestamp together with
+    /// the operation, e.g. to forward the entry to replicas.
+    pub fn append_entry(&mut self, operation: Operation) -> Result<WalEntry> {
+        // Step 1: Log the entry
+        let entry = self.write_entry(operation)?;
 
-        // Step 3: Serialize entry
-        let bytes = wal_entry.serialize()?;
+        // Step 2: Sync based on strategy
+        self.sync_by_strategy()?;
 
-        // Step 4: Write to buffer
-        self.file.write_all(&bytes)?;
+        // Step 3: Return the entry with its assigned LSN
+        Ok(entry)
+    }
 
-        // Step 5: Increment uncommitted count
-        self.uncommitted_count += 1;
+    /// Append an entry logged by another writer, keeping its LSN and timestamp
+    ///
+    /// For replicas copying a primary's log. The entry's LSN must be the
+    /// one this writer would assign next; anything else is a gap or a
+    /// replay and returns `AtlasError::WalWrite` without logging it.
+    pub fn append_replicated(&mut self, entry: &WalEntry) -> Result<()> {
+        if entry.lsn != self.current_lsn {
+            return Err(AtlasError::WalWrite(format!(
+                "Replicated entry has LSN {}, expected {}",
+                entry.lsn, self.current_lsn
+            )));
+        }
 
-        // Step 6: Sync based on strategy
+        self.write_logged(entry)?;
+        self.sync_by_strategy()
+    }
+
+    /// Sync if the sync strategy calls for it after an append
+    fn sync_by_strategy(&mut self) -> Result<()> {
         match self.sync_strategy {
             WalSyncStrategy::EveryWrite => {
                 // Flush buffer and fsync immediately (most durable)
@@ -104,10 +293,134 @@ impl WalWriter {
                     self.sync()?;
                 }
             }
+            WalSyncStrategy::NoSync => {}
         }
+        Ok(())
+    }
+
+    /// Append operations as one atomic batch, then sync once
+    ///
+    /// Logs `BatchBegin`, the operations, and `BatchCommit`. Recovery drops
+    /// a batch whose commit marker is missing, so the operations are all
+    /// or nothing. The sync strategy is ignored: the batch is durable when
+    /// this returns. Returns the LSN of the commit marker.
+    pub fn append_batch(&mut self, operations: &[Operation]) -> Result<u64> {
+        let count = u32::try_from(operations.len()).map_err(|_| {
+            AtlasError::WalWrite(format!("Batch too large: {} operations", operations.len()))
+        })?;
+
+        self.write_entry(Operation::BatchBegin { count })?;
+        for operation in operations {
+            self.write_entry(operation.clone())?;
+        }
+        let commit = self.write_entry(Operation::BatchCommit)?;
+
+        self.sync()?;
+        Ok(commit.lsn)
+    }
+
+    /// Log a checkpoint: everything up to the last LSN assigned is flushed
+    ///
+    /// Marks a safe truncation point without truncating; recovery replays
+    /// only what follows the latest checkpoint. The marker takes no LSN of
+    /// its own (its header carries the last LSN logged), so replicas see
+    /// contiguous LSNs. Synced whatever the sync strategy. Returns the
+    /// checkpointed LSN.
+    pub fn checkpoint(&mut self) -> Result<u64> {
+        let flushed_up_to_lsn = self.current_lsn.saturating_sub(1);
+        let entry = WalEntry::new(flushed_up_to_lsn, Operation::Checkpoint { flushed_up_to_lsn });
+        self.write_logged(&entry)?;
+        self.sync()?;
 
-        // Step 7: Return assigned LSN
-        Ok(lsn)
+        self.checkpoint_bytes = self.size_bytes;
+        Ok(flushed_up_to_lsn)
+    }
+
+    /// Write one entry to the buffer without syncing
+    ///
+    /// Returns the entry with its assigned LSN
+    fn write_entry(&mut self, operation: Operation) -> Result<WalEntry> {
+        // Step 1: Create WAL entry with the next LSN
+        let wal_entry = WalEntry::new(self.current_lsn, operation);
+
+        // Step 2: Log it
+        self.write_logged(&wal_entry)?;
+
+        Ok(wal_entry)
+    }
+
+    /// Write an entry whose LSN is `current_lsn` to the buffer without syncing
+    fn write_logged(&mut self, wal_entry: &WalEntry) -> Result<()> {
+        // Step 1: Advance the LSN counter past this entry
+        self.current_lsn = wal_entry.lsn + 1;
+
+        let span = tracing::trace_span!(
+            "wal.append",
+            lsn = wal_entry.lsn,
+            bytes = tracing::field::Empty
+        );
+        let _enter = span.enter();
+
+        // Step 2: Serialize entry
+        self.last_timestamp = wal_entry.timestamp;
+        let bytes = wal_entry.serialize()?;
+        span.record("bytes", bytes.len());
+
+        // Step 3: Start a new segment if this entry would overfill the open one
+        let len = bytes.len() as u64;
+        let limit = self.max_segment_bytes;
+        if limit > 0 && self.segment_bytes > 0 && self.segment_bytes + len > limit {
+            self.roll_segment()?;
+        }
+        if !matches!(wal_entry.operation, Operation::Checkpoint { .. }) {
+            self.first_lsn.get_or_insert(wal_entry.lsn);
+            self.last_lsn = Some(wal_entry.lsn);
+        }
+
+        // Step 4: Write to buffer
+        self.file.write_all(&bytes)?;
+        self.segment_bytes += len;
+        self.size_bytes += len;
+
+        // Step 5: Increment uncommitted count
+        self.uncommitted_count += 1;
+
+        Ok(())
+    }
+
+    /// Seal the open segment and continue in the next one
+    ///
+    /// The sealed segment is synced first, so only the last segment can
+    /// end in a partial entry.
+    fn roll_segment(&mut self) -> Result<()> {
+        self.sync()?;
+        self.sealed.push(self.open_segment_info());
+
+        self.segment_index += 1;
+        let path = segment_path(&self.path, self.segment_index);
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)?;
+        Self::sync_parent_dir(&path)?;
+        self.file = BufWriter::with_capacity(self.file.capacity(), file);
+
+        self.segment_bytes = 0;
+        self.first_lsn = None;
+        self.last_lsn = None;
+        tracing::debug!(segment = %path.display(), "WAL rolled over to a new segment");
+        self.preallocate()
+    }
+
+    /// The segment being written
+    fn open_segment_info(&self) -> SegmentInfo {
+        SegmentInfo {
+            path: segment_path(&self.path, self.segment_index),
+            first_lsn: self.first_lsn,
+            last_lsn: self.last_lsn,
+            byte_size: self.segment_bytes,
+        }
     }
 
     /// Force sync to disk (fsync)
@@ -120,8 +433,13 @@ impl WalWriter {
         // Step 2: Get underlying file handle
         let file = self.file.get_ref();
 
-        // Step 3: Force sync to disk (fsync syscall)
-        file.sync_all()?;
+        // Step 3: Force sync to disk (fsync syscall). A preallocated file
+        // keeps its size, so its data is all that needs syncing.
+        if self.preallocate_bytes > 0 {
+            file.sync_data()?;
+        } else {
+            file.sync_all()?;
+        }
 // Step 4: Reset uncommitted counter
         self.uncommitted_count = 0;
 
@@ -129,6 +447,19 @@ impl WalWriter {
         Ok(())
     }
 
+    /// Get the sync strategy in force
+    pub fn sync_strategy(&self) -> WalSyncStrategy {
+        self.sync_strategy
+    }
+
+    /// Swap the sync strategy for later appends
+    ///
+    /// Entries already written keep whatever durability they had; call
+    /// `sync` first to put them on disk.
+    pub fn set_sync_strategy(&mut self, strategy: WalSyncStrategy) {
+        self.sync_strategy = strategy;
+    }
+
     /// Get the current LSN (next LSN to be assigned)
     pub fn current_lsn(&self) -> u64 {
         self.current_lsn
@@ -139,27 +470,119 @@ impl WalWriter {
         self.uncommitted_count
     }
 
+    /// Get the size of the log in bytes (buffered entries included)
+    pub fn size_bytes(&self) -> u64 {
+        self.size_bytes
+    }
+
+    /// Get the bytes logged since the latest checkpoint
+    ///
+    /// The whole log if there is no checkpoint yet (including whatever an
+    /// `open_append` found in the file).
+    pub fn bytes_since_checkpoint(&self) -> u64 {
+        self.size_bytes - self.checkpoint_bytes
+    }
+
+    /// List the files making up the log, oldest first, with their LSN ranges
+    ///
+    /// Together they cover every entry since the last truncation,
+    /// checkpoint markers aside, and each range picks up where the previous
+    /// one ends. A single segment unless `with_segment_bytes` is set.
+    pub fn segments(&self) -> Vec<SegmentInfo> {
+        let mut segments = self.sealed.clone();
+        segments.push(self.open_segment_info());
+        segments
+    }
+
+    /// Get the unix-millis timestamp of the last entry written (0 if none yet)
+    pub fn last_timestamp(&self) -> u64 {
+        self.last_timestamp
+    }
+
     /// Truncate WAL file (used after MemTable flush)
     ///
-    /// Clears all entries and resets LSN to 1
+    /// Clears all entries (later segments included) and resets LSN to 1
     pub fn truncate(&mut self) -> Result<()> {
+        self.reset(1)
+    }
+
+    /// Truncate WAL file, continuing LSNs from `next_lsn`
+    ///
+    /// The engine uses LSNs as sequence numbers, so it keeps them
+    /// increasing across flushes instead of restarting at 1.
+    pub fn reset(&mut self, next_lsn: u64) -> Result<()> {
         // Step 1: Flush any pending writes
         self.file.flush()?;
 
-        // Step 2: Get mutable reference to underlying file
+        // Step 2: Go back to the first segment and remove the rest. They go
+        // before the first is truncated, so a crash can't leave an empty
+        // first segment followed by stale ones.
+        if self.segment_index > 0 {
+            let file = OpenOptions::new().write(true).open(&self.path)?;
+            self.file = BufWriter::with_capacity(self.file.capacity(), file);
+            Self::remove_later_segments(&self.path)?;
+            self.sealed.clear();
+            self.segment_index = 0;
+        }
+
+        // Step 3: Get mutable reference to underlying file
         let file = self.file.get_mut();
 
-        // Step 3: Truncate file to 0 bytes
+        // Step 4: Truncate file to 0 bytes
         file.set_len(0)?;
 
-        // Step 4: Seek to start (though file is empty)
+        // Step 5: Seek to start (though file is empty)
         use std::io::Seek;
         file.seek(std::io::SeekFrom::Start(0))?;
 
-        // Step 5: Reset LSN counter and uncommitted count
-        self.current_lsn = 1;
+        // Step 6: Reset LSN counter, uncommitted count, and sizes
+        self.current_lsn = next_lsn;
         self.uncommitted_count = 0;
+        self.size_bytes = 0;
+        self.segment_bytes = 0;
+        self.checkpoint_bytes = 0;
+        self.first_lsn = None;
+        self.last_lsn = None;
+
+        // Step 7: Zero-fill it again if preallocating
+        self.preallocate()?;
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Claim the next LSN without logging an entry
+    ///
+    /// For writes that bypass the log (bulk loads) but still need a
+    /// sequence number.
+    pub fn reserve_lsn(&mut self) -> u64 {
+        let lsn = self.current_lsn;
+        self.current_lsn += 1;
+        lsn
+    }
+
+    /// Remove every segment after the first, newest first, and sync that
+    ///
+    /// Removing newest first keeps the remaining segments contiguous if
+    /// this is cut short; the sync keeps them from coming back after a crash.
+    fn remove_later_segments(path: &Path) -> Result<()> {
+        let later = later_segments(path)?;
+        if later.is_empty() {
+            return Ok(());
+        }
+        for (segment, _) in later.iter().rev() {
+            fs::remove_file(segment)?;
+        }
+        Self::sync_parent_dir(path)
+    }
+
+    /// Fsync the directory containing a newly created WAL file
+    ///
+    /// Otherwise a crash could lose the file's directory entry, and with it
+    /// every write the WAL had already acknowledged as durable.
+    fn sync_parent_dir(path: &Path) -> Result<()> {
+        match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => sync_dir(dir),
+            _ => sync_dir(Path::new(".")),
+        }
+    }
+}
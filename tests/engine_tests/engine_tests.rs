@@ -1,18 +1,51 @@
 //! Tests for Engine
 //!
 //! These tests verify:
-//! - Basic get/put/delete operations
+//! - Basic get/put/delete operations (and presence checks, including
+//!   whether a delete found a live value)
+//! - Streaming gets (large values copied from SSTables in chunks), and gets
+//!   sharing MemTable values instead of copying them
+//! - Per-value checksums failing reads of a value corrupted on disk
 //! - Command execution
-//! - Flush to SSTable
-//! - Crash recovery from WAL
-//! - Concurrent access patterns
-//! - Engine lifecycle (open/close)
-
+//! - Flush to SSTable (memtable and WAL size limits, flush stats, polling
+//!   whether one is needed)
+//! - Configured I/O buffer sizes (round trip, bounds checked)
+//! - Clearing all data
+//! - Switching the WAL sync strategy at runtime
+//! - Crash recovery from WAL (including entries synced in the background,
+//!   retrying a failed recovery flush, replaying only past a checkpoint, a
+//!   preallocated WAL, and one split into segments)
+//! - Atomic write batches (all-or-nothing on recovery)
+//! - Reads as of a sequence number (historical versions)
+//! - Cursor scans paging through every key exactly once, and cut short by a
+//!   deadline
+//! - Reverse scans, key-only scans, and range counts (tombstones excluded,
+//!   empty on a brand-new database), plus the approximate key count
+//! - Custom key comparators (case-insensitive keys across flush and reopen)
+//! - Last-write timestamps (memtable, SSTable, GETMETA)
+//! - HEALTH reporting storage problems that PING can't see
+//! - Dump export/import round trips (full backups)
+//! - Write observer (change-data-capture) callbacks
+//! - WAL subscriptions (entries streamed in LSN order)
+//! - Replicas applying a primary's WAL stream (LSN gaps rejected, no flush
+//!   inside a replicated batch)
+//! - Concurrent access patterns (a panicking writer doesn't poison the engine)
+//! - Engine lifecycle (open/close, flush on drop)
+
+use std::ops::Bound;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
-use atlaskv::config::{Config, WalSyncStrategy};
+use atlaskv::comparator::CaseInsensitiveComparator;
+use atlaskv::config::{Config, WalSyncStrategy, MAX_IO_BUFFER_BYTES, MIN_IO_BUFFER_BYTES};
+use atlaskv::dump::DumpWriter;
 use atlaskv::engine::Engine;
-use atlaskv::protocol::Command;
+use atlaskv::merge::{I64AddOperator, MergeOperator};
+use atlaskv::protocol::{decode_entries, decode_keys, decode_scan_page, Command};
+use atlaskv::wal::{Operation, WalEntry, WalRecovery, WalWriter};
+use atlaskv::AtlasError;
 use tempfile::TempDir;
 
 // =============================================================================
@@ -96,8 +129,11 @@ fn test_engine_delete() {
     engine.put(b"key", b"value").unwrap();
     assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
 
-    engine.delete(b"key").unwrap();
+    assert!(engine.delete(b"key").unwrap());
     assert_eq!(engine.get(b"key").unwrap(), None);
+
+    // Already deleted: nothing live to remove
+    assert!(!engine.delete(b"key").unwrap());
 }
 
 #[test]
@@ -105,10 +141,31 @@ fn test_engine_delete_nonexistent_key() {
     let (_temp, engine) = setup_temp_engine();
 
     // Should not error
-    engine.delete(b"nonexistent").unwrap();
+    assert!(!engine.delete(b"nonexistent").unwrap());
     assert_eq!(engine.get(b"nonexistent").unwrap(), None);
 }
 
+#[test]
+fn test_engine_delete_reports_values_in_sstables() {
+    let (_temp, engine) = setup_temp_engine();
+
+    engine.put(b"flushed", b"value").unwrap();
+    engine.put(b"flushed_then_deleted", b"value").unwrap();
+    engine.delete(b"flushed_then_deleted").unwrap();
+    engine.flush().unwrap();
+
+    assert!(engine.delete(b"flushed").unwrap());
+    assert!(!engine.delete(b"flushed_then_deleted").unwrap());
+    assert!(!engine.delete(b"never_written").unwrap());
+
+    // Tombstones are written either way, so replay matches the old WAL
+    let config = engine.config().clone();
+    drop(engine);
+    let engine = Engine::open(config).unwrap();
+    assert_eq!(engine.get(b"flushed").unwrap(), None);
+    assert!(!engine.delete(b"flushed").unwrap());
+}
+
 #[test]
 fn test_engine_multiple_keys() {
     let (_temp, engine) = setup_temp_engine();
@@ -122,6 +179,274 @@ fn test_engine_multiple_keys() {
     assert_eq!(engine.get(b"key3").unwrap(), Some(b"value3".to_vec()));
 }
 
+#[test]
+fn test_engine_get_arc_shares_memtable_value() {
+    let (_temp, engine) = setup_temp_engine();
+
+    let value = vec![0xAB; 64 * 1024];
+    engine.put(b"hot", &value).unwrap();
+
+    // Both reads hand out the MemTable's own allocation
+    let first = engine.get_arc(b"hot").unwrap().unwrap();
+    let second = engine.get_arc(b"hot").unwrap().unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(*first, value);
+    assert_eq!(engine.get(b"hot").unwrap(), Some(value.clone()));
+
+    // Overwriting doesn't touch values already handed out
+    engine.put(b"hot", b"new").unwrap();
+    assert_eq!(*first, value);
+    assert_eq!(engine.get_arc(b"hot").unwrap().unwrap().as_slice(), b"new");
+
+    // From an SSTable, and gone once deleted
+    engine.flush().unwrap();
+    assert_eq!(engine.get_arc(b"hot").unwrap().unwrap().as_slice(), b"new");
+    engine.delete(b"hot").unwrap();
+    assert_eq!(engine.get_arc(b"hot").unwrap(), None);
+}
+
+#[test]
+fn test_engine_get_streaming_matches_get() {
+    let (_temp, engine) = setup_temp_engine();
+
+    // 1 MB, not a multiple of the copy chunk, so the last chunk is partial
+    let value: Vec<u8> = (0..1024 * 1024 + 7).map(|i| (i % 251) as u8).collect();
+    engine.put(b"big", &value).unwrap();
+    engine.put(b"gone", b"soon").unwrap();
+    engine.delete(b"gone").unwrap();
+
+    // From the memtable
+    let mut streamed = Vec::new();
+    assert_eq!(engine.get_streaming(b"big", &mut streamed).unwrap(), Some(value.len() as u64));
+    assert_eq!(Some(streamed), engine.get(b"big").unwrap());
+
+    // From an SSTable, copied out of the file
+    engine.flush().unwrap();
+    let mut streamed = Vec::new();
+    assert_eq!(engine.get_streaming(b"big", &mut streamed).unwrap(), Some(value.len() as u64));
+    assert_eq!(Some(streamed), engine.get(b"big").unwrap());
+
+    // Deleted and missing keys write nothing
+    let mut streamed = Vec::new();
+    assert_eq!(engine.get_streaming(b"gone", &mut streamed).unwrap(), None);
+    assert_eq!(engine.get_streaming(b"missing", &mut streamed).unwrap(), None);
+    assert!(streamed.is_empty());
+}
+
+#[test]
+fn test_engine_per_value_checksum_catches_corrupt_value() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .per_value_checksum(true)
+        .build();
+    let engine = Engine::open(config).unwrap();
+    engine.put(b"precious", b"irreplaceable bytes").unwrap();
+    engine.put(b"other", b"fine").unwrap();
+    engine.flush().unwrap();
+
+    // Rot one byte of the value in the SSTable
+    let sstable = std::fs::read_dir(temp_dir.path().join("sstables"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "sst"))
+        .unwrap();
+    let mut bytes = std::fs::read(&sstable).unwrap();
+    let at = bytes.windows(19).position(|w| w == b"irreplaceable bytes").unwrap();
+    bytes[at] = b'I';
+    std::fs::write(&sstable, &bytes).unwrap();
+
+    let result = engine.get(b"precious");
+    assert!(matches!(result, Err(AtlasError::Storage(msg)) if msg.contains("checksum")));
+    let result = engine.get_streaming(b"precious", Vec::new());
+    assert!(matches!(result, Err(AtlasError::Storage(msg)) if msg.contains("checksum")));
+    assert_eq!(engine.get(b"other").unwrap(), Some(b"fine".to_vec()));
+}
+
+// =============================================================================
+// Append Tests
+// =============================================================================
+
+#[test]
+fn test_engine_append_missing_key() {
+    let (_temp, engine) = setup_temp_engine();
+
+    let len = engine.append(b"log", b"first").unwrap();
+
+    assert_eq!(len, 5);
+    assert_eq!(engine.get(b"log").unwrap(), Some(b"first".to_vec()));
+}
+
+#[test]
+fn test_engine_append_many_times() {
+    let (_temp, engine) = setup_temp_engine();
+
+    let mut expected = Vec::new();
+    for i in 0..1000 {
+        let line = format!("line{:04};", i);
+        expected.extend_from_slice(line.as_bytes());
+
+        let len = engine.append(b"log", line.as_bytes()).unwrap();
+        assert_eq!(len, expected.len());
+    }
+
+    assert_eq!(expected.len(), 1000 * 9);
+    assert_eq!(engine.get(b"log").unwrap(), Some(expected));
+}
+
+#[test]
+fn test_engine_append_reads_through_sstable() {
+    let (_temp, engine) = setup_temp_engine();
+
+    engine.put(b"key", b"abc").unwrap();
+    engine.flush().unwrap();
+
+    let len = engine.append(b"key", b"def").unwrap();
+
+    assert_eq!(len, 6);
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"abcdef".to_vec()));
+}
+
+#[test]
+fn test_engine_append_survives_recovery() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .build();
+
+    {
+        let engine = Engine::open(config.clone()).unwrap();
+        engine.append(b"key", b"abc").unwrap();
+        engine.append(b"key", b"def").unwrap();
+        // Crash! (drop without close)
+    }
+
+    let engine = Engine::open(config).unwrap();
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"abcdef".to_vec()));
+}
+
+// =============================================================================
+// GetSet Tests
+// =============================================================================
+
+#[test]
+fn test_engine_get_set_returns_previous_value() {
+    let (_temp, engine) = setup_temp_engine();
+
+    assert_eq!(engine.get_set(b"key", b"v1").unwrap(), None);
+    assert_eq!(engine.get_set(b"key", b"v2").unwrap(), Some(b"v1".to_vec()));
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"v2".to_vec()));
+
+    engine.delete(b"key").unwrap();
+    assert_eq!(engine.get_set(b"key", b"v3").unwrap(), None);
+}
+
+#[test]
+fn test_engine_get_set_concurrent_swaps_are_atomic() {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    let (_temp, engine) = setup_temp_engine();
+    let engine = Arc::new(engine);
+
+    let mut handles = vec![];
+    for t in 0..2 {
+        let engine_clone = Arc::clone(&engine);
+        handles.push(thread::spawn(move || {
+            let mut seen = Vec::new();
+            for i in 0..100 {
+                let value = format!("thread{}_value{}", t, i);
+                seen.push(engine_clone.get_set(b"slot", value.as_bytes()).unwrap());
+            }
+            seen
+        }));
+    }
+
+    let mut returned: Vec<Option<Vec<u8>>> = Vec::new();
+    for handle in handles {
+        returned.extend(handle.join().unwrap());
+    }
+
+    // Exactly one caller saw the empty slot
+    assert_eq!(returned.iter().filter(|v| v.is_none()).count(), 1);
+
+    // Every written value is either returned exactly once or is the final value
+    let mut returned_values: Vec<Vec<u8>> = returned.into_iter().flatten().collect();
+    returned_values.push(engine.get(b"slot").unwrap().unwrap());
+
+    let unique: HashSet<_> = returned_values.iter().cloned().collect();
+    assert_eq!(unique.len(), returned_values.len());
+    assert_eq!(unique.len(), 200);
+}
+
+// =============================================================================
+// PutIfAbsent Tests
+// =============================================================================
+
+#[test]
+fn test_engine_put_if_absent() {
+    let (_temp, engine) = setup_temp_engine();
+
+    assert!(engine.put_if_absent(b"key", b"first").unwrap());
+    assert!(!engine.put_if_absent(b"key", b"second").unwrap());
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"first".to_vec()));
+
+    // A tombstoned key counts as absent, also once flushed
+    engine.delete(b"key").unwrap();
+    engine.flush().unwrap();
+    assert!(engine.put_if_absent(b"key", b"third").unwrap());
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"third".to_vec()));
+}
+
+#[test]
+fn test_engine_contains() {
+    let (_temp, engine) = setup_temp_engine();
+
+    engine.put(b"flushed", b"v").unwrap();
+    engine.put(b"deleted_later", b"v").unwrap();
+    engine.flush().unwrap();
+    engine.delete(b"deleted_later").unwrap();
+    engine.put(b"fresh", b"v").unwrap();
+
+    assert!(engine.contains(b"flushed").unwrap());
+    assert!(engine.contains(b"fresh").unwrap());
+    assert!(!engine.contains(b"missing").unwrap());
+
+    // The MemTable tombstone shadows the SSTable value, also once flushed
+    assert!(!engine.contains(b"deleted_later").unwrap());
+    engine.flush().unwrap();
+    assert!(!engine.contains(b"deleted_later").unwrap());
+    assert!(engine.contains(b"flushed").unwrap());
+}
+
+#[test]
+fn test_engine_put_if_absent_concurrent_exactly_one_wins() {
+    let (_temp, engine) = setup_temp_engine();
+    let engine = Arc::new(engine);
+
+    let handles: Vec<_> = (0..16)
+        .map(|t| {
+            let engine = Arc::clone(&engine);
+            thread::spawn(move || {
+                let value = format!("candidate{}", t);
+                engine.put_if_absent(b"leader", value.as_bytes()).unwrap().then_some(value)
+            })
+        })
+        .collect();
+
+    let winners: Vec<String> = handles
+        .into_iter()
+        .filter_map(|handle| handle.join().unwrap())
+        .collect();
+
+    assert_eq!(winners.len(), 1);
+    assert_eq!(
+        engine.get(b"leader").unwrap(),
+        Some(winners[0].as_bytes().to_vec())
+    );
+}
+
 // =============================================================================
 // Command Execution Tests
 // =============================================================================
@@ -168,8 +493,133 @@ fn test_engine_execute_delete() {
         })
         .unwrap();
 
-    assert_eq!(result, None); // Delete returns None
+    assert_eq!(result, Some(vec![1])); // The key had a live value
     assert_eq!(engine.get(b"key").unwrap(), None);
+
+    let result = engine
+        .execute(Command::Delete {
+            key: b"key".to_vec(),
+        })
+        .unwrap();
+    assert_eq!(result, Some(vec![0]));
+}
+
+#[test]
+fn test_engine_execute_append() {
+    let (_temp, engine) = setup_temp_engine();
+
+    engine.put(b"key", b"abc").unwrap();
+
+    let result = engine
+        .execute(Command::Append {
+            key: b"key".to_vec(),
+            data: b"de".to_vec(),
+        })
+        .unwrap();
+
+    assert_eq!(result, Some(5u64.to_be_bytes().to_vec()));
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"abcde".to_vec()));
+}
+
+#[test]
+fn test_engine_execute_get_set() {
+    let (_temp, engine) = setup_temp_engine();
+
+    engine.put(b"key", b"old").unwrap();
+
+    let result = engine
+        .execute(Command::GetSet {
+            key: b"key".to_vec(),
+            value: b"new".to_vec(),
+        })
+        .unwrap();
+
+    assert_eq!(result, Some(b"old".to_vec()));
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"new".to_vec()));
+}
+
+#[test]
+fn test_engine_execute_setnx() {
+    let (_temp, engine) = setup_temp_engine();
+
+    let setnx = |value: &[u8]| Command::SetNx {
+        key: b"key".to_vec(),
+        value: value.to_vec(),
+    };
+
+    assert_eq!(engine.execute(setnx(b"first")).unwrap(), Some(vec![1]));
+    assert_eq!(engine.execute(setnx(b"second")).unwrap(), Some(vec![0]));
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"first".to_vec()));
+}
+
+#[test]
+fn test_engine_execute_sstables() {
+    let (_temp, engine) = setup_temp_engine();
+
+    assert_eq!(engine.execute(Command::SsTables).unwrap(), Some(Vec::new()));
+
+    engine.put(b"apple", b"1").unwrap();
+    engine.put(b"cherry", b"2").unwrap();
+    engine.flush().unwrap();
+    engine.put(b"banana", b"3").unwrap();
+    engine.flush().unwrap();
+
+    let payload = engine.execute(Command::SsTables).unwrap().unwrap();
+    let text = String::from_utf8(payload).unwrap();
+    let lines: Vec<Vec<&str>> = text.lines().map(|l| l.split('\t').collect()).collect();
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0][0], "2");
+    assert_eq!(lines[0][1], "1");
+    assert_eq!(&lines[0][3..], ["banana", "banana"]);
+    assert_eq!(lines[1][0], "1");
+    assert_eq!(lines[1][1], "2");
+    assert_eq!(&lines[1][3..], ["apple", "cherry"]);
+
+    let stats = engine.sstable_stats();
+    assert_eq!(lines[0][2], stats[0].file_size.to_string());
+}
+
+#[test]
+fn test_engine_execute_stats_json() {
+    let (_temp, engine) = setup_temp_engine();
+
+    engine.put(b"apple", b"1").unwrap();
+    engine.put(b"cherry", b"2").unwrap();
+    engine.flush().unwrap();
+    engine.put(b"banana", b"3").unwrap();
+    engine.delete(b"apple").unwrap();
+    engine.get(b"banana").unwrap();
+
+    let payload = engine.execute(Command::StatsJson).unwrap().unwrap();
+    let stats: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+    let file_size = engine.sstable_stats()[0].file_size;
+
+    assert_eq!(stats["memtable_entries"], 2);
+    assert_eq!(stats["memtable_size_bytes"], engine.memtable_size() as u64);
+    assert_eq!(stats["sstable_count"], 1);
+    assert_eq!(stats["sstable_total_bytes"], file_size);
+    assert_eq!(stats["read_only"], false);
+    assert_eq!(stats["block_cache"], serde_json::Value::Null);
+
+    // The flush emptied the WAL, which now holds the last two writes
+    let segment = &stats["wal_segments"][0];
+    assert_eq!(stats["wal_segments"].as_array().unwrap().len(), 1);
+    assert_eq!(segment["first_lsn"], engine.last_seq().unwrap() - 1);
+    assert_eq!(segment["last_lsn"], engine.last_seq().unwrap());
+    assert_eq!(segment["size_bytes"], engine.wal_segments()[0].byte_size);
+
+    assert_eq!(stats["ops"]["puts"], 3);
+    assert_eq!(stats["ops"]["deletes"], 1);
+    assert_eq!(stats["ops"]["gets"], 1);
+    assert_eq!(stats["ops"]["flushes"], 1);
+
+    let sstable = &stats["sstables"][0];
+    assert_eq!(sstable["id"], 1);
+    assert_eq!(sstable["entry_count"], 2);
+    assert_eq!(sstable["file_size_bytes"], file_size);
+    assert_eq!(sstable["min_key"], "apple");
+    assert_eq!(sstable["max_key"], "cherry");
 }
 
 #[test]
@@ -233,6 +683,27 @@ fn test_engine_auto_flush_on_size_limit() {
     }
 }
 
+#[test]
+fn test_engine_auto_flush_on_wal_size_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .memtable_size_limit(1024 * 1024)
+        .wal_max_bytes(512)
+        .build();
+    let engine = Engine::open(config).unwrap();
+
+    // Overwrites keep the MemTable tiny while every one grows the WAL
+    for i in 0..50 {
+        let value = format!("value_{:02}", i);
+        engine.put(b"hot_key", value.as_bytes()).unwrap();
+    }
+
+    assert!(engine.sstable_count() >= 1);
+    assert_eq!(engine.get(b"hot_key").unwrap(), Some(b"value_49".to_vec()));
+}
+
 #[test]
 fn test_engine_flush_empty_memtable() {
     let (_temp, engine) = setup_temp_engine();
@@ -240,51 +711,317 @@ fn test_engine_flush_empty_memtable() {
     // Flushing empty memtable should be a no-op
     engine.flush().unwrap();
     assert_eq!(engine.sstable_count(), 0);
-}
 
-// =============================================================================
-// Crash Recovery Tests
-// =============================================================================
+    let stats = engine.flush_stats().unwrap();
+    assert!(stats.was_empty);
+    assert_eq!(stats.entries, 0);
+    assert_eq!(engine.sstable_count(), 0);
+}
 
 #[test]
-fn test_engine_recovery_from_wal() {
-    let temp_dir = TempDir::new().unwrap();
-    let data_dir = temp_dir.path().to_path_buf();
+fn test_engine_needs_flush_flips_at_memtable_limit() {
+    let (_temp, engine) = setup_temp_engine_with_small_memtable();
+    let limit = engine.memtable_size_limit();
+    assert!(!engine.needs_flush());
+
+    // A replicated batch holds off the flush, so the MemTable can pass
+    // its limit; write until it does
+    let mut lsn = 1;
+    let mut apply = |operation| {
+        engine.apply_replicated(WalEntry::new(lsn, operation)).unwrap();
+        lsn += 1;
+    };
+    apply(Operation::BatchBegin { count: u32::MAX });
+    let mut i = 0;
+    while engine.memtable_size() < limit {
+        assert!(!engine.needs_flush(), "{} of {} bytes", engine.memtable_size(), limit);
+        apply(Operation::Put { key: format!("key{:04}", i).into_bytes(), value: vec![0; 64] });
+        i += 1;
+    }
+    assert!(engine.needs_flush());
+    assert_eq!(engine.sstable_count(), 0);
 
-    // First engine - write data, don't flush (simulating crash)
-    {
-        let config = Config::builder()
-            .data_dir(&data_dir)
-            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
-            .build();
-        let engine = Engine::open(config).unwrap();
+    // The commit flushes, clearing it
+    apply(Operation::BatchCommit);
+    assert!(!engine.needs_flush());
+    assert_eq!(engine.sstable_count(), 1);
+}
 
-        engine.put(b"key1", b"value1").unwrap();
-        engine.put(b"key2", b"value2").unwrap();
-        engine.delete(b"key1").unwrap();
-        engine.put(b"key3", b"value3").unwrap();
+#[test]
+fn test_engine_flush_stats_report_new_sstable() {
+    let (temp_dir, engine) = setup_temp_engine();
 
-        // Don't call close() - simulating crash
-        // Data is in WAL but not flushed to SSTable
-        drop(engine);
+    for i in 0..25 {
+        engine.put(format!("key{:02}", i).as_bytes(), b"value").unwrap();
     }
+    engine.delete(b"key03").unwrap();
+    engine.delete(b"gone").unwrap();
+
+    // 24 live keys; no older SSTable could hold either deleted key, so
+    // neither tombstone is written
+    let stats = engine.flush_stats().unwrap();
+    assert!(!stats.was_empty);
+    assert_eq!(stats.entries, 24);
+    assert_eq!(stats.sstable_id, 1);
+
+    let path = temp_dir.path().join("sstables").join("sstable_000001.sst");
+    assert_eq!(stats.bytes, std::fs::metadata(path).unwrap().len());
+
+    engine.put(b"more", b"value").unwrap();
+    let stats = engine.flush_stats().unwrap();
+    assert_eq!((stats.entries, stats.sstable_id), (1, 2));
+}
 
-    // Second engine - should recover from WAL
-    {
-        let config = Config::builder()
-            .data_dir(&data_dir)
-            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
-            .build();
-        let engine = Engine::open(config).unwrap();
-
-        // Recovered data should be in SSTable (immediately flushed on recovery)
-        assert_eq!(engine.sstable_count(), 1);
+#[test]
+fn test_engine_compact() {
+    let (_temp, engine) = setup_temp_engine_with_small_memtable();
 
-        // Verify data was recovered correctly
-        assert_eq!(engine.get(b"key1").unwrap(), None); // Was deleted
-        assert_eq!(engine.get(b"key2").unwrap(), Some(b"value2".to_vec()));
-        assert_eq!(engine.get(b"key3").unwrap(), Some(b"value3".to_vec()));
+    for i in 0..20 {
+        let key = format!("key{:02}", i);
+        let value = format!("value_that_is_definitely_long_enough_{:02}", i);
+        engine.put(key.as_bytes(), value.as_bytes()).unwrap();
     }
+    engine.delete(b"key05").unwrap();
+    engine.flush().unwrap();
+    assert!(engine.sstable_count() > 1);
+
+    engine.compact().unwrap();
+
+    assert_eq!(engine.sstable_count(), 1);
+    assert_eq!(engine.get(b"key05").unwrap(), None);
+    assert_eq!(
+        engine.get(b"key19").unwrap(),
+        Some(b"value_that_is_definitely_long_enough_19".to_vec())
+    );
+}
+
+#[test]
+fn test_engine_clear() {
+    let (temp_dir, engine) = setup_temp_engine();
+
+    // Half the keys in an SSTable, half still in the MemTable
+    for i in 0..100 {
+        let key = format!("key{:03}", i);
+        engine.put(key.as_bytes(), b"value").unwrap();
+        if i == 49 {
+            engine.flush().unwrap();
+        }
+    }
+    assert!(engine.sstable_count() > 0);
+    assert!(engine.memtable_entry_count() > 0);
+
+    engine.clear().unwrap();
+
+    assert_eq!(engine.sstable_count(), 0);
+    assert_eq!(engine.memtable_entry_count(), 0);
+    for i in 0..100 {
+        let key = format!("key{:03}", i);
+        assert_eq!(engine.get(key.as_bytes()).unwrap(), None);
+    }
+
+    // Still usable, and nothing comes back on reopen
+    engine.put(b"after", b"clear").unwrap();
+    engine.flush().unwrap();
+    drop(engine);
+
+    let config = Config::builder().data_dir(temp_dir.path()).build();
+    let engine = Engine::open(config).unwrap();
+    assert_eq!(engine.sstable_count(), 1);
+    assert_eq!(engine.get(b"key000").unwrap(), None);
+    assert_eq!(engine.get(b"after").unwrap(), Some(b"clear".to_vec()));
+}
+
+#[test]
+fn test_engine_round_trips_with_configured_io_buffers() {
+    for buffer_bytes in [MIN_IO_BUFFER_BYTES, 1024 * 1024] {
+        let temp_dir = TempDir::new().unwrap();
+        let config = || {
+            Config::builder()
+                .data_dir(temp_dir.path())
+                .write_buffer_bytes(buffer_bytes)
+                .read_buffer_bytes(buffer_bytes)
+                .build()
+        };
+
+        // A large MemTable, with values both smaller and larger than the buffer
+        let engine = Engine::open(config()).unwrap();
+        for i in 0..200 {
+            let value = vec![i as u8; if i % 50 == 0 { 64 * 1024 } else { 4096 }];
+            engine.put(format!("key{:03}", i).as_bytes(), &value).unwrap();
+        }
+        engine.flush().unwrap();
+        // Left in the WAL, so reopening replays it through the read buffer
+        engine.put(b"unflushed", b"value").unwrap();
+        drop(engine);
+
+        let engine = Engine::open(config()).unwrap();
+        for i in 0..200 {
+            let value = engine.get(format!("key{:03}", i).as_bytes()).unwrap().unwrap();
+            assert_eq!(value.len(), if i % 50 == 0 { 64 * 1024 } else { 4096 });
+            assert!(value.iter().all(|&b| b == i as u8));
+        }
+        assert_eq!(engine.get(b"unflushed").unwrap(), Some(b"value".to_vec()));
+    }
+}
+
+#[test]
+fn test_engine_rejects_out_of_range_io_buffers() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .write_buffer_bytes(MIN_IO_BUFFER_BYTES - 1)
+        .build();
+    assert!(matches!(Engine::open(config), Err(AtlasError::Config(_))));
+
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .read_buffer_bytes(MAX_IO_BUFFER_BYTES + 1)
+        .build();
+    assert!(matches!(Engine::open_read_only(config), Err(AtlasError::Config(_))));
+}
+
+#[test]
+fn test_engine_set_wal_sync_strategy_at_runtime() {
+    let temp_dir = TempDir::new().unwrap();
+    let wal_path = temp_dir.path().join("wal.log");
+    let wal_len = || std::fs::metadata(&wal_path).unwrap().len();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .build();
+    let engine = Engine::open(config).unwrap();
+
+    engine.put(b"key1", b"value1").unwrap();
+    let synced = wal_len();
+    assert!(synced > 0);
+
+    // Under NoSync appends stay in the buffer
+    engine.set_wal_sync_strategy(WalSyncStrategy::NoSync).unwrap();
+    engine.put(b"key2", b"value2").unwrap();
+    engine.put(b"key3", b"value3").unwrap();
+    assert_eq!(wal_len(), synced);
+
+    // Switching back syncs what NoSync left behind, then every write again
+    engine.set_wal_sync_strategy(WalSyncStrategy::EveryWrite).unwrap();
+    let resynced = wal_len();
+    assert!(resynced > synced);
+    engine.put(b"key4", b"value4").unwrap();
+    assert!(wal_len() > resynced);
+}
+
+// =============================================================================
+// Crash Recovery Tests
+// =============================================================================
+
+#[test]
+fn test_engine_recovery_from_wal() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().to_path_buf();
+
+    // First engine - write data, don't flush (simulating crash)
+    {
+        let config = Config::builder()
+            .data_dir(&data_dir)
+            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+            .build();
+        let engine = Engine::open(config).unwrap();
+
+        engine.put(b"key1", b"value1").unwrap();
+        engine.put(b"key2", b"value2").unwrap();
+        engine.delete(b"key1").unwrap();
+        engine.put(b"key3", b"value3").unwrap();
+
+        // Don't call close() - simulating crash
+        // Data is in WAL but not flushed to SSTable
+        drop(engine);
+    }
+
+    // Second engine - should recover from WAL
+    {
+        let config = Config::builder()
+            .data_dir(&data_dir)
+            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+            .build();
+        let engine = Engine::open(config).unwrap();
+
+        // Recovered data should be in SSTable (immediately flushed on recovery)
+        assert_eq!(engine.sstable_count(), 1);
+
+        // Verify data was recovered correctly
+        assert_eq!(engine.get(b"key1").unwrap(), None); // Was deleted
+        assert_eq!(engine.get(b"key2").unwrap(), Some(b"value2".to_vec()));
+        assert_eq!(engine.get(b"key3").unwrap(), Some(b"value3".to_vec()));
+    }
+}
+
+#[test]
+fn test_engine_recovery_from_preallocated_wal() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .wal_preallocate_bytes(256 * 1024)
+        .build();
+    let wal_path = temp_dir.path().join("wal.log");
+
+    {
+        let engine = Engine::open(config.clone()).unwrap();
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 256 * 1024);
+
+        engine.put(b"key1", b"value1").unwrap();
+        engine.put(b"key2", b"value2").unwrap();
+        engine.delete(b"key1").unwrap();
+
+        // Don't close - simulating a crash with the zero tail still in place
+        drop(engine);
+    }
+    assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 256 * 1024);
+
+    let engine = Engine::open(config).unwrap();
+    assert_eq!(engine.sstable_count(), 1);
+    assert_eq!(engine.get(b"key1").unwrap(), None);
+    assert_eq!(engine.get(b"key2").unwrap(), Some(b"value2".to_vec()));
+
+    // The WAL starts over, preallocated again
+    assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 256 * 1024);
+}
+
+#[test]
+fn test_engine_recovery_from_segmented_wal() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .wal_segment_bytes(1024)
+        .build();
+
+    {
+        let engine = Engine::open(config.clone()).unwrap();
+        for i in 0..100 {
+            engine.put(format!("key{:03}", i).as_bytes(), b"value").unwrap();
+        }
+
+        let segments = engine.wal_segments();
+        assert!(segments.len() > 2, "{} segments", segments.len());
+        for pair in segments.windows(2) {
+            assert_eq!(pair[0].last_lsn.unwrap() + 1, pair[1].first_lsn.unwrap());
+        }
+
+        // Don't close - simulating a crash
+        drop(engine);
+    }
+
+    let engine = Engine::open(config).unwrap();
+    assert_eq!(engine.sstable_count(), 1);
+    for i in 0..100 {
+        let key = format!("key{:03}", i);
+        assert_eq!(engine.get(key.as_bytes()).unwrap(), Some(b"value".to_vec()), "{}", key);
+    }
+
+    // The recovered entries were flushed, so the WAL is back to one file
+    assert_eq!(engine.wal_segments().len(), 1);
+    assert!(!temp_dir.path().join("wal.log.1").exists());
 }
 
 #[test]
@@ -292,38 +1029,1490 @@ fn test_engine_no_data_loss_after_recovery() {
     let temp_dir = TempDir::new().unwrap();
     let data_dir = temp_dir.path().to_path_buf();
 
-    // Write, crash, recover, crash again, recover again
-    {
-        let config = Config::builder()
-            .data_dir(&data_dir)
-            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
-            .build();
-        let engine = Engine::open(config).unwrap();
-        engine.put(b"key", b"value").unwrap();
-        drop(engine); // Crash
+    // Write, crash, recover, crash again, recover again
+    {
+        let config = Config::builder()
+            .data_dir(&data_dir)
+            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+            .build();
+        let engine = Engine::open(config).unwrap();
+        engine.put(b"key", b"value").unwrap();
+        drop(engine); // Crash
+    }
+
+    // First recovery
+    {
+        let config = Config::builder()
+            .data_dir(&data_dir)
+            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+            .build();
+        let engine = Engine::open(config).unwrap();
+        assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
+        // Crash again without writing anything new
+        drop(engine);
+    }
+
+    // Second recovery - data should still be there (in SSTable from first recovery)
+    {
+        let config = Config::builder()
+            .data_dir(&data_dir)
+            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+            .build();
+        let engine = Engine::open(config).unwrap();
+        assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
+    }
+}
+
+#[test]
+fn test_engine_recovery_replays_only_past_checkpoint() {
+    let temp_dir = TempDir::new().unwrap();
+    let wal_path = temp_dir.path().join("wal.log");
+    let config = || {
+        Config::builder()
+            .data_dir(temp_dir.path())
+            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+            .wal_checkpoint_max_bytes(1024 * 1024)
+            .build()
+    };
+
+    let engine = Engine::open(config()).unwrap();
+    engine.put(b"key1", b"value1").unwrap();
+    engine.put(b"key2", b"value2").unwrap();
+    engine.flush().unwrap();
+
+    // The flush logged a checkpoint instead of truncating
+    assert!(std::fs::metadata(&wal_path).unwrap().len() > 0);
+
+    engine.put(b"key3", b"value3").unwrap();
+    engine.delete(b"key1").unwrap();
+
+    // Crash without running Drop (nothing flushed after the checkpoint)
+    std::mem::forget(engine);
+
+    let (entries, result) = WalRecovery::recover(&wal_path).unwrap();
+    let ops: Vec<Operation> = entries.into_iter().map(|e| e.operation).collect();
+    assert_eq!(ops.len(), 2);
+    assert!(matches!(&ops[0], Operation::Put { key, .. } if key == b"key3"));
+    assert!(matches!(&ops[1], Operation::Delete { key } if key == b"key1"));
+    assert_eq!(result.entries_recovered, 2);
+    assert_eq!(result.checkpoint_lsn, Some(2));
+    assert_eq!(result.last_lsn, 4);
+
+    let engine = Engine::open(config()).unwrap();
+    assert_eq!(engine.sstable_count(), 2);
+    assert_eq!(engine.get(b"key1").unwrap(), None);
+    assert_eq!(engine.get(b"key2").unwrap(), Some(b"value2".to_vec()));
+    assert_eq!(engine.get(b"key3").unwrap(), Some(b"value3".to_vec()));
+}
+
+#[test]
+fn test_engine_flush_truncates_wal_at_checkpoint_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let wal_path = temp_dir.path().join("wal.log");
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_checkpoint_max_bytes(4096)
+        .build();
+    let engine = Engine::open(config).unwrap();
+
+    engine.put(b"key", b"value").unwrap();
+    engine.flush().unwrap();
+    let checkpointed = std::fs::metadata(&wal_path).unwrap().len();
+    assert!(checkpointed > 0);
+
+    // Past the limit, the next flush truncates as usual
+    engine.put(b"big", &[0u8; 8192]).unwrap();
+    engine.flush().unwrap();
+    assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 0);
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
+}
+
+#[test]
+fn test_engine_open_retries_failed_recovery_flush() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = |retries| {
+        Config::builder()
+            .data_dir(temp_dir.path())
+            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+            .open_flush_retries(retries)
+            .build()
+    };
+
+    {
+        let engine = Engine::open(config(0)).unwrap();
+        engine.put(b"key", b"value").unwrap();
+        drop(engine); // Crash: the write is only in the WAL
+    }
+
+    // A directory squatting on the first SSTable's temp name fails that
+    // flush; a failed flush skips its id, so the next attempt gets past it
+    let blocker = temp_dir.path().join("sstables").join("sstable_000001.sst.tmp");
+    std::fs::create_dir(&blocker).unwrap();
+    std::fs::write(blocker.join("file"), b"").unwrap();
+
+    // Without retries the open fails, and the WAL is left for next time
+    assert!(Engine::open(config(0)).is_err());
+
+    let engine = Engine::open(config(2)).unwrap();
+    assert_eq!(engine.sstable_count(), 1);
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
+}
+
+#[test]
+fn test_engine_background_sync_makes_idle_writes_durable() {
+    let temp_dir = TempDir::new().unwrap();
+    let wal_path = temp_dir.path().join("wal.log");
+    let config = || {
+        Config::builder()
+            .data_dir(temp_dir.path())
+            .wal_sync_strategy(WalSyncStrategy::EveryNEntries { count: 1000 })
+            .wal_background_sync_ms(20)
+            .build()
+    };
+
+    let engine = Engine::open(config()).unwrap();
+    engine.put(b"key1", b"value1").unwrap();
+    engine.put(b"key2", b"value2").unwrap();
+    engine.delete(b"key1").unwrap();
+
+    // Far short of the count, so only the timer puts them on disk
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while std::fs::metadata(&wal_path).unwrap().len() == 0 {
+        assert!(Instant::now() < deadline, "WAL never synced");
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    // Crash without running Drop (nothing flushed, WAL buffer abandoned)
+    std::mem::forget(engine);
+
+    let engine = Engine::open(config()).unwrap();
+    assert_eq!(engine.get(b"key1").unwrap(), None);
+    assert_eq!(engine.get(b"key2").unwrap(), Some(b"value2".to_vec()));
+}
+
+#[test]
+fn test_engine_background_sync_stops_with_engine() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_background_sync_ms(60_000)
+        .build();
+
+    // Dropping wakes the thread mid-interval rather than waiting it out
+    let start = Instant::now();
+    let engine = Engine::open(config).unwrap();
+    engine.put(b"key", b"value").unwrap();
+    drop(engine);
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+// =============================================================================
+// Write Batch Tests
+// =============================================================================
+
+fn open_engine_at(dir: &std::path::Path) -> Engine {
+    let config = Config::builder()
+        .data_dir(dir)
+        .wal_sync_strategy(WalSyncStrategy::EveryNEntries { count: 100 })
+        .build();
+    Engine::open(config).unwrap()
+}
+
+#[test]
+fn test_engine_write_batch_applies_in_order() {
+    let temp_dir = TempDir::new().unwrap();
+
+    {
+        let engine = open_engine_at(temp_dir.path());
+        engine.put(b"old", b"value").unwrap();
+
+        engine
+            .write_batch(vec![
+                Operation::Put { key: b"a".to_vec(), value: b"1".to_vec() },
+                Operation::Put { key: b"b".to_vec(), value: b"2".to_vec() },
+                Operation::Delete { key: b"old".to_vec() },
+                Operation::Put { key: b"a".to_vec(), value: b"3".to_vec() },
+            ])
+            .unwrap();
+
+        assert_eq!(engine.get(b"a").unwrap(), Some(b"3".to_vec()));
+        assert_eq!(engine.get(b"b").unwrap(), Some(b"2".to_vec()));
+        assert_eq!(engine.get(b"old").unwrap(), None);
+        drop(engine); // Crash: the batch was synced despite EveryNEntries
+    }
+
+    let engine = open_engine_at(temp_dir.path());
+    assert_eq!(engine.get(b"a").unwrap(), Some(b"3".to_vec()));
+    assert_eq!(engine.get(b"b").unwrap(), Some(b"2".to_vec()));
+    assert_eq!(engine.get(b"old").unwrap(), None);
+}
+
+#[test]
+fn test_engine_write_batch_rejects_invalid_ops() {
+    let (_temp, engine) = setup_temp_engine();
+
+    // Nested markers and merges without an operator fail before anything is logged
+    let result = engine.write_batch(vec![
+        Operation::Put { key: b"a".to_vec(), value: b"1".to_vec() },
+        Operation::BatchCommit,
+    ]);
+    assert!(matches!(result, Err(AtlasError::Storage(_))));
+
+    let result = engine.write_batch(vec![
+        Operation::Put { key: b"a".to_vec(), value: b"1".to_vec() },
+        Operation::Merge { key: b"n".to_vec(), operand: 1i64.to_le_bytes().to_vec() },
+    ]);
+    assert!(matches!(result, Err(AtlasError::Config(_))));
+
+    assert_eq!(engine.get(b"a").unwrap(), None);
+    engine.write_batch(Vec::new()).unwrap();
+}
+
+#[test]
+fn test_engine_recovery_drops_batch_missing_commit() {
+    let temp_dir = TempDir::new().unwrap();
+
+    {
+        let engine = open_engine_at(temp_dir.path());
+        engine.put(b"before", b"kept").unwrap();
+        engine.flush().unwrap();
+        engine.put(b"unbatched", b"kept").unwrap();
+        drop(engine);
+    }
+
+    // Crash mid-batch: the begin marker and operations hit the log, the commit didn't
+    {
+        let wal_path = temp_dir.path().join("wal.log");
+        let mut wal = WalWriter::open_append(&wal_path, WalSyncStrategy::EveryWrite, 2).unwrap();
+        wal.append(Operation::BatchBegin { count: 3 }).unwrap();
+        wal.append(Operation::Put { key: b"x".to_vec(), value: b"1".to_vec() }).unwrap();
+        wal.append(Operation::Delete { key: b"before".to_vec() }).unwrap();
+        wal.append(Operation::Put { key: b"y".to_vec(), value: b"2".to_vec() }).unwrap();
+    }
+
+    let engine = open_engine_at(temp_dir.path());
+    assert_eq!(engine.get(b"unbatched").unwrap(), Some(b"kept".to_vec()));
+    assert_eq!(engine.get(b"before").unwrap(), Some(b"kept".to_vec()));
+    assert_eq!(engine.get(b"x").unwrap(), None);
+    assert_eq!(engine.get(b"y").unwrap(), None);
+}
+
+// =============================================================================
+// Versioned Read Tests
+// =============================================================================
+
+#[test]
+fn test_engine_get_as_of_reads_each_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let engine = open_engine_at(temp_dir.path());
+    assert_eq!(engine.last_seq().unwrap(), 0);
+
+    // Three writes, each flushed to its own SSTable
+    let mut seqs = Vec::new();
+    for value in [b"v1", b"v2", b"v3"] {
+        engine.put(b"key", value).unwrap();
+        seqs.push(engine.last_seq().unwrap());
+        engine.flush().unwrap();
+    }
+    assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(engine.sstable_count(), 3);
+
+    assert_eq!(engine.get_as_of(b"key", seqs[0] - 1).unwrap(), None);
+    assert_eq!(engine.get_as_of(b"key", seqs[0]).unwrap(), Some(b"v1".to_vec()));
+    assert_eq!(engine.get_as_of(b"key", seqs[1]).unwrap(), Some(b"v2".to_vec()));
+    assert_eq!(engine.get_as_of(b"key", seqs[2]).unwrap(), Some(b"v3".to_vec()));
+    assert_eq!(engine.get_as_of(b"key", u64::MAX).unwrap(), Some(b"v3".to_vec()));
+
+    // A later delete hides the key now, but not as of earlier seqs
+    engine.delete(b"key").unwrap();
+    let deleted_at = engine.last_seq().unwrap();
+    assert_eq!(engine.get_as_of(b"key", deleted_at).unwrap(), None);
+    assert_eq!(engine.get_as_of(b"key", seqs[1]).unwrap(), Some(b"v2".to_vec()));
+}
+
+#[test]
+fn test_engine_seqs_keep_increasing_across_reopen() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let (first, second) = {
+        let engine = open_engine_at(temp_dir.path());
+        engine.put(b"key", b"old").unwrap();
+        let first = engine.last_seq().unwrap();
+        engine.put(b"key", b"new").unwrap();
+        (first, engine.last_seq().unwrap())
+        // Dropped without flushing: the WAL is replayed on reopen
+    };
+
+    let engine = open_engine_at(temp_dir.path());
+    assert!(engine.last_seq().unwrap() >= second);
+
+    // The recovered entry kept its WAL sequence number
+    assert_eq!(engine.get_as_of(b"key", first).unwrap(), None);
+    assert_eq!(engine.get_as_of(b"key", second).unwrap(), Some(b"new".to_vec()));
+
+    engine.put(b"key", b"newer").unwrap();
+    assert!(engine.last_seq().unwrap() > second);
+    assert_eq!(engine.get_as_of(b"key", second).unwrap(), Some(b"new".to_vec()));
+}
+
+#[test]
+fn test_engine_get_with_meta_timestamp_advances_on_overwrite() {
+    let (_temp_dir, engine) = setup_temp_engine();
+    assert_eq!(engine.get_with_meta(b"key").unwrap(), None);
+
+    engine.put(b"key", b"v1").unwrap();
+    let (value, first) = engine.get_with_meta(b"key").unwrap().unwrap();
+    assert_eq!(value, b"v1");
+    assert!(first > 0);
+
+    // The timestamp survives a flush to SSTable
+    engine.flush().unwrap();
+    assert_eq!(engine.get_with_meta(b"key").unwrap(), Some((b"v1".to_vec(), first)));
+
+    thread::sleep(std::time::Duration::from_millis(5));
+    engine.put(b"key", b"v2").unwrap();
+    let (value, second) = engine.get_with_meta(b"key").unwrap().unwrap();
+    assert_eq!(value, b"v2");
+    assert!(second > first);
+
+    engine.delete(b"key").unwrap();
+    assert_eq!(engine.get_with_meta(b"key").unwrap(), None);
+}
+
+#[test]
+fn test_engine_execute_get_meta() {
+    let (_temp_dir, engine) = setup_temp_engine();
+    engine.put(b"key", b"value").unwrap();
+    let (_, timestamp) = engine.get_with_meta(b"key").unwrap().unwrap();
+
+    let payload = engine
+        .execute(Command::GetMeta {
+            key: b"key".to_vec(),
+        })
+        .unwrap()
+        .unwrap();
+    assert_eq!(&payload[..8], &timestamp.to_be_bytes());
+    assert_eq!(&payload[8..], b"value");
+
+    let result = engine
+        .execute(Command::GetMeta {
+            key: b"missing".to_vec(),
+        })
+        .unwrap();
+    assert_eq!(result, None);
+}
+
+#[test]
+fn test_engine_health_reports_unusable_storage_dir() {
+    let (temp_dir, engine) = setup_temp_engine();
+    engine.put(b"key", b"value").unwrap();
+
+    let status = engine.execute(Command::Health).unwrap().unwrap();
+    assert!(String::from_utf8(status).unwrap().starts_with("OK"));
+
+    // Tests run as root, which ignores permission bits, so take the
+    // directory away instead of making it read-only
+    std::fs::remove_dir_all(temp_dir.path().join("sstables")).unwrap();
+
+    let err = engine.execute(Command::Health).unwrap_err();
+    assert!(err.to_string().contains("storage dir"), "{}", err);
+    assert_eq!(engine.execute(Command::Ping).unwrap(), Some(b"PONG".to_vec()));
+}
+
+// =============================================================================
+// Merge Tests
+// =============================================================================
+
+fn setup_temp_engine_with_counter(dir: &std::path::Path) -> Engine {
+    let config = Config::builder()
+        .data_dir(dir)
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .merge_operator(I64AddOperator)
+        .build();
+    Engine::open(config).unwrap()
+}
+
+fn counter(engine: &Engine, key: &[u8]) -> Option<i64> {
+    engine
+        .get(key)
+        .unwrap()
+        .map(|v| i64::from_le_bytes(v.try_into().unwrap()))
+}
+
+#[test]
+fn test_engine_merge_i64_add_end_to_end() {
+    let temp_dir = TempDir::new().unwrap();
+
+    {
+        let engine = setup_temp_engine_with_counter(temp_dir.path());
+
+        // Missing key counts as 0
+        engine.merge(b"hits", &5i64.to_le_bytes()).unwrap();
+        assert_eq!(counter(&engine, b"hits"), Some(5));
+
+        // Flush collapses the operands into a single value
+        engine.flush().unwrap();
+        assert_eq!(engine.sstable_stats()[0].entry_count, 1);
+        assert_eq!(counter(&engine, b"hits"), Some(5));
+
+        // Operands over an SSTable base resolve on read
+        engine.merge(b"hits", &10i64.to_le_bytes()).unwrap();
+        engine.merge(b"hits", &(-3i64).to_le_bytes()).unwrap();
+        assert_eq!(counter(&engine, b"hits"), Some(12));
+
+        // Crash with operands only in the WAL
+    }
+
+    // Recovery replays the MERGE entries and flushes resolved values
+    let engine = setup_temp_engine_with_counter(temp_dir.path());
+    assert_eq!(counter(&engine, b"hits"), Some(12));
+    assert_eq!(engine.memtable_entry_count(), 0);
+
+    engine.merge(b"hits", &1i64.to_le_bytes()).unwrap();
+    engine.flush().unwrap();
+    assert_eq!(counter(&engine, b"hits"), Some(13));
+}
+
+#[test]
+fn test_engine_merge_after_delete_and_put() {
+    let temp_dir = TempDir::new().unwrap();
+    let engine = setup_temp_engine_with_counter(temp_dir.path());
+
+    engine.put(b"n", &100i64.to_le_bytes()).unwrap();
+    engine.flush().unwrap();
+
+    engine.delete(b"n").unwrap();
+    engine.merge(b"n", &1i64.to_le_bytes()).unwrap();
+    assert_eq!(counter(&engine, b"n"), Some(1));
+
+    engine.put(b"n", &40i64.to_le_bytes()).unwrap();
+    engine.merge(b"n", &2i64.to_le_bytes()).unwrap();
+    assert_eq!(counter(&engine, b"n"), Some(42));
+}
+
+#[test]
+fn test_engine_merge_without_operator_rejected() {
+    let (temp_dir, engine) = setup_temp_engine();
+
+    let result = engine.merge(b"hits", &1i64.to_le_bytes());
+
+    assert!(matches!(result, Err(AtlasError::Config(_))));
+    assert_eq!(engine.memtable_entry_count(), 0);
+    assert_eq!(std::fs::metadata(temp_dir.path().join("wal.log")).unwrap().len(), 0);
+}
+
+// =============================================================================
+// Size Limit Tests
+// =============================================================================
+
+fn setup_temp_engine_with_limits(max_key: usize, max_value: usize) -> (TempDir, Engine) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .max_key_size(max_key)
+        .max_value_size(max_value)
+        .build();
+    let engine = Engine::open(config).unwrap();
+    (temp_dir, engine)
+}
+
+#[test]
+fn test_engine_default_size_limits() {
+    let config = Config::default();
+    assert_eq!(config.max_key_size, 64 * 1024);
+    assert_eq!(config.max_value_size, 16 * 1024 * 1024);
+}
+
+#[test]
+fn test_engine_key_at_limit_accepted() {
+    let (_temp, engine) = setup_temp_engine_with_limits(16, 64);
+
+    let key = vec![b'k'; 16];
+    engine.put(&key, b"value").unwrap();
+    assert_eq!(engine.get(&key).unwrap(), Some(b"value".to_vec()));
+
+    engine.delete(&key).unwrap();
+    assert_eq!(engine.get(&key).unwrap(), None);
+}
+
+#[test]
+fn test_engine_key_over_limit_rejected() {
+    let (temp_dir, engine) = setup_temp_engine_with_limits(16, 64);
+
+    let key = vec![b'k'; 17];
+    let result = engine.put(&key, b"value");
+    assert!(matches!(result, Err(AtlasError::Storage(ref msg)) if msg.contains("Key too large")));
+
+    assert!(matches!(engine.delete(&key), Err(AtlasError::Storage(_))));
+    assert!(matches!(engine.append(&key, b"x"), Err(AtlasError::Storage(_))));
+    assert!(matches!(engine.get_set(&key, b"x"), Err(AtlasError::Storage(_))));
+
+    // Nothing reached the WAL or MemTable
+    assert_eq!(engine.memtable_entry_count(), 0);
+    assert_eq!(std::fs::metadata(temp_dir.path().join("wal.log")).unwrap().len(), 0);
+}
+
+#[test]
+fn test_engine_value_over_limit_rejected() {
+    let (temp_dir, engine) = setup_temp_engine_with_limits(16, 64);
+
+    engine.put(b"key", &[0u8; 64]).unwrap();
+
+    let result = engine.put(b"other", &[0u8; 65]);
+    assert!(matches!(result, Err(AtlasError::Storage(ref msg)) if msg.contains("Value too large")));
+    assert_eq!(engine.get(b"other").unwrap(), None);
+
+    // Appending past the limit is rejected too, leaving the value intact
+    assert!(matches!(engine.append(b"key", b"x"), Err(AtlasError::Storage(_))));
+    assert_eq!(engine.get(b"key").unwrap(), Some(vec![0u8; 64]));
+
+    let wal_len = std::fs::metadata(temp_dir.path().join("wal.log")).unwrap().len();
+    assert!(matches!(engine.put(b"other", &[0u8; 1000]), Err(AtlasError::Storage(_))));
+    assert_eq!(
+        std::fs::metadata(temp_dir.path().join("wal.log")).unwrap().len(),
+        wal_len
+    );
+}
+
+#[test]
+fn test_engine_writes_rejected_once_database_full() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .max_total_bytes(4096)
+        .build();
+    let engine = Engine::open(config).unwrap();
+
+    // Fill up until the cap is hit
+    let value = [0u8; 100];
+    let mut written = 0;
+    let full = loop {
+        match engine.put(format!("key{:03}", written).as_bytes(), &value) {
+            Ok(()) => written += 1,
+            Err(e) => break e,
+        }
+        assert!(written < 100, "cap never enforced");
+    };
+    assert!(matches!(full, AtlasError::Storage(ref msg) if msg == "database full"));
+    assert!(written > 0);
+
+    // The rejected write never reached the WAL
+    let wal_len = std::fs::metadata(temp_dir.path().join("wal.log")).unwrap().len();
+    let result = engine.put(b"another", &value);
+    assert!(matches!(result, Err(AtlasError::Storage(ref msg)) if msg == "database full"));
+    assert_eq!(engine.get(b"another").unwrap(), None);
+    assert_eq!(std::fs::metadata(temp_dir.path().join("wal.log")).unwrap().len(), wal_len);
+
+    // Deletes are still accepted, and everything written is readable
+    engine.delete(b"key000").unwrap();
+    assert_eq!(engine.get(b"key001").unwrap(), Some(value.to_vec()));
+}
+
+#[test]
+fn test_engine_writes_stall_then_stop_with_too_many_sstables() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .sstable_stall_threshold(2)
+        .sstable_stop_threshold(4)
+        .build();
+    let engine = Engine::open(config).unwrap();
+
+    // Nothing compacts, so each flush adds an SSTable until writes are refused
+    let mut flushes = 0;
+    let stopped = loop {
+        match engine.put(format!("key{}", flushes).as_bytes(), b"value") {
+            Ok(()) => engine.flush().unwrap(),
+            Err(e) => break e,
+        }
+        flushes += 1;
+        assert!(flushes < 10, "writes never stopped");
+    };
+    assert!(matches!(stopped, AtlasError::Storage(ref msg) if msg.contains("too many sstables")));
+    assert_eq!(engine.sstable_count(), 5);
+    assert!(matches!(engine.delete(b"key0"), Err(AtlasError::Storage(_))));
+
+    // Reads still work, and compaction lets writes through again
+    assert_eq!(engine.get(b"key0").unwrap(), Some(b"value".to_vec()));
+    engine.compact().unwrap();
+    engine.put(b"after", b"compact").unwrap();
+    assert_eq!(engine.get(b"after").unwrap(), Some(b"compact".to_vec()));
+}
+
+// =============================================================================
+// Bulk Load Tests
+// =============================================================================
+
+#[test]
+fn test_engine_bulk_load_100k_entries() {
+    let (temp_dir, engine) = setup_temp_engine();
+
+    let entries = (0..100_000).map(|i| {
+        (
+            format!("key{:06}", i).into_bytes(),
+            format!("value{}", i).into_bytes(),
+        )
+    });
+    engine.bulk_load(entries).unwrap();
+
+    // Nothing went through the WAL or MemTable
+    assert_eq!(engine.memtable_entry_count(), 0);
+    assert_eq!(std::fs::metadata(temp_dir.path().join("wal.log")).unwrap().len(), 0);
+    assert_eq!(engine.sstable_count(), 1);
+
+    for i in 0..100_000 {
+        let key = format!("key{:06}", i);
+        let expected = format!("value{}", i);
+        assert_eq!(engine.get(key.as_bytes()).unwrap(), Some(expected.into_bytes()));
+    }
+
+    // Registered in the manifest, so it survives a reopen
+    let config = engine.config().clone();
+    drop(engine);
+    let engine = Engine::open(config).unwrap();
+    assert_eq!(engine.get(b"key099999").unwrap(), Some(b"value99999".to_vec()));
+}
+
+#[test]
+fn test_engine_bulk_load_rejects_unsorted_input() {
+    let (temp_dir, engine) = setup_temp_engine();
+
+    let entries = vec![
+        (b"a".to_vec(), b"1".to_vec()),
+        (b"c".to_vec(), b"2".to_vec()),
+        (b"b".to_vec(), b"3".to_vec()),
+    ];
+    let result = engine.bulk_load(entries.into_iter());
+
+    assert!(matches!(result, Err(AtlasError::Storage(_))));
+    assert_eq!(engine.sstable_count(), 0);
+    assert_eq!(engine.get(b"a").unwrap(), None);
+
+    // No temp file left behind
+    let leftovers = std::fs::read_dir(temp_dir.path().join("sstables"))
+        .unwrap()
+        .filter(|e| e.as_ref().unwrap().path().to_string_lossy().ends_with(".tmp"))
+        .count();
+    assert_eq!(leftovers, 0);
+}
+
+#[test]
+fn test_engine_bulk_load_shadows_earlier_writes() {
+    let (_temp, engine) = setup_temp_engine();
+
+    engine.put(b"key", b"old").unwrap();
+    engine
+        .bulk_load(vec![(b"key".to_vec(), b"loaded".to_vec())].into_iter())
+        .unwrap();
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"loaded".to_vec()));
+
+    // Later writes still win
+    engine.put(b"key", b"new").unwrap();
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"new".to_vec()));
+}
+
+// =============================================================================
+// Comparator Tests
+// =============================================================================
+
+#[test]
+fn test_engine_case_insensitive_comparator() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = || {
+        Config::builder()
+            .data_dir(temp_dir.path())
+            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+            .comparator(CaseInsensitiveComparator)
+            .build()
+    };
+
+    let engine = Engine::open(config()).unwrap();
+    engine.put(b"Apple", b"1").unwrap();
+    engine.put(b"CHERRY", b"3").unwrap();
+    engine.flush().unwrap();
+    engine.put(b"banana", b"2").unwrap();
+    engine.put(b"cherry", b"three").unwrap(); // shadows the flushed CHERRY
+    engine.delete(b"APPLE").unwrap();
+    engine.flush().unwrap();
+    engine.put(b"Date", b"4").unwrap();
+    drop(engine);
+
+    // The WAL replays in comparator order too
+    let engine = Engine::open(config()).unwrap();
+    assert_eq!(engine.get(b"apple").unwrap(), None);
+    assert_eq!(engine.get(b"BANANA").unwrap(), Some(b"2".to_vec()));
+    assert_eq!(engine.get(b"Cherry").unwrap(), Some(b"three".to_vec()));
+    assert_eq!(engine.get(b"date").unwrap(), Some(b"4".to_vec()));
+
+    let keys = engine.scan_keys(Bound::Unbounded, Bound::Unbounded, 10).unwrap();
+    assert_eq!(keys, vec![b"banana".to_vec(), b"cherry".to_vec(), b"Date".to_vec()]);
+    let entries = engine.scan_rev(Bound::Included(b"B"), Bound::Excluded(b"d"), 10).unwrap();
+    assert_eq!(
+        entries,
+        vec![(b"cherry".to_vec(), b"three".to_vec()), (b"banana".to_vec(), b"2".to_vec())]
+    );
+
+    // Compaction merges the SSTables in the same order
+    engine.compact().unwrap();
+    assert_eq!(engine.sstable_count(), 1);
+    assert_eq!(engine.get(b"CHERRY").unwrap(), Some(b"three".to_vec()));
+}
+
+// =============================================================================
+// Dump Tests
+// =============================================================================
+
+#[test]
+fn test_export_import_round_trip() {
+    let (temp_dir, engine) = setup_temp_engine();
+
+    for i in 0..200 {
+        let key = format!("key{:03}", i);
+        engine.put(key.as_bytes(), format!("value{}", i).as_bytes()).unwrap();
+    }
+    engine.flush().unwrap();
+    // Some live entries only in the MemTable, and deletes across both
+    engine.put(b"key050", b"rewritten").unwrap();
+    engine.put(b"zzz", b"memtable-only").unwrap();
+    engine.delete(b"key007").unwrap();
+    engine.delete(b"key150").unwrap();
+
+    let path = temp_dir.path().join("backup.dump");
+    let exported = engine.export(std::fs::File::create(&path).unwrap()).unwrap();
+    assert_eq!(exported, 199);
+
+    let (_restored_dir, restored) = setup_temp_engine();
+    let imported = restored.import(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(imported, 199);
+
+    assert_eq!(restored.iter().unwrap(), engine.iter().unwrap());
+    assert_eq!(restored.get(b"key050").unwrap(), Some(b"rewritten".to_vec()));
+    assert_eq!(restored.get(b"key007").unwrap(), None);
+}
+
+#[test]
+fn test_import_sorts_unsorted_dump() {
+    let (_temp, engine) = setup_temp_engine();
+
+    let mut dump = DumpWriter::new(Vec::new()).unwrap();
+    dump.write_entry(b"b", b"first").unwrap();
+    dump.write_entry(b"a", b"1").unwrap();
+    dump.write_entry(b"b", b"second").unwrap();
+    let bytes = dump.finish().unwrap();
+
+    // Duplicates collapse to the later record
+    assert_eq!(engine.import(bytes.as_slice()).unwrap(), 2);
+    assert_eq!(
+        engine.iter().unwrap(),
+        vec![
+            (b"a".to_vec(), b"1".to_vec()),
+            (b"b".to_vec(), b"second".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn test_import_corrupt_dump_loads_nothing() {
+    let (_source_dir, source) = setup_temp_engine();
+    source.put(b"key", b"value").unwrap();
+    let mut bytes = Vec::new();
+    source.export(&mut bytes).unwrap();
+
+    // Flip a value byte: records still parse, only the CRC catches it
+    let value_at = bytes.len() - 16 - 5;
+    bytes[value_at] ^= 0xFF;
+
+    let (_temp, engine) = setup_temp_engine();
+    let result = engine.import(bytes.as_slice());
+    assert!(matches!(result, Err(AtlasError::Serialization(_))));
+    assert_eq!(engine.sstable_count(), 0);
+    assert_eq!(engine.get(b"key").unwrap(), None);
+
+    // Truncated mid-record
+    let result = engine.import(&bytes[..bytes.len() - 20]);
+    assert!(matches!(result, Err(AtlasError::Serialization(_))));
+}
+
+// =============================================================================
+// Reverse Scan Tests
+// =============================================================================
+
+fn scan_key(i: u32) -> Vec<u8> {
+    format!("key{:03}", i).into_bytes()
+}
+
+#[test]
+fn test_engine_scan_rev_across_sstables() {
+    let (_temp, engine) = setup_temp_engine();
+
+    // Keys 0..100 split across two SSTables, interleaved
+    for i in (0..100).step_by(2) {
+        engine.put(&scan_key(i), b"first").unwrap();
+    }
+    engine.flush().unwrap();
+    for i in (1..100).step_by(2) {
+        engine.put(&scan_key(i), b"second").unwrap();
+    }
+    engine.flush().unwrap();
+    assert_eq!(engine.sstable_count(), 2);
+
+    let entries = engine.scan_rev(Bound::Unbounded, Bound::Unbounded, 100).unwrap();
+    let keys: Vec<_> = entries.iter().map(|(k, _)| k.clone()).collect();
+    let expected: Vec<_> = (0..100).rev().map(scan_key).collect();
+    assert_eq!(keys, expected);
+
+    // Limit keeps the highest keys
+    let entries = engine.scan_rev(Bound::Unbounded, Bound::Unbounded, 10).unwrap();
+    let keys: Vec<_> = entries.iter().map(|(k, _)| k.clone()).collect();
+    let expected: Vec<_> = (90..100).rev().map(scan_key).collect();
+    assert_eq!(keys, expected);
+
+    // Bounded range
+    let entries = engine
+        .scan_rev(Bound::Included(&scan_key(10)[..]), Bound::Excluded(&scan_key(20)[..]), 100)
+        .unwrap();
+    let keys: Vec<_> = entries.iter().map(|(k, _)| k.clone()).collect();
+    let expected: Vec<_> = (10..20).rev().map(scan_key).collect();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn test_engine_scan_rev_newest_wins() {
+    let (_temp, engine) = setup_temp_engine();
+
+    engine.put(b"a", b"old").unwrap();
+    engine.put(b"b", b"old").unwrap();
+    engine.put(b"c", b"old").unwrap();
+    engine.flush().unwrap();
+
+    engine.put(b"a", b"new").unwrap();
+    engine.delete(b"b").unwrap();
+    engine.flush().unwrap();
+
+    // MemTable shadows both SSTables
+    engine.put(b"b", b"revived").unwrap();
+    engine.delete(b"c").unwrap();
+
+    let entries = engine.scan_rev(Bound::Unbounded, Bound::Unbounded, 10).unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            (b"b".to_vec(), b"revived".to_vec()),
+            (b"a".to_vec(), b"new".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn test_engine_execute_scan_rev() {
+    let (_temp, engine) = setup_temp_engine();
+    for key in [b"a", b"b", b"c", b"d"] {
+        engine.put(key, key).unwrap();
     }
 
-    // First recovery
-    {
-        let config = Config::builder()
-            .data_dir(&data_dir)
-            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
-            .build();
-        let engine = Engine::open(config).unwrap();
-        assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
-        // Crash again without writing anything new
-        drop(engine);
+    // Empty end = no upper bound
+    let payload = engine
+        .execute(Command::ScanRev { start: b"b".to_vec(), end: vec![], limit: 2 })
+        .unwrap()
+        .unwrap();
+    let entries = decode_entries(&payload).unwrap();
+    assert_eq!(
+        entries,
+        vec![(b"d".to_vec(), b"d".to_vec()), (b"c".to_vec(), b"c".to_vec())]
+    );
+}
+
+#[test]
+fn test_engine_scan_keys_matches_full_scan() {
+    let (_temp, engine) = setup_temp_engine();
+
+    // Live keys, overwrites, and deletes spread over two SSTables and the MemTable
+    for i in 0..60 {
+        engine.put(&scan_key(i), b"first").unwrap();
+    }
+    engine.flush().unwrap();
+    for i in (0..60).step_by(3) {
+        engine.delete(&scan_key(i)).unwrap();
+    }
+    engine.flush().unwrap();
+    for i in (0..60).step_by(6) {
+        engine.put(&scan_key(i), b"revived").unwrap();
     }
+    engine.delete(&scan_key(59)).unwrap();
+    engine.put(&scan_key(60), b"memtable only").unwrap();
+
+    let full: Vec<_> = engine.iter().unwrap().into_iter().map(|(k, _)| k).collect();
+    let keys = engine.scan_keys(Bound::Unbounded, Bound::Unbounded, usize::MAX).unwrap();
+    assert_eq!(keys, full);
+    assert!(!keys.contains(&scan_key(3)));
+    assert!(keys.contains(&scan_key(6)));
+
+    // Bounded range with a limit keeps the lowest keys
+    let keys = engine
+        .scan_keys(Bound::Included(&scan_key(10)[..]), Bound::Excluded(&scan_key(30)[..]), 5)
+        .unwrap();
+    let expected: Vec<_> = full.into_iter().filter(|k| k >= &scan_key(10)).take(5).collect();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn test_engine_execute_scan_keys() {
+    let (_temp, engine) = setup_temp_engine();
+    for key in [b"a", b"b", b"c", b"d"] {
+        engine.put(key, key).unwrap();
+    }
+    engine.delete(b"c").unwrap();
+
+    // Empty end = no upper bound
+    let payload = engine
+        .execute(Command::ScanKeys { start: b"b".to_vec(), end: vec![], limit: 10 })
+        .unwrap()
+        .unwrap();
+    assert_eq!(decode_keys(&payload).unwrap(), vec![b"b".to_vec(), b"d".to_vec()]);
+}
+
+#[test]
+fn test_engine_scan_after_pages_through_every_key_once() {
+    let (_temp, engine) = setup_temp_engine();
+    let page_key = |i: u32| format!("key{:04}", i).into_bytes();
+
+    // 1000 live keys over two SSTables and the MemTable, plus deleted ones
+    for i in 0..1100 {
+        engine.put(&page_key(i), b"v1").unwrap();
+        if i == 400 || i == 800 {
+            engine.flush().unwrap();
+        }
+    }
+    for i in (0..1100).step_by(11) {
+        engine.delete(&page_key(i)).unwrap();
+    }
+    assert_eq!(engine.count_range(Bound::Unbounded, Bound::Unbounded).unwrap(), 1000);
+
+    let mut seen = Vec::new();
+    let mut after_key: Option<Vec<u8>> = None;
+    loop {
+        let page = engine.scan_after(after_key.as_deref(), 100).unwrap();
+        assert!(page.entries.len() <= 100);
+        after_key = page.entries.last().map(|(key, _)| key.clone());
+        seen.extend(page.entries.into_iter().map(|(key, _)| key));
+        if !page.more {
+            break;
+        }
+    }
+
+    let expected: Vec<_> = (0..1100).filter(|i| i % 11 != 0).map(page_key).collect();
+    assert_eq!(seen, expected);
+
+    // Through execute, the last page says nothing remains
+    let payload = engine
+        .execute(Command::ScanCursor { after_key: Some(page_key(1090)), limit: 100 })
+        .unwrap()
+        .unwrap();
+    let page = decode_scan_page(&payload).unwrap();
+    assert_eq!(page.entries.len(), 9);
+    assert!(!page.more);
+}
+
+#[test]
+fn test_engine_scan_after_until_returns_partial_page_at_deadline() {
+    let (_temp, engine) = setup_temp_engine();
+    let page_key = |i: u32| format!("key{:06}", i).into_bytes();
+    let all = 100_000;
+    engine.bulk_load((0..all as u32).map(|i| (page_key(i), b"v".to_vec()))).unwrap();
+
+    // A deadline already gone stops the scan before its first entry
+    let page = engine.scan_after_until(None, all, Instant::now()).unwrap();
+    assert!(page.entries.is_empty());
+    assert!(page.more && page.deadline_exceeded);
+
+    // A tiny one cuts the scan short, but what came back is still a
+    // correct prefix
+    let deadline = Instant::now() + Duration::from_millis(1);
+    let page = engine.scan_after_until(None, all, deadline).unwrap();
+    assert!(page.more && page.deadline_exceeded);
+    assert!(page.entries.len() < all, "scanned all {} keys within 1 ms", all);
+    for (i, (key, _)) in page.entries.iter().enumerate() {
+        assert_eq!(key, &page_key(i as u32));
+    }
+
+    // A generous one never trips
+    let deadline = Instant::now() + Duration::from_secs(3600);
+    let page = engine.scan_after_until(Some(&page_key(100)), 10, deadline).unwrap();
+    assert_eq!(page.entries.len(), 10);
+    assert!(page.more && !page.deadline_exceeded);
+
+    // Through execute, from a deadline of 0 ms
+    let payload = engine
+        .execute(Command::WithDeadline {
+            deadline_ms: 0,
+            command: Box::new(Command::ScanCursor { after_key: None, limit: 100 }),
+        })
+        .unwrap()
+        .unwrap();
+    let page = decode_scan_page(&payload).unwrap();
+    assert!(page.entries.is_empty() && page.deadline_exceeded);
+
+    // Point commands ignore the deadline
+    let payload = engine
+        .execute(Command::WithDeadline { deadline_ms: 0, command: Box::new(Command::Ping) })
+        .unwrap();
+    assert_eq!(payload, Some(b"PONG".to_vec()));
+
+    // Scans that couldn't flag a partial result are refused, not run unbounded
+    let unbounded = [
+        Command::ScanRev { start: Vec::new(), end: Vec::new(), limit: 10 },
+        Command::ScanKeys { start: Vec::new(), end: Vec::new(), limit: 10 },
+        Command::Count { start: Vec::new(), end: Vec::new() },
+    ];
+    for command in unbounded {
+        let result = engine.execute(Command::WithDeadline {
+            deadline_ms: 60_000,
+            command: Box::new(command),
+        });
+        assert!(matches!(result, Err(AtlasError::Protocol(msg)) if msg.contains("SCANCURSOR")));
+    }
+}
+
+#[test]
+fn test_engine_count_range_skips_tombstones_across_sstables() {
+    let (_temp, engine) = setup_temp_engine();
+
+    // Overlapping SSTables: the second deletes every third key of the first
+    // and overwrites some others; the MemTable deletes and adds one more
+    for i in 0..30 {
+        engine.put(&scan_key(i), b"v1").unwrap();
+    }
+    engine.flush().unwrap();
+    for i in 0..30 {
+        if i % 3 == 0 {
+            engine.delete(&scan_key(i)).unwrap();
+        } else if i % 5 == 0 {
+            engine.put(&scan_key(i), b"v2").unwrap();
+        }
+    }
+    engine.flush().unwrap();
+    engine.delete(&scan_key(1)).unwrap();
+    engine.put(&scan_key(30), b"v3").unwrap();
+    assert_eq!(engine.sstable_count(), 2);
+
+    // 30 keys, 10 deleted in SSTables, 1 in the MemTable, 1 added
+    assert_eq!(engine.count_range(Bound::Unbounded, Bound::Unbounded).unwrap(), 20);
+    let full = engine.scan_keys(Bound::Unbounded, Bound::Unbounded, usize::MAX).unwrap();
+    assert_eq!(full.len(), 20);
+
+    // [key000, key010) holds 0-9 minus 0, 1, 3, 6, 9
+    let count = engine
+        .count_range(Bound::Included(&scan_key(0)[..]), Bound::Excluded(&scan_key(10)[..]))
+        .unwrap();
+    assert_eq!(count, 5);
+
+    let payload = engine
+        .execute(Command::Count { start: scan_key(10), end: vec![] })
+        .unwrap()
+        .unwrap();
+    assert_eq!(u64::from_be_bytes(payload.try_into().unwrap()), 15);
+}
+
+#[test]
+fn test_engine_approx_key_count_bounds_exact_count() {
+    let (_temp, engine) = setup_temp_engine();
+    assert_eq!(engine.approx_key_count(), 0);
+
+    // Overwrites and deletes spread over two SSTables and the MemTable
+    for i in 0..20 {
+        engine.put(&scan_key(i), b"v1").unwrap();
+    }
+    engine.flush().unwrap();
+    for i in (0..20).step_by(2) {
+        engine.delete(&scan_key(i)).unwrap();
+    }
+    engine.flush().unwrap();
+    engine.put(&scan_key(1), b"v2").unwrap();
+    engine.put(&scan_key(20), b"v2").unwrap();
+
+    let exact = engine.count_range(Bound::Unbounded, Bound::Unbounded).unwrap();
+    assert_eq!(exact, 11);
+    // 20 + 10 SSTable entries + 2 MemTable entries
+    assert_eq!(engine.approx_key_count(), 32);
+    assert!(engine.approx_key_count() >= exact);
+}
+
+/// Every scan flavour against an engine with no MemTable entries and no SSTables
+fn assert_scans_empty(engine: &Engine) {
+    let all = (Bound::Unbounded, Bound::Unbounded);
+    // What a prefix scan for "key" becomes: [key, kez)
+    let prefix = (Bound::Included(&b"key"[..]), Bound::Excluded(&b"kez"[..]));
+
+    for (start, end) in [all, prefix] {
+        assert!(engine.scan_rev(start, end, usize::MAX).unwrap().is_empty());
+        assert!(engine.scan_keys(start, end, usize::MAX).unwrap().is_empty());
+        assert_eq!(engine.count_range(start, end).unwrap(), 0);
+    }
+    assert!(engine.iter().unwrap().is_empty());
+
+    let payload = engine
+        .execute(Command::ScanRev { start: vec![], end: vec![], limit: 10 })
+        .unwrap()
+        .unwrap();
+    assert!(decode_entries(&payload).unwrap().is_empty());
+    let payload = engine
+        .execute(Command::Count { start: b"key".to_vec(), end: b"kez".to_vec() })
+        .unwrap()
+        .unwrap();
+    assert_eq!(u64::from_be_bytes(payload.try_into().unwrap()), 0);
+}
+
+#[test]
+fn test_engine_scans_of_empty_database() {
+    let (temp_dir, engine) = setup_temp_engine();
+    assert_eq!(engine.sstable_count(), 0);
+    assert_eq!(engine.memtable_entry_count(), 0);
+    assert_scans_empty(&engine);
+
+    // Still nothing to merge after a reopen (the empty flush writes no SSTable)
+    drop(engine);
+    let engine = Engine::open(Config::builder().data_dir(temp_dir.path()).build()).unwrap();
+    assert_eq!(engine.sstable_count(), 0);
+    assert_scans_empty(&engine);
+}
+
+// =============================================================================
+// Write Observer Tests
+// =============================================================================
+
+#[test]
+fn test_write_observer_sees_writes_in_wal_order() {
+    let (temp_dir, engine) = setup_temp_engine();
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    engine.set_write_observer(Box::new(move |op: &Operation| {
+        sink.lock().unwrap().push(op.clone());
+    }));
+
+    engine.put(b"a", b"1").unwrap();
+    engine.append(b"a", b"2").unwrap();
+    engine.delete(b"b").unwrap();
+
+    let seen = seen.lock().unwrap().clone();
+    assert_eq!(
+        seen,
+        vec![
+            Operation::Put { key: b"a".to_vec(), value: b"1".to_vec() },
+            Operation::Put { key: b"a".to_vec(), value: b"12".to_vec() },
+            Operation::Delete { key: b"b".to_vec() },
+        ]
+    );
+
+    // The feed matches what a WAL tail would see
+    let mut logged = Vec::new();
+    WalRecovery::recover_with(&temp_dir.path().join("wal.log"), |entry| {
+        logged.push(entry.operation.clone());
+    })
+    .unwrap();
+    assert_eq!(logged, seen);
+}
+
+#[test]
+fn test_write_observer_ignores_rejected_writes() {
+    let (_temp, engine) = setup_temp_engine();
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&count);
+    engine.set_write_observer(Box::new(move |_: &Operation| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    }));
+
+    let too_big = vec![0u8; engine.config().max_key_size + 1];
+    assert!(engine.put(&too_big, b"v").is_err());
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn test_panicking_write_observer_leaves_engine_usable() {
+    let (_temp, engine) = setup_temp_engine();
+
+    engine.set_write_observer(Box::new(|op: &Operation| {
+        if matches!(op, Operation::Put { key, .. } if key == b"boom") {
+            panic!("observer failure");
+        }
+    }));
+
+    // The write that triggered the panic is still applied
+    engine.put(b"boom", b"value").unwrap();
+    assert_eq!(engine.get(b"boom").unwrap(), Some(b"value".to_vec()));
+
+    // And the write lock isn't poisoned
+    engine.put(b"after", b"ok").unwrap();
+    engine.flush().unwrap();
+    assert_eq!(engine.get(b"after").unwrap(), Some(b"ok".to_vec()));
+}
+
+#[test]
+fn test_subscribe_wal_streams_entries_in_lsn_order() {
+    let (temp_dir, engine) = setup_temp_engine();
+
+    // Writes from before subscribing aren't replayed
+    engine.put(b"early", b"0").unwrap();
+
+    let receiver = engine.subscribe_wal();
+    engine.put(b"a", b"1").unwrap();
+    engine.delete(b"b").unwrap();
+    engine
+        .write_batch(vec![
+            Operation::Put { key: b"c".to_vec(), value: b"3".to_vec() },
+            Operation::Delete { key: b"a".to_vec() },
+        ])
+        .unwrap();
+
+    let received: Vec<_> = receiver.try_iter().collect();
+    let operations: Vec<_> = received.iter().map(|entry| entry.operation.clone()).collect();
+    assert_eq!(
+        operations,
+        vec![
+            Operation::Put { key: b"a".to_vec(), value: b"1".to_vec() },
+            Operation::Delete { key: b"b".to_vec() },
+            Operation::BatchBegin { count: 2 },
+            Operation::Put { key: b"c".to_vec(), value: b"3".to_vec() },
+            Operation::Delete { key: b"a".to_vec() },
+            Operation::BatchCommit,
+        ]
+    );
+    assert!(received.windows(2).all(|pair| pair[1].lsn == pair[0].lsn + 1));
+
+    // Single writes arrive exactly as logged
+    let mut logged = Vec::new();
+    WalRecovery::recover_with(&temp_dir.path().join("wal.log"), |entry| {
+        logged.push(entry.clone());
+    })
+    .unwrap();
+    assert_eq!(&logged[1..3], &received[..2]);
+}
+
+#[test]
+fn test_subscribe_wal_drops_entries_for_full_channel() {
+    let (_temp, engine) = setup_temp_engine();
+
+    let receiver = engine.subscribe_wal();
+    let capacity = 1024;
+    for i in 0..capacity + 5 {
+        engine.put(format!("key{}", i).as_bytes(), b"v").unwrap();
+    }
+
+    // Writes never block on a slow subscriber; the overflow is counted
+    assert_eq!(receiver.len(), capacity);
+    assert_eq!(engine.metrics().wal_stream_dropped.load(Ordering::Relaxed), 5);
+
+    // Dropping the receiver unsubscribes without counting drops
+    drop(receiver);
+    engine.put(b"after", b"v").unwrap();
+    assert_eq!(engine.metrics().wal_stream_dropped.load(Ordering::Relaxed), 5);
+}
+
+// =============================================================================
+// Replication Tests
+// =============================================================================
+
+#[test]
+fn test_apply_replicated_stream_matches_primary() {
+    let (_primary_dir, primary) = setup_temp_engine();
+    let (replica_dir, replica) = setup_temp_engine_with_small_memtable();
+
+    let stream = primary.subscribe_wal();
+    for i in 0..20 {
+        primary.put(format!("key{:02}", i).as_bytes(), format!("value{}", i).as_bytes()).unwrap();
+    }
+    primary.delete(b"key03").unwrap();
+    primary.put(b"key05", b"updated").unwrap();
+    primary
+        .write_batch(vec![
+            Operation::Put { key: b"batch".to_vec(), value: b"1".to_vec() },
+            Operation::Delete { key: b"key07".to_vec() },
+        ])
+        .unwrap();
+    primary.flush().unwrap();
+    primary.put(b"after_flush", b"x").unwrap();
+
+    // The small MemTable makes the replica flush along the way
+    for entry in stream.try_iter() {
+        replica.apply_replicated(entry).unwrap();
+    }
+    assert!(replica.sstable_count() > 0);
+    assert_eq!(replica.last_seq().unwrap(), primary.last_seq().unwrap());
+
+    let mut keys: Vec<Vec<u8>> = (0..20).map(|i| format!("key{:02}", i).into_bytes()).collect();
+    keys.extend([b"batch".to_vec(), b"after_flush".to_vec()]);
+    for key in &keys {
+        assert_eq!(replica.get(key).unwrap(), primary.get(key).unwrap());
+    }
+
+    // Replicated writes are durable on the replica
+    drop(replica);
+    let reopened = Engine::open(
+        Config::builder()
+            .data_dir(replica_dir.path())
+            .memtable_size_limit(100)
+            .build(),
+    )
+    .unwrap();
+    for key in &keys {
+        assert_eq!(reopened.get(key).unwrap(), primary.get(key).unwrap());
+    }
+}
+
+#[test]
+fn test_apply_replicated_rejects_gaps_and_duplicates() {
+    let (_primary_dir, primary) = setup_temp_engine();
+    let (_replica_dir, replica) = setup_temp_engine();
+
+    let stream = primary.subscribe_wal();
+    primary.put(b"a", b"1").unwrap();
+    primary.put(b"b", b"2").unwrap();
+    primary.put(b"c", b"3").unwrap();
+    let entries: Vec<_> = stream.try_iter().collect();
+
+    // A gap
+    let result = replica.apply_replicated(entries[1].clone());
+    assert!(matches!(result, Err(AtlasError::Storage(_))));
+    assert_eq!(replica.get(b"b").unwrap(), None);
+
+    replica.apply_replicated(entries[0].clone()).unwrap();
+    replica.apply_replicated(entries[1].clone()).unwrap();
+
+    // A duplicate, then an entry from the past
+    let result = replica.apply_replicated(entries[1].clone());
+    assert!(matches!(result, Err(AtlasError::Storage(_))));
+    let result = replica.apply_replicated(entries[0].clone());
+    assert!(matches!(result, Err(AtlasError::Storage(_))));
+
+    replica.apply_replicated(entries[2].clone()).unwrap();
+    assert_eq!(replica.get(b"c").unwrap(), Some(b"3".to_vec()));
+    assert_eq!(replica.last_seq().unwrap(), 3);
+}
+
+#[test]
+fn test_apply_replicated_refuses_flush_mid_batch() {
+    let (_primary_dir, primary) = setup_temp_engine();
+    let (replica_dir, replica) = setup_temp_engine();
+    let reopen = || Engine::open(Config::builder().data_dir(replica_dir.path()).build()).unwrap();
+
+    let stream = primary.subscribe_wal();
+    primary.put(b"before", b"0").unwrap();
+    primary
+        .write_batch(vec![
+            Operation::Put { key: b"batch_a".to_vec(), value: b"1".to_vec() },
+            Operation::Put { key: b"batch_b".to_vec(), value: b"2".to_vec() },
+        ])
+        .unwrap();
+    primary.put(b"after", b"3").unwrap();
+    let entries: Vec<_> = stream.try_iter().collect();
+    assert!(matches!(entries[1].operation, Operation::BatchBegin { .. }));
+
+    // Stop inside the batch: a flush would split it, so it's refused
+    for entry in &entries[..3] {
+        replica.apply_replicated(entry.clone()).unwrap();
+    }
+    assert!(matches!(replica.flush(), Err(AtlasError::Storage(_))));
+    assert_eq!(replica.sstable_count(), 0);
+
+    // A crash here loses the whole batch, but nothing before it
+    drop(replica);
+    let replica = reopen();
+    assert_eq!(replica.get(b"before").unwrap(), Some(b"0".to_vec()));
+    assert_eq!(replica.get(b"batch_a").unwrap(), None);
+    assert_eq!(replica.last_seq().unwrap(), entries[0].lsn);
+
+    // Resend the batch, refuse another flush halfway, then finish the stream
+    for entry in &entries[1..3] {
+        replica.apply_replicated(entry.clone()).unwrap();
+    }
+    assert!(matches!(replica.flush(), Err(AtlasError::Storage(_))));
+    for entry in &entries[3..] {
+        replica.apply_replicated(entry.clone()).unwrap();
+    }
+    let stats = replica.flush_stats().unwrap();
+    assert_eq!(stats.entries, 3);
+
+    // Everything, including what was logged after the batch, survives a restart
+    drop(replica);
+    let replica = reopen();
+    for (key, value) in [("before", "0"), ("batch_a", "1"), ("batch_b", "2"), ("after", "3")] {
+        assert_eq!(replica.get(key.as_bytes()).unwrap(), Some(value.as_bytes().to_vec()));
+    }
+    assert_eq!(replica.last_seq().unwrap(), primary.last_seq().unwrap());
+}
+
+// =============================================================================
+// Read-Only Mode Tests
+// =============================================================================
+
+#[test]
+fn test_engine_read_only_alongside_writer() {
+    let (temp_dir, writer) = setup_temp_engine();
+
+    // Some data in an SSTable, some only in the WAL
+    writer.put(b"flushed", b"1").unwrap();
+    writer.flush().unwrap();
+    writer.put(b"unflushed", b"2").unwrap();
+    writer.delete(b"flushed").unwrap();
+
+    let config = Config::builder().data_dir(temp_dir.path()).build();
+    let reader = Engine::open_read_only(config).unwrap();
+
+    assert!(reader.is_read_only());
+    assert!(!writer.is_read_only());
+    assert_eq!(reader.get(b"flushed").unwrap(), None);
+    assert_eq!(reader.get(b"unflushed").unwrap(), Some(b"2".to_vec()));
+
+    // Writes are refused
+    assert!(matches!(reader.put(b"k", b"v"), Err(AtlasError::Storage(_))));
+    assert!(matches!(reader.delete(b"unflushed"), Err(AtlasError::Storage(_))));
+    assert!(matches!(reader.flush(), Err(AtlasError::Storage(_))));
+    reader.close().unwrap();
+
+    // Writer is unaffected
+    writer.put(b"after", b"3").unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.get(b"after").unwrap(), Some(b"3".to_vec()));
+    assert_eq!(writer.get(b"unflushed").unwrap(), Some(b"2".to_vec()));
+}
+
+#[test]
+fn test_engine_read_only_leaves_files_untouched() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .build();
 
-    // Second recovery - data should still be there (in SSTable from first recovery)
     {
-        let config = Config::builder()
-            .data_dir(&data_dir)
-            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
-            .build();
-        let engine = Engine::open(config).unwrap();
-        assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
+        let engine = Engine::open(config.clone()).unwrap();
+        engine.put(b"key", b"value").unwrap();
+        drop(engine); // Crash: data only in WAL
     }
+
+    let wal_path = temp_dir.path().join("wal.log");
+    let wal_len = std::fs::metadata(&wal_path).unwrap().len();
+
+    let engine = Engine::open_read_only(config).unwrap();
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
+    assert_eq!(engine.sstable_count(), 0); // No recovery flush
+
+    assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), wal_len);
+}
+
+#[test]
+fn test_engine_read_only_missing_dir_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path().join("missing"))
+        .build();
+
+    assert!(Engine::open_read_only(config).is_err());
+    assert!(!temp_dir.path().join("missing").exists());
 }
 
 // =============================================================================
@@ -360,6 +2549,37 @@ fn test_engine_close_flushes_data() {
     }
 }
 
+#[test]
+fn test_engine_flush_on_drop() {
+    let temp_dir = TempDir::new().unwrap();
+    let wal_path = temp_dir.path().join("wal.log");
+    let config = |flush_on_drop| {
+        Config::builder()
+            .data_dir(temp_dir.path())
+            .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+            .flush_on_drop(flush_on_drop)
+            .build()
+    };
+
+    // Off (the default): a drop behaves like a crash
+    let engine = Engine::open(config(false)).unwrap();
+    engine.put(b"crash", b"1").unwrap();
+    drop(engine);
+    assert!(std::fs::metadata(&wal_path).unwrap().len() > 0);
+
+    // On: the drop flushes, leaving nothing to replay
+    let engine = Engine::open(config(true)).unwrap();
+    assert_eq!(engine.sstable_count(), 1);
+    engine.put(b"dropped", b"2").unwrap();
+    drop(engine);
+    assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 0);
+
+    let engine = Engine::open(config(false)).unwrap();
+    assert_eq!(engine.sstable_count(), 2);
+    assert_eq!(engine.get(b"crash").unwrap(), Some(b"1".to_vec()));
+    assert_eq!(engine.get(b"dropped").unwrap(), Some(b"2".to_vec()));
+}
+
 #[test]
 fn test_engine_open_path_convenience() {
     let temp_dir = TempDir::new().unwrap();
@@ -434,6 +2654,105 @@ fn test_engine_concurrent_reads() {
     }
 }
 
+/// Merge operator that panics on a `boom` operand, and otherwise keeps the last one
+#[derive(Debug)]
+struct PanickingOperator;
+
+impl MergeOperator for PanickingOperator {
+    fn merge(&self, _existing: Option<&[u8]>, operands: &[Vec<u8>]) -> Vec<u8> {
+        if operands.iter().any(|operand| operand == b"boom") {
+            panic!("merge operator failed");
+        }
+        operands.last().cloned().unwrap_or_default()
+    }
+}
+
+#[test]
+fn test_engine_usable_after_writer_panics_holding_write_lock() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .merge_operator(PanickingOperator)
+        .build();
+    let engine = Arc::new(Engine::open(config).unwrap());
+    engine.put(b"key", b"base").unwrap();
+
+    // Merging onto a value runs the operator with the write lock held
+    let writer = {
+        let engine = Arc::clone(&engine);
+        thread::spawn(move || engine.merge(b"key", b"boom"))
+    };
+    assert!(writer.join().is_err());
+
+    engine.put(b"other", b"value").unwrap();
+    engine.merge(b"key", b"fine").unwrap();
+    engine.delete(b"other").unwrap();
+    engine.flush().unwrap();
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"fine".to_vec()));
+    assert_eq!(engine.get(b"other").unwrap(), None);
+    assert!(engine.health().is_ok());
+}
+
+#[test]
+fn test_engine_writes_interleaved_with_flushes() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryNEntries { count: 100 })
+        .build();
+    let engine = Arc::new(Engine::open(config.clone()).unwrap());
+
+    let done = Arc::new(AtomicBool::new(false));
+    let flusher = {
+        let engine = Arc::clone(&engine);
+        let done = Arc::clone(&done);
+        thread::spawn(move || {
+            while !done.load(Ordering::Relaxed) {
+                engine.flush().unwrap();
+            }
+        })
+    };
+
+    // Each writer puts its keys, then deletes every third one
+    let mut handles = vec![];
+    for t in 0..4 {
+        let engine = Arc::clone(&engine);
+        handles.push(thread::spawn(move || {
+            for i in 0..200 {
+                let key = format!("t{}_key{:03}", t, i);
+                engine.put(key.as_bytes(), b"value").unwrap();
+                if i % 3 == 0 {
+                    engine.delete(key.as_bytes()).unwrap();
+                }
+            }
+        }));
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    done.store(true, Ordering::Relaxed);
+    flusher.join().unwrap();
+
+    let check = |engine: &Engine| {
+        for t in 0..4 {
+            for i in 0..200 {
+                let key = format!("t{}_key{:03}", t, i);
+                let expected = (i % 3 != 0).then(|| b"value".to_vec());
+                assert_eq!(engine.get(key.as_bytes()).unwrap(), expected, "{}", key);
+            }
+        }
+    };
+    check(&engine);
+
+    // Nothing was lost from the WAL or SSTables either
+    drop(Arc::try_unwrap(engine).ok().unwrap());
+    check(&Engine::open(config).unwrap());
+}
+
 #[test]
 fn test_engine_concurrent_writes() {
     use std::sync::Arc;
@@ -24,8 +24,8 @@ fn test_config_default() {
     assert_eq!(config.memtable_size_limit, 64 * 1024 * 1024); // 64 MB
     assert_eq!(config.listen_addr, "127.0.0.1:6379");
     assert_eq!(config.max_connections, 1024);
-    assert_eq!(config.read_timeout_ms, 5000);
-    assert_eq!(config.write_timeout_ms, 5000);
+    assert_eq!(config.read_timeout_ms, 30000);
+    assert_eq!(config.write_timeout_ms, 30000);
 }
 
 #[test]
@@ -0,0 +1,98 @@
+//! Tests for numeric key encodings
+//!
+//! These tests verify:
+//! - Round-trips for unsigned and signed values, including extremes
+//! - Byte order of encoded keys matches numeric order
+//! - Encoded keys scan back in numeric order from the engine
+//! - Wrong-length input is rejected
+
+use std::ops::Bound;
+
+use atlaskv::config::{Config, WalSyncStrategy};
+use atlaskv::keys::{decode_i64_be, decode_u64_be, encode_i64_be, encode_u64_be};
+use atlaskv::{AtlasError, Engine};
+use tempfile::TempDir;
+
+// =============================================================================
+// Round-Trip Tests
+// =============================================================================
+
+#[test]
+fn test_u64_round_trip() {
+    for value in [0, 1, 255, 256, u32::MAX as u64, u64::MAX] {
+        assert_eq!(decode_u64_be(&encode_u64_be(value)).unwrap(), value);
+    }
+}
+
+#[test]
+fn test_i64_round_trip() {
+    for value in [i64::MIN, -256, -1, 0, 1, 256, i64::MAX] {
+        assert_eq!(decode_i64_be(&encode_i64_be(value)).unwrap(), value);
+    }
+}
+
+// =============================================================================
+// Ordering Tests
+// =============================================================================
+
+#[test]
+fn test_i64_orders_around_zero() {
+    assert!(encode_i64_be(-1) < encode_i64_be(0));
+    assert!(encode_i64_be(0) < encode_i64_be(1));
+}
+
+#[test]
+fn test_u64_byte_order_matches_numeric_order() {
+    let values = [0u64, 1, 255, 256, 65_535, 65_536, u64::MAX - 1, u64::MAX];
+    for pair in values.windows(2) {
+        assert!(encode_u64_be(pair[0]) < encode_u64_be(pair[1]), "{:?}", pair);
+    }
+
+    // The little-endian encoding gets this wrong
+    assert!(256u64.to_le_bytes() < 1u64.to_le_bytes());
+}
+
+#[test]
+fn test_i64_byte_order_matches_numeric_order() {
+    let values = [i64::MIN, i64::MIN + 1, -65_536, -256, -1, 0, 1, 256, i64::MAX];
+    for pair in values.windows(2) {
+        assert!(encode_i64_be(pair[0]) < encode_i64_be(pair[1]), "{:?}", pair);
+    }
+}
+
+#[test]
+fn test_engine_scan_returns_numeric_order() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .build();
+    let engine = Engine::open(config).unwrap();
+
+    let values = [-300i64, -2, 0, 7, 1_000];
+    for value in values {
+        engine.put(&encode_i64_be(value), b"v").unwrap();
+    }
+    engine.flush().unwrap();
+
+    let entries = engine
+        .scan_rev(Bound::Unbounded, Bound::Unbounded, 10)
+        .unwrap();
+    let scanned: Vec<i64> = entries
+        .iter()
+        .map(|(key, _)| decode_i64_be(key).unwrap())
+        .collect();
+
+    assert_eq!(scanned, vec![1_000, 7, 0, -2, -300]);
+}
+
+// =============================================================================
+// Error Tests
+// =============================================================================
+
+#[test]
+fn test_decode_wrong_length_rejected() {
+    assert!(matches!(decode_u64_be(&[0; 7]), Err(AtlasError::Serialization(_))));
+    assert!(matches!(decode_u64_be(&[0; 9]), Err(AtlasError::Serialization(_))));
+    assert!(matches!(decode_i64_be(&[]), Err(AtlasError::Serialization(_))));
+}
@@ -0,0 +1,2 @@
+// Key encoding tests
+mod encoding_tests;
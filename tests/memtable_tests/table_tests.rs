@@ -2,13 +2,20 @@
 //!
 //! Tests verify:
 //! - Basic CRUD operations
+//! - Sequence numbers on writes
 //! - Size tracking
 //! - Tombstone handling
-//! - Sorted iteration
+//! - Merge operand queuing
+//! - Sorted iteration (byte order, or a configured comparator)
 //! - Clear functionality
 //! - Concurrent access patterns
 
-use atlaskv::memtable::{MemTable, MemTableEntry};
+use std::ops::Bound;
+use std::sync::Arc;
+
+use atlaskv::comparator::CaseInsensitiveComparator;
+use atlaskv::memtable::{MemTable, MemTableEntry, DEFAULT_ENTRY_OVERHEAD};
+use atlaskv::merge::I64AddOperator;
 
 // =============================================================================
 // Basic Operations Tests
@@ -29,7 +36,7 @@ fn test_put_and_get() {
     memtable.put(b"key1".to_vec(), b"value1".to_vec());
     
     let result = memtable.get(b"key1");
-    assert_eq!(result, Some(MemTableEntry::Value(b"value1".to_vec())));
+    assert_eq!(result, Some(MemTableEntry::Value(Arc::new(b"value1".to_vec()))));
 }
 
 #[test]
@@ -49,9 +56,9 @@ fn test_put_multiple_entries() {
     memtable.put(b"key3".to_vec(), b"value3".to_vec());
     
     assert_eq!(memtable.entry_count(), 3);
-    assert_eq!(memtable.get(b"key1"), Some(MemTableEntry::Value(b"value1".to_vec())));
-    assert_eq!(memtable.get(b"key2"), Some(MemTableEntry::Value(b"value2".to_vec())));
-    assert_eq!(memtable.get(b"key3"), Some(MemTableEntry::Value(b"value3".to_vec())));
+    assert_eq!(memtable.get(b"key1"), Some(MemTableEntry::Value(Arc::new(b"value1".to_vec()))));
+    assert_eq!(memtable.get(b"key2"), Some(MemTableEntry::Value(Arc::new(b"value2".to_vec()))));
+    assert_eq!(memtable.get(b"key3"), Some(MemTableEntry::Value(Arc::new(b"value3".to_vec()))));
 }
 
 #[test]
@@ -62,7 +69,28 @@ fn test_put_overwrites_existing() {
     memtable.put(b"key1".to_vec(), b"value2".to_vec());
     
     assert_eq!(memtable.entry_count(), 1);
-    assert_eq!(memtable.get(b"key1"), Some(MemTableEntry::Value(b"value2".to_vec())));
+    assert_eq!(memtable.get(b"key1"), Some(MemTableEntry::Value(Arc::new(b"value2".to_vec()))));
+}
+
+#[test]
+fn test_writes_record_sequence_numbers() {
+    let memtable = MemTable::new();
+
+    memtable.put_at(b"key".to_vec(), b"v1".to_vec(), 3, 1_000);
+    memtable.put_at(b"key".to_vec(), b"v2".to_vec(), 5, 2_000);
+    memtable.delete_at(b"gone".to_vec(), 6, 3_000);
+    memtable.put(b"plain".to_vec(), b"v".to_vec());
+
+    // Only the latest version of a key is kept
+    let found = memtable.get_with_seq(b"key").unwrap();
+    assert_eq!(found.entry, MemTableEntry::Value(Arc::new(b"v2".to_vec())));
+    assert_eq!(found.seq, 5);
+    assert_eq!(found.timestamp, 2_000);
+
+    assert_eq!(memtable.get_with_seq(b"gone").unwrap().seq, 6);
+    assert_eq!(memtable.get_with_seq(b"gone").unwrap().timestamp, 3_000);
+    assert_eq!(memtable.get_with_seq(b"plain").unwrap().seq, 0);
+    assert!(memtable.get_with_seq(b"missing").is_none());
 }
 
 // =============================================================================
@@ -98,7 +126,55 @@ fn test_put_after_delete() {
     memtable.delete(b"key1".to_vec());
     memtable.put(b"key1".to_vec(), b"value2".to_vec());
     
-    assert_eq!(memtable.get(b"key1"), Some(MemTableEntry::Value(b"value2".to_vec())));
+    assert_eq!(memtable.get(b"key1"), Some(MemTableEntry::Value(Arc::new(b"value2".to_vec()))));
+}
+
+// =============================================================================
+// Merge Tests
+// =============================================================================
+
+#[test]
+fn test_merge_without_base_queues_operands() {
+    let memtable = MemTable::new();
+
+    memtable.merge(b"counter".to_vec(), 1i64.to_le_bytes().to_vec(), &I64AddOperator);
+    memtable.merge(b"counter".to_vec(), 2i64.to_le_bytes().to_vec(), &I64AddOperator);
+
+    assert_eq!(
+        memtable.get(b"counter"),
+        Some(MemTableEntry::Merge(vec![
+            1i64.to_le_bytes().to_vec(),
+            2i64.to_le_bytes().to_vec(),
+        ]))
+    );
+    assert_eq!(memtable.size(), DEFAULT_ENTRY_OVERHEAD + 7 + 16);
+}
+
+#[test]
+fn test_merge_over_value_folds_immediately() {
+    let memtable = MemTable::new();
+
+    memtable.put(b"counter".to_vec(), 10i64.to_le_bytes().to_vec());
+    memtable.merge(b"counter".to_vec(), 5i64.to_le_bytes().to_vec(), &I64AddOperator);
+
+    assert_eq!(
+        memtable.get(b"counter"),
+        Some(MemTableEntry::Value(Arc::new(15i64.to_le_bytes().to_vec())))
+    );
+    assert_eq!(memtable.size(), DEFAULT_ENTRY_OVERHEAD + 7 + 8);
+}
+
+#[test]
+fn test_merge_over_tombstone_starts_from_nothing() {
+    let memtable = MemTable::new();
+
+    memtable.delete(b"counter".to_vec());
+    memtable.merge(b"counter".to_vec(), 3i64.to_le_bytes().to_vec(), &I64AddOperator);
+
+    assert_eq!(
+        memtable.get(b"counter"),
+        Some(MemTableEntry::Value(Arc::new(3i64.to_le_bytes().to_vec())))
+    );
 }
 
 // =============================================================================
@@ -114,7 +190,7 @@ fn test_size_tracking_put() {
     
     memtable.put(b"key".to_vec(), b"value".to_vec());
     
-    let expected_size = b"key".len() + b"value".len();
+    let expected_size = DEFAULT_ENTRY_OVERHEAD + b"key".len() + b"value".len();
     assert_eq!(memtable.size(), expected_size);
 }
 
@@ -125,8 +201,8 @@ fn test_size_tracking_multiple_puts() {
     memtable.put(b"key1".to_vec(), b"value1".to_vec());
     memtable.put(b"key2".to_vec(), b"value2".to_vec());
     
-    let expected_size = (b"key1".len() + b"value1".len()) + 
-                        (b"key2".len() + b"value2".len());
+    let expected_size = (DEFAULT_ENTRY_OVERHEAD + b"key1".len() + b"value1".len()) + 
+                        (DEFAULT_ENTRY_OVERHEAD + b"key2".len() + b"value2".len());
     assert_eq!(memtable.size(), expected_size);
 }
 
@@ -140,8 +216,8 @@ fn test_size_tracking_overwrite() {
     memtable.put(b"key".to_vec(), b"much_longer_value".to_vec());
     let size_after_second = memtable.size();
     
-    assert_eq!(size_after_first, b"key".len() + b"short".len());
-    assert_eq!(size_after_second, b"key".len() + b"much_longer_value".len());
+    assert_eq!(size_after_first, DEFAULT_ENTRY_OVERHEAD + b"key".len() + b"short".len());
+    assert_eq!(size_after_second, DEFAULT_ENTRY_OVERHEAD + b"key".len() + b"much_longer_value".len());
 }
 
 #[test]
@@ -154,8 +230,36 @@ fn test_size_tracking_delete() {
     memtable.delete(b"key".to_vec());
     let size_after_delete = memtable.size();
     
-    assert_eq!(size_after_put, b"key".len() + b"value".len());
-    assert_eq!(size_after_delete, b"key".len()); // Tombstone = just key
+    assert_eq!(size_after_put, DEFAULT_ENTRY_OVERHEAD + b"key".len() + b"value".len());
+    assert_eq!(size_after_delete, DEFAULT_ENTRY_OVERHEAD + b"key".len()); // Tombstone = overhead + key
+}
+
+#[test]
+fn test_size_tracking_includes_entry_overhead() {
+    let memtable = MemTable::new();
+
+    let mut payload_bytes = 0;
+    for i in 0..10_000u32 {
+        let key = i.to_be_bytes().to_vec();
+        payload_bytes += key.len() + 1;
+        memtable.put(key, vec![0u8]);
+    }
+
+    assert_eq!(memtable.entry_count(), 10_000);
+    assert_eq!(memtable.size(), payload_bytes + 10_000 * DEFAULT_ENTRY_OVERHEAD);
+    assert!(memtable.should_flush(10_000 * DEFAULT_ENTRY_OVERHEAD));
+}
+
+#[test]
+fn test_size_tracking_custom_entry_overhead() {
+    let memtable = MemTable::with_entry_overhead(0);
+    assert_eq!(memtable.entry_overhead(), 0);
+
+    memtable.put(b"key".to_vec(), b"value".to_vec());
+    assert_eq!(memtable.size(), b"key".len() + b"value".len());
+
+    memtable.clear();
+    assert_eq!(memtable.size(), 0);
 }
 
 // =============================================================================
@@ -216,12 +320,124 @@ fn test_iter_clones_data() {
     
     // Snapshot should still have old value
     if let MemTableEntry::Value(v) = &entries[0].1 {
-        assert_eq!(v, b"value");
+        assert_eq!(v.as_slice(), b"value");
     } else {
         panic!("Expected Value");
     }
 }
 
+#[test]
+fn test_with_sorted_borrows_entries_in_order() {
+    let memtable = MemTable::new();
+
+    memtable.put(b"cherry".to_vec(), b"3".to_vec());
+    memtable.delete(b"banana".to_vec());
+    memtable.put(b"apple".to_vec(), b"1".to_vec());
+
+    let keys: Vec<Vec<u8>> = memtable.with_sorted(|entries| {
+        assert_eq!(entries.len(), 3);
+        entries.map(|(key, _)| key.to_vec()).collect()
+    });
+
+    assert_eq!(keys, vec![b"apple".to_vec(), b"banana".to_vec(), b"cherry".to_vec()]);
+}
+
+// =============================================================================
+// Range Tests
+// =============================================================================
+
+fn memtable_with_keys(keys: &[&[u8]]) -> MemTable {
+    let memtable = MemTable::new();
+    for key in keys {
+        memtable.put(key.to_vec(), b"v".to_vec());
+    }
+    memtable
+}
+
+#[test]
+fn test_range_empty() {
+    let memtable = memtable_with_keys(&[b"a", b"c", b"e"]);
+
+    // No keys between the bounds
+    let entries = memtable.range(Bound::Excluded(b"a"), Bound::Excluded(b"c"));
+    assert!(entries.is_empty());
+
+    // Inverted bounds yield nothing instead of panicking
+    let entries = memtable.range(Bound::Included(b"e"), Bound::Included(b"a"));
+    assert!(entries.is_empty());
+
+    // Empty memtable
+    let entries = MemTable::new().range(Bound::Unbounded, Bound::Unbounded);
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn test_range_single_element() {
+    let memtable = memtable_with_keys(&[b"a", b"c", b"e"]);
+
+    let entries = memtable.range(Bound::Included(b"c"), Bound::Included(b"c"));
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].0, b"c");
+
+    let entries = memtable.range(Bound::Included(b"c"), Bound::Excluded(b"c"));
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn test_range_inclusive_exclusive_bounds() {
+    let memtable = memtable_with_keys(&[b"a", b"b", b"c", b"d"]);
+
+    let entries = memtable.range(Bound::Included(b"b"), Bound::Excluded(b"d"));
+    let keys: Vec<_> = entries.iter().map(|(k, _)| k.as_slice()).collect();
+    assert_eq!(keys, vec![b"b".as_slice(), b"c".as_slice()]);
+
+    let entries = memtable.range(Bound::Excluded(b"a"), Bound::Included(b"d"));
+    let keys: Vec<_> = entries.iter().map(|(k, _)| k.as_slice()).collect();
+    assert_eq!(keys, vec![b"b".as_slice(), b"c".as_slice(), b"d".as_slice()]);
+}
+
+#[test]
+fn test_range_unbounded_start_to_key() {
+    let memtable = MemTable::new();
+    memtable.put(b"key1".to_vec(), b"value1".to_vec());
+    memtable.delete(b"key2".to_vec());
+    memtable.put(b"key3".to_vec(), b"value3".to_vec());
+    memtable.put(b"key4".to_vec(), b"value4".to_vec());
+
+    let entries = memtable.range(Bound::Unbounded, Bound::Included(b"key3"));
+
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0], (b"key1".to_vec(), MemTableEntry::Value(Arc::new(b"value1".to_vec()))));
+    assert_eq!(entries[1], (b"key2".to_vec(), MemTableEntry::Tombstone));
+    assert_eq!(entries[2], (b"key3".to_vec(), MemTableEntry::Value(Arc::new(b"value3".to_vec()))));
+}
+
+#[test]
+fn test_case_insensitive_comparator_orders_and_matches_keys() {
+    let memtable = MemTable::new().with_comparator(Arc::new(CaseInsensitiveComparator));
+
+    memtable.put(b"CHERRY".to_vec(), b"3".to_vec());
+    memtable.put(b"banana".to_vec(), b"2".to_vec());
+    memtable.put(b"Apple".to_vec(), b"1".to_vec());
+
+    // Byte order would put the uppercase keys first
+    let keys: Vec<Vec<u8>> = memtable.iter().into_iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, vec![b"Apple".to_vec(), b"banana".to_vec(), b"CHERRY".to_vec()]);
+
+    // Any spelling finds the key; a write replaces the value, not the stored spelling
+    assert_eq!(memtable.get(b"apple"), Some(MemTableEntry::Value(Arc::new(b"1".to_vec()))));
+    memtable.put(b"APPLE".to_vec(), b"one".to_vec());
+    memtable.delete(b"Banana".to_vec());
+    assert_eq!(memtable.entry_count(), 3);
+    assert_eq!(memtable.get(b"aPPle"), Some(MemTableEntry::Value(Arc::new(b"one".to_vec()))));
+    assert_eq!(memtable.get(b"BANANA"), Some(MemTableEntry::Tombstone));
+
+    let entries = memtable.range(Bound::Included(b"b"), Bound::Excluded(b"d"));
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0], (b"banana".to_vec(), MemTableEntry::Tombstone));
+    assert_eq!(entries[1], (b"CHERRY".to_vec(), MemTableEntry::Value(Arc::new(b"3".to_vec()))));
+}
+
 // =============================================================================
 // Clear Tests
 // =============================================================================
@@ -287,7 +503,7 @@ fn test_empty_key() {
     
     memtable.put(vec![], b"value".to_vec());
     
-    assert_eq!(memtable.get(&[]), Some(MemTableEntry::Value(b"value".to_vec())));
+    assert_eq!(memtable.get(&[]), Some(MemTableEntry::Value(Arc::new(b"value".to_vec()))));
 }
 
 #[test]
@@ -296,7 +512,7 @@ fn test_empty_value() {
     
     memtable.put(b"key".to_vec(), vec![]);
     
-    assert_eq!(memtable.get(b"key"), Some(MemTableEntry::Value(vec![])));
+    assert_eq!(memtable.get(b"key"), Some(MemTableEntry::Value(Arc::new(vec![]))));
 }
 
 #[test]
@@ -308,7 +524,7 @@ fn test_large_value() {
     
     if let Some(MemTableEntry::Value(v)) = memtable.get(b"big_key") {
         assert_eq!(v.len(), 1024 * 1024);
-        assert_eq!(v, large_value);
+        assert_eq!(*v, large_value);
     } else {
         panic!("Expected Value");
     }
@@ -352,7 +568,7 @@ fn test_concurrent_reads() {
         let handle = thread::spawn(move || {
             for _ in 0..100 {
                 let result = mt.get(b"key");
-                assert_eq!(result, Some(MemTableEntry::Value(b"value".to_vec())));
+                assert_eq!(result, Some(MemTableEntry::Value(Arc::new(b"value".to_vec()))));
             }
         });
         handles.push(handle);
@@ -390,3 +606,48 @@ fn test_concurrent_writes() {
     
     assert_eq!(memtable.entry_count(), 100);
 }
+
+#[test]
+fn test_concurrent_put_delete_same_key_size_consistent() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let memtable = Arc::new(MemTable::new());
+
+    let mut handles = vec![];
+
+    for i in 0..8usize {
+        let mt = Arc::clone(&memtable);
+        let handle = thread::spawn(move || {
+            for j in 0..1000usize {
+                // Varying value sizes so deltas go both up and down
+                if (i + j) % 3 == 0 {
+                    mt.delete(b"contended".to_vec());
+                } else {
+                    mt.put(b"contended".to_vec(), vec![0u8; (i * 7 + j) % 50]);
+                }
+            }
+        });
+        handles.push(handle);
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    // Recompute from the surviving entries
+    let recomputed: usize = memtable
+        .iter()
+        .iter()
+        .map(|(key, entry)| {
+            let value_len = match entry {
+                MemTableEntry::Value(v) => v.len(),
+                _ => 0,
+            };
+            memtable.entry_overhead() + key.len() + value_len
+        })
+        .sum();
+
+    assert_eq!(memtable.entry_count(), 1);
+    assert_eq!(memtable.size(), recomputed);
+}
@@ -0,0 +1,146 @@
+//! Async Server Tests (require the `tokio` feature)
+//!
+//! These tests verify:
+//! - Commands round-trip through `AsyncServer` using the async codec
+//! - An auth token gates commands the same way as on the threaded server
+//! - Shutdown stops the accept loop
+
+use std::sync::Arc;
+
+use atlaskv::config::{Config, ConfigBuilder, WalSyncStrategy};
+use atlaskv::network::AsyncServer;
+use atlaskv::protocol::{read_response_async, write_command_async, Command, Response, Status};
+use atlaskv::Engine;
+use tempfile::TempDir;
+use tokio::io::BufReader;
+use tokio::net::TcpStream;
+use tokio::runtime::Runtime;
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+fn setup_server() -> (TempDir, Arc<AsyncServer>) {
+    setup_server_with(|builder| builder)
+}
+
+/// Like `setup_server`, with extra config applied on top
+fn setup_server_with<F>(configure: F) -> (TempDir, Arc<AsyncServer>)
+where
+    F: FnOnce(ConfigBuilder) -> ConfigBuilder,
+{
+    let temp_dir = TempDir::new().unwrap();
+    let builder = Config::builder()
+        .data_dir(temp_dir.path())
+        .listen_addr("127.0.0.1:0")
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite);
+    let config = configure(builder).build();
+    let engine = Arc::new(Engine::open(config.clone()).unwrap());
+    (temp_dir, Arc::new(AsyncServer::new(config, engine)))
+}
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+async fn send(client: &mut BufReader<TcpStream>, command: &Command) -> Response {
+    write_command_async(client.get_mut(), command).await.unwrap();
+    read_response_async(client).await.unwrap()
+}
+
+// =============================================================================
+// Round-Trip Tests
+// =============================================================================
+
+#[test]
+fn test_async_put_get_round_trip() {
+    let (_temp, server) = setup_server();
+
+    runtime().block_on(async {
+        let listener = server.bind().unwrap();
+        let addr = listener.local_addr().unwrap();
+        let serving = tokio::spawn({
+            let server = Arc::clone(&server);
+            async move { server.serve(listener).await }
+        });
+
+        let mut client = BufReader::new(TcpStream::connect(addr).await.unwrap());
+
+        let put = Command::Put {
+            key: b"key".to_vec(),
+            value: b"value".to_vec(),
+        };
+        assert_eq!(send(&mut client, &put).await.status, Status::Ok);
+
+        let get = Command::Get { key: b"key".to_vec() };
+        let response = send(&mut client, &get).await;
+        assert_eq!(response.status, Status::Ok);
+        assert_eq!(response.payload, Some(b"value".to_vec()));
+
+        let missing = Command::Get { key: b"missing".to_vec() };
+        assert_eq!(send(&mut client, &missing).await.payload, None);
+
+        server.shutdown();
+        serving.await.unwrap().unwrap();
+    });
+}
+
+// =============================================================================
+// Auth Tests
+// =============================================================================
+
+#[test]
+fn test_async_commands_require_auth() {
+    let (_temp, server) = setup_server_with(|builder| builder.auth_token("s3cret"));
+
+    runtime().block_on(async {
+        let listener = server.bind().unwrap();
+        let addr = listener.local_addr().unwrap();
+        let serving = tokio::spawn({
+            let server = Arc::clone(&server);
+            async move { server.serve(listener).await }
+        });
+
+        let mut client = BufReader::new(TcpStream::connect(addr).await.unwrap());
+
+        let get = Command::Get { key: b"key".to_vec() };
+        assert_eq!(send(&mut client, &get).await.status, Status::Error);
+
+        let auth = Command::Auth {
+            token: b"s3cret".to_vec(),
+        };
+        assert_eq!(send(&mut client, &auth).await.status, Status::Ok);
+        assert_eq!(send(&mut client, &get).await.status, Status::Ok);
+
+        // A second client with the wrong token is turned away
+        let mut intruder = BufReader::new(TcpStream::connect(addr).await.unwrap());
+        let wrong = Command::Auth {
+            token: b"guess".to_vec(),
+        };
+        assert_eq!(send(&mut intruder, &wrong).await.status, Status::Error);
+
+        server.shutdown();
+        serving.await.unwrap().unwrap();
+    });
+}
+
+// =============================================================================
+// Shutdown Tests
+// =============================================================================
+
+#[test]
+fn test_async_shutdown_before_serve() {
+    let (_temp, server) = setup_server();
+    server.shutdown();
+    assert!(!server.is_running());
+
+    runtime().block_on(async {
+        let listener = server.bind().unwrap();
+        // Returns immediately: the shutdown flag is already set
+        server.serve(listener).await.unwrap();
+    });
+}
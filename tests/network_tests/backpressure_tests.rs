@@ -0,0 +1,74 @@
+//! Backpressure Tests
+//!
+//! These tests verify against a real `Server` with one worker:
+//! - Connections past `max_connections` get a "server busy" error frame
+//! - Connections that find the worker queue full get the same, not a silent drop
+//! - Queued connections are still served once the worker frees up
+
+use std::io::Write;
+use std::net::TcpStream;
+
+use atlaskv::protocol::{encode_command, read_response, Command, Status};
+
+use super::common::{connect, start_server, TestServer};
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Start a single-worker server accepting up to `max_connections`
+fn start_single_worker(max_connections: usize) -> TestServer {
+    start_server(|config| config.worker_threads(1).max_connections(max_connections))
+}
+
+/// Send a PING and return the response status
+fn ping(stream: &mut TcpStream) -> Status {
+    stream.write_all(&encode_command(&Command::Ping)).unwrap();
+    read_response(stream).unwrap().status
+}
+
+/// Assert the server answered with a busy error frame
+fn assert_busy(stream: &mut TcpStream) {
+    let response = read_response(stream).unwrap();
+    assert_eq!(response.status, Status::Error);
+
+    let message = String::from_utf8(response.payload.unwrap()).unwrap();
+    assert!(message.starts_with("server busy"), "unexpected error: {}", message);
+}
+
+// =============================================================================
+// Rejection Tests
+// =============================================================================
+
+#[test]
+fn test_connection_over_limit_gets_busy_response() {
+    let server = start_single_worker(1);
+
+    // The only worker holds the first connection
+    let mut first = connect(&server);
+    assert_eq!(ping(&mut first), Status::Ok);
+
+    let mut rejected = connect(&server);
+    assert_busy(&mut rejected);
+}
+
+#[test]
+fn test_full_worker_queue_gets_busy_response() {
+    // One worker, and room for two connections waiting on it
+    let server = start_single_worker(2);
+
+    let mut active = connect(&server);
+    assert_eq!(ping(&mut active), Status::Ok);
+
+    let mut queued: Vec<TcpStream> = (0..2).map(|_| connect(&server)).collect();
+
+    let mut rejected = connect(&server);
+    assert_busy(&mut rejected);
+
+    // Queued connections were kept, and are served once the worker frees up
+    drop(active);
+    for stream in queued.iter_mut() {
+        assert_eq!(ping(stream), Status::Ok);
+        stream.shutdown(std::net::Shutdown::Both).unwrap();
+    }
+}
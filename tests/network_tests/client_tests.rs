@@ -0,0 +1,251 @@
+//! Client Tests
+//!
+//! These tests verify against a real `Server`:
+//! - Typed get/put/delete/ping round-trips on one reused connection
+//! - VERSION reports the server build and its format versions
+//! - Missing keys come back as `None`
+//! - Server error responses surface as `AtlasError::Server`
+//! - Frame CRCs negotiated by `connect_with_crc`
+//! - Forward scans over a range spanning several cursor pages
+//! - Cursor scan pages, optionally under a server-side deadline
+
+use std::net::TcpListener;
+use std::time::Duration;
+
+use atlaskv::protocol::PROTOCOL_VERSION;
+use atlaskv::wal::WAL_FORMAT_VERSION;
+use atlaskv::{AtlasError, Client};
+
+use super::common::{connect_with, start_server, TestServer};
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Start a server rejecting values over `max_value_size`
+fn start_limited_server(max_value_size: usize) -> TestServer {
+    start_server(|config| config.max_value_size(max_value_size))
+}
+
+/// Connect a `Client`, retrying while the server thread is still binding
+fn connect(server: &TestServer) -> Client {
+    connect_with(server, Client::connect)
+}
+
+// =============================================================================
+// Round-Trip Tests
+// =============================================================================
+
+#[test]
+fn test_client_put_get_delete() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+
+    client.ping().unwrap();
+    client.put(b"key", b"value").unwrap();
+    assert_eq!(client.get(b"key").unwrap(), Some(b"value".to_vec()));
+
+    assert!(client.delete(b"key").unwrap());
+    assert_eq!(client.get(b"key").unwrap(), None);
+    assert!(!client.delete(b"key").unwrap());
+}
+
+#[test]
+fn test_client_put_if_absent() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+
+    assert!(client.put_if_absent(b"leader", b"node-1").unwrap());
+    assert!(!client.put_if_absent(b"leader", b"node-2").unwrap());
+    assert_eq!(client.get(b"leader").unwrap(), Some(b"node-1".to_vec()));
+}
+
+#[test]
+fn test_client_get_missing_key() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+
+    assert_eq!(client.get(b"never_written").unwrap(), None);
+}
+
+#[test]
+fn test_client_reuses_connection() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+
+    for i in 0..100u32 {
+        client.put(&i.to_be_bytes(), &i.to_le_bytes()).unwrap();
+    }
+    for i in 0..100u32 {
+        assert_eq!(client.get(&i.to_be_bytes()).unwrap(), Some(i.to_le_bytes().to_vec()));
+    }
+}
+
+#[test]
+fn test_client_scan() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+
+    // Enough keys that the scan takes several SCANCURSOR pages
+    for i in 0..1000u32 {
+        client.put(&i.to_be_bytes(), &i.to_le_bytes()).unwrap();
+    }
+    client.delete(&500u32.to_be_bytes()).unwrap();
+
+    let entries = client.scan(b"", None).unwrap();
+    assert_eq!(entries.len(), 999);
+    assert!(entries.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert!(!entries.iter().any(|(key, _)| key == &500u32.to_be_bytes()));
+
+    // `start` is inclusive and `end` exclusive
+    let entries = client.scan(&100u32.to_be_bytes(), Some(&700u32.to_be_bytes())).unwrap();
+    let expected: Vec<_> = (100..700u32)
+        .filter(|&i| i != 500)
+        .map(|i| (i.to_be_bytes().to_vec(), i.to_le_bytes().to_vec()))
+        .collect();
+    assert_eq!(entries, expected);
+
+    // A deleted `start` isn't returned; an empty range returns nothing
+    let entries = client.scan(&500u32.to_be_bytes(), Some(&502u32.to_be_bytes())).unwrap();
+    assert_eq!(entries, vec![(501u32.to_be_bytes().to_vec(), 501u32.to_le_bytes().to_vec())]);
+    assert!(client.scan(&7u32.to_be_bytes(), Some(&7u32.to_be_bytes())).unwrap().is_empty());
+}
+
+#[test]
+fn test_client_scan_rev() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+
+    for key in [b"a", b"b", b"c", b"d"] {
+        client.put(key, key).unwrap();
+    }
+
+    let entries = client.scan_rev(b"b", None, 10).unwrap();
+    let keys: Vec<_> = entries.into_iter().map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![b"d".to_vec(), b"c".to_vec(), b"b".to_vec()]);
+
+    let entries = client.scan_rev(b"a", Some(b"c"), 1).unwrap();
+    assert_eq!(entries, vec![(b"b".to_vec(), b"b".to_vec())]);
+}
+
+#[test]
+fn test_client_scan_keys() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+
+    for key in [b"a", b"b", b"c", b"d"] {
+        client.put(key, key).unwrap();
+    }
+    client.delete(b"c").unwrap();
+
+    let keys = client.scan_keys(b"b", None, 10).unwrap();
+    assert_eq!(keys, vec![b"b".to_vec(), b"d".to_vec()]);
+
+    let keys = client.scan_keys(b"a", Some(b"d"), 1).unwrap();
+    assert_eq!(keys, vec![b"a".to_vec()]);
+}
+
+#[test]
+fn test_client_scan_page() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+
+    for key in [b"a", b"b", b"c", b"d"] {
+        client.put(key, key).unwrap();
+    }
+    client.delete(b"c").unwrap();
+
+    let page = client.scan_page(None, 2).unwrap();
+    let expected = vec![(b"a".to_vec(), b"a".to_vec()), (b"b".to_vec(), b"b".to_vec())];
+    assert_eq!(page.entries, expected);
+    assert!(page.more);
+
+    let page = client.scan_page(Some(b"b"), 2).unwrap();
+    assert_eq!(page.entries, vec![(b"d".to_vec(), b"d".to_vec())]);
+    assert!(!page.more);
+}
+
+#[test]
+fn test_client_scan_page_with_deadline() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+    for key in [b"a", b"b", b"c"] {
+        client.put(key, key).unwrap();
+    }
+
+    // No time at all: nothing scanned, flagged so the caller knows to retry
+    let page = client.scan_page_with_deadline(None, 10, Duration::ZERO).unwrap();
+    assert!(page.entries.is_empty());
+    assert!(page.more && page.deadline_exceeded);
+
+    let page = client.scan_page_with_deadline(None, 10, Duration::from_secs(60)).unwrap();
+    assert_eq!(page.entries.len(), 3);
+    assert!(!page.more && !page.deadline_exceeded);
+}
+
+#[test]
+fn test_client_count() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+
+    for key in [b"a", b"b", b"c", b"d"] {
+        client.put(key, key).unwrap();
+    }
+    client.delete(b"c").unwrap();
+
+    assert_eq!(client.count(b"", None).unwrap(), 3);
+    assert_eq!(client.count(b"b", Some(b"d")).unwrap(), 1);
+}
+
+#[test]
+fn test_client_version() {
+    let server = start_limited_server(1024);
+    let mut client = connect(&server);
+
+    let info = client.version().unwrap();
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.version, atlaskv::VERSION);
+    assert_eq!(info.proto_version, PROTOCOL_VERSION);
+    assert_eq!(info.wal_format, WAL_FORMAT_VERSION);
+    assert_eq!(info.sstable_format, 6);
+}
+
+// =============================================================================
+// Error Tests
+// =============================================================================
+
+#[test]
+fn test_client_server_error() {
+    let server = start_limited_server(4);
+    let mut client = connect(&server);
+
+    // Value exceeds the server's max_value_size
+    let result = client.put(b"key", b"too long");
+    assert!(matches!(result, Err(AtlasError::Server(_))));
+
+    // The server drops the connection after a limit violation; reconnect
+    let mut client = connect(&server);
+    assert_eq!(client.get(b"key").unwrap(), None);
+}
+
+#[test]
+fn test_client_connect_refused() {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    assert!(matches!(Client::connect(addr), Err(AtlasError::Network(_))));
+}
+
+#[test]
+fn test_client_with_frame_crc() {
+    let server = start_limited_server(1024);
+
+    let mut client = connect_with(&server, Client::connect_with_crc);
+
+    client.put(b"key", b"value").unwrap();
+    assert_eq!(client.get(b"key").unwrap(), Some(b"value".to_vec()));
+    client.delete(b"key").unwrap();
+    assert_eq!(client.get(b"key").unwrap(), None);
+}
@@ -0,0 +1,105 @@
+//! Shared Server Fixture
+//!
+//! A real `Server` on a free local port, for the tests that talk to one over TCP.
+
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use atlaskv::config::{Config, ConfigBuilder, WalSyncStrategy};
+use atlaskv::network::Server;
+use atlaskv::Engine;
+use tempfile::TempDir;
+
+/// Running server plus what's needed to stop it
+///
+/// Dropping it signals shutdown and joins the server thread.
+pub struct TestServer {
+    _temp_dir: TempDir,
+    pub engine: Arc<Engine>,
+    pub addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+    /// Signal shutdown without waiting for the server to stop
+    pub fn signal_shutdown(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+
+    /// Wait for the server thread to return, failing after `limit`
+    pub fn join_within(&mut self, limit: Duration) {
+        let handle = self.handle.take().expect("server already joined");
+        let start = Instant::now();
+        while !handle.is_finished() {
+            assert!(start.elapsed() < limit, "Server did not stop within {:?}", limit);
+            thread::sleep(Duration::from_millis(10));
+        }
+        handle.join().unwrap();
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.signal_shutdown();
+        if let Some(handle) = self.handle.take() {
+            handle.join().unwrap();
+        }
+    }
+}
+
+/// Start a server, letting `configure` adjust the config before it's built
+///
+/// The data directory, listen address and WAL sync strategy are already set.
+pub fn start_server(configure: impl FnOnce(ConfigBuilder) -> ConfigBuilder) -> TestServer {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Reserve a free port; the server rebinds it with SO_REUSEADDR
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let builder = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .listen_addr(addr.to_string());
+    let config = configure(builder).build();
+    let engine = Arc::new(Engine::open(config.clone()).unwrap());
+
+    let mut server = Server::new(config, Arc::clone(&engine));
+    let shutdown = server.shutdown_handle();
+    let handle = thread::spawn(move || server.run().unwrap());
+
+    TestServer {
+        _temp_dir: temp_dir,
+        engine,
+        addr,
+        shutdown,
+        handle: Some(handle),
+    }
+}
+
+/// Connect with `attempt`, retrying while the server thread is still binding
+pub fn connect_with<T, E>(
+    server: &TestServer,
+    mut attempt: impl FnMut(SocketAddr) -> Result<T, E>,
+) -> T {
+    for _ in 0..50 {
+        if let Ok(connection) = attempt(server.addr) {
+            return connection;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    panic!("Server at {} never accepted a connection", server.addr);
+}
+
+/// Open a raw TCP connection with a 5s read timeout
+pub fn connect(server: &TestServer) -> TcpStream {
+    let stream = connect_with(server, TcpStream::connect);
+    stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    stream
+}
@@ -0,0 +1,471 @@
+//! Connection Tests
+//!
+//! These tests verify:
+//! - Commands round-trip over a real TCP connection
+//! - Admin commands (FLUSH) reach the engine
+//! - The optional HELLO handshake negotiates the protocol version
+//! - Frame CRCs, once negotiated, guard every later frame
+//! - With an auth token set, only AUTH with the right token unlocks commands
+//! - Idle connections are reaped independently of the per-read timeout
+//! - Connections close once they've sent `max_requests_per_connection` commands
+//! - Socket options (TCP_NODELAY, buffer sizes, keepalive) follow the config
+
+use std::io::{BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use atlaskv::config::{Config, WalSyncStrategy};
+use atlaskv::network::Connection;
+use atlaskv::protocol::{
+    capabilities, decode_hello_response, encode_command_with_crc, read_response,
+    read_response_with_crc, write_command, write_command_with_crc, Command, Response, Status,
+    CAP_FRAME_CRC, PROTOCOL_VERSION,
+};
+use atlaskv::{AtlasError, Engine};
+use tempfile::TempDir;
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+fn setup_temp_engine() -> (TempDir, Arc<Engine>) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .build();
+    let engine = Arc::new(Engine::open(config).unwrap());
+    (temp_dir, engine)
+}
+
+/// Like `setup_temp_engine`, but clients must AUTH with `token`
+fn setup_auth_engine(token: &str) -> (TempDir, Arc<Engine>) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .auth_token(token)
+        .build();
+    let engine = Arc::new(Engine::open(config).unwrap());
+    (temp_dir, engine)
+}
+
+fn auth(token: &[u8]) -> Command {
+    Command::Auth {
+        token: token.to_vec(),
+    }
+}
+
+/// Accept a single client on an ephemeral port and serve it on a background thread
+fn spawn_connection(engine: Arc<Engine>) -> (TcpStream, JoinHandle<()>) {
+    spawn_connection_with(engine, |_| {})
+}
+
+/// Like `spawn_connection`, but lets the test configure the server-side connection
+fn spawn_connection_with<F>(engine: Arc<Engine>, configure: F) -> (TcpStream, JoinHandle<()>)
+where
+    F: FnOnce(&mut Connection) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut conn = Connection::new(stream, engine).unwrap();
+        configure(&mut conn);
+        conn.handle().unwrap();
+    });
+
+    let client = TcpStream::connect(addr).unwrap();
+    (client, handle)
+}
+
+fn send(client: &mut TcpStream, command: &Command) -> Response {
+    write_command(client, command).unwrap();
+    let mut reader = BufReader::new(&*client);
+    read_response(&mut reader).unwrap()
+}
+
+// =============================================================================
+// Command Tests
+// =============================================================================
+
+#[test]
+fn test_flush_over_connection() {
+    let (_temp, engine) = setup_temp_engine();
+    let (mut client, handle) = spawn_connection(Arc::clone(&engine));
+
+    let response = send(
+        &mut client,
+        &Command::Put {
+            key: b"key".to_vec(),
+            value: b"value".to_vec(),
+        },
+    );
+    assert_eq!(response.status, Status::Ok);
+    assert_eq!(engine.sstable_count(), 0);
+
+    let response = send(&mut client, &Command::Flush);
+    assert_eq!(response.status, Status::Ok);
+    assert_eq!(response.payload, None);
+
+    assert_eq!(engine.sstable_count(), 1);
+    assert_eq!(engine.memtable_entry_count(), 0);
+    assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
+
+    drop(client);
+    handle.join().unwrap();
+}
+
+// =============================================================================
+// Handshake Tests
+// =============================================================================
+
+#[test]
+fn test_hello_matching_version() {
+    let (_temp, engine) = setup_temp_engine();
+    let (mut client, handle) = spawn_connection(engine);
+
+    let hello = Command::Hello {
+        proto_version: PROTOCOL_VERSION,
+        features: 0,
+    };
+    let response = send(&mut client, &hello);
+    assert_eq!(response.status, Status::Ok);
+
+    let (version, caps) = decode_hello_response(&response.payload.unwrap()).unwrap();
+    assert_eq!(version, PROTOCOL_VERSION);
+    assert_eq!(caps, capabilities());
+
+    // Connection stays usable after the handshake
+    let response = send(&mut client, &Command::Ping);
+    assert_eq!(response.status, Status::Ok);
+
+    // A second HELLO is rejected but doesn't close the connection
+    let hello = Command::Hello {
+        proto_version: PROTOCOL_VERSION,
+        features: 0,
+    };
+    let response = send(&mut client, &hello);
+    assert_eq!(response.status, Status::Error);
+    let response = send(&mut client, &Command::Ping);
+    assert_eq!(response.status, Status::Ok);
+
+    drop(client);
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_hello_mismatched_version_closes_connection() {
+    let (_temp, engine) = setup_temp_engine();
+    let (mut client, handle) = spawn_connection(engine);
+
+    let hello = Command::Hello {
+        proto_version: PROTOCOL_VERSION + 1,
+        features: 0,
+    };
+    let response = send(&mut client, &hello);
+    assert_eq!(response.status, Status::Error);
+    let message = String::from_utf8(response.payload.unwrap()).unwrap();
+    assert!(message.contains("Unsupported protocol version"));
+
+    client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let mut buf = [0u8; 1];
+    let n = client.read(&mut buf).unwrap();
+    assert_eq!(n, 0, "expected EOF after failed handshake");
+
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_commands_without_hello_use_default_version() {
+    let (_temp, engine) = setup_temp_engine();
+    let (mut client, handle) = spawn_connection(engine);
+
+    let response = send(&mut client, &Command::Ping);
+    assert_eq!(response.status, Status::Ok);
+    assert_eq!(response.payload, Some(b"PONG".to_vec()));
+
+    drop(client);
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_hello_enables_frame_crc() {
+    let (_temp, engine) = setup_temp_engine();
+    let (mut client, handle) = spawn_connection(engine);
+
+    let hello = Command::Hello {
+        proto_version: PROTOCOL_VERSION,
+        features: CAP_FRAME_CRC,
+    };
+    let response = send(&mut client, &hello);
+    let (_, caps) = decode_hello_response(&response.payload.unwrap()).unwrap();
+    assert_eq!(caps, capabilities() | CAP_FRAME_CRC);
+
+    // Both directions now carry CRCs
+    let put = Command::Put {
+        key: b"key".to_vec(),
+        value: b"value".to_vec(),
+    };
+    write_command_with_crc(&mut client, &put).unwrap();
+    let response = read_response_with_crc(&mut BufReader::new(&client)).unwrap();
+    assert_eq!(response.status, Status::Ok);
+
+    write_command_with_crc(&mut client, &Command::Get { key: b"key".to_vec() }).unwrap();
+    let response = read_response_with_crc(&mut BufReader::new(&client)).unwrap();
+    assert_eq!(response.payload, Some(b"value".to_vec()));
+
+    drop(client);
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_corrupt_frame_rejected_after_crc_negotiated() {
+    let (_temp, engine) = setup_temp_engine();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        Connection::new(stream, engine).unwrap().handle()
+    });
+    let mut client = TcpStream::connect(addr).unwrap();
+
+    let hello = Command::Hello {
+        proto_version: PROTOCOL_VERSION,
+        features: CAP_FRAME_CRC,
+    };
+    assert_eq!(send(&mut client, &hello).status, Status::Ok);
+
+    // Flip a key byte: the frame still parses, only the CRC catches it
+    let mut frame = encode_command_with_crc(&Command::Put {
+        key: b"key".to_vec(),
+        value: b"value".to_vec(),
+    });
+    frame[9] ^= 0x01;
+    client.write_all(&frame).unwrap();
+
+    let response = read_response_with_crc(&mut BufReader::new(&client)).unwrap();
+    assert_eq!(response.status, Status::Error);
+    let message = String::from_utf8(response.payload.unwrap()).unwrap();
+    assert!(message.contains("crc mismatch"));
+
+    match handle.join().unwrap() {
+        Err(AtlasError::Protocol(msg)) => assert_eq!(msg, "crc mismatch"),
+        other => panic!("Expected crc mismatch, got {:?}", other),
+    }
+}
+
+// =============================================================================
+// Auth Tests
+// =============================================================================
+
+#[test]
+fn test_auth_with_correct_token() {
+    let (_temp, engine) = setup_auth_engine("s3cret");
+    let (mut client, handle) = spawn_connection(engine);
+
+    assert_eq!(send(&mut client, &auth(b"s3cret")).status, Status::Ok);
+
+    let put = Command::Put {
+        key: b"key".to_vec(),
+        value: b"value".to_vec(),
+    };
+    assert_eq!(send(&mut client, &put).status, Status::Ok);
+    let response = send(&mut client, &Command::Get { key: b"key".to_vec() });
+    assert_eq!(response.payload, Some(b"value".to_vec()));
+
+    drop(client);
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_auth_with_wrong_token_closes_connection() {
+    let (_temp, engine) = setup_auth_engine("s3cret");
+    let (mut client, handle) = spawn_connection(engine);
+
+    // Same length as the real token, so only the contents differ
+    let response = send(&mut client, &auth(b"s3cred"));
+    assert_eq!(response.status, Status::Error);
+    let message = String::from_utf8(response.payload.unwrap()).unwrap();
+    assert!(message.contains("invalid auth token"));
+
+    client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let mut buf = [0u8; 1];
+    let n = client.read(&mut buf).unwrap();
+    assert_eq!(n, 0, "expected EOF after failed auth");
+
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_get_before_auth_rejected() {
+    let (_temp, engine) = setup_auth_engine("s3cret");
+    engine.put(b"key", b"value").unwrap();
+    let (mut client, handle) = spawn_connection(engine);
+
+    let response = send(&mut client, &Command::Get { key: b"key".to_vec() });
+    assert_eq!(response.status, Status::Error);
+    let message = String::from_utf8(response.payload.unwrap()).unwrap();
+    assert_eq!(message, "authentication required");
+
+    // The connection stays open for the client to authenticate
+    assert_eq!(send(&mut client, &auth(b"s3cret")).status, Status::Ok);
+    let response = send(&mut client, &Command::Get { key: b"key".to_vec() });
+    assert_eq!(response.payload, Some(b"value".to_vec()));
+
+    drop(client);
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_auth_without_configured_token_is_accepted() {
+    let (_temp, engine) = setup_temp_engine();
+    let (mut client, handle) = spawn_connection(engine);
+
+    assert_eq!(send(&mut client, &auth(b"anything")).status, Status::Ok);
+    assert_eq!(send(&mut client, &Command::Ping).status, Status::Ok);
+
+    drop(client);
+    handle.join().unwrap();
+}
+
+// =============================================================================
+// Timeout Tests
+// =============================================================================
+
+#[test]
+fn test_active_client_outlives_read_timeout_until_idle() {
+    let (_temp, engine) = setup_temp_engine();
+    let (mut client, handle) = spawn_connection_with(engine, |conn| {
+        conn.set_timeouts(50, 1000).unwrap();
+        conn.set_idle_timeout(300);
+    });
+
+    // Ping every 100ms for well past both the read and idle timeouts
+    let started = Instant::now();
+    let mut last_ping = started;
+    while started.elapsed() < Duration::from_millis(700) {
+        thread::sleep(Duration::from_millis(100));
+        let response = send(&mut client, &Command::Ping);
+        assert_eq!(response.status, Status::Ok);
+        last_ping = Instant::now();
+    }
+
+    // Go quiet: the server should close the connection after the idle timeout
+    client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let mut buf = [0u8; 1];
+    let n = client.read(&mut buf).unwrap();
+
+    assert_eq!(n, 0, "expected EOF from server closing idle connection");
+    assert!(last_ping.elapsed() >= Duration::from_millis(300));
+
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_connection_closes_after_max_requests() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .max_requests_per_connection(3)
+        .build();
+    let engine = Arc::new(Engine::open(config).unwrap());
+    let (mut client, handle) = spawn_connection(engine);
+
+    // Pipeline all five: the server answers three, then hangs up
+    for _ in 0..5 {
+        write_command(&mut client, &Command::Ping).unwrap();
+    }
+    client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let mut reader = BufReader::new(&client);
+    for _ in 0..3 {
+        assert_eq!(read_response(&mut reader).unwrap().status, Status::Ok);
+    }
+
+    // Closing with commands still unread may reset rather than end the stream
+    let mut buf = [0u8; 1];
+    match reader.read(&mut buf) {
+        Ok(n) => assert_eq!(n, 0, "expected EOF after the third response"),
+        Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::ConnectionReset),
+    }
+
+    handle.join().unwrap();
+}
+
+// =============================================================================
+// Socket Option Tests
+// =============================================================================
+
+/// Accept one connection under `config` and return the server-side socket
+fn accept_with_config(config: Config) -> (TempDir, TcpStream, TcpStream) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config {
+        data_dir: temp_dir.path().to_path_buf(),
+        ..config
+    };
+    let engine = Arc::new(Engine::open(config).unwrap());
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+
+    // Same socket as the connection's, so its options show through
+    let server_side = stream.try_clone().unwrap();
+    let _conn = Connection::new(stream, engine).unwrap();
+    (temp_dir, server_side, client)
+}
+
+#[test]
+fn test_connection_nodelay_on_by_default() {
+    let (_temp, server_side, _client) = accept_with_config(Config::default());
+    assert!(server_side.nodelay().unwrap());
+}
+
+#[test]
+fn test_connection_nodelay_disabled_by_config() {
+    let config = Config::builder().tcp_nodelay(false).build();
+    let (_temp, server_side, _client) = accept_with_config(config);
+    assert!(!server_side.nodelay().unwrap());
+}
+
+#[test]
+fn test_connection_socket_buffer_sizes_from_config() {
+    let config = Config::builder()
+        .tcp_send_buffer_bytes(256 * 1024)
+        .tcp_recv_buffer_bytes(512 * 1024)
+        .build();
+    let (_temp, server_side, _client) = accept_with_config(config);
+
+    // The OS may round the sizes up (Linux doubles them), never down
+    let socket = socket2::SockRef::from(&server_side);
+    assert!(socket.send_buffer_size().unwrap() >= 256 * 1024);
+    assert!(socket.recv_buffer_size().unwrap() >= 512 * 1024);
+}
+
+#[test]
+fn test_connection_keepalive_off_by_default() {
+    let (_temp, server_side, _client) = accept_with_config(Config::default());
+    assert!(!socket2::SockRef::from(&server_side).keepalive().unwrap());
+}
+
+#[test]
+fn test_connection_keepalive_from_config() {
+    let config = Config::builder()
+        .keepalive_interval_ms(7000)
+        .keepalive_retries(4)
+        .build();
+    let (_temp, server_side, _client) = accept_with_config(config);
+
+    let socket = socket2::SockRef::from(&server_side);
+    assert!(socket.keepalive().unwrap());
+    #[cfg(target_os = "linux")]
+    {
+        assert_eq!(socket.keepalive_time().unwrap(), Duration::from_secs(7));
+        assert_eq!(socket.keepalive_interval().unwrap(), Duration::from_secs(7));
+        assert_eq!(socket.keepalive_retries().unwrap(), 4);
+    }
+}
@@ -0,0 +1,13 @@
+//! Network Tests
+//!
+//! Integration tests for client connections over real TCP sockets.
+
+mod common;
+
+mod backpressure_tests;
+mod client_tests;
+mod connection_tests;
+mod metrics_tests;
+mod shutdown_tests;
+#[cfg(feature = "tokio")]
+mod async_server_tests;
@@ -0,0 +1,119 @@
+//! Metrics Tests
+//!
+//! These tests verify:
+//! - Connections report commands, errors and bytes into shared counters
+//! - The Prometheus endpoint serves those counters over HTTP
+//! - Unknown paths get a 404
+
+use std::io::{BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use atlaskv::config::{Config, WalSyncStrategy};
+use atlaskv::metrics::ServerMetrics;
+use atlaskv::network::{Connection, MetricsServer};
+use atlaskv::protocol::{read_response, write_command, Command, Status};
+use atlaskv::Engine;
+use tempfile::TempDir;
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+fn setup_temp_engine() -> (TempDir, Arc<Engine>) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config::builder()
+        .data_dir(temp_dir.path())
+        .wal_sync_strategy(WalSyncStrategy::EveryWrite)
+        .build();
+    let engine = Arc::new(Engine::open(config).unwrap());
+    (temp_dir, engine)
+}
+
+/// Send raw HTTP and return the full response text
+fn http_get(addr: std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+// =============================================================================
+// Endpoint Tests
+// =============================================================================
+
+#[test]
+fn test_metrics_endpoint_reports_connection_counters() {
+    let (_temp, engine) = setup_temp_engine();
+    let metrics = Arc::new(ServerMetrics::default());
+
+    // Serve one client connection that reports into `metrics`
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handle = thread::spawn({
+        let engine = Arc::clone(&engine);
+        let metrics = Arc::clone(&metrics);
+        move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut conn = Connection::with_metrics(stream, engine, metrics).unwrap();
+            conn.handle().unwrap();
+        }
+    });
+
+    let mut client = TcpStream::connect(addr).unwrap();
+    let commands = [
+        Command::Put { key: b"key".to_vec(), value: b"value".to_vec() },
+        Command::Get { key: b"key".to_vec() },
+        Command::Hello { proto_version: 1, features: 0 }, // not first frame: error
+    ];
+    let mut statuses = Vec::new();
+    for command in &commands {
+        write_command(&mut client, command).unwrap();
+        statuses.push(read_response(&mut BufReader::new(&client)).unwrap().status);
+    }
+    assert_eq!(statuses, vec![Status::Ok, Status::Ok, Status::Error]);
+    drop(client);
+    handle.join().unwrap();
+
+    assert_eq!(metrics.total_connections.load(Ordering::Relaxed), 1);
+    assert_eq!(metrics.total_commands.load(Ordering::Relaxed), 3);
+    assert_eq!(metrics.total_errors.load(Ordering::Relaxed), 1);
+    assert!(metrics.bytes_read.load(Ordering::Relaxed) > 0);
+    assert!(metrics.bytes_written.load(Ordering::Relaxed) > 0);
+
+    // Scrape
+    let active = Arc::new(AtomicUsize::new(0));
+    let server = MetricsServer::start("127.0.0.1:0", engine, metrics, active).unwrap();
+    let response = http_get(server.local_addr(), "/metrics");
+
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+    assert!(response.contains("\natlaskv_commands_total 3\n"));
+    assert!(response.contains("\natlaskv_errors_total 1\n"));
+    assert!(response.contains("\natlaskv_connections_total 1\n"));
+    assert!(response.contains("atlaskv_engine_ops_total{op=\"put\"} 1\n"));
+    assert!(response.contains("atlaskv_engine_ops_total{op=\"get\"} 1\n"));
+    assert!(response.contains("# TYPE atlaskv_commands_total counter"));
+
+    server.shutdown();
+}
+
+#[test]
+fn test_metrics_endpoint_unknown_path() {
+    let (_temp, engine) = setup_temp_engine();
+    let server = MetricsServer::start(
+        "127.0.0.1:0",
+        engine,
+        Arc::new(ServerMetrics::default()),
+        Arc::new(AtomicUsize::new(0)),
+    )
+    .unwrap();
+
+    let response = http_get(server.local_addr(), "/other");
+    assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+    server.shutdown();
+}
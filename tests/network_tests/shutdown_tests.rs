@@ -0,0 +1,83 @@
+//! Shutdown Drain Tests
+//!
+//! These tests verify against a real `Server`:
+//! - A request in flight when shutdown is signaled still gets its response
+//! - The connection closes after that response instead of reading another
+//! - Idle connections don't hold shutdown past their read timeout
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+use atlaskv::protocol::{encode_command, read_response, Command, Status};
+
+use super::common::{connect, start_server, TestServer};
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Start a server that drains in-flight requests for up to 5s on shutdown
+fn start_draining_server() -> TestServer {
+    start_server(|config| config.shutdown_drain_ms(5000))
+}
+
+/// Assert the server closed the connection
+fn assert_closed(stream: &mut TcpStream) {
+    let mut buf = [0u8; 1];
+    match stream.read(&mut buf) {
+        Ok(0) => {}
+        Ok(_) => panic!("Unexpected data after drain"),
+        Err(e) => assert_eq!(e.kind(), ErrorKind::ConnectionReset),
+    }
+}
+
+// =============================================================================
+// Drain Tests
+// =============================================================================
+
+#[test]
+fn test_in_flight_request_answered_during_shutdown() {
+    let mut server = start_draining_server();
+    let mut stream = connect(&server);
+
+    // Start a PUT: send only part of the frame so the request is in flight
+    let frame = encode_command(&Command::Put {
+        key: b"drain_key".to_vec(),
+        value: b"drain_value".to_vec(),
+    });
+    stream.write_all(&frame[..3]).unwrap();
+    thread::sleep(Duration::from_millis(200));
+
+    server.signal_shutdown();
+    thread::sleep(Duration::from_millis(200));
+
+    // Finish the frame after shutdown was signaled
+    stream.write_all(&frame[3..]).unwrap();
+    let response = read_response(&mut stream).unwrap();
+    assert_eq!(response.status, Status::Ok);
+
+    assert_closed(&mut stream);
+    server.join_within(Duration::from_secs(5));
+
+    assert_eq!(
+        server.engine.get(b"drain_key").unwrap(),
+        Some(b"drain_value".to_vec())
+    );
+}
+
+#[test]
+fn test_idle_connection_closed_promptly_on_shutdown() {
+    let mut server = start_draining_server();
+    let mut stream = connect(&server);
+
+    // Complete one command so a worker is definitely holding the connection
+    stream.write_all(&encode_command(&Command::Ping)).unwrap();
+    assert_eq!(read_response(&mut stream).unwrap().status, Status::Ok);
+
+    // The read timeout is 30s; draining must not wait for it
+    server.signal_shutdown();
+    assert_closed(&mut stream);
+    server.join_within(Duration::from_secs(2));
+}
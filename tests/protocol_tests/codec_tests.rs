@@ -5,11 +5,22 @@
 use std::io::Cursor;
 use atlaskv::protocol::{
     Command, Response, Status,
-    encode_command, decode_command,
+    encode_command, decode_command, decode_command_parts, decode_command_with_limits,
     encode_response, decode_response,
     read_command, write_command,
     read_response, write_response,
+    capabilities, encode_hello_response, decode_hello_response,
+    encode_get_meta_response, decode_get_meta_response,
+    encode_version_response, decode_version_response, ServerVersion,
+    read_command_with_limits, CommandLimits,
+    encode_entries, decode_entries, encode_keys, decode_keys,
+    encode_scan_page, decode_scan_page, ScanPage,
+    encode_command_with_crc, decode_command_with_crc,
+    encode_response_with_crc, decode_response_with_crc,
+    read_command_with_crc, read_response_with_crc, write_response_with_crc,
+    CAP_FRAME_CRC, CRC_SIZE, HEADER_SIZE,
 };
+use atlaskv::AtlasError;
 
 // =============================================================================
 // Command Encoding/Decoding Tests
@@ -73,6 +84,438 @@ fn test_encode_decode_ping() {
     }
 }
 
+#[test]
+fn test_encode_decode_health() {
+    let encoded = encode_command(&Command::Health);
+    assert_eq!(encoded, [0x1B, 0x00, 0x00, 0x00, 0x00]);
+
+    match decode_command(&encoded).unwrap() {
+        Command::Health => {}
+        _ => panic!("Expected HEALTH command"),
+    }
+
+    let result = decode_command(&[0x1B, 0x00, 0x00, 0x00, 0x01, 0x00]);
+    assert!(result.unwrap_err().to_string().contains("unexpected payload"));
+}
+
+#[test]
+fn test_encode_decode_flush() {
+    let cmd = Command::Flush;
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded[0], 0x0F);
+
+    let decoded = decode_command(&encoded).unwrap();
+
+    match decoded {
+        Command::Flush => {}
+        _ => panic!("Expected FLUSH command"),
+    }
+}
+
+#[test]
+fn test_encode_decode_append() {
+    let cmd = Command::Append {
+        key: b"log".to_vec(),
+        data: b"line".to_vec(),
+    };
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded[0], 0x10);
+
+    let decoded = decode_command(&encoded).unwrap();
+
+    match decoded {
+        Command::Append { key, data } => {
+            assert_eq!(key, b"log");
+            assert_eq!(data, b"line");
+        }
+        _ => panic!("Expected APPEND command"),
+    }
+}
+
+#[test]
+fn test_encode_decode_getset() {
+    let cmd = Command::GetSet {
+        key: b"slot".to_vec(),
+        value: b"next".to_vec(),
+    };
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded[0], 0x11);
+
+    let decoded = decode_command(&encoded).unwrap();
+
+    match decoded {
+        Command::GetSet { key, value } => {
+            assert_eq!(key, b"slot");
+            assert_eq!(value, b"next");
+        }
+        _ => panic!("Expected GETSET command"),
+    }
+}
+
+#[test]
+fn test_encode_decode_setnx() {
+    let cmd = Command::SetNx {
+        key: b"leader".to_vec(),
+        value: b"node-1".to_vec(),
+    };
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded[0], 0x16);
+
+    let decoded = decode_command(&encoded).unwrap();
+
+    match decoded {
+        Command::SetNx { key, value } => {
+            assert_eq!(key, b"leader");
+            assert_eq!(value, b"node-1");
+        }
+        _ => panic!("Expected SETNX command"),
+    }
+}
+
+#[test]
+fn test_encode_decode_auth() {
+    let cmd = Command::Auth {
+        token: b"s3cret".to_vec(),
+    };
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded[0], 0x18);
+    assert_eq!(&encoded[5..], b"s3cret");
+
+    match decode_command(&encoded).unwrap() {
+        Command::Auth { token } => assert_eq!(token, b"s3cret"),
+        _ => panic!("Expected AUTH command"),
+    }
+}
+
+#[test]
+fn test_encode_decode_get_meta() {
+    let cmd = Command::GetMeta {
+        key: b"mykey".to_vec(),
+    };
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded[0], 0x19);
+
+    match decode_command(&encoded).unwrap() {
+        Command::GetMeta { key } => assert_eq!(key, b"mykey"),
+        _ => panic!("Expected GETMETA command"),
+    }
+
+    let payload = encode_get_meta_response(b"value", 1_700_000_000_000);
+    assert_eq!(&payload[..8], &1_700_000_000_000u64.to_be_bytes());
+    assert_eq!(
+        decode_get_meta_response(&payload).unwrap(),
+        (b"value".to_vec(), 1_700_000_000_000)
+    );
+    assert!(decode_get_meta_response(&payload[..7]).is_err());
+}
+
+#[test]
+fn test_encode_decode_hello() {
+    let cmd = Command::Hello {
+        proto_version: 0x0102,
+        features: 0,
+    };
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded, vec![0x12, 0x00, 0x00, 0x00, 0x02, 0x01, 0x02]);
+
+    let decoded = decode_command(&encoded).unwrap();
+
+    match decoded {
+        Command::Hello {
+            proto_version,
+            features,
+        } => {
+            assert_eq!(proto_version, 0x0102);
+            assert_eq!(features, 0);
+        }
+        _ => panic!("Expected HELLO command"),
+    }
+}
+
+#[test]
+fn test_encode_decode_hello_with_features() {
+    let cmd = Command::Hello {
+        proto_version: 1,
+        features: CAP_FRAME_CRC,
+    };
+    let encoded = encode_command(&cmd);
+    assert_eq!(&encoded[..5], &[0x12, 0x00, 0x00, 0x00, 0x0A]);
+    assert_eq!(&encoded[7..], &CAP_FRAME_CRC.to_be_bytes());
+
+    match decode_command(&encoded).unwrap() {
+        Command::Hello {
+            proto_version,
+            features,
+        } => {
+            assert_eq!(proto_version, 1);
+            assert_eq!(features, CAP_FRAME_CRC);
+        }
+        _ => panic!("Expected HELLO command"),
+    }
+}
+
+#[test]
+fn test_decode_hello_bad_length() {
+    let encoded = vec![0x12, 0x00, 0x00, 0x00, 0x03, 0x00, 0x01, 0x00];
+    assert!(decode_command(&encoded).is_err());
+}
+
+#[test]
+fn test_encode_decode_sstables() {
+    let encoded = encode_command(&Command::SsTables);
+    assert_eq!(encoded, vec![0x13, 0x00, 0x00, 0x00, 0x00]);
+
+    let decoded = decode_command(&encoded).unwrap();
+    assert!(matches!(decoded, Command::SsTables));
+}
+
+#[test]
+fn test_encode_decode_stats_json() {
+    let encoded = encode_command(&Command::StatsJson);
+    assert_eq!(encoded, vec![0x15, 0x00, 0x00, 0x00, 0x00]);
+
+    let decoded = decode_command(&encoded).unwrap();
+    assert!(matches!(decoded, Command::StatsJson));
+
+    // Payload must be empty
+    let with_payload = vec![0x15, 0x00, 0x00, 0x00, 0x01, 0xFF];
+    assert!(decode_command(&with_payload).is_err());
+}
+
+#[test]
+fn test_encode_decode_scan_rev() {
+    let cmd = Command::ScanRev {
+        start: b"a".to_vec(),
+        end: b"zz".to_vec(),
+        limit: 25,
+    };
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded[0], 0x14);
+
+    match decode_command(&encoded).unwrap() {
+        Command::ScanRev { start, end, limit } => {
+            assert_eq!(start, b"a");
+            assert_eq!(end, b"zz");
+            assert_eq!(limit, 25);
+        }
+        _ => panic!("Expected SCANREV command"),
+    }
+
+    // Missing limit
+    let mut truncated = encoded.clone();
+    truncated.truncate(encoded.len() - 1);
+    let payload_len = (truncated.len() - 5) as u32;
+    truncated[1..5].copy_from_slice(&payload_len.to_be_bytes());
+    assert!(decode_command(&truncated).is_err());
+}
+
+#[test]
+fn test_encode_decode_scan_keys() {
+    let cmd = Command::ScanKeys {
+        start: b"a".to_vec(),
+        end: vec![],
+        limit: 7,
+    };
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded[0], 0x1A);
+
+    match decode_command(&encoded).unwrap() {
+        Command::ScanKeys { start, end, limit } => {
+            assert_eq!(start, b"a");
+            assert!(end.is_empty());
+            assert_eq!(limit, 7);
+        }
+        _ => panic!("Expected SCANKEYS command"),
+    }
+}
+
+#[test]
+fn test_encode_decode_count() {
+    let cmd = Command::Count {
+        start: b"user:".to_vec(),
+        end: b"user;".to_vec(),
+    };
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded[0], 0x1C);
+
+    match decode_command(&encoded).unwrap() {
+        Command::Count { start, end } => {
+            assert_eq!(start, b"user:");
+            assert_eq!(end, b"user;");
+        }
+        _ => panic!("Expected COUNT command"),
+    }
+
+    // A trailing limit (as in SCANKEYS) is rejected
+    let mut extra = encoded.clone();
+    extra.extend_from_slice(&10u32.to_be_bytes());
+    let payload_len = (extra.len() - 5) as u32;
+    extra[1..5].copy_from_slice(&payload_len.to_be_bytes());
+    assert!(decode_command(&extra).is_err());
+}
+
+#[test]
+fn test_encode_decode_version() {
+    let encoded = encode_command(&Command::Version);
+    assert_eq!(encoded[0], 0x1D);
+    assert!(matches!(decode_command(&encoded).unwrap(), Command::Version));
+
+    let info = ServerVersion {
+        version: "1.2.3".to_string(),
+        sstable_format: 4,
+        wal_format: 1,
+        proto_version: 1,
+    };
+    let payload = encode_version_response(&info);
+    assert_eq!(&payload[..6], &[0, 1, 0, 4, 0, 1]);
+    assert_eq!(decode_version_response(&payload).unwrap(), info);
+    assert!(decode_version_response(&payload[..5]).is_err());
+}
+
+#[test]
+fn test_encode_decode_scan_cursor() {
+    for after_key in [None, Some(vec![]), Some(b"key0042".to_vec())] {
+        let cmd = Command::ScanCursor { after_key: after_key.clone(), limit: 100 };
+        let encoded = encode_command(&cmd);
+        assert_eq!(encoded[0], 0x1F);
+
+        match decode_command(&encoded).unwrap() {
+            Command::ScanCursor { after_key: decoded, limit } => {
+                assert_eq!(decoded, after_key);
+                assert_eq!(limit, 100);
+            }
+            _ => panic!("Expected SCANCURSOR command"),
+        }
+    }
+
+    // An unknown has_after flag is rejected
+    let mut encoded = encode_command(&Command::ScanCursor { after_key: None, limit: 1 });
+    encoded[5] = 2;
+    assert!(decode_command(&encoded).is_err());
+}
+
+#[test]
+fn test_scan_page_round_trip() {
+    let entries = vec![(b"a".to_vec(), b"1".to_vec()), (b"b".to_vec(), vec![])];
+    for (more, deadline_exceeded) in [(false, false), (true, false), (true, true)] {
+        let page = ScanPage { entries: entries.clone(), more, deadline_exceeded };
+        assert_eq!(decode_scan_page(&encode_scan_page(&page)).unwrap(), page);
+    }
+    let empty = ScanPage::default();
+    assert_eq!(decode_scan_page(&encode_scan_page(&empty)).unwrap(), empty);
+
+    // Flags: more = 0x01, deadline exceeded = 0x02
+    let flagged = ScanPage { more: true, deadline_exceeded: true, ..ScanPage::default() };
+    assert_eq!(encode_scan_page(&flagged)[0], 0x03);
+
+    assert!(decode_scan_page(&[]).is_err());
+    assert!(decode_scan_page(&[4]).is_err());
+}
+
+#[test]
+fn test_encode_decode_with_deadline() {
+    let inner = Command::ScanCursor { after_key: Some(b"k".to_vec()), limit: 10 };
+    let cmd = Command::WithDeadline { deadline_ms: 250, command: Box::new(inner.clone()) };
+    let encoded = encode_command(&cmd);
+    assert_eq!(encoded[0], 0x20);
+    assert_eq!(&encoded[5..9], &250u32.to_be_bytes());
+    assert_eq!(&encoded[9..], encode_command(&inner).as_slice());
+
+    match decode_command(&encoded).unwrap() {
+        Command::WithDeadline { deadline_ms, command } => {
+            assert_eq!(deadline_ms, 250);
+            assert!(matches!(
+                *command,
+                Command::ScanCursor { after_key: Some(ref key), limit: 10 } if key == b"k"
+            ));
+        }
+        _ => panic!("Expected WITHDEADLINE command"),
+    }
+
+    // Nested deadlines, a missing deadline, and bytes past the wrapped
+    // command are all rejected
+    let nested = Command::WithDeadline { deadline_ms: 1, command: Box::new(cmd) };
+    assert!(decode_command(&encode_command(&nested)).is_err());
+    assert!(decode_command(&[0x20, 0, 0, 0, 2, 0, 0]).is_err());
+
+    let mut payload = 1u32.to_be_bytes().to_vec();
+    payload.extend_from_slice(&encode_command(&Command::Ping));
+    payload.push(0);
+    let mut trailing = vec![0x20];
+    trailing.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    trailing.extend_from_slice(&payload);
+    assert!(decode_command(&trailing).is_err());
+}
+
+#[test]
+fn test_deeply_nested_deadlines_rejected_without_recursing() {
+    // 20,000 WITHDEADLINE headers around a PING (~180 KB): decoding them
+    // recursively would overflow the stack
+    let depth = 20_000;
+    let ping = encode_command(&Command::Ping);
+    let mut bytes = Vec::with_capacity(depth * 9 + ping.len());
+    for level in (1..=depth).rev() {
+        let payload_len = 4 + ping.len() + 9 * (level - 1);
+        bytes.push(0x20);
+        bytes.extend_from_slice(&(payload_len as u32).to_be_bytes());
+        bytes.extend_from_slice(&1u32.to_be_bytes());
+    }
+    bytes.extend_from_slice(&ping);
+
+    match decode_command(&bytes) {
+        Err(AtlasError::Protocol(msg)) => assert!(msg.contains("nested"), "{}", msg),
+        other => panic!("expected a protocol error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_keys_round_trip() {
+    let keys = vec![b"a".to_vec(), vec![], b"zz".to_vec()];
+    let payload = encode_keys(&keys);
+    assert_eq!(decode_keys(&payload).unwrap(), keys);
+    assert!(decode_keys(&[]).unwrap().is_empty());
+
+    assert!(decode_keys(&payload[..payload.len() - 1]).is_err());
+}
+
+#[test]
+fn test_entries_round_trip() {
+    let entries = vec![
+        (b"b".to_vec(), b"2".to_vec()),
+        (b"a".to_vec(), vec![]),
+    ];
+    let payload = encode_entries(&entries);
+    assert_eq!(decode_entries(&payload).unwrap(), entries);
+    assert!(decode_entries(&[]).unwrap().is_empty());
+
+    assert!(decode_entries(&payload[..payload.len() - 1]).is_err());
+}
+
+#[test]
+fn test_hello_response_round_trip() {
+    let payload = encode_hello_response(1, capabilities());
+    assert_eq!(payload.len(), 10);
+
+    let (version, caps) = decode_hello_response(&payload).unwrap();
+    assert_eq!(version, 1);
+    assert_eq!(caps, capabilities());
+
+    assert!(decode_hello_response(&payload[..9]).is_err());
+}
+
+#[test]
+fn test_capabilities_cover_known_commands() {
+    let caps = capabilities();
+    let supported = [
+        0x01, 0x02, 0x03, 0x04, 0x0F, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x18, 0x19, 0x1A,
+        0x1B, 0x1C, 0x1D, 0x1F, 0x20,
+    ];
+    for byte in supported {
+        assert!(caps & (1 << byte) != 0, "missing capability bit 0x{:02x}", byte);
+    }
+    assert_eq!(caps & (1 << 0x05), 0);
+}
+
 #[test]
 fn test_encode_decode_empty_key() {
     let cmd = Command::Get { key: vec![] };
@@ -190,6 +633,110 @@ fn test_incomplete_payload() {
     assert!(result.unwrap_err().to_string().contains("Incomplete"));
 }
 
+#[test]
+fn test_decode_command_parts_matches_decode_command() {
+    let cmd = Command::Put {
+        key: b"key".to_vec(),
+        value: b"value".to_vec(),
+    };
+    let encoded = encode_command(&cmd);
+    let payload = &encoded[HEADER_SIZE..];
+
+    let limits = CommandLimits::unlimited();
+    match decode_command_parts(encoded[0], payload.len(), payload, &limits).unwrap() {
+        Command::Put { key, value } => {
+            assert_eq!(key, b"key");
+            assert_eq!(value, b"value");
+        }
+        other => panic!("Expected PUT command, got {:?}", other),
+    }
+
+    // Bytes past payload_len are ignored, missing ones are an error
+    let mut padded = payload.to_vec();
+    padded.extend_from_slice(b"trailing");
+    assert!(decode_command_parts(encoded[0], payload.len(), &padded, &limits).is_ok());
+    let err = decode_command_parts(encoded[0], payload.len(), &payload[..4], &limits).unwrap_err();
+    assert!(err.to_string().contains("Incomplete payload"));
+}
+
+#[test]
+fn test_put_key_over_max_key_size_rejected() {
+    let limits = CommandLimits {
+        max_key_size: 4,
+        max_value_size: usize::MAX,
+    };
+    let fits = encode_command(&Command::Put {
+        key: b"four".to_vec(),
+        value: b"value".to_vec(),
+    });
+    assert!(decode_command_with_limits(&fits, &limits).is_ok());
+
+    let too_long = encode_command(&Command::Put {
+        key: b"fives".to_vec(),
+        value: b"value".to_vec(),
+    });
+    match decode_command_with_limits(&too_long, &limits) {
+        Err(AtlasError::Protocol(msg)) => {
+            assert_eq!(msg, "PUT command: key too large (5 bytes, max 4)")
+        }
+        other => panic!("Expected key too large, got {:?}", other),
+    }
+
+    // The limit also reaches a PUT wrapped in WITHDEADLINE
+    let wrapped = encode_command(&Command::WithDeadline {
+        deadline_ms: 100,
+        command: Box::new(Command::Put {
+            key: b"fives".to_vec(),
+            value: Vec::new(),
+        }),
+    });
+    assert!(decode_command_with_limits(&wrapped, &limits).is_err());
+    assert!(decode_command(&wrapped).is_ok());
+
+    // A key_len past the payload is still an incomplete key, not a panic
+    let mut absurd = fits.clone();
+    absurd[HEADER_SIZE..HEADER_SIZE + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+    match decode_command(&absurd) {
+        Err(AtlasError::Protocol(msg)) => assert!(msg.contains("incomplete key"), "{}", msg),
+        other => panic!("Expected incomplete key, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_random_bytes_never_panics() {
+    // xorshift64, so failures reproduce
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let limits = CommandLimits {
+        max_key_size: 16,
+        max_value_size: usize::MAX,
+    };
+    for _ in 0..20_000 {
+        let len = (next() % 64) as usize;
+        let mut bytes: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+        if bytes.len() >= HEADER_SIZE {
+            // Mostly valid command types and payload lengths, so decoding
+            // gets past the header
+            let cmd_type = (next() % 0x21) as u8;
+            let payload_len = (len - HEADER_SIZE) as u32 + (next() % 3) as u32;
+            bytes[0] = cmd_type;
+            bytes[1..HEADER_SIZE].copy_from_slice(&payload_len.to_be_bytes());
+        }
+
+        for result in [decode_command(&bytes), decode_command_with_limits(&bytes, &limits)] {
+            if let Err(e) = result {
+                assert!(matches!(e, AtlasError::Protocol(_)), "untyped error {:?}", e);
+            }
+        }
+    }
+}
+
 #[test]
 fn test_unknown_command_type() {
     let bytes = [0xFF, 0x00, 0x00, 0x00, 0x00]; // Unknown cmd type
@@ -223,6 +770,13 @@ fn test_ping_with_unexpected_payload() {
     assert!(result.unwrap_err().to_string().contains("unexpected payload"));
 }
 
+#[test]
+fn test_hello_wrong_payload_size() {
+    let bytes = [0x12, 0x00, 0x00, 0x00, 0x01, 0x01];
+    let result = decode_command(&bytes);
+    assert!(result.is_err());
+}
+
 // =============================================================================
 // Stream I/O Tests
 // =============================================================================
@@ -263,6 +817,46 @@ fn test_stream_write_read_response() {
     assert_eq!(decoded.payload, Some(b"result".to_vec()));
 }
 
+#[test]
+fn test_stream_read_command_within_limits() {
+    let limits = CommandLimits { max_key_size: 3, max_value_size: 5 };
+    let cmd = Command::Put {
+        key: b"key".to_vec(),
+        value: b"value".to_vec(),
+    };
+
+    let mut buffer = Vec::new();
+    write_command(&mut buffer, &cmd).unwrap();
+
+    let decoded = read_command_with_limits(&mut Cursor::new(buffer), &limits).unwrap();
+    assert!(matches!(decoded, Command::Put { .. }));
+}
+
+#[test]
+fn test_stream_read_command_key_over_limit() {
+    let limits = CommandLimits { max_key_size: 3, max_value_size: 5 };
+
+    let mut buffer = Vec::new();
+    write_command(&mut buffer, &Command::Get { key: b"long".to_vec() }).unwrap();
+
+    let result = read_command_with_limits(&mut Cursor::new(buffer), &limits);
+    assert!(result.unwrap_err().to_string().contains("Key too large"));
+}
+
+#[test]
+fn test_stream_read_command_value_rejected_before_payload() {
+    let limits = CommandLimits { max_key_size: 64, max_value_size: 1024 };
+
+    // Header claims a ~16 MB PUT, but only the key length follows: the
+    // limit must trip from the length fields alone, without reading further
+    let mut buffer = vec![0x02];
+    buffer.extend_from_slice(&(16_000_000u32).to_be_bytes());
+    buffer.extend_from_slice(&3u32.to_be_bytes());
+
+    let result = read_command_with_limits(&mut Cursor::new(buffer), &limits);
+    assert!(result.unwrap_err().to_string().contains("Value too large"));
+}
+
 #[test]
 fn test_stream_multiple_commands() {
     let commands = vec![
@@ -316,6 +910,112 @@ fn test_stream_multiple_responses() {
     }
 }
 
+// =============================================================================
+// Frame CRC Tests
+// =============================================================================
+
+#[test]
+fn test_command_crc_round_trip() {
+    let cmd = Command::Put {
+        key: b"key".to_vec(),
+        value: b"value".to_vec(),
+    };
+    let plain = encode_command(&cmd);
+    let framed = encode_command_with_crc(&cmd);
+
+    assert_eq!(framed.len(), plain.len() + CRC_SIZE);
+    assert_eq!(&framed[..plain.len()], &plain[..]);
+    assert_eq!(&framed[plain.len()..], &crc32fast::hash(&plain).to_be_bytes());
+
+    match decode_command_with_crc(&framed).unwrap() {
+        Command::Put { key, value } => {
+            assert_eq!(key, b"key");
+            assert_eq!(value, b"value");
+        }
+        _ => panic!("Expected PUT command"),
+    }
+}
+
+#[test]
+fn test_command_crc_detects_corrupt_byte() {
+    let framed = encode_command_with_crc(&Command::Get {
+        key: b"hello".to_vec(),
+    });
+
+    // Every single-byte flip in the header, payload, or CRC is caught
+    for i in 0..framed.len() {
+        let mut corrupt = framed.clone();
+        corrupt[i] ^= 0x01;
+        assert!(decode_command_with_crc(&corrupt).is_err(), "flip at byte {} not caught", i);
+    }
+
+    // A payload flip keeps the frame well-formed, so only the CRC catches it
+    let mut corrupt = framed.clone();
+    corrupt[HEADER_SIZE + 5] ^= 0x20;
+    match decode_command_with_crc(&corrupt) {
+        Err(AtlasError::Protocol(msg)) => assert_eq!(msg, "crc mismatch"),
+        other => panic!("Expected crc mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_response_crc_detects_corrupt_byte() {
+    let response = Response::ok(Some(b"value".to_vec()));
+    let framed = encode_response_with_crc(&response);
+
+    let decoded = decode_response_with_crc(&framed).unwrap();
+    assert_eq!(decoded.payload, Some(b"value".to_vec()));
+
+    let mut corrupt = framed.clone();
+    corrupt[HEADER_SIZE] ^= 0xFF;
+    match decode_response_with_crc(&corrupt) {
+        Err(AtlasError::Protocol(msg)) => assert_eq!(msg, "crc mismatch"),
+        other => panic!("Expected crc mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_crc_missing_trailer_rejected() {
+    let plain = encode_command(&Command::Ping);
+    assert!(decode_command_with_crc(&plain).is_err());
+}
+
+#[test]
+fn test_crc_disabled_frames_still_decode() {
+    // Plain frames are unchanged by the CRC support
+    let cmd = Command::Delete { key: b"k".to_vec() };
+    let plain = encode_command(&cmd);
+    assert_eq!(plain, vec![0x03, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, b'k']);
+    assert!(matches!(decode_command(&plain).unwrap(), Command::Delete { .. }));
+
+    let response = encode_response(&Response::not_found());
+    assert_eq!(decode_response(&response).unwrap().status, Status::NotFound);
+}
+
+#[test]
+fn test_crc_stream_round_trip() {
+    let framed = encode_command_with_crc(&Command::Append {
+        key: b"log".to_vec(),
+        data: b"line".to_vec(),
+    });
+    let mut cursor = Cursor::new(framed);
+    let cmd = read_command_with_crc(&mut cursor, &CommandLimits::unlimited()).unwrap();
+    assert!(matches!(cmd, Command::Append { .. }));
+
+    let mut corrupt = encode_command_with_crc(&Command::Ping);
+    *corrupt.last_mut().unwrap() ^= 0x01;
+    match read_command_with_crc(&mut Cursor::new(corrupt), &CommandLimits::unlimited()) {
+        Err(AtlasError::Protocol(msg)) => assert_eq!(msg, "crc mismatch"),
+        other => panic!("Expected crc mismatch, got {:?}", other),
+    }
+
+    let mut buf = Vec::new();
+    write_response_with_crc(&mut buf, &Response::ok(None)).unwrap();
+    assert_eq!(buf.len(), HEADER_SIZE + CRC_SIZE);
+    let response = read_response_with_crc(&mut Cursor::new(buf)).unwrap();
+    assert_eq!(response.status, Status::Ok);
+}
+
 // =============================================================================
 // Wire Format Verification Tests
 // =============================================================================
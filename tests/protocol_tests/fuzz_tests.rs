@@ -0,0 +1,119 @@
+//! Fuzz Tests
+//!
+//! Property tests feeding arbitrary bytes to the decoders, which see
+//! attacker-controlled input on the server. These tests verify:
+//! - Random bytes never panic `decode_command` or `decode_response`
+//! - Frames with a valid header and a random payload never panic either,
+//!   however deeply WITHDEADLINE frames nest around them
+//! - Every failure is a typed `AtlasError::Protocol`
+
+use proptest::prelude::*;
+
+use atlaskv::protocol::{
+    decode_command, decode_command_with_crc, decode_entries, decode_get_meta_response,
+    decode_hello_response, decode_keys, decode_response, decode_response_with_crc,
+    decode_scan_page, decode_version_response, CommandType, HEADER_SIZE,
+};
+use atlaskv::{AtlasError, Result};
+
+/// Assert a decode result is `Ok` or a protocol error
+fn assert_typed<T: std::fmt::Debug>(result: Result<T>) {
+    if let Err(e) = result {
+        assert!(matches!(e, AtlasError::Protocol(_)), "untyped error: {:?}", e);
+    }
+}
+
+/// A frame whose header names `code` and the exact payload length
+fn frame(code: u8, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![code];
+    bytes.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// `depth` WITHDEADLINE frames around `inner`, built outside in (wrapping
+/// one level at a time would copy the whole frame per level)
+fn nested_deadlines(depth: usize, inner: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(depth * (HEADER_SIZE + 4) + inner.len());
+    for level in (1..=depth).rev() {
+        let payload_len = inner.len() + (HEADER_SIZE + 4) * (level - 1) + 4;
+        bytes.push(CommandType::WithDeadline as u8);
+        bytes.extend_from_slice(&(payload_len as u32).to_be_bytes());
+        bytes.extend_from_slice(&(level as u32).to_be_bytes());
+    }
+    bytes.extend_from_slice(inner);
+    bytes
+}
+
+proptest! {
+    #[test]
+    fn decode_command_random_bytes(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        assert_typed(decode_command(&bytes));
+        assert_typed(decode_command_with_crc(&bytes));
+    }
+
+    #[test]
+    fn decode_response_random_bytes(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        assert_typed(decode_response(&bytes));
+        assert_typed(decode_response_with_crc(&bytes));
+    }
+
+    #[test]
+    fn decode_command_random_payload(
+        index in 0..CommandType::ALL.len(),
+        payload in prop::collection::vec(any::<u8>(), 0..256),
+    ) {
+        let bytes = frame(CommandType::ALL[index] as u8, &payload);
+        assert_typed(decode_command(&bytes));
+    }
+
+    #[test]
+    fn decode_command_wrapped_random_payload(
+        index in 0..CommandType::ALL.len(),
+        deadline_ms in any::<u32>(),
+        payload in prop::collection::vec(any::<u8>(), 0..256),
+    ) {
+        let mut wrapped = deadline_ms.to_be_bytes().to_vec();
+        wrapped.extend_from_slice(&frame(CommandType::ALL[index] as u8, &payload));
+        let bytes = frame(CommandType::WithDeadline as u8, &wrapped);
+        assert_typed(decode_command(&bytes));
+    }
+
+    #[test]
+    fn decode_command_nested_deadlines(
+        depth in prop_oneof![1..4usize, 1000..30_000usize],
+        index in 0..CommandType::ALL.len(),
+        payload in prop::collection::vec(any::<u8>(), 0..64),
+    ) {
+        let bytes = nested_deadlines(depth, &frame(CommandType::ALL[index] as u8, &payload));
+        assert_typed(decode_command(&bytes));
+    }
+
+    #[test]
+    fn decode_response_payloads_random_bytes(
+        payload in prop::collection::vec(any::<u8>(), 0..256),
+    ) {
+        assert_typed(decode_hello_response(&payload));
+        assert_typed(decode_version_response(&payload));
+        assert_typed(decode_get_meta_response(&payload));
+        assert_typed(decode_scan_page(&payload));
+        assert_typed(decode_entries(&payload));
+        assert_typed(decode_keys(&payload));
+    }
+}
+
+#[test]
+fn test_short_frames_rejected_not_panicking() {
+    // Every strict prefix of a valid frame, cut inside the header or payload
+    let bytes = frame(CommandType::Put as u8, &[0, 0, 0, 3, b'k', b'e', b'y', b'v']);
+    for len in 0..bytes.len() {
+        match decode_command(&bytes[..len]) {
+            Err(AtlasError::Protocol(msg)) => {
+                let expected = if len < HEADER_SIZE { "Incomplete header" } else { "Incomplete" };
+                assert!(msg.contains(expected), "{} bytes: {}", len, msg);
+            }
+            other => panic!("{} bytes: expected a protocol error, got {:?}", len, other),
+        }
+    }
+    assert!(decode_command(&bytes).is_ok());
+}
@@ -3,3 +3,4 @@
 //! Integration tests for the protocol codec.
 
 mod codec_tests;
+mod fuzz_tests;
@@ -0,0 +1,86 @@
+//! Tests for compaction input selection
+//!
+//! These tests verify:
+//! - `AllFiles` picks every SSTable
+//! - `OverlapBased` picks the newest run of overlapping SSTables (two or more)
+//! - `TombstoneRatio` picks everything once tombstones pass the ratio
+
+use atlaskv::comparator::BytewiseComparator;
+use atlaskv::config::CompactionStrategy;
+use atlaskv::storage::{select_inputs, CompactionCandidate, SSTableStats};
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Synthetic SSTable metadata for keys `min..=max`
+fn candidate(id: u64, min: &str, max: &str, entries: u64, tombstones: u64) -> CompactionCandidate {
+    CompactionCandidate {
+        stats: SSTableStats {
+            id,
+            entry_count: entries,
+            file_size: entries * 32,
+            min_key: Some(min.as_bytes().to_vec()),
+            max_key: Some(max.as_bytes().to_vec()),
+        },
+        tombstones,
+    }
+}
+
+fn select(strategy: CompactionStrategy, candidates: &[CompactionCandidate]) -> Vec<u64> {
+    select_inputs(strategy, candidates, &BytewiseComparator)
+}
+
+// =============================================================================
+// Strategy Tests
+// =============================================================================
+
+#[test]
+fn test_all_files_picks_every_sstable() {
+    let candidates = [candidate(2, "m", "p", 10, 0), candidate(1, "a", "c", 10, 0)];
+    assert_eq!(select(CompactionStrategy::AllFiles, &candidates), [2, 1]);
+    assert!(select(CompactionStrategy::AllFiles, &[]).is_empty());
+}
+
+#[test]
+fn test_overlap_based_picks_overlapping_group() {
+    // Newest first: 6-4 keep overlapping the range so far, 3 is off on its
+    // own, and 2 (overlapping everything) is cut off behind it
+    let candidates = [
+        candidate(6, "user:m", "user:p", 100, 0),
+        candidate(5, "user:k", "user:n", 100, 0),
+        candidate(4, "user:n", "user:z", 100, 0),
+        candidate(3, "admin:a", "admin:c", 100, 0),
+        candidate(2, "admin:a", "user:z", 100, 0),
+        candidate(1, "user:a", "user:z", 100, 0),
+    ];
+    assert_eq!(select(CompactionStrategy::OverlapBased, &candidates), [6, 5, 4]);
+}
+
+#[test]
+fn test_overlap_based_touching_ranges_overlap() {
+    // Sharing a boundary key means sharing a key
+    let candidates = [candidate(2, "m", "p", 10, 0), candidate(1, "a", "m", 10, 0)];
+    assert_eq!(select(CompactionStrategy::OverlapBased, &candidates), [2, 1]);
+}
+
+#[test]
+fn test_overlap_based_needs_two_overlapping_sstables() {
+    let candidates = [candidate(3, "m", "p", 10, 0), candidate(2, "a", "c", 10, 0)];
+    assert!(select(CompactionStrategy::OverlapBased, &candidates).is_empty());
+    assert!(select(CompactionStrategy::OverlapBased, &candidates[..1]).is_empty());
+}
+
+#[test]
+fn test_tombstone_ratio_threshold() {
+    let strategy = CompactionStrategy::TombstoneRatio { min_ratio: 0.25 };
+
+    // 20 of 100 entries are tombstones: below the ratio
+    let candidates = [candidate(2, "a", "m", 50, 20), candidate(1, "n", "z", 50, 0)];
+    assert!(select(strategy, &candidates).is_empty());
+
+    // 25 of 100: compact everything
+    let candidates = [candidate(2, "a", "m", 50, 20), candidate(1, "n", "z", 50, 5)];
+    assert_eq!(select(strategy, &candidates), [2, 1]);
+    assert!(select(strategy, &[]).is_empty());
+}
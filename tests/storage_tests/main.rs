@@ -1,3 +1,4 @@
 // Storage tests
 mod sstable_tests;
 mod manager_tests;
+mod compaction_tests;
@@ -1,15 +1,33 @@
 //! Tests for SSTable implementation
 //!
 //! These tests verify:
-//! - SSTable creation and writing
+//! - SSTable creation and writing (strictly increasing keys, values short of
+//!   the tombstone marker)
 //! - O(log n) key lookups via in-memory index
 //! - Tombstone handling
-//! - Iterator over all entries
-//! - Min/max key range filtering
-//! - File format validation
+//! - Value locations for positioned reads straight from the file
+//! - Presence checks that never read value bytes
+//! - Per-entry sequence numbers (version 1 files read as seq 0)
+//! - Per-entry write timestamps (version 3 files read as timestamp 0)
+//! - Iterator over all entries (and over keys only)
+//! - Data blocks (block boundaries, last partial block, restart points)
+//! - Prefix-compressed index keys
+//! - Min/max key range filtering (single keys and key ranges)
+//! - Custom key comparators (write order check, lookups, range checks)
+//! - File format validation (and lookups in a file truncated after open)
+//! - Forward compatibility: newer minor versions read, newer majors and
+//!   unknown footer flags rejected
+//! - Per-value checksums catching a corrupted value on read (version 5
+//!   files, which have none, still read)
+//! - Rebuilding a lost index from the data blocks
 
-use std::path::PathBuf;
-use atlaskv::storage::{SSTable, SSTableBuilder, SSTableReader};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use atlaskv::comparator::{CaseInsensitiveComparator, Comparator};
+use atlaskv::storage::{
+    positioned_read_bytes, EntryMeta, SSTable, SSTableBuilder, SSTableReader, MAX_VALUE_SIZE,
+};
 use atlaskv::AtlasError;
 use tempfile::TempDir;
 
@@ -24,7 +42,7 @@ fn setup_temp_sstable() -> (TempDir, PathBuf) {
 }
 
 /// Create an SSTable with numbered entries
-fn create_sstable_with_entries(path: &PathBuf, count: usize) -> SSTable {
+fn create_sstable_with_entries(path: &Path, count: usize) -> SSTable {
     let mut builder = SSTableBuilder::new(path).unwrap();
     // Keys must be added in sorted order
     for i in 0..count {
@@ -35,6 +53,20 @@ fn create_sstable_with_entries(path: &PathBuf, count: usize) -> SSTable {
     builder.finish().unwrap()
 }
 
+/// Create an SSTable of 38-byte entries (`key00000` → `v00000`) in small blocks
+///
+/// A 130-byte block size closes each block after 4 entries (4 × 38 bytes
+/// plus the 8-byte restart trailer passes 130; 3 entries fall short).
+fn create_blocked_sstable(path: &Path, count: usize) {
+    let mut builder = SSTableBuilder::new(path).unwrap().with_block_size(130);
+    for i in 0..count {
+        builder
+            .add(format!("key{:05}", i).as_bytes(), format!("v{:05}", i).as_bytes())
+            .unwrap();
+    }
+    builder.finish().unwrap();
+}
+
 // =============================================================================
 // SSTableBuilder Tests
 // =============================================================================
@@ -101,6 +133,110 @@ fn test_builder_with_tombstone() {
     assert_eq!(sstable.entry_count(), 3);
 }
 
+#[test]
+fn test_builder_value_size_stays_below_tombstone_marker() {
+    // A ValLen of u32::MAX would read back as a tombstone
+    assert_eq!(MAX_VALUE_SIZE as u64, u32::MAX as u64 - 1);
+    assert!(SSTableBuilder::check_value_size(0).is_ok());
+    assert!(SSTableBuilder::check_value_size(MAX_VALUE_SIZE).is_ok());
+
+    // Checked by length alone, so no 4 GiB value is needed
+    for len in [MAX_VALUE_SIZE + 1, usize::MAX] {
+        let result = SSTableBuilder::check_value_size(len);
+        assert!(matches!(result, Err(AtlasError::Storage(msg)) if msg.contains("too large")));
+    }
+}
+
+#[test]
+fn test_builder_rejects_out_of_order_key() {
+    let (_temp, path) = setup_temp_sstable();
+
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    builder.add(b"banana", b"2").unwrap();
+
+    let result = builder.add(b"apple", b"1");
+    assert!(matches!(result, Err(AtlasError::Storage(msg)) if msg.contains("strictly increasing")));
+
+    // The rejected key was not written; later sorted keys still build a valid table
+    builder.add(b"cherry", b"3").unwrap();
+    builder.finish().unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert!(matches!(reader.get(b"apple"), Err(AtlasError::KeyNotFound)));
+    assert_eq!(reader.get(b"cherry").unwrap(), Some(b"3".to_vec()));
+}
+
+#[test]
+fn test_builder_rejects_duplicate_key() {
+    let (_temp, path) = setup_temp_sstable();
+
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    builder.add(b"key", b"value").unwrap();
+
+    assert!(matches!(builder.add(b"key", b"other"), Err(AtlasError::Storage(_))));
+    assert!(matches!(builder.add_tombstone(b"key"), Err(AtlasError::Storage(_))));
+    assert_eq!(builder.finish().unwrap().entry_count(), 1);
+}
+
+#[test]
+fn test_builder_unchecked_accepts_sorted_keys() {
+    let (_temp, path) = setup_temp_sstable();
+
+    let mut builder = SSTableBuilder::new_unchecked(&path).unwrap();
+    builder.add(b"a", b"1").unwrap();
+    builder.add_tombstone(b"b").unwrap();
+    let sstable = builder.finish().unwrap();
+
+    assert_eq!(sstable.entry_count(), 2);
+}
+
+#[test]
+fn test_builder_records_sequence_numbers() {
+    let (_temp, path) = setup_temp_sstable();
+
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    builder.add_at(b"apple", b"1", 7).unwrap();
+    builder.add_tombstone_at(b"banana", 42).unwrap();
+    builder.add(b"cherry", b"3").unwrap();
+    builder.finish().unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert_eq!(reader.get_with_seq(b"apple").unwrap(), Some((Some(b"1".to_vec()), 7)));
+    assert_eq!(reader.get_with_seq(b"banana").unwrap(), Some((None, 42)));
+    assert_eq!(reader.get_with_seq(b"cherry").unwrap(), Some((Some(b"3".to_vec()), 0)));
+    assert_eq!(reader.get_with_seq(b"durian").unwrap(), None);
+    assert_eq!(reader.max_seq(), 42);
+
+    assert_eq!(reader.get(b"apple").unwrap(), Some(b"1".to_vec()));
+    assert_eq!(reader.get(b"banana").unwrap(), None);
+}
+
+#[test]
+fn test_builder_records_write_timestamps() {
+    let (_temp, path) = setup_temp_sstable();
+
+    let meta = EntryMeta { seq: 3, timestamp: 1_700_000_000_000 };
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    builder.add_entry(b"apple", Some(b"1"), meta).unwrap();
+    builder.add_entry(b"banana", None, EntryMeta { seq: 4, timestamp: 5 }).unwrap();
+    builder.add_at(b"cherry", b"3", 9).unwrap();
+    builder.finish().unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert_eq!(reader.get_with_meta(b"apple").unwrap(), Some((Some(b"1".to_vec()), meta)));
+    assert_eq!(
+        reader.get_with_meta(b"banana").unwrap(),
+        Some((None, EntryMeta { seq: 4, timestamp: 5 }))
+    );
+    assert_eq!(
+        reader.get_with_meta(b"cherry").unwrap(),
+        Some((Some(b"3".to_vec()), EntryMeta::at_seq(9)))
+    );
+
+    let entries: Vec<_> = reader.iter_with_seqs().unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(entries[0].2, meta);
+}
+
 // =============================================================================
 // SSTableReader Tests - Lookups
 // =============================================================================
@@ -192,6 +328,63 @@ fn test_reader_random_access() {
     }
 }
 
+#[test]
+fn test_reader_value_location() {
+    use std::os::unix::fs::FileExt;
+
+    let (_temp, path) = setup_temp_sstable();
+
+    // Enough entries for several restart points per block
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    for i in 0..100 {
+        let key = format!("key{:05}", i);
+        if i % 10 == 5 {
+            builder.add_tombstone(key.as_bytes()).unwrap();
+        } else {
+            builder.add(key.as_bytes(), format!("value{}", i).as_bytes()).unwrap();
+        }
+    }
+    builder.finish().unwrap();
+
+    let reader = SSTableReader::open(&path).unwrap();
+    for i in (0..100).filter(|i| i % 10 != 5) {
+        let key = format!("key{:05}", i);
+        let (offset, len) = reader.value_location(key.as_bytes()).unwrap().unwrap();
+
+        let mut value = vec![0u8; len as usize];
+        reader.file().read_exact_at(&mut value, offset).unwrap();
+        assert_eq!(value, format!("value{}", i).as_bytes());
+    }
+
+    // Tombstones are found but have no value; absent keys aren't found
+    assert_eq!(reader.value_location(b"key00015").unwrap(), None);
+    assert!(matches!(reader.value_location(b"key00015x"), Err(AtlasError::KeyNotFound)));
+    assert!(matches!(reader.value_location(b"zzz"), Err(AtlasError::KeyNotFound)));
+}
+
+#[test]
+fn test_reader_contains_skips_value_bytes() {
+    let (_temp, path) = setup_temp_sstable();
+
+    let big = vec![0x5A; 4 * 1024 * 1024];
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    builder.add(b"a", b"small").unwrap();
+    builder.add(b"big", &big).unwrap();
+    builder.add_tombstone(b"c").unwrap();
+    builder.finish().unwrap();
+
+    let reader = SSTableReader::open(&path).unwrap();
+    let before = positioned_read_bytes();
+    assert_eq!(reader.contains(b"big").unwrap(), Some(true));
+    let read = positioned_read_bytes() - before;
+    assert!(read > 0 && read < 1024, "read {} bytes", read);
+
+    assert_eq!(reader.contains(b"a").unwrap(), Some(true));
+    assert_eq!(reader.contains(b"c").unwrap(), Some(false));
+    assert_eq!(reader.contains(b"b").unwrap(), None);
+    assert_eq!(reader.contains(b"zzz").unwrap(), None);
+}
+
 // =============================================================================
 // SSTableReader Tests - Iterator
 // =============================================================================
@@ -251,6 +444,214 @@ fn test_iterator_includes_tombstones() {
     assert_eq!(entries[2], (b"c".to_vec(), Some(b"3".to_vec())));
 }
 
+#[test]
+fn test_iter_rev_returns_descending_entries() {
+    let (_temp, path) = setup_temp_sstable();
+    create_sstable_with_entries(&path, 10);
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    let keys: Vec<_> = reader.iter_rev().map(|r| r.unwrap().0).collect();
+
+    let expected: Vec<_> = (0..10).rev().map(|i| format!("key{:05}", i).into_bytes()).collect();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn test_range_rev_bounds_and_tombstones() {
+    let (_temp, path) = setup_temp_sstable();
+
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    builder.add(b"a", b"1").unwrap();
+    builder.add_tombstone(b"b").unwrap();
+    builder.add(b"c", b"3").unwrap();
+    builder.add(b"d", b"4").unwrap();
+    builder.finish().unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    let entries: Vec<_> = reader
+        .range_rev(Bound::Included(&b"a"[..]), Bound::Excluded(&b"d"[..]))
+        .map(|r| r.unwrap())
+        .collect();
+
+    assert_eq!(
+        entries,
+        vec![
+            (b"c".to_vec(), Some(b"3".to_vec())),
+            (b"b".to_vec(), None), // Tombstone
+            (b"a".to_vec(), Some(b"1".to_vec())),
+        ]
+    );
+
+    // Inverted range is empty rather than a panic
+    let inverted = reader.range_rev(Bound::Included(&b"d"[..]), Bound::Included(&b"a"[..]));
+    assert_eq!(inverted.count(), 0);
+}
+
+#[test]
+fn test_range_keys_skips_values() {
+    let (_temp, path) = setup_temp_sstable();
+    create_blocked_sstable(&path, 10);
+
+    // Bounds inside the first block and the last, partial one
+    let mut reader = SSTableReader::open(&path).unwrap();
+    let keys: Vec<_> = reader
+        .range_keys(Bound::Excluded(&b"key00002"[..]), Bound::Included(&b"key00008"[..]))
+        .map(|r| r.unwrap())
+        .collect();
+    let expected: Vec<_> = (3..=8).map(|i| (format!("key{:05}", i).into_bytes(), true)).collect();
+    assert_eq!(keys, expected);
+
+    let (_temp, path) = setup_temp_sstable();
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    builder.add(b"a", b"1").unwrap();
+    builder.add_tombstone(b"b").unwrap();
+    builder.finish().unwrap();
+
+    // Tombstones come back flagged rather than dropped
+    let mut reader = SSTableReader::open(&path).unwrap();
+    let keys: Vec<_> = reader
+        .range_keys(Bound::Unbounded, Bound::Unbounded)
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(keys, vec![(b"a".to_vec(), true), (b"b".to_vec(), false)]);
+}
+
+// =============================================================================
+// Data Block Tests
+// =============================================================================
+
+#[test]
+fn test_entries_grouped_into_blocks() {
+    let (_temp, path) = setup_temp_sstable();
+    create_blocked_sstable(&path, 10);
+
+    // 4 + 4 + a partial block of 2
+    let reader = SSTableReader::open(&path).unwrap();
+    assert_eq!(reader.block_count(), 3);
+    assert_eq!(reader.entry_count(), 10);
+    assert_eq!(reader.min_key(), Some(&b"key00000"[..]));
+    assert_eq!(reader.max_key(), Some(&b"key00009"[..]));
+}
+
+#[test]
+fn test_get_at_block_boundaries() {
+    let (_temp, path) = setup_temp_sstable();
+    create_blocked_sstable(&path, 10);
+    let mut reader = SSTableReader::open(&path).unwrap();
+
+    // Last and first keys of adjacent blocks, and both keys of the partial one
+    for i in [3, 4, 7, 8, 9] {
+        let value = reader.get(format!("key{:05}", i).as_bytes()).unwrap();
+        assert_eq!(value, Some(format!("v{:05}", i).into_bytes()));
+    }
+
+    // Gaps between blocks and past either end
+    for missing in ["key00003x", "key00007x", "key00009x", "a", "z"] {
+        let result = reader.get(missing.as_bytes());
+        assert!(matches!(result, Err(AtlasError::KeyNotFound)), "{}", missing);
+    }
+}
+
+#[test]
+fn test_iterators_cross_block_boundaries() {
+    let (_temp, path) = setup_temp_sstable();
+    create_blocked_sstable(&path, 10);
+    let mut reader = SSTableReader::open(&path).unwrap();
+
+    let expected: Vec<_> = (0..10).map(|i| format!("key{:05}", i).into_bytes()).collect();
+
+    let keys: Vec<_> = reader.iter().unwrap().map(|r| r.unwrap().0).collect();
+    assert_eq!(keys, expected);
+
+    let keys: Vec<_> = reader.iter_rev().map(|r| r.unwrap().0).collect();
+    assert_eq!(keys, expected.iter().rev().cloned().collect::<Vec<_>>());
+
+    // Bounds inside the first block and the last, partial one
+    let keys: Vec<_> = reader
+        .range_rev(Bound::Excluded(&b"key00002"[..]), Bound::Included(&b"key00008"[..]))
+        .map(|r| r.unwrap().0)
+        .collect();
+    assert_eq!(keys, expected[3..=8].iter().rev().cloned().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_oversized_entry_gets_own_block() {
+    let (_temp, path) = setup_temp_sstable();
+
+    let big = vec![0xAB; 500];
+    let mut builder = SSTableBuilder::new(&path).unwrap().with_block_size(100);
+    builder.add(b"a", b"small").unwrap();
+    builder.add(b"b", &big).unwrap();
+    builder.add(b"c", b"small").unwrap();
+    builder.finish().unwrap();
+
+    // "a" and "b" share a block (closed by "b"), "c" starts the next
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert_eq!(reader.block_count(), 2);
+    assert_eq!(reader.get(b"b").unwrap(), Some(big));
+    assert_eq!(reader.get(b"c").unwrap(), Some(b"small".to_vec()));
+}
+
+#[test]
+fn test_lookups_use_restart_points_in_large_block() {
+    let (_temp, path) = setup_temp_sstable();
+
+    // One block holding many restart intervals
+    let mut builder = SSTableBuilder::new(&path).unwrap().with_block_size(1 << 20);
+    for i in (0..200).step_by(2) {
+        builder.add(format!("key{:05}", i).as_bytes(), b"v").unwrap();
+    }
+    builder.finish().unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert_eq!(reader.block_count(), 1);
+    for i in 0..200 {
+        let result = reader.get(format!("key{:05}", i).as_bytes());
+        if i % 2 == 0 {
+            assert_eq!(result.unwrap(), Some(b"v".to_vec()));
+        } else {
+            assert!(matches!(result, Err(AtlasError::KeyNotFound)));
+        }
+    }
+}
+
+#[test]
+fn test_index_keys_share_prefixes() {
+    let (_temp, path) = setup_temp_sstable();
+    let prefix = "tenant:acme-corporation:region:eu-west-1:users:";
+    let key = |i: usize| format!("{}{:08}:email", prefix, i);
+    let key_len = key(0).len() as u64;
+
+    let mut builder = SSTableBuilder::new(&path).unwrap().with_block_size(256);
+    for i in 0..10_000 {
+        builder.add(key(i).as_bytes(), b"v").unwrap();
+    }
+    builder.finish().unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    let blocks = reader.block_count() as u64;
+    assert!(blocks > 1000);
+
+    // Index block size, from the footer's index offset
+    let bytes = std::fs::read(&path).unwrap();
+    let footer_at = bytes.len() - 16;
+    let index_offset = u64::from_le_bytes(bytes[footer_at..footer_at + 8].try_into().unwrap());
+    let index_size = footer_at as u64 - index_offset;
+
+    // Whole keys would take [KeyLen(4)][Offset(8)][Len(8)][Key] per block
+    let uncompressed = 8 + 4 + key_len + blocks * (20 + key_len);
+    assert!(index_size * 2 < uncompressed, "{} vs {} bytes", index_size, uncompressed);
+
+    for i in (0..10_000).step_by(97) {
+        assert_eq!(reader.get(key(i).as_bytes()).unwrap(), Some(b"v".to_vec()));
+    }
+    assert_eq!(reader.get(key(9_999).as_bytes()).unwrap(), Some(b"v".to_vec()));
+    assert!(matches!(
+        reader.get(format!("{}missing", prefix).as_bytes()),
+        Err(AtlasError::KeyNotFound)
+    ));
+}
+
 // =============================================================================
 // SSTable Metadata Tests
 // =============================================================================
@@ -290,6 +691,66 @@ fn test_might_contain_out_of_range() {
     assert!(matches!(reader.get(b"date"), Err(AtlasError::KeyNotFound)));
 }
 
+#[test]
+fn test_overlaps_range() {
+    let (_temp, path) = setup_temp_sstable();
+
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    builder.add(b"banana", b"1").unwrap();
+    builder.add(b"cherry", b"2").unwrap();
+    builder.finish().unwrap();
+    let reader = SSTableReader::open(&path).unwrap();
+
+    let inc = |key: &'static [u8]| Bound::Included(key);
+    let exc = |key: &'static [u8]| Bound::Excluded(key);
+    assert!(reader.overlaps(Bound::Unbounded, Bound::Unbounded));
+    assert!(reader.overlaps(inc(b"apple"), inc(b"banana")));
+    assert!(reader.overlaps(inc(b"cherry"), Bound::Unbounded));
+    assert!(reader.overlaps(inc(b"blueberry"), exc(b"bz")));
+
+    // Touching a bound that excludes the edge key doesn't count
+    assert!(!reader.overlaps(inc(b"apple"), exc(b"banana")));
+    assert!(!reader.overlaps(exc(b"cherry"), Bound::Unbounded));
+    assert!(!reader.overlaps(inc(b"date"), Bound::Unbounded));
+}
+
+#[test]
+fn test_case_insensitive_comparator() {
+    let (_temp_dir, path) = setup_temp_sstable();
+    let comparator: Arc<dyn Comparator> = Arc::new(CaseInsensitiveComparator);
+
+    // Out of byte order ("Cherry" < "banana"), but in case-insensitive order
+    let mut builder = SSTableBuilder::new(&path).unwrap().with_comparator(comparator.clone());
+    builder.add(b"Apple", b"1").unwrap();
+    builder.add(b"banana", b"2").unwrap();
+    builder.add(b"Cherry", b"3").unwrap();
+    // Equal under the comparator, so not strictly increasing
+    assert!(matches!(builder.add(b"CHERRY", b"4"), Err(AtlasError::Storage(_))));
+    builder.finish().unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap().with_comparator(comparator);
+    assert_eq!(reader.get(b"APPLE").unwrap(), Some(b"1".to_vec()));
+    assert_eq!(reader.get(b"Banana").unwrap(), Some(b"2".to_vec()));
+    assert_eq!(reader.get(b"cherry").unwrap(), Some(b"3".to_vec()));
+    assert!(reader.might_contain(b"BLUEBERRY"));
+    assert!(!reader.might_contain(b"date"));
+
+    let keys: Vec<Vec<u8>> = reader
+        .range_keys(Bound::Included(b"B"), Bound::Unbounded)
+        .map(|entry| entry.unwrap().0)
+        .collect();
+    assert_eq!(keys, vec![b"banana".to_vec(), b"Cherry".to_vec()]);
+}
+
+#[test]
+fn test_builder_rejects_comparator_order_by_default() {
+    let (_temp_dir, path) = setup_temp_sstable();
+
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    builder.add(b"banana", b"2").unwrap();
+    assert!(matches!(builder.add(b"Cherry", b"3"), Err(AtlasError::Storage(_))));
+}
+
 // =============================================================================
 // Large Data Tests
 // =============================================================================
@@ -351,3 +812,387 @@ fn test_open_invalid_magic() {
     let result = SSTableReader::open(&path);
     assert!(matches!(result, Err(AtlasError::Storage(_))));
 }
+
+#[test]
+fn test_open_truncated_file() {
+    let (_temp, path) = setup_temp_sstable();
+    create_sstable_with_entries(&path, 10);
+
+    let len = std::fs::metadata(&path).unwrap().len();
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(len - 10)
+        .unwrap();
+
+    let result = SSTableReader::open(&path);
+    assert!(matches!(result, Err(AtlasError::SSTableTruncated(_))));
+}
+
+#[test]
+fn test_get_from_file_truncated_after_open() {
+    let (_temp, path) = setup_temp_sstable();
+    create_blocked_sstable(&path, 20);
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert_eq!(reader.get(b"key00001").unwrap(), Some(b"v00001".to_vec()));
+
+    // The index still points into the data that was cut off
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(100)
+        .unwrap();
+
+    match reader.get(b"key00019") {
+        Err(AtlasError::Storage(msg)) => {
+            assert!(msg.contains("key00019"), "{}", msg);
+            assert!(msg.contains("past the end of the file"), "{}", msg);
+        }
+        other => panic!("expected a storage error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_open_file_shorter_than_header() {
+    let (_temp, path) = setup_temp_sstable();
+
+    std::fs::write(&path, b"ATKV").unwrap();
+
+    let result = SSTableReader::open(&path);
+    assert!(matches!(result, Err(AtlasError::SSTableTruncated(_))));
+}
+
+#[test]
+fn test_open_version_1_file_without_seqs() {
+    let (_temp, path) = setup_temp_sstable();
+
+    // One entry "k" -> "v" in the version 1 layout (index entries lack Seq)
+    let mut data = Vec::new();
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(b"kv");
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"ATKV");
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u64.to_le_bytes());
+    let data_offset = bytes.len() as u64;
+    bytes.extend_from_slice(&data);
+    let index_offset = bytes.len() as u64;
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.extend_from_slice(&data_offset.to_le_bytes());
+    bytes.extend_from_slice(b"k");
+    bytes.extend_from_slice(&index_offset.to_le_bytes());
+    bytes.extend_from_slice(&crc32fast::hash(&data).to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert_eq!(reader.get(b"k").unwrap(), Some(b"v".to_vec()));
+    assert_eq!(reader.get_with_seq(b"k").unwrap(), Some((Some(b"v".to_vec()), 0)));
+    assert_eq!(reader.max_seq(), 0);
+    assert_eq!(reader.value_location(b"k").unwrap(), Some((data_offset + 9, 1)));
+}
+
+#[test]
+fn test_open_rejects_empty_block_index() {
+    let (_temp, path) = setup_temp_sstable();
+
+    // Header, then a footer pointing at a zero-length index
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"ATKV");
+    bytes.extend_from_slice(&4u16.to_le_bytes());
+    bytes.extend_from_slice(&0u64.to_le_bytes());
+    let index_offset = bytes.len() as u64;
+    bytes.extend_from_slice(&index_offset.to_le_bytes());
+    bytes.extend_from_slice(&crc32fast::hash(&[]).to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    std::fs::write(&path, &bytes).unwrap();
+
+    let result = SSTableReader::open(&path);
+    assert!(matches!(result, Err(AtlasError::SSTableTruncated(_))));
+}
+
+#[test]
+fn test_open_version_3_file_without_timestamps() {
+    let (_temp, path) = setup_temp_sstable();
+
+    // One block holding "k" -> "v" at seq 5, with no Timestamp in the entry
+    let mut block = Vec::new();
+    block.extend_from_slice(&1u32.to_le_bytes());
+    block.extend_from_slice(&1u32.to_le_bytes());
+    block.extend_from_slice(&5u64.to_le_bytes());
+    block.extend_from_slice(b"kv");
+    block.extend_from_slice(&0u32.to_le_bytes());
+    block.extend_from_slice(&1u32.to_le_bytes());
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"ATKV");
+    bytes.extend_from_slice(&3u16.to_le_bytes());
+    bytes.extend_from_slice(&1u64.to_le_bytes());
+    let data_offset = bytes.len() as u64;
+    bytes.extend_from_slice(&block);
+    let index_offset = bytes.len() as u64;
+    bytes.extend_from_slice(&5u64.to_le_bytes());
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.extend_from_slice(b"k");
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.extend_from_slice(&data_offset.to_le_bytes());
+    bytes.extend_from_slice(&(block.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(b"k");
+    bytes.extend_from_slice(&index_offset.to_le_bytes());
+    bytes.extend_from_slice(&crc32fast::hash(&block).to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert_eq!(
+        reader.get_with_meta(b"k").unwrap(),
+        Some((Some(b"v".to_vec()), EntryMeta::at_seq(5)))
+    );
+    assert_eq!(reader.max_seq(), 5);
+}
+
+/// Rewrite a built SSTable as if a newer writer had put `extension` between
+/// its data blocks and index, stamping `minor` into the footer
+fn insert_extension_block(path: &Path, extension: &[u8], minor: u16) {
+    let mut bytes = std::fs::read(path).unwrap();
+    let footer_at = bytes.len() - 16;
+    let index_offset = u64::from_le_bytes(bytes[footer_at..footer_at + 8].try_into().unwrap());
+
+    let moved_offset = index_offset + extension.len() as u64;
+    bytes[footer_at..footer_at + 8].copy_from_slice(&moved_offset.to_le_bytes());
+    bytes[footer_at + 12..footer_at + 14].copy_from_slice(&minor.to_le_bytes());
+    bytes.splice(index_offset as usize..index_offset as usize, extension.iter().copied());
+    std::fs::write(path, &bytes).unwrap();
+}
+
+#[test]
+fn test_open_newer_minor_skips_extension_blocks() {
+    let (_temp, path) = setup_temp_sstable();
+    let mut builder = SSTableBuilder::new(&path).unwrap().with_block_size(256);
+    for i in 0..300u64 {
+        let key = format!("key{:05}", i);
+        let value = (i % 7 != 3).then(|| format!("value{}", i).into_bytes());
+        builder.add_entry(key.as_bytes(), value.as_deref(), EntryMeta::at_seq(i + 1)).unwrap();
+    }
+    builder.finish().unwrap();
+    let expected: Vec<_> =
+        SSTableReader::open(&path).unwrap().iter_with_seqs().unwrap().map(Result::unwrap).collect();
+
+    // An unknown block (say, a filter) written by minor version 1
+    insert_extension_block(&path, &[0xAB; 100], 1);
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert!(reader.block_count() > 1);
+    let entries: Vec<_> = reader.iter_with_seqs().unwrap().map(Result::unwrap).collect();
+    assert_eq!(entries, expected);
+    assert_eq!(reader.get(b"key00299").unwrap(), Some(b"value299".to_vec()));
+    assert_eq!(reader.max_seq(), 300);
+}
+
+#[test]
+fn test_open_rejects_gap_before_index_at_current_minor() {
+    let (_temp, path) = setup_temp_sstable();
+    create_sstable_with_entries(&path, 10);
+
+    // Without a newer minor, nothing may sit between the data and the index
+    insert_extension_block(&path, &[0xAB; 100], 0);
+
+    let result = SSTableReader::open(&path);
+    assert!(matches!(result, Err(AtlasError::SSTableTruncated(_))));
+}
+
+#[test]
+fn test_open_rejects_newer_major_version() {
+    let (_temp, path) = setup_temp_sstable();
+    create_sstable_with_entries(&path, 10);
+
+    let mut bytes = std::fs::read(&path).unwrap();
+    let major = u16::from_le_bytes(bytes[4..6].try_into().unwrap());
+    bytes[4..6].copy_from_slice(&(major + 1).to_le_bytes());
+    std::fs::write(&path, &bytes).unwrap();
+
+    let result = SSTableReader::open(&path);
+    assert!(matches!(result, Err(AtlasError::Storage(msg)) if msg.contains("version")));
+}
+
+#[test]
+fn test_open_rejects_unknown_footer_flags() {
+    let (_temp, path) = setup_temp_sstable();
+    create_sstable_with_entries(&path, 10);
+
+    let mut bytes = std::fs::read(&path).unwrap();
+    let flags_at = bytes.len() - 2;
+    bytes[flags_at..].copy_from_slice(&0x8000u16.to_le_bytes());
+    std::fs::write(&path, &bytes).unwrap();
+
+    let result = SSTableReader::open(&path);
+    assert!(matches!(result, Err(AtlasError::Storage(msg)) if msg.contains("flags")));
+}
+
+#[test]
+fn test_open_version_5_file_ignores_footer_flags() {
+    let (_temp, path) = setup_temp_sstable();
+    create_sstable_with_entries(&path, 10);
+
+    // Version 5 has the same layout as a version 6 file without value
+    // checksums, and its footer's last two bytes were reserved
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[4..6].copy_from_slice(&5u16.to_le_bytes());
+    let flags_at = bytes.len() - 2;
+    bytes[flags_at..].copy_from_slice(&0xFFFFu16.to_le_bytes());
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert!(!reader.has_value_checksums());
+    assert_eq!(reader.get(b"key00009").unwrap(), Some(b"value9".to_vec()));
+}
+
+// =============================================================================
+// Value Checksum Tests
+// =============================================================================
+
+#[test]
+fn test_value_checksums_round_trip() {
+    let (_temp, path) = setup_temp_sstable();
+    let mut builder = SSTableBuilder::new(&path).unwrap().with_value_checksums(true);
+    builder.add(b"a", b"alpha").unwrap();
+    builder.add_tombstone(b"b").unwrap();
+    builder.add(b"c", b"").unwrap();
+    let sstable = builder.finish().unwrap();
+
+    // 4 bytes per value, none for the tombstone
+    let (_temp_plain, plain_path) = setup_temp_sstable();
+    let mut builder = SSTableBuilder::new(&plain_path).unwrap();
+    builder.add(b"a", b"alpha").unwrap();
+    builder.add_tombstone(b"b").unwrap();
+    builder.add(b"c", b"").unwrap();
+    assert_eq!(sstable.file_size, builder.finish().unwrap().file_size + 8);
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert!(reader.has_value_checksums());
+    assert_eq!(reader.get(b"a").unwrap(), Some(b"alpha".to_vec()));
+    assert_eq!(reader.get(b"b").unwrap(), None);
+    assert_eq!(reader.get(b"c").unwrap(), Some(Vec::new()));
+    assert_eq!(reader.value_location(b"a").unwrap().map(|(_, len)| len), Some(5));
+    let entries: Vec<_> = reader.iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(
+        entries,
+        [
+            (b"a".to_vec(), Some(b"alpha".to_vec())),
+            (b"b".to_vec(), None),
+            (b"c".to_vec(), Some(Vec::new())),
+        ]
+    );
+}
+
+#[test]
+fn test_value_checksum_catches_flipped_value_byte() {
+    let (_temp, path) = setup_temp_sstable();
+    let mut builder = SSTableBuilder::new(&path).unwrap().with_value_checksums(true);
+    for i in 0..50 {
+        builder
+            .add(format!("key{:05}", i).as_bytes(), format!("value{:05}", i).as_bytes())
+            .unwrap();
+    }
+    builder.finish().unwrap();
+
+    // Flip one bit of a value in the middle of the (single) block
+    let reader = SSTableReader::open(&path).unwrap();
+    let (offset, _) = reader.value_location(b"key00020").unwrap().unwrap();
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[offset as usize + 3] ^= 0x01;
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert_eq!(reader.block_count(), 1);
+    let result = reader.get(b"key00020");
+    assert!(matches!(result, Err(AtlasError::Storage(msg)) if msg.contains("checksum")));
+
+    // Only that value is lost: its neighbours in the block still read
+    assert_eq!(reader.get(b"key00019").unwrap(), Some(b"value00019".to_vec()));
+    assert_eq!(reader.get(b"key00021").unwrap(), Some(b"value00021".to_vec()));
+
+    // Reading every value, as compaction does, trips over it too
+    let entries: Result<Vec<_>, _> = reader.iter_with_seqs().unwrap().collect();
+    assert!(matches!(entries, Err(AtlasError::Storage(_))));
+}
+
+#[test]
+fn test_flipped_value_byte_unnoticed_without_checksums() {
+    let (_temp, path) = setup_temp_sstable();
+    create_sstable_with_entries(&path, 50);
+
+    let reader = SSTableReader::open(&path).unwrap();
+    let (offset, _) = reader.value_location(b"key00020").unwrap().unwrap();
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[offset as usize] ^= 0x01;
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut reader = SSTableReader::open(&path).unwrap();
+    assert_eq!(reader.get(b"key00020").unwrap(), Some(b"walue20".to_vec()));
+}
+
+// =============================================================================
+// Index Rebuild Tests
+// =============================================================================
+
+#[test]
+fn test_rebuild_index_after_index_block_zeroed() {
+    let (_temp, path) = setup_temp_sstable();
+
+    // Several blocks, each with several restart points, plus tombstones
+    let mut builder = SSTableBuilder::new(&path).unwrap();
+    for i in 0..300u64 {
+        let key = format!("key{:05}", i);
+        let meta = EntryMeta { seq: i + 1, timestamp: 1_000 + i };
+        let value = (i % 7 != 3).then(|| format!("value{}", i).into_bytes());
+        builder.add_entry(key.as_bytes(), value.as_deref(), meta).unwrap();
+    }
+    builder.finish().unwrap();
+
+    let mut original = SSTableReader::open(&path).unwrap();
+    let expected: Vec<_> = original.iter_with_seqs().unwrap().map(Result::unwrap).collect();
+    assert!(original.block_count() > 1);
+
+    // Zero the whole index block, between the data and the footer
+    let mut bytes = std::fs::read(&path).unwrap();
+    let footer_at = bytes.len() - 16;
+    let index_offset = u64::from_le_bytes(bytes[footer_at..footer_at + 8].try_into().unwrap());
+    bytes[index_offset as usize..footer_at].fill(0);
+    std::fs::write(&path, &bytes).unwrap();
+    assert!(SSTableReader::open(&path).is_err());
+
+    let mut rebuilt = SSTableReader::rebuild_index(&path).unwrap();
+    assert_eq!(rebuilt.block_count(), original.block_count());
+    assert_eq!(rebuilt.entry_count(), 300);
+    assert_eq!(rebuilt.max_seq(), 300);
+    assert_eq!(rebuilt.min_key(), Some(&b"key00000"[..]));
+    assert_eq!(rebuilt.max_key(), Some(&b"key00299"[..]));
+
+    let recovered: Vec<_> = rebuilt.iter_with_seqs().unwrap().map(Result::unwrap).collect();
+    assert_eq!(recovered, expected);
+    assert_eq!(rebuilt.get(b"key00151").unwrap(), Some(b"value151".to_vec()));
+    assert_eq!(rebuilt.get(b"key00003").unwrap(), None);
+}
+
+#[test]
+fn test_rebuild_index_without_footer_keeps_whole_blocks() {
+    let (_temp, path) = setup_temp_sstable();
+    create_blocked_sstable(&path, 20);
+
+    // Cut the file 100 bytes into the fourth block (blocks are 160 bytes):
+    // no footer, so the guess drops 16 more bytes, still inside block 4
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..14 + 3 * 160 + 100]).unwrap();
+
+    let mut rebuilt = SSTableReader::rebuild_index(&path).unwrap();
+    assert_eq!(rebuilt.block_count(), 3);
+    let keys: Vec<_> = rebuilt.iter().unwrap().map(|entry| entry.unwrap().0).collect();
+    assert_eq!(keys, (0..12).map(|i| format!("key{:05}", i).into_bytes()).collect::<Vec<_>>());
+    assert!(matches!(rebuilt.get(b"key00012"), Err(AtlasError::KeyNotFound)));
+}
@@ -1,13 +1,24 @@
 //! Tests for WAL Entry serialization and deserialization
 //!
 //! These tests verify:
-//! - Round-trip serialization for all operation types
+//! - Round-trip serialization for all operation types (batch markers and
+//!   checkpoints included)
 //! - CRC32 corruption detection
 //! - Edge cases (truncation, malformed data, large values)
-
-use atlaskv::wal::{Operation, WalEntry, HEADER_SIZE};
+//! - Explicit on-disk data format (version, op type, timestamp,
+//!   length-prefixed fields)
+//! - Version 1 (bincode) entries still decoding, even when their LSN starts
+//!   with the current version byte
+
+use atlaskv::wal::{
+    Operation, WalEntry, HEADER_SIZE, OP_BATCH_BEGIN, OP_BATCH_COMMIT, OP_CHECKPOINT, OP_DELETE,
+    OP_MERGE, OP_PUT, WAL_FORMAT_VERSION,
+};
 use atlaskv::AtlasError;
 
+/// The version byte leading every data section
+const VERSION: u8 = WAL_FORMAT_VERSION as u8;
+
 // =============================================================================
 // Serialization Round-Trip Tests
 // =============================================================================
@@ -40,6 +51,50 @@ fn test_serialize_deserialize_delete() {
     assert_eq!(entry, recovered);
 }
 
+#[test]
+fn test_serialize_deserialize_merge() {
+    let entry = WalEntry::new(
+        7,
+        Operation::Merge {
+            key: b"counter".to_vec(),
+            operand: 1i64.to_le_bytes().to_vec(),
+        },
+    );
+
+    let bytes = entry.serialize().unwrap();
+    assert_eq!(bytes.len(), entry.serialized_size().unwrap());
+    assert_eq!(bytes[HEADER_SIZE + 1], OP_MERGE);
+
+    let recovered = WalEntry::deserialize(&bytes).unwrap();
+    assert_eq!(entry, recovered);
+}
+
+#[test]
+fn test_serialize_deserialize_batch_markers() {
+    let begin = WalEntry::new(8, Operation::BatchBegin { count: 3 });
+    let bytes = begin.serialize().unwrap();
+    assert_eq!(bytes.len(), begin.serialized_size().unwrap());
+    assert_eq!(bytes[HEADER_SIZE + 1], OP_BATCH_BEGIN);
+    assert_eq!(&bytes[HEADER_SIZE + 10..], &3u32.to_le_bytes());
+    assert_eq!(WalEntry::deserialize(&bytes).unwrap(), begin);
+
+    let commit = WalEntry::new(12, Operation::BatchCommit);
+    let bytes = commit.serialize().unwrap();
+    assert_eq!(bytes.len(), HEADER_SIZE + 10);
+    assert_eq!(bytes[HEADER_SIZE + 1], OP_BATCH_COMMIT);
+    assert_eq!(WalEntry::deserialize(&bytes).unwrap(), commit);
+}
+
+#[test]
+fn test_serialize_deserialize_checkpoint() {
+    let entry = WalEntry::new(42, Operation::Checkpoint { flushed_up_to_lsn: 42 });
+    let bytes = entry.serialize().unwrap();
+    assert_eq!(bytes.len(), entry.serialized_size().unwrap());
+    assert_eq!(bytes[HEADER_SIZE + 1], OP_CHECKPOINT);
+    assert_eq!(&bytes[HEADER_SIZE + 10..], &42u64.to_le_bytes());
+    assert_eq!(WalEntry::deserialize(&bytes).unwrap(), entry);
+}
+
 #[test]
 fn test_serialize_deserialize_empty_key() {
     let entry = WalEntry::new(
@@ -222,3 +277,179 @@ fn test_compute_crc_consistency() {
 
     assert_eq!(crc1, crc2);
 }
+
+// =============================================================================
+// Explicit Format Tests
+// =============================================================================
+
+/// Frame a hand-built data section with LSN + CRC + Len header
+fn frame(lsn: u64, data: &[u8]) -> Vec<u8> {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&lsn.to_le_bytes());
+    hasher.update(&(data.len() as u32).to_le_bytes());
+    hasher.update(data);
+    let crc = hasher.finalize();
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&lsn.to_le_bytes());
+    bytes.extend_from_slice(&crc.to_le_bytes());
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+#[test]
+fn test_deserialize_hand_crafted_put() {
+    let mut data = vec![VERSION, OP_PUT];
+    data.extend_from_slice(&1_700_000_000_000u64.to_le_bytes()); // timestamp
+    data.extend_from_slice(&3u32.to_le_bytes());
+    data.extend_from_slice(b"key");
+    data.extend_from_slice(&5u32.to_le_bytes());
+    data.extend_from_slice(b"value");
+
+    let entry = WalEntry::deserialize(&frame(7, &data)).unwrap();
+
+    assert_eq!(entry.lsn, 7);
+    assert_eq!(entry.timestamp, 1_700_000_000_000);
+    assert_eq!(
+        entry.operation,
+        Operation::Put {
+            key: b"key".to_vec(),
+            value: b"value".to_vec(),
+        }
+    );
+}
+
+#[test]
+fn test_deserialize_hand_crafted_delete() {
+    let mut data = vec![VERSION, OP_DELETE];
+    data.extend_from_slice(&42u64.to_le_bytes());
+    data.extend_from_slice(&2u32.to_le_bytes());
+    data.extend_from_slice(b"gone");
+
+    // Key length says 2, but 4 key bytes follow: trailing data is rejected
+    let result = WalEntry::deserialize(&frame(1, &data));
+    assert!(matches!(result, Err(AtlasError::WalCorruption(_))));
+
+    let mut data = vec![VERSION, OP_DELETE];
+    data.extend_from_slice(&42u64.to_le_bytes());
+    data.extend_from_slice(&4u32.to_le_bytes());
+    data.extend_from_slice(b"gone");
+
+    let entry = WalEntry::deserialize(&frame(1, &data)).unwrap();
+    assert_eq!(entry.timestamp, 42);
+    assert_eq!(entry.operation, Operation::Delete { key: b"gone".to_vec() });
+}
+
+#[test]
+fn test_serialize_matches_documented_layout() {
+    let entry = WalEntry {
+        lsn: 9,
+        operation: Operation::Put {
+            key: b"k".to_vec(),
+            value: b"vv".to_vec(),
+        },
+        timestamp: 0x0102030405060708,
+    };
+
+    let bytes = entry.serialize().unwrap();
+    let data = &bytes[HEADER_SIZE..];
+
+    assert_eq!(data[0], VERSION);
+    assert_eq!(data[1], OP_PUT);
+    assert_eq!(&data[2..10], &0x0102030405060708u64.to_le_bytes());
+    assert_eq!(&data[10..14], &1u32.to_le_bytes());
+    assert_eq!(&data[14..15], b"k");
+    assert_eq!(&data[15..19], &2u32.to_le_bytes());
+    assert_eq!(&data[19..21], b"vv");
+    assert_eq!(data.len(), 21);
+}
+
+#[test]
+fn test_unknown_op_type_rejected() {
+    let mut data = vec![VERSION, 0x7F];
+    data.extend_from_slice(&0u64.to_le_bytes());
+
+    let result = WalEntry::deserialize(&frame(1, &data));
+    assert!(matches!(result, Err(AtlasError::WalCorruption(_))));
+}
+
+#[test]
+fn test_unknown_version_rejected() {
+    let mut data = vec![VERSION + 1, OP_DELETE];
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(b"k");
+
+    let result = WalEntry::deserialize(&frame(1, &data));
+    assert!(matches!(result, Err(AtlasError::WalCorruption(_))));
+}
+
+#[test]
+fn test_field_length_past_end_rejected() {
+    let mut data = vec![VERSION, OP_PUT];
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.extend_from_slice(&1000u32.to_le_bytes()); // claims 1000-byte key
+    data.extend_from_slice(b"short");
+
+    let result = WalEntry::deserialize(&frame(1, &data));
+    assert!(matches!(result, Err(AtlasError::WalCorruption(_))));
+}
+
+// =============================================================================
+// Version 1 (bincode) Entries
+// =============================================================================
+
+/// Data section as version 1 wrote it: bincode's default encoding of
+/// `WalEntry` (LSN, u32 variant, u64-length-prefixed fields, timestamp)
+fn legacy_data(lsn: u64, variant: u32, fields: &[&[u8]], timestamp: u64) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&lsn.to_le_bytes());
+    data.extend_from_slice(&variant.to_le_bytes());
+    for field in fields {
+        data.extend_from_slice(&(field.len() as u64).to_le_bytes());
+        data.extend_from_slice(field);
+    }
+    data.extend_from_slice(&timestamp.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_deserialize_version_1_entries() {
+    let put = legacy_data(7, 0, &[b"key", b"value"], 1_700_000_000_000);
+    let entry = WalEntry::deserialize(&frame(7, &put)).unwrap();
+    assert_eq!(entry.lsn, 7);
+    assert_eq!(entry.timestamp, 1_700_000_000_000);
+    assert_eq!(
+        entry.operation,
+        Operation::Put {
+            key: b"key".to_vec(),
+            value: b"value".to_vec(),
+        }
+    );
+
+    let delete = legacy_data(8, 1, &[b"key"], 42);
+    let entry = WalEntry::deserialize(&frame(8, &delete)).unwrap();
+    assert_eq!(entry.timestamp, 42);
+    assert_eq!(entry.operation, Operation::Delete { key: b"key".to_vec() });
+}
+
+#[test]
+fn test_deserialize_version_1_entry_starting_with_version_byte() {
+    // The LSN's low byte is the current version byte, so the data section
+    // opens like a current one; it must still decode as version 1
+    let lsn = VERSION as u64 + 256;
+    let data = legacy_data(lsn, 1, &[b"key"], 42);
+    assert_eq!(data[0], VERSION);
+
+    let entry = WalEntry::deserialize(&frame(lsn, &data)).unwrap();
+    assert_eq!(entry.operation, Operation::Delete { key: b"key".to_vec() });
+}
+
+#[test]
+fn test_version_1_entry_with_mismatched_lsn_rejected() {
+    let data = legacy_data(7, 1, &[b"key"], 42);
+
+    let result = WalEntry::deserialize(&frame(8, &data));
+    assert!(matches!(result, Err(AtlasError::WalCorruption(_))));
+}
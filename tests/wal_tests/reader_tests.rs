@@ -5,11 +5,13 @@
 //! - Iterator functionality
 //! - Partial write handling
 //! - Empty file handling
+//! - Segmented logs (offsets running on across files, zero tails skipped)
 
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
-use atlaskv::wal::{Operation, WalEntry, WalReader};
+use atlaskv::config::WalSyncStrategy;
+use atlaskv::wal::{Operation, WalEntry, WalReader, WalWriter};
 use tempfile::TempDir;
 
 // =============================================================================
@@ -58,7 +60,7 @@ fn test_read_single_entry() {
         },
     );
     
-    write_entries_to_wal(&wal_path, &[original.clone()]);
+    write_entries_to_wal(&wal_path, std::slice::from_ref(&original));
 
     let mut reader = WalReader::open(&wal_path).unwrap();
     let entry = reader.next_entry().unwrap().unwrap();
@@ -207,6 +209,74 @@ fn test_partial_data() {
     assert!(reader.next_entry().unwrap().is_none());
 }
 
+// =============================================================================
+// Offset Tests
+// =============================================================================
+
+#[test]
+fn test_offsets_match_hand_constructed_file() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    // PUT k=v: 16-byte header + version(1) + op(1) + timestamp(8) + 4+1 key + 4+1 value
+    // = 36 bytes
+    let first = WalEntry::new(1, Operation::Put { key: b"k".to_vec(), value: b"v".to_vec() });
+    let second = WalEntry::new(2, Operation::Put { key: b"j".to_vec(), value: b"w".to_vec() });
+    assert_eq!(first.serialize().unwrap().len(), 36);
+
+    let mut file = File::create(&wal_path).unwrap();
+    file.write_all(&first.serialize().unwrap()).unwrap();
+    file.write_all(&second.serialize().unwrap()).unwrap();
+    file.write_all(&[0u8; 5]).unwrap(); // torn header at offset 72
+    file.sync_all().unwrap();
+
+    let mut reader = WalReader::open(&wal_path).unwrap();
+    assert_eq!(reader.file_size(), 77);
+    assert_eq!(reader.position(), 0);
+
+    reader.next_entry().unwrap().unwrap();
+    assert_eq!(reader.last_entry_offset(), 0);
+    assert_eq!(reader.position(), 36);
+
+    reader.next_entry().unwrap().unwrap();
+    assert_eq!(reader.last_entry_offset(), 36);
+    assert_eq!(reader.position(), 72);
+
+    // Torn tail: nothing consumed, position marks where it starts
+    assert!(reader.next_entry().unwrap().is_none());
+    assert_eq!(reader.position(), 72);
+    assert_eq!(reader.last_entry_offset(), 36);
+    assert!(!reader.is_at_eof());
+}
+
+#[test]
+fn test_offsets_run_on_across_preallocated_segments() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    // Each 36-byte entry gets its own segment, zero-filled to 64 bytes
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::EveryWrite)
+        .unwrap()
+        .with_preallocation(64)
+        .unwrap()
+        .with_segment_bytes(40);
+    for key in [b"a", b"b", b"c"] {
+        writer.append(Operation::Put { key: key.to_vec(), value: b"v".to_vec() }).unwrap();
+    }
+    assert_eq!(writer.segments().len(), 3);
+    drop(writer);
+
+    let mut reader = WalReader::open(&wal_path).unwrap();
+    assert_eq!(reader.file_size(), 3 * 64);
+    for (lsn, offset) in [(1, 0), (2, 64), (3, 128)] {
+        assert_eq!(reader.next_entry().unwrap().unwrap().lsn, lsn);
+        assert_eq!(reader.last_entry_offset(), offset);
+    }
+
+    // The last segment's zero tail ends the log
+    assert!(reader.next_entry().unwrap().is_none());
+    assert_eq!(reader.position(), 128 + 36);
+    assert!(reader.is_at_eof());
+}
+
 // =============================================================================
 // Edge Cases
 // =============================================================================
@@ -221,7 +291,7 @@ fn test_large_entry() {
         value: large_value.clone(),
     });
     
-    write_entries_to_wal(&wal_path, &[entry.clone()]);
+    write_entries_to_wal(&wal_path, std::slice::from_ref(&entry));
 
     let mut reader = WalReader::open(&wal_path).unwrap();
     let read_entry = reader.next_entry().unwrap().unwrap();
@@ -238,7 +308,7 @@ fn test_delete_operation() {
     let (_temp, wal_path) = setup_temp_wal();
     
     let entry = WalEntry::new(5, Operation::Delete { key: b"deleted_key".to_vec() });
-    write_entries_to_wal(&wal_path, &[entry.clone()]);
+    write_entries_to_wal(&wal_path, std::slice::from_ref(&entry));
 
     let mut reader = WalReader::open(&wal_path).unwrap();
     let read_entry = reader.next_entry().unwrap().unwrap();
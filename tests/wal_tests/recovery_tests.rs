@@ -3,13 +3,20 @@
 //! These tests verify:
 //! - Recovery from a clean WAL (no corruption)
 //! - Recovery from an empty WAL
+//! - Recovery from a preallocated WAL (stops at the zero tail)
 //! - Recovery with partial writes (truncated tail)
 //! - Recovery with corrupted entries (CRC mismatch)
+//! - Reporting the first corrupt entry's LSN and offset
 //! - Verify mode (stats only, no entries returned)
+//! - Callback-based recovery (`recover_with`)
+//! - Atomic batches (uncommitted batches dropped whole)
+//! - Checkpoints (only entries after the latest one are replayed)
+//! - Logs written as version 1 (bincode) replaying in full
+//! - Logs split into segments (read in order, a batch spanning two of them)
 
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use atlaskv::wal::{Operation, WalEntry, WalWriter, WalRecovery};
 use atlaskv::config::WalSyncStrategy;
 use tempfile::TempDir;
@@ -25,7 +32,7 @@ fn setup_temp_wal() -> (TempDir, PathBuf) {
 }
 
 /// Write entries using WalWriter (produces a well-formed WAL)
-fn write_entries_via_writer(path: &PathBuf, count: usize) {
+fn write_entries_via_writer(path: &Path, count: usize) {
     let mut writer = WalWriter::open(path, WalSyncStrategy::EveryWrite).unwrap();
     for i in 0..count {
         writer.append(Operation::Put {
@@ -48,6 +55,7 @@ fn test_recover_empty_file() {
     assert_eq!(result.entries_corrupted, 0);
     assert_eq!(result.last_lsn, 0);
     assert!(!result.was_truncated);
+    assert_eq!(result.truncate_offset, None);
 }
 
 #[test]
@@ -102,6 +110,61 @@ fn test_recover_preserves_operations() {
     assert!(matches!(entries[2].operation, Operation::Put { .. }));
 }
 
+#[test]
+fn test_recover_stops_at_preallocated_zero_tail() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::EveryWrite)
+        .unwrap()
+        .with_preallocation(64 * 1024)
+        .unwrap();
+    for i in 0..10u8 {
+        writer.append(Operation::Put { key: vec![i], value: vec![i; 100] }).unwrap();
+    }
+    assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 64 * 1024);
+    assert!(writer.size_bytes() < 64 * 1024);
+
+    // The zeros past the last entry are neither entries nor corruption
+    let (entries, result) = WalRecovery::recover(&wal_path).unwrap();
+    assert_eq!(entries.len(), 10);
+    assert_eq!(result.last_lsn, 10);
+    assert_eq!(result.entries_corrupted, 0);
+    assert!(!result.was_truncated);
+    assert_eq!(result.first_corrupt_offset, None);
+
+    // Truncation zero-fills it again; new entries start at the front
+    writer.reset(11).unwrap();
+    assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 64 * 1024);
+    writer.append(Operation::Delete { key: vec![0] }).unwrap();
+
+    let (entries, result) = WalRecovery::recover(&wal_path).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].lsn, 11);
+    assert!(!result.was_truncated);
+}
+
+#[test]
+fn test_recover_outgrown_preallocation() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::EveryWrite)
+        .unwrap()
+        .with_preallocation(1024)
+        .unwrap();
+    for i in 0..20u8 {
+        writer.append(Operation::Put { key: vec![i], value: vec![i; 100] }).unwrap();
+    }
+
+    // Past the preallocated size the file just grows
+    let len = std::fs::metadata(&wal_path).unwrap().len();
+    assert_eq!(len, writer.size_bytes());
+    assert!(len > 1024);
+
+    let (entries, result) = WalRecovery::recover(&wal_path).unwrap();
+    assert_eq!(entries.len(), 20);
+    assert!(!result.was_truncated);
+}
+
 // =============================================================================
 // Recover: Partial Write Tests (was_truncated = true)
 // =============================================================================
@@ -124,8 +187,9 @@ fn test_recover_partial_header_at_tail() {
     assert_eq!(entries.len(), 1);
     assert_eq!(result.entries_recovered, 1);
     assert_eq!(result.last_lsn, 1);
-    // Trailing garbage means truncation
+    // Trailing garbage means truncation, right after the good entry
     assert!(result.was_truncated);
+    assert_eq!(result.truncate_offset, Some(bytes.len() as u64));
 }
 
 #[test]
@@ -151,6 +215,7 @@ fn test_recover_partial_data_at_tail() {
     assert_eq!(entries.len(), 1);
     assert_eq!(result.entries_recovered, 1);
     assert!(result.was_truncated);
+    assert_eq!(result.truncate_offset, Some(good_bytes.len() as u64));
 }
 
 // =============================================================================
@@ -185,6 +250,7 @@ fn test_recover_corrupted_entry() {
     assert_eq!(result.entries_corrupted, 1);
     assert_eq!(result.last_lsn, 1);
     assert!(result.was_truncated);
+    assert_eq!(result.truncate_offset, Some(good_bytes.len() as u64));
 }
 
 #[test]
@@ -209,6 +275,101 @@ fn test_recover_corruption_at_first_entry() {
     assert_eq!(result.entries_corrupted, 1);
     assert_eq!(result.last_lsn, 0);
     assert!(result.was_truncated);
+    assert_eq!(result.truncate_offset, Some(0));
+}
+
+// =============================================================================
+// Recover: Corrupt Entry Location Tests
+// =============================================================================
+
+#[test]
+fn test_recover_reports_corrupt_lsn_and_offset() {
+    let (_temp, wal_path) = setup_temp_wal();
+    write_entries_via_writer(&wal_path, 3);
+    let corrupt_at = std::fs::metadata(&wal_path).unwrap().len();
+
+    // Entry 4 is corrupt, entry 5 after it is fine but unreachable
+    let mut bad = WalEntry::new(4, Operation::Delete { key: b"k".to_vec() }).serialize().unwrap();
+    *bad.last_mut().unwrap() ^= 0xFF;
+    let good = WalEntry::new(5, Operation::Delete { key: b"k".to_vec() }).serialize().unwrap();
+
+    let mut file = std::fs::OpenOptions::new().append(true).open(&wal_path).unwrap();
+    file.write_all(&bad).unwrap();
+    file.write_all(&good).unwrap();
+    file.sync_all().unwrap();
+
+    let (_, result) = WalRecovery::recover(&wal_path).unwrap();
+    assert_eq!(result.first_corrupt_lsn, Some(4));
+    assert_eq!(result.first_corrupt_offset, Some(corrupt_at));
+
+    // verify() reports the same location
+    let verified = WalRecovery::verify(&wal_path).unwrap();
+    assert_eq!(verified.first_corrupt_lsn, Some(4));
+    assert_eq!(verified.first_corrupt_offset, Some(corrupt_at));
+}
+
+#[test]
+fn test_recover_reports_partial_write_location() {
+    let (_temp, wal_path) = setup_temp_wal();
+    write_entries_via_writer(&wal_path, 2);
+    let partial_at = std::fs::metadata(&wal_path).unwrap().len();
+
+    // Full header, but the data section is cut short
+    let bytes = WalEntry::new(3, Operation::Delete { key: b"key".to_vec() }).serialize().unwrap();
+    let mut file = std::fs::OpenOptions::new().append(true).open(&wal_path).unwrap();
+    file.write_all(&bytes[..bytes.len() - 2]).unwrap();
+    file.sync_all().unwrap();
+
+    let result = WalRecovery::verify(&wal_path).unwrap();
+    assert_eq!(result.first_corrupt_lsn, Some(3));
+    assert_eq!(result.first_corrupt_offset, Some(partial_at));
+
+    // A cut-short header has no LSN to report, only the offset
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&wal_path)
+        .unwrap()
+        .set_len(partial_at + 4)
+        .unwrap();
+
+    let result = WalRecovery::verify(&wal_path).unwrap();
+    assert_eq!(result.first_corrupt_lsn, None);
+    assert_eq!(result.first_corrupt_offset, Some(partial_at));
+}
+
+#[test]
+fn test_recover_corruption_inside_batch_keeps_both_offsets() {
+    let (_temp, wal_path) = setup_temp_wal();
+    write_entries_via_writer(&wal_path, 1);
+    let batch_start = std::fs::metadata(&wal_path).unwrap().len();
+
+    let mut bytes = WalEntry::new(2, Operation::BatchBegin { count: 3 }).serialize().unwrap();
+    let ops = batch_ops("bad");
+    bytes.extend(WalEntry::new(3, ops[0].clone()).serialize().unwrap());
+    let corrupt_at = batch_start + bytes.len() as u64;
+    let mut bad = WalEntry::new(4, ops[1].clone()).serialize().unwrap();
+    *bad.last_mut().unwrap() ^= 0xFF;
+    bytes.extend(bad);
+
+    let mut file = std::fs::OpenOptions::new().append(true).open(&wal_path).unwrap();
+    file.write_all(&bytes).unwrap();
+    file.sync_all().unwrap();
+
+    // The cut goes back to the batch, the report points at the bad entry
+    let result = WalRecovery::verify(&wal_path).unwrap();
+    assert_eq!(result.truncate_offset, Some(batch_start));
+    assert_eq!(result.first_corrupt_lsn, Some(4));
+    assert_eq!(result.first_corrupt_offset, Some(corrupt_at));
+}
+
+#[test]
+fn test_recover_clean_wal_reports_no_corruption() {
+    let (_temp, wal_path) = setup_temp_wal();
+    write_entries_via_writer(&wal_path, 3);
+
+    let result = WalRecovery::verify(&wal_path).unwrap();
+    assert_eq!(result.first_corrupt_lsn, None);
+    assert_eq!(result.first_corrupt_offset, None);
 }
 
 // =============================================================================
@@ -302,3 +463,293 @@ fn test_recover_and_verify_agree() {
     assert_eq!(recover_result.last_lsn, verify_result.last_lsn);
     assert_eq!(recover_result.was_truncated, verify_result.was_truncated);
 }
+
+// =============================================================================
+// Recover-With Callback Tests
+// =============================================================================
+
+#[test]
+fn test_recover_with_visits_entries_in_order() {
+    let (_temp, wal_path) = setup_temp_wal();
+    write_entries_via_writer(&wal_path, 10);
+
+    let mut keys = Vec::new();
+    let mut lsns = Vec::new();
+    let result = WalRecovery::recover_with(&wal_path, |entry| {
+        lsns.push(entry.lsn);
+        if let Operation::Put { key, .. } = &entry.operation {
+            keys.push(key.clone());
+        }
+    })
+    .unwrap();
+
+    let (entries, recover_result) = WalRecovery::recover(&wal_path).unwrap();
+    assert_eq!(lsns, entries.iter().map(|e| e.lsn).collect::<Vec<_>>());
+    assert_eq!(keys[0], b"key0");
+    assert_eq!(keys[9], b"key9");
+    assert_eq!(result.entries_recovered, 10);
+    assert_eq!(result.last_lsn, recover_result.last_lsn);
+}
+
+#[test]
+fn test_recover_with_stops_at_partial_write() {
+    let (_temp, wal_path) = setup_temp_wal();
+    write_entries_via_writer(&wal_path, 3);
+
+    // Append a torn entry
+    let entry = WalEntry::new(99, Operation::Delete { key: b"torn".to_vec() });
+    let mut bytes = entry.serialize().unwrap();
+    bytes.truncate(bytes.len() - 1);
+    let mut file = std::fs::OpenOptions::new().append(true).open(&wal_path).unwrap();
+    file.write_all(&bytes).unwrap();
+    file.sync_all().unwrap();
+
+    let mut seen = 0;
+    let result = WalRecovery::recover_with(&wal_path, |_| seen += 1).unwrap();
+
+    assert_eq!(seen, 3);
+    assert!(result.was_truncated);
+}
+
+// =============================================================================
+// Batch Tests
+// =============================================================================
+
+fn batch_ops(prefix: &str) -> Vec<Operation> {
+    (0..3)
+        .map(|i| Operation::Put {
+            key: format!("{}{}", prefix, i).into_bytes(),
+            value: b"value".to_vec(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_recover_committed_batch() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::EveryNEntries { count: 100 })
+        .unwrap();
+    writer.append(Operation::Delete { key: b"solo".to_vec() }).unwrap();
+    let commit_lsn = writer.append_batch(&batch_ops("batch")).unwrap();
+    assert_eq!(commit_lsn, 6); // solo + begin + 3 ops + commit
+    drop(writer);
+
+    let (entries, result) = WalRecovery::recover(&wal_path).unwrap();
+
+    // Markers are consumed; the batch's operations come back in order
+    let mut expected = vec![Operation::Delete { key: b"solo".to_vec() }];
+    expected.extend(batch_ops("batch"));
+    let ops: Vec<Operation> = entries.into_iter().map(|e| e.operation).collect();
+    assert_eq!(ops, expected);
+    assert_eq!(result.entries_recovered, 4);
+    assert_eq!(result.last_lsn, commit_lsn);
+    assert!(!result.was_truncated);
+}
+
+#[test]
+fn test_recover_drops_batch_without_commit() {
+    let (_temp, wal_path) = setup_temp_wal();
+    write_entries_via_writer(&wal_path, 2);
+    let batch_start = std::fs::metadata(&wal_path).unwrap().len();
+
+    // Crash mid-batch: begin marker and operations, no commit marker
+    let mut bytes = WalEntry::new(3, Operation::BatchBegin { count: 3 }).serialize().unwrap();
+    for (i, op) in batch_ops("lost").into_iter().enumerate() {
+        bytes.extend(WalEntry::new(4 + i as u64, op).serialize().unwrap());
+    }
+    let mut file = std::fs::OpenOptions::new().append(true).open(&wal_path).unwrap();
+    file.write_all(&bytes).unwrap();
+    file.sync_all().unwrap();
+
+    let (entries, result) = WalRecovery::recover(&wal_path).unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(result.last_lsn, 2);
+    assert!(result.was_truncated);
+    assert_eq!(result.truncate_offset, Some(batch_start));
+}
+
+#[test]
+fn test_recover_stops_at_stray_commit() {
+    let (_temp, wal_path) = setup_temp_wal();
+    write_entries_via_writer(&wal_path, 1);
+    let stray_at = std::fs::metadata(&wal_path).unwrap().len();
+
+    let bytes = WalEntry::new(2, Operation::BatchCommit).serialize().unwrap();
+    let mut file = std::fs::OpenOptions::new().append(true).open(&wal_path).unwrap();
+    file.write_all(&bytes).unwrap();
+    file.sync_all().unwrap();
+
+    let result = WalRecovery::verify(&wal_path).unwrap();
+
+    assert_eq!(result.entries_recovered, 1);
+    assert_eq!(result.entries_corrupted, 1);
+    assert_eq!(result.truncate_offset, Some(stray_at));
+}
+
+#[test]
+fn test_recover_across_segments() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    // Four 45-byte PUTs fit in a segment
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::EveryWrite)
+        .unwrap()
+        .with_segment_bytes(200);
+    for i in 0..10 {
+        writer.append(Operation::Put {
+            key: format!("key{}", i).into_bytes(),
+            value: format!("value{}", i).into_bytes(),
+        }).unwrap();
+    }
+    let batch_start = writer.size_bytes();
+    writer.append_batch(&batch_ops("batch")).unwrap();
+    let segments = writer.segments();
+    drop(writer);
+
+    // The batch starts in the third segment and ends in the fourth
+    assert_eq!(segments.len(), 4);
+    assert_eq!(segments[2].last_lsn, Some(12));
+    assert_eq!(segments[3].first_lsn, Some(13));
+
+    let (entries, result) = WalRecovery::recover(&wal_path).unwrap();
+    assert_eq!(entries.len(), 13);
+    assert_eq!(result.last_lsn, 15);
+    assert!(!result.was_truncated);
+
+    // Tear the commit marker: the whole batch goes, from where it began
+    let last = &segments[3].path;
+    let len = std::fs::metadata(last).unwrap().len();
+    std::fs::OpenOptions::new().write(true).open(last).unwrap().set_len(len - 3).unwrap();
+
+    let (entries, result) = WalRecovery::recover(&wal_path).unwrap();
+    assert_eq!(entries.len(), 10);
+    assert_eq!(result.last_lsn, 10);
+    assert_eq!(result.truncate_offset, Some(batch_start));
+}
+
+// =============================================================================
+// Checkpoint Tests
+// =============================================================================
+
+#[test]
+fn test_recover_replays_only_past_latest_checkpoint() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::EveryWrite).unwrap();
+    for ops in [batch_ops("first"), batch_ops("second")] {
+        for op in ops {
+            writer.append(op).unwrap();
+        }
+        assert!(writer.bytes_since_checkpoint() > 0);
+        writer.checkpoint().unwrap();
+        assert_eq!(writer.bytes_since_checkpoint(), 0);
+    }
+    writer.append(Operation::Delete { key: b"after".to_vec() }).unwrap();
+    drop(writer);
+
+    let (entries, result) = WalRecovery::recover(&wal_path).unwrap();
+
+    // Checkpoints take no LSN of their own: 6 puts, then the delete
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].lsn, 7);
+    assert_eq!(entries[0].operation, Operation::Delete { key: b"after".to_vec() });
+    assert_eq!(result.entries_recovered, 1);
+    assert_eq!(result.checkpoint_lsn, Some(6));
+    assert_eq!(result.last_lsn, 7);
+    assert!(!result.was_truncated);
+}
+
+#[test]
+fn test_recover_with_passes_checkpoints() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::EveryWrite).unwrap();
+    writer.append(Operation::Delete { key: b"before".to_vec() }).unwrap();
+    assert_eq!(writer.checkpoint().unwrap(), 1);
+    drop(writer);
+
+    let mut seen = Vec::new();
+    let result = WalRecovery::recover_with(&wal_path, |entry| seen.push(entry.operation.clone()))
+        .unwrap();
+
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[1], Operation::Checkpoint { flushed_up_to_lsn: 1 });
+    assert_eq!(result.entries_recovered, 0);
+    assert_eq!(result.checkpoint_lsn, Some(1));
+}
+
+#[test]
+fn test_recover_stops_at_checkpoint_inside_batch() {
+    let (_temp, wal_path) = setup_temp_wal();
+    write_entries_via_writer(&wal_path, 1);
+    let batch_start = std::fs::metadata(&wal_path).unwrap().len();
+
+    let mut bytes = WalEntry::new(2, Operation::BatchBegin { count: 1 }).serialize().unwrap();
+    bytes.extend(
+        WalEntry::new(2, Operation::Checkpoint { flushed_up_to_lsn: 2 }).serialize().unwrap(),
+    );
+    let mut file = std::fs::OpenOptions::new().append(true).open(&wal_path).unwrap();
+    file.write_all(&bytes).unwrap();
+    file.sync_all().unwrap();
+
+    let result = WalRecovery::verify(&wal_path).unwrap();
+
+    assert_eq!(result.entries_recovered, 1);
+    assert_eq!(result.entries_corrupted, 1);
+    assert_eq!(result.checkpoint_lsn, None);
+    assert_eq!(result.truncate_offset, Some(batch_start));
+}
+
+// =============================================================================
+// Version 1 (bincode) WAL Tests
+// =============================================================================
+
+/// A PUT entry as version 1 logged it: the usual header around bincode's
+/// encoding of `WalEntry`
+fn legacy_put(lsn: u64, key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&lsn.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes()); // Operation::Put
+    for field in [key, value] {
+        data.extend_from_slice(&(field.len() as u64).to_le_bytes());
+        data.extend_from_slice(field);
+    }
+    data.extend_from_slice(&1_700_000_000_000u64.to_le_bytes());
+
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&lsn.to_le_bytes());
+    hasher.update(&(data.len() as u32).to_le_bytes());
+    hasher.update(&data);
+
+    let mut bytes = lsn.to_le_bytes().to_vec();
+    bytes.extend_from_slice(&hasher.finalize().to_le_bytes());
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&data);
+    bytes
+}
+
+#[test]
+fn test_recover_version_1_wal() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    // LSN 2's first byte matches the current version byte
+    let mut bytes = Vec::new();
+    for lsn in 1..=300u64 {
+        bytes.extend(legacy_put(lsn, format!("key{}", lsn).as_bytes(), b"old"));
+    }
+    let mut file = File::create(&wal_path).unwrap();
+    file.write_all(&bytes).unwrap();
+    file.sync_all().unwrap();
+
+    let (entries, result) = WalRecovery::recover(&wal_path).unwrap();
+
+    assert_eq!(entries.len(), 300);
+    assert_eq!(result.last_lsn, 300);
+    assert_eq!(result.entries_corrupted, 0);
+    assert!(!result.was_truncated);
+    assert_eq!(
+        entries[1].operation,
+        Operation::Put { key: b"key2".to_vec(), value: b"old".to_vec() }
+    );
+}
@@ -3,13 +3,17 @@
 //! These tests verify:
 //! - Writing entries to WAL
 //! - LSN generation and sequencing
-//! - Sync strategies (EveryWrite, EveryNEntries)
-//! - Truncation
+//! - Appending replicated entries (LSN and timestamp kept)
+//! - Sync strategies (EveryWrite, EveryNEntries, NoSync, switched at runtime)
+//! - Truncation and size tracking
+//! - Listing segments (the single file and its LSN range)
+//! - Rolling over to a new segment at a size limit (contiguous LSN ranges,
+//!   read back in order, removed again by truncation)
 //! - Integration with reader
 
 use std::path::PathBuf;
 use atlaskv::config::WalSyncStrategy;
-use atlaskv::wal::{Operation, WalWriter, WalReader};
+use atlaskv::wal::{Operation, SegmentInfo, WalEntry, WalWriter, WalReader};
 use tempfile::TempDir;
 
 // =============================================================================
@@ -77,6 +81,19 @@ fn test_lsn_sequential() {
     }
 }
 
+#[test]
+fn test_open_creates_wal_in_fresh_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path().join("fresh");
+    std::fs::create_dir(&dir).unwrap();
+
+    // First creation fsyncs the parent directory; reopening skips it
+    let wal_path = dir.join("test.wal");
+    WalWriter::open_append(&wal_path, WalSyncStrategy::EveryWrite, 1).unwrap();
+    assert!(wal_path.exists());
+    WalWriter::open(&wal_path, WalSyncStrategy::EveryWrite).unwrap();
+}
+
 // =============================================================================
 // Sync Strategy Tests
 // =============================================================================
@@ -139,6 +156,28 @@ fn test_manual_sync() {
     assert_eq!(writer.uncommitted_count(), 0);
 }
 
+#[test]
+fn test_set_sync_strategy() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::EveryWrite).unwrap();
+    writer.set_sync_strategy(WalSyncStrategy::NoSync);
+    assert!(matches!(writer.sync_strategy(), WalSyncStrategy::NoSync));
+
+    for i in 0..3 {
+        writer.append(Operation::Put {
+            key: format!("k{}", i).into_bytes(),
+            value: b"v".to_vec(),
+        }).unwrap();
+    }
+    assert_eq!(writer.uncommitted_count(), 3);
+
+    // Back to EveryWrite: the next append syncs everything pending
+    writer.set_sync_strategy(WalSyncStrategy::EveryWrite);
+    writer.append(Operation::Put { key: b"k3".to_vec(), value: b"v".to_vec() }).unwrap();
+    assert_eq!(writer.uncommitted_count(), 0);
+}
+
 // =============================================================================
 // Write + Read Integration Tests
 // =============================================================================
@@ -226,6 +265,56 @@ fn test_truncate_resets_lsn() {
     assert_eq!(lsn, 1);
 }
 
+#[test]
+fn test_reset_continues_lsns() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::EveryWrite).unwrap();
+    writer.append(Operation::Put { key: b"k1".to_vec(), value: b"v1".to_vec() }).unwrap();
+    writer.append(Operation::Put { key: b"k2".to_vec(), value: b"v2".to_vec() }).unwrap();
+
+    let next = writer.current_lsn();
+    writer.reset(next).unwrap();
+    assert_eq!(writer.size_bytes(), 0);
+    assert_eq!(writer.current_lsn(), 3);
+
+    // Reserved LSNs are skipped by later appends
+    assert_eq!(writer.reserve_lsn(), 3);
+    let lsn = writer.append(Operation::Delete { key: b"k1".to_vec() }).unwrap();
+    assert_eq!(lsn, 4);
+    drop(writer);
+
+    let mut reader = WalReader::open(&wal_path).unwrap();
+    assert_eq!(reader.next_entry().unwrap().unwrap().lsn, 4);
+    assert!(reader.next_entry().unwrap().is_none());
+}
+
+#[test]
+fn test_append_replicated_keeps_lsn_and_timestamp() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    let entry = WalEntry {
+        lsn: 7,
+        operation: Operation::Put { key: b"k".to_vec(), value: b"v".to_vec() },
+        timestamp: 1_234,
+    };
+    let mut writer = WalWriter::open_append(&wal_path, WalSyncStrategy::EveryWrite, 6).unwrap();
+
+    // Only the next LSN is accepted
+    assert!(writer.append_replicated(&entry).is_err());
+    assert_eq!(writer.size_bytes(), 0);
+    writer.reserve_lsn();
+    writer.append_replicated(&entry).unwrap();
+    assert!(writer.append_replicated(&entry).is_err());
+    assert_eq!(writer.current_lsn(), 8);
+    assert_eq!(writer.last_timestamp(), 1_234);
+    drop(writer);
+
+    let mut reader = WalReader::open(&wal_path).unwrap();
+    assert_eq!(reader.next_entry().unwrap().unwrap(), entry);
+    assert!(reader.next_entry().unwrap().is_none());
+}
+
 #[test]
 fn test_truncate_clears_file() {
     let (_temp, wal_path) = setup_temp_wal();
@@ -243,6 +332,128 @@ fn test_truncate_clears_file() {
     assert!(reader.next_entry().unwrap().is_none());
 }
 
+#[test]
+fn test_size_bytes_tracks_appends_and_truncate() {
+    let (_temp, wal_path) = setup_temp_wal();
+
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::EveryWrite).unwrap();
+    assert_eq!(writer.size_bytes(), 0);
+
+    writer.append(Operation::Put { key: b"k1".to_vec(), value: b"v1".to_vec() }).unwrap();
+    writer.append(Operation::Delete { key: b"k1".to_vec() }).unwrap();
+    assert_eq!(writer.size_bytes(), std::fs::metadata(&wal_path).unwrap().len());
+    let written = writer.size_bytes();
+    drop(writer);
+
+    // Reopening picks up the existing length
+    let mut writer = WalWriter::open_append(&wal_path, WalSyncStrategy::EveryWrite, 3).unwrap();
+    assert_eq!(writer.size_bytes(), written);
+
+    writer.truncate().unwrap();
+    assert_eq!(writer.size_bytes(), 0);
+}
+
+#[test]
+fn test_segments_report_the_single_file() {
+    let (_temp, wal_path) = setup_temp_wal();
+    let segment = |first_lsn, last_lsn, byte_size| SegmentInfo {
+        path: wal_path.clone(),
+        first_lsn,
+        last_lsn,
+        byte_size,
+    };
+
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::NoSync).unwrap();
+    assert_eq!(writer.segments(), [segment(None, None, 0)]);
+
+    writer.reset(10).unwrap();
+    for i in 0..3u8 {
+        writer.append(Operation::Put { key: vec![i], value: b"v".to_vec() }).unwrap();
+    }
+    // A checkpoint carries LSN 12 again, so the range doesn't move
+    writer.checkpoint().unwrap();
+    assert_eq!(writer.segments(), [segment(Some(10), Some(12), writer.size_bytes())]);
+    let written = writer.size_bytes();
+    drop(writer);
+
+    // Reopening scans the file for its range
+    let mut writer = WalWriter::open_append(&wal_path, WalSyncStrategy::NoSync, 13).unwrap();
+    assert_eq!(writer.segments(), [segment(Some(10), Some(12), written)]);
+    writer.append(Operation::Delete { key: vec![0] }).unwrap();
+    assert_eq!(writer.segments()[0].last_lsn, Some(13));
+
+    // Truncation empties it, and LSNs carry on from there
+    writer.reset(14).unwrap();
+    assert_eq!(writer.segments(), [segment(None, None, 0)]);
+    writer.append(Operation::Delete { key: vec![1] }).unwrap();
+    assert_eq!(writer.segments()[0].first_lsn, Some(14));
+}
+
+#[test]
+fn test_segments_roll_over_at_size_limit() {
+    let (_temp, wal_path) = setup_temp_wal();
+    let put = |i: u8| Operation::Put { key: vec![i], value: vec![0xAB; 64] };
+    let entry_size = WalEntry::new(1, put(0)).serialized_size().unwrap() as u64;
+
+    // Room for three entries per segment: the fourth starts a second file
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::NoSync)
+        .unwrap()
+        .with_segment_bytes(3 * entry_size);
+    for i in 0..5 {
+        writer.append(put(i)).unwrap();
+    }
+    writer.sync().unwrap();
+
+    let segments = writer.segments();
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].path, wal_path);
+    assert_eq!(segments[1].path, wal_path.with_extension("wal.1"));
+    assert_eq!((segments[0].first_lsn, segments[0].last_lsn), (Some(1), Some(3)));
+    assert_eq!((segments[1].first_lsn, segments[1].last_lsn), (Some(4), Some(5)));
+    assert_eq!(segments[0].byte_size, 3 * entry_size);
+    assert_eq!(segments[0].byte_size + segments[1].byte_size, writer.size_bytes());
+    for segment in &segments {
+        assert_eq!(std::fs::metadata(&segment.path).unwrap().len(), segment.byte_size);
+    }
+
+    // Readers go through both files in order
+    let lsns: Vec<u64> = WalReader::open(&wal_path)
+        .unwrap()
+        .entries()
+        .map(|entry| entry.unwrap().lsn)
+        .collect();
+    assert_eq!(lsns, [1, 2, 3, 4, 5]);
+    drop(writer);
+
+    // Reopening finds both and appends to the second
+    let mut writer = WalWriter::open_append(&wal_path, WalSyncStrategy::NoSync, 6)
+        .unwrap()
+        .with_segment_bytes(3 * entry_size);
+    assert_eq!(writer.segments(), segments);
+    writer.append(put(5)).unwrap();
+    assert_eq!(writer.segments().len(), 2);
+    assert_eq!(writer.segments()[1].last_lsn, Some(6));
+
+    // Truncation goes back to the first file and removes the second
+    writer.reset(7).unwrap();
+    assert!(!segments[1].path.exists());
+    writer.append(put(6)).unwrap();
+    assert_eq!(writer.segments().len(), 1);
+    assert_eq!(writer.segments()[0].first_lsn, Some(7));
+    drop(writer);
+
+    // So does opening afresh
+    let mut writer = WalWriter::open(&wal_path, WalSyncStrategy::NoSync)
+        .unwrap()
+        .with_segment_bytes(entry_size);
+    writer.append(put(0)).unwrap();
+    writer.append(put(1)).unwrap();
+    assert!(segments[1].path.exists());
+    drop(writer);
+    WalWriter::open(&wal_path, WalSyncStrategy::NoSync).unwrap();
+    assert!(!segments[1].path.exists());
+}
+
 #[test]
 fn test_truncate_then_write() {
     let (_temp, wal_path) = setup_temp_wal();